@@ -0,0 +1,325 @@
+use std::convert::TryInto;
+
+use parity_scale_codec::{Decode, Encode};
+use sp_core::hashing::keccak_256;
+use sp_core::{ecdsa, H256};
+use sp_runtime::traits::Keccak256;
+
+use crate::common::types::blockchain_error::BlockchainError;
+use crate::common::types::blockchain_result::BlockchainResult;
+use crate::mmr::HeaderMmr;
+
+/// BEEFY validator set tracker.
+///
+/// Validators themselves aren't stored, only the keccak merkle root of their
+/// Ethereum addresses, which is what MMR leaves commit to. Signers of a
+/// commitment prove their membership with merkle proofs instead.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Encode, Decode)]
+pub struct BeefyValidatorSet {
+    pub id: u64,
+    pub len: u32,
+    pub root: H256,
+}
+
+impl BeefyValidatorSet {
+    /// Minimum amount of signatures for a commitment to be final,
+    /// which is more than 2/3 of the validators.
+    pub fn threshold(&self) -> usize {
+        let len = self.len as usize;
+        len - len.saturating_sub(1) / 3
+    }
+}
+
+/// Commitment to MMR root at given block, signed by BEEFY validators.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub struct Commitment {
+    /// Root of MMR of all blocks up to and including `block_number`
+    pub payload: H256,
+    pub block_number: u32,
+    pub validator_set_id: u64,
+}
+
+/// Commitment along with signatures of validators, ordered the same way
+/// as validators are. Validators which haven't signed have `None` instead.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub struct SignedCommitment {
+    pub commitment: Commitment,
+    pub signatures: Vec<Option<ecdsa::Signature>>,
+}
+
+/// Leaf of relay chain MMR, appended for every block.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub struct MmrLeaf {
+    /// Number and hash of the parent of block which appended the leaf
+    pub parent_number_and_hash: (u32, H256),
+    pub parachain_heads: H256,
+    pub beefy_next_authority_set: BeefyValidatorSet,
+}
+
+/// Proof of MMR leaf, consisting of sibling hashes on the path from the leaf
+/// to the peak of its mountain and all peaks of the MMR.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub struct MmrLeafProof {
+    pub leaf_index: u64,
+    pub leaf_count: u64,
+    pub items: Vec<H256>,
+    pub peaks: Vec<H256>,
+}
+
+/// Everything needed to finalize a header with BEEFY: signed commitment,
+/// merkle proofs of signers' addresses (one per present signature, in the same
+/// order) and MMR leaf of the header's child, proven against signed MMR root.
+///
+/// This is a simplified format, which real relay chains don't produce: leaves
+/// lack `version` and `leaf_extra` of pallet-beefy-mmr, the leaf hash is keccak
+/// of the SCALE encoded `MmrLeaf` and MMR nodes are merged as `keccak(left || right)`,
+/// so proofs of pallet-mmr won't verify. Relayers have to build proofs in this format.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub struct BeefyFinalityProof {
+    pub signed_commitment: SignedCommitment,
+    pub validator_proofs: Vec<Vec<H256>>,
+    pub leaf: MmrLeaf,
+    pub leaf_proof: MmrLeafProof,
+}
+
+impl SignedCommitment {
+    /// Verifies that commitment is signed by more than 2/3 of the validator set.
+    pub fn verify(
+        &self,
+        validator_set: &BeefyValidatorSet,
+        validator_proofs: &[Vec<H256>],
+    ) -> BlockchainResult<()> {
+        if self.commitment.validator_set_id != validator_set.id {
+            return Err(BlockchainError::BadJustification(format!(
+                "Commitment is signed by validator set {}, current one is {}",
+                self.commitment.validator_set_id, validator_set.id
+            )));
+        }
+        if validator_set.len == 0 {
+            return Err(BlockchainError::InvalidAuthoritiesSet);
+        }
+        if self.signatures.len() != validator_set.len as usize {
+            return Err(BlockchainError::BadJustification(format!(
+                "Expected {} signature slots, got {}",
+                validator_set.len,
+                self.signatures.len()
+            )));
+        }
+
+        let signatures_count = self.signatures.iter().filter(|s| s.is_some()).count();
+        if signatures_count < validator_set.threshold() {
+            return Err(BlockchainError::BadJustification(format!(
+                "Commitment has {} signatures, at least {} are required",
+                signatures_count,
+                validator_set.threshold()
+            )));
+        }
+        if validator_proofs.len() != signatures_count {
+            return Err(BlockchainError::BadJustification(format!(
+                "Expected {} validator proofs, got {}",
+                signatures_count,
+                validator_proofs.len()
+            )));
+        }
+
+        let message = keccak_256(&self.commitment.encode());
+        let signatures = self
+            .signatures
+            .iter()
+            .enumerate()
+            .filter_map(|(index, s)| s.as_ref().map(|s| (index as u32, s)));
+        for ((index, signature), proof) in signatures.zip(validator_proofs) {
+            let address = recover_address(&message, signature).ok_or_else(|| {
+                BlockchainError::BadJustification(format!(
+                    "Invalid signature of validator {}",
+                    index
+                ))
+            })?;
+            if !verify_merkle_proof(
+                &validator_set.root,
+                &address,
+                index,
+                validator_set.len,
+                proof,
+            ) {
+                return Err(BlockchainError::BadJustification(format!(
+                    "Signature {} isn't made by a validator of set {}",
+                    index, validator_set.id
+                )));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl BeefyFinalityProof {
+    /// Verifies that the leaf is part of MMR whose root is signed by the
+    /// validator set, and returns the leaf.
+    pub fn verify(&self, validator_set: &BeefyValidatorSet) -> BlockchainResult<&MmrLeaf> {
+        self.signed_commitment
+            .verify(validator_set, &self.validator_proofs)?;
+
+        let commitment = &self.signed_commitment.commitment;
+        if self.leaf.parent_number_and_hash.0 >= commitment.block_number {
+            return Err(BlockchainError::InvalidProof(format!(
+                "Leaf of block {} can't be part of MMR at block {}",
+                self.leaf.parent_number_and_hash.0 + 1,
+                commitment.block_number
+            )));
+        }
+
+        let mmr = HeaderMmr::from_peaks(self.leaf_proof.leaf_count, self.leaf_proof.peaks.clone())?;
+        if mmr.root::<Keccak256>() != Some(commitment.payload) {
+            return Err(BlockchainError::InvalidProof(
+                "MMR peaks don't match the signed MMR root".into(),
+            ));
+        }
+        mmr.verify_proof::<Keccak256>(
+            self.leaf_proof.leaf_index,
+            H256(keccak_256(&self.leaf.encode())),
+            &self.leaf_proof.items,
+        )?;
+
+        Ok(&self.leaf)
+    }
+}
+
+/// Recovers Ethereum address of the signer of prehashed `message`.
+fn recover_address(message: &[u8; 32], signature: &ecdsa::Signature) -> Option<[u8; 20]> {
+    let (signature, recovery_id): (secp256k1::Signature, secp256k1::RecoveryId) =
+        signature.try_into().ok()?;
+    let public = secp256k1::recover(
+        &secp256k1::Message::parse(message),
+        &signature,
+        &recovery_id,
+    )
+    .ok()?;
+    Some(ethereum_address(&public))
+}
+
+/// Ethereum address of the public key, which is the last 20 bytes
+/// of keccak hash of the uncompressed key without its prefix.
+pub fn ethereum_address(public: &secp256k1::PublicKey) -> [u8; 20] {
+    let hash = keccak_256(&public.serialize()[1..]);
+    let mut address = [0u8; 20];
+    address.copy_from_slice(&hash[12..]);
+    address
+}
+
+/// Verifies merkle proof of `leaf` at `position` in the binary keccak merkle
+/// tree of `width` leaves. Leaves are hashed before being put to the tree, and
+/// the last node of a level with odd amount of nodes is promoted as is.
+pub fn verify_merkle_proof(
+    root: &H256,
+    leaf: &[u8],
+    mut position: u32,
+    mut width: u32,
+    proof: &[H256],
+) -> bool {
+    if position >= width {
+        return false;
+    }
+
+    let mut node = H256(keccak_256(leaf));
+    let mut proof = proof.iter();
+    while width > 1 {
+        if position ^ 1 < width {
+            let sibling = match proof.next() {
+                Some(sibling) => sibling,
+                None => return false,
+            };
+            node = if position & 1 == 0 {
+                merge(&node, sibling)
+            } else {
+                merge(sibling, &node)
+            };
+        }
+        position >>= 1;
+        width = width / 2 + width % 2;
+    }
+
+    proof.next().is_none() && node == *root
+}
+
+pub(crate) fn merge(left: &H256, right: &H256) -> H256 {
+    let mut concatenated = left.as_bytes().to_vec();
+    concatenated.extend_from_slice(right.as_bytes());
+    H256(keccak_256(&concatenated))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::beefy::verify_merkle_proof;
+    use crate::test_utils::{
+        create_finality_proof, merkle_root_and_proof, validator_addresses, validator_keys,
+        validator_set,
+    };
+    use crate::types::Header;
+    use sp_core::H256;
+    use sp_runtime::traits::Header as HeaderT;
+
+    #[test]
+    fn test_merkle_proof_verification() {
+        let addresses = validator_addresses(&validator_keys(5));
+        let root = merkle_root_and_proof(&addresses, 0).0;
+        for (position, address) in addresses.iter().enumerate() {
+            let proof = merkle_root_and_proof(&addresses, position).1;
+            assert!(verify_merkle_proof(
+                &root,
+                address,
+                position as u32,
+                5,
+                &proof
+            ));
+        }
+
+        // Last leaf of odd width is promoted, so its proof is shorter
+        let proof = merkle_root_and_proof(&addresses, 4).1;
+        assert_eq!(proof.len(), 1);
+        assert!(!verify_merkle_proof(&root, &addresses[3], 4, 5, &proof));
+        assert!(!verify_merkle_proof(&root, &addresses[4], 5, 5, &proof));
+        assert!(!verify_merkle_proof(&root, &addresses[4], 4, 5, &[]));
+    }
+
+    #[test]
+    fn test_signed_commitment_verification() {
+        let keys = validator_keys(4);
+        let set = validator_set(3, &keys);
+        assert_eq!(set.threshold(), 3);
+        let header = Header::new(
+            10,
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+        );
+
+        let proof = create_finality_proof(&header, 3, &keys, &[0, 1, 3], Default::default());
+        assert_eq!(proof.verify(&set).unwrap(), &proof.leaf);
+
+        // Not enough signatures
+        let proof = create_finality_proof(&header, 3, &keys, &[0, 1], Default::default());
+        assert!(proof.verify(&set).is_err());
+
+        // Wrong validator set id
+        let proof = create_finality_proof(&header, 4, &keys, &[0, 1, 2], Default::default());
+        assert!(proof.verify(&set).is_err());
+
+        // Signer isn't part of the set
+        let mut other_keys = keys.clone();
+        other_keys[2] = validator_keys(5)[4].clone();
+        let proof = create_finality_proof(&header, 3, &other_keys, &[0, 1, 2], Default::default());
+        assert!(proof.verify(&set).is_err());
+
+        // Leaf isn't part of signed MMR
+        let mut proof = create_finality_proof(&header, 3, &keys, &[0, 1, 2], Default::default());
+        proof.leaf.parachain_heads = H256::repeat_byte(1);
+        assert!(proof.verify(&set).is_err());
+
+        // Peaks don't match signed MMR root
+        let mut proof = create_finality_proof(&header, 3, &keys, &[0, 1, 2], Default::default());
+        proof.leaf_proof.peaks = vec![H256::zero()];
+        assert!(proof.verify(&set).is_err());
+    }
+}
@@ -67,7 +67,7 @@ where
         if should_store_next_authority_change && possible_next_change_in_authority.is_some() {
             let next_change_in_authority = possible_next_change_in_authority.unwrap();
             store_next_authority_change(self.aux_store.clone(), next_change_in_authority.deref())
-                .map_err(|err| Self::Error::Other(Box::new(err)))?;
+                .map_err(ConsensusError::from)?;
         }
 
         result
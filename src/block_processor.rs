@@ -13,18 +13,19 @@ use crate::common::types::incoming_block::IncomingBlock;
 use crate::common::utils::initialize_storage;
 use crate::db;
 use crate::grandpa_block_import::GrandpaLightBlockImport;
+use crate::storage::Storage;
 use crate::types::Block;
 use crate::verifier::GrandpaVerifier;
 use sp_runtime::traits::{Block as BlockT, Header, NumberFor};
 use std::sync::Arc;
 
 pub type BlockProcessor<B> =
-    Box<dyn FnMut(IncomingBlock<B>) -> Result<BlockImportResult<NumberFor<B>>, String>>;
+    Box<dyn FnMut(IncomingBlock<B>) -> Result<BlockImportResult<NumberFor<B>>, BlockImportError>>;
 
 pub fn setup_block_processor(
     encoded_data: Vec<u8>,
     max_non_finalized_blocks_allowed: u64,
-) -> BlockchainResult<(BlockProcessor<Block>, db::Data)> {
+) -> BlockchainResult<(BlockProcessor<Block>, db::Data, Arc<Storage>)> {
     let (data, storage) = initialize_storage(encoded_data, max_non_finalized_blocks_allowed)?;
 
     // Custom client implementation with dummy runtime
@@ -33,21 +34,22 @@ pub fn setup_block_processor(
     // We need to re-initialize grandpa light import queue because
     // current version read/write authority set from private field instead of
     // auxiliary storage.
+    let processor_storage = storage.clone();
     let block_processor_fn = Box::new(move |incoming_block: IncomingBlock<Block>| {
-        let grandpa_block_import = GrandpaLightBlockImport::new(client.clone(), storage.clone());
-        let mut grandpa_verifier = GrandpaVerifier::new(storage.clone());
+        let grandpa_block_import =
+            GrandpaLightBlockImport::new(client.clone(), processor_storage.clone());
+        let mut grandpa_verifier = GrandpaVerifier::new(processor_storage.clone());
         let mut block_import_wrapper: BlockImportWrapper<_, _> =
-            BlockImportWrapper::new(grandpa_block_import.clone(), storage.clone());
+            BlockImportWrapper::new(grandpa_block_import.clone(), processor_storage.clone());
         import_single_block(
             &mut block_import_wrapper,
             BlockOrigin::NetworkBroadcast,
             incoming_block,
             &mut grandpa_verifier,
         )
-        .map_err(|e| format!("{:?}", e))
     });
 
-    Ok((block_processor_fn, data))
+    Ok((block_processor_fn, data, storage))
 }
 
 /// Single block import function.
@@ -87,9 +89,7 @@ fn import_single_block<B: BlockT, V: Verifier<B>>(
         r => return Ok(r), // Any other successful result means that the block is already imported.
     }
 
-    let mut import_block = verifier
-        .verify(block_origin, header, justification, block.body)
-        .map_err(|msg| BlockImportError::VerificationFailed(msg))?;
+    let mut import_block = verifier.verify(block_origin, header, justification, block.body)?;
 
     import_block.allow_missing_state = block.allow_missing_state;
 
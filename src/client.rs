@@ -267,9 +267,7 @@ where
             }
         };
 
-        match block_status(&BlockId::Hash(hash))
-            .map_err(|e| ConsensusError::ClientImport(e.to_string()))?
-        {
+        match block_status(&BlockId::Hash(hash)).map_err(ConsensusError::from)? {
             ImportBlockStatus::InChainWithState | ImportBlockStatus::Queued if !import_existing => {
                 return Ok(ImportResult::AlreadyInChain)
             }
@@ -280,9 +278,7 @@ where
             ImportBlockStatus::KnownBad => return Ok(ImportResult::KnownBad),
         }
 
-        match block_status(&BlockId::Hash(parent_hash))
-            .map_err(|e| ConsensusError::ClientImport(e.to_string()))?
-        {
+        match block_status(&BlockId::Hash(parent_hash)).map_err(ConsensusError::from)? {
             ImportBlockStatus::InChainWithState | ImportBlockStatus::Queued => {}
             ImportBlockStatus::Unknown => return Ok(ImportResult::UnknownParent),
             ImportBlockStatus::InChainPruned if allow_missing_state => {}
@@ -308,15 +304,14 @@ where
         } = block;
 
         if !intermediates.is_empty() {
-            return Err(BlockchainError::IncompletePipeline)
-                .map_err(|e| ConsensusError::ClientImport(e.to_string()).into());
+            return Err(BlockchainError::IncompletePipeline.into());
         }
 
         let hash = header.hash();
         let status = self
             .storage
             .status(BlockId::Hash(hash))
-            .map_err(|e| ConsensusError::ClientImport(e.to_string()))?;
+            .map_err(ConsensusError::from)?;
 
         match status {
             BlockStatus::InChain => return Ok(ImportResult::AlreadyInChain),
@@ -325,7 +320,7 @@ where
 
         self.storage
             .import_header(header, NewBlockState::Best)
-            .map_err(|e| ConsensusError::ClientImport(e.to_string()))?;
+            .map_err(ConsensusError::from)?;
 
         Ok(ImportResult::imported(true))
     }
@@ -1,3 +1,5 @@
+#[cfg(feature = "std")]
 pub(crate) mod traits;
 pub(crate) mod types;
+#[cfg(feature = "std")]
 pub(crate) mod utils;
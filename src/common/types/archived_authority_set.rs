@@ -0,0 +1,15 @@
+use crate::common::types::light_authority_set::LightAuthoritySet;
+use parity_scale_codec::{Decode, Encode};
+use sp_runtime::traits::{Block as BlockT, NumberFor};
+
+/// Authority set along with the header at which it got activated. Sets are
+/// archived, so that justifications of past sets remain verifiable.
+#[derive(Debug, Clone, Encode, Decode)]
+pub struct ArchivedAuthoritySet<Block>
+where
+    Block: BlockT,
+{
+    pub authority_set: LightAuthoritySet,
+    pub activation_number: NumberFor<Block>,
+    pub activation_hash: Block::Hash,
+}
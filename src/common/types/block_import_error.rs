@@ -1,4 +1,7 @@
+use crate::common::types::blockchain_error::BlockchainError;
 use crate::common::types::consensus_error::ConsensusError;
+use core::fmt;
+use std::fmt::{Display, Formatter};
 
 /// Block import error.
 #[derive(Debug)]
@@ -18,3 +21,25 @@ pub enum BlockImportError {
     /// Other error.
     Other(ConsensusError),
 }
+
+impl Display for BlockImportError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            BlockImportError::IncompleteHeader => write!(f, "Block is missing header"),
+            BlockImportError::VerificationFailed(s) => {
+                write!(f, "Block verification failed: {}", s)
+            }
+            BlockImportError::BadBlock => write!(f, "Block is known to be bad"),
+            BlockImportError::MissingState => write!(f, "Parent state is missing"),
+            BlockImportError::UnknownParent => write!(f, "Block has an unknown parent"),
+            BlockImportError::Cancelled => write!(f, "Block import has been cancelled"),
+            BlockImportError::Other(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl From<BlockchainError> for BlockImportError {
+    fn from(e: BlockchainError) -> Self {
+        BlockImportError::Other(e.into())
+    }
+}
@@ -1,12 +1,17 @@
+#[cfg(feature = "std")]
 use crate::common::types::consensus_error::ConsensusError;
-use core::fmt;
+use alloc::string::String;
+use sp_std::fmt;
+use sp_std::fmt::{Display, Formatter};
+use sp_std::prelude::*;
+#[cfg(feature = "std")]
 use std::error;
-use std::fmt::{Display, Formatter};
 
 /// Substrate Client error
 #[derive(Debug)]
 pub enum BlockchainError {
     /// Consensus Error
+    #[cfg(feature = "std")]
     Consensus(ConsensusError),
     /// Backend error.
     Backend(String),
@@ -34,8 +39,11 @@ pub enum BlockchainError {
     Msg(String),
     /// Error while decoding data
     DataDecode(String),
+    /// Proof supplied for the header is invalid.
+    InvalidProof(String),
 }
 
+#[cfg(feature = "std")]
 impl error::Error for BlockchainError {
     fn source(&self) -> Option<&(dyn error::Error + 'static)> {
         match self {
@@ -62,6 +70,7 @@ impl BlockchainError {
 impl Display for BlockchainError {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         match self {
+            #[cfg(feature = "std")]
             BlockchainError::Consensus(e) => write!(f, "Consensus: {}", e),
             BlockchainError::Backend(s) => write!(f, "Backend error: {}", s),
             BlockchainError::UnknownBlock(s) => write!(f, "UnknownBlock: {}", s),
@@ -96,6 +105,7 @@ impl Display for BlockchainError {
             BlockchainError::IncompletePipeline => write!(f, "Incomplete block import pipeline."),
             BlockchainError::Msg(s) => write!(f, "{}", s),
             BlockchainError::DataDecode(s) => write!(f, "Error while decoding data: {}", s),
+            BlockchainError::InvalidProof(s) => write!(f, "Invalid proof: {}", s),
         }
     }
 }
@@ -0,0 +1,33 @@
+use crate::beefy::BeefyValidatorSet;
+use crate::common::types::archived_authority_set::ArchivedAuthoritySet;
+use crate::common::types::justification_verification::JustificationVerification;
+use crate::common::types::light_authority_set::LightAuthoritySet;
+use crate::common::types::next_change_in_authority::NextChangeInAuthority;
+use crate::common::types::retention_policy::RetentionPolicy;
+use crate::mmr::HeaderMmr;
+use parity_scale_codec::{Decode, Encode};
+use sp_runtime::traits::{Block as BlockT, NumberFor};
+
+/// Portable copy of light client storage, which allows to restore
+/// the client elsewhere without replaying history from a trusted header.
+#[derive(Encode, Decode)]
+pub struct ClientSnapshot<Block>
+where
+    Block: BlockT,
+{
+    /// Last finalized header, or `None` if nothing is finalized yet.
+    pub possible_finalized_header: Option<Block::Header>,
+    /// Retained headers, from the oldest to the best one.
+    pub retained_headers: Vec<Block::Header>,
+    pub light_authority_set: LightAuthoritySet,
+    /// Archived authority sets, from the oldest to the current one.
+    pub authority_set_archive: Vec<ArchivedAuthoritySet<Block>>,
+    pub possible_next_change_in_authority: Option<NextChangeInAuthority<Block>>,
+    pub possible_beefy_validator_set: Option<BeefyValidatorSet>,
+    pub possible_next_beefy_validator_set: Option<BeefyValidatorSet>,
+    pub header_mmr: HeaderMmr<Block::Hash>,
+    /// Headers kept outside of the window by the policy aren't carried,
+    /// only the policy itself.
+    pub retention_policy: RetentionPolicy<NumberFor<Block>>,
+    pub justification_verification: JustificationVerification,
+}
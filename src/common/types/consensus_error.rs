@@ -1,3 +1,4 @@
+use crate::common::types::blockchain_error::BlockchainError;
 use core::fmt;
 use std::error;
 use std::error::Error;
@@ -25,9 +26,25 @@ pub enum ConsensusError {
     ClientImport(String),
     /// Error from the client while importing
     ChainLookup(String),
+    /// Error of client storage while importing, kept typed so that
+    /// callers can tell e.g. unknown block from non-sequential import
+    Blockchain(Box<BlockchainError>),
 }
 
-impl Error for ConsensusError {}
+impl Error for ConsensusError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Self::Blockchain(e) => Some(e.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl From<BlockchainError> for ConsensusError {
+    fn from(e: BlockchainError) -> Self {
+        ConsensusError::Blockchain(Box::new(e))
+    }
+}
 
 impl Display for ConsensusError {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
@@ -44,6 +61,7 @@ impl Display for ConsensusError {
             Self::Other(e) => write!(f, "Other error: {}", e),
             Self::ClientImport(s) => write!(f, "Import failed: {}", s),
             Self::ChainLookup(s) => write!(f, "Chain lookup failed: {}", s),
+            Self::Blockchain(e) => write!(f, "Import failed: {}", e),
         }
     }
 }
@@ -1,3 +1,5 @@
+use crate::justification::VerifiedWeight;
+
 /// Auxiliary data associated with an imported block result.
 #[derive(Debug, Default, PartialEq, Eq)]
 pub struct ImportedAux {
@@ -13,4 +15,6 @@ pub struct ImportedAux {
     pub needs_finality_proof: bool,
     /// Whether the block that was imported is the new best block.
     pub is_new_best: bool,
+    /// Weight proven by bounded verification of the imported justification.
+    pub possible_verified_weight: Option<VerifiedWeight>,
 }
@@ -0,0 +1,36 @@
+use crate::common::types::block_import_result::BlockImportResult;
+use crate::common::types::next_change_in_authority::NextChangeInAuthority;
+use sp_runtime::traits::{Block as BlockT, NumberFor};
+
+/// Outcome of header ingestion, as known to the import pipeline.
+pub struct IngestedHeader<Block>
+where
+    Block: BlockT,
+{
+    pub block_import_result: BlockImportResult<NumberFor<Block>>,
+    /// Number and hash of last finalized header before the ingestion.
+    pub possible_previous_finalized: Option<(NumberFor<Block>, Block::Hash)>,
+    /// Number and hash of last finalized header after the ingestion.
+    pub possible_finalized: Option<(NumberFor<Block>, Block::Hash)>,
+    /// Authority set change signaled by the ingested header.
+    pub possible_scheduled_change: Option<NextChangeInAuthority<Block>>,
+    /// Id of validator set of the client's finality gadget before the ingestion.
+    pub previous_set_id: u64,
+    /// Id of validator set of the client's finality gadget after the ingestion.
+    pub set_id: u64,
+}
+
+impl<Block> IngestedHeader<Block>
+where
+    Block: BlockT,
+{
+    /// Whether ingestion finalized new headers.
+    pub fn finalized(&self) -> bool {
+        self.possible_finalized != self.possible_previous_finalized
+    }
+
+    /// Whether ingestion enacted new validator set.
+    pub fn validator_set_enacted(&self) -> bool {
+        self.set_id != self.previous_set_id
+    }
+}
@@ -0,0 +1,12 @@
+use parity_scale_codec::{Decode, Encode};
+
+/// Decides how GRANDPA justifications are verified when headers are imported.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Encode, Decode)]
+pub enum JustificationVerification {
+    /// Every precommit signature and votes ancestry header is checked.
+    #[default]
+    Full,
+    /// Signatures are only checked until supermajority of voter set weight
+    /// is proven, see `GrandpaJustification::verify_bounded`.
+    Bounded,
+}
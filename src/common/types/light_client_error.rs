@@ -0,0 +1,163 @@
+use crate::common::types::block_import_error::BlockImportError;
+use crate::common::types::blockchain_error::BlockchainError;
+use crate::common::types::consensus_error::ConsensusError;
+use core::fmt;
+use std::error;
+use std::fmt::{Display, Formatter};
+
+/// Error returned by light client, preserving which part of
+/// the import pipeline it originated from.
+#[derive(Debug)]
+pub enum LightClientError {
+    /// Block import error
+    BlockImport(BlockImportError),
+    /// Blockchain error
+    Blockchain(BlockchainError),
+    /// Consensus error
+    Consensus(ConsensusError),
+}
+
+impl LightClientError {
+    /// Stable numeric code of the error, which can be matched on by
+    /// callers instead of error message. Codes must never be reused
+    /// or reassigned, new variants need to get new codes.
+    pub fn code(&self) -> u32 {
+        match self {
+            LightClientError::BlockImport(e) => match e {
+                BlockImportError::IncompleteHeader => 100,
+                BlockImportError::VerificationFailed(_) => 101,
+                BlockImportError::BadBlock => 102,
+                BlockImportError::MissingState => 103,
+                BlockImportError::UnknownParent => 104,
+                BlockImportError::Cancelled => 105,
+                BlockImportError::Other(e) => consensus_error_code(e),
+            },
+            LightClientError::Blockchain(e) => blockchain_error_code(e),
+            LightClientError::Consensus(e) => consensus_error_code(e),
+        }
+    }
+}
+
+fn blockchain_error_code(e: &BlockchainError) -> u32 {
+    match e {
+        BlockchainError::Consensus(e) => consensus_error_code(e),
+        BlockchainError::Blockchain(e) => blockchain_error_code(e),
+        BlockchainError::Backend(_) => 201,
+        BlockchainError::UnknownBlock(_) => 202,
+        BlockchainError::InvalidAuthoritiesSet => 203,
+        BlockchainError::JustificationDecode => 204,
+        BlockchainError::BadJustification(_) => 205,
+        BlockchainError::NotAvailableOnLightClient => 206,
+        BlockchainError::NonSequentialFinalization(_) => 207,
+        BlockchainError::NonSequentialImport(_) => 208,
+        BlockchainError::NotInFinalizedChain => 209,
+        BlockchainError::IncompletePipeline => 210,
+        BlockchainError::Msg(_) => 211,
+        BlockchainError::DataDecode(_) => 212,
+        BlockchainError::InvalidProof(_) => 213,
+    }
+}
+
+fn consensus_error_code(e: &ConsensusError) -> u32 {
+    match e {
+        ConsensusError::StateUnavailable(_) => 301,
+        ConsensusError::IoTerminated => 302,
+        ConsensusError::NoIntermediate => 303,
+        ConsensusError::InvalidIntermediate => 304,
+        ConsensusError::FaultyTimer(_) => 305,
+        ConsensusError::InvalidAuthoritiesSet => 306,
+        ConsensusError::InvalidJustification => 307,
+        ConsensusError::Other(_) => 308,
+        ConsensusError::ClientImport(_) => 309,
+        ConsensusError::ChainLookup(_) => 310,
+        ConsensusError::Blockchain(e) => blockchain_error_code(e),
+    }
+}
+
+impl error::Error for LightClientError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            LightClientError::BlockImport(_) => None,
+            LightClientError::Blockchain(e) => Some(e),
+            LightClientError::Consensus(e) => Some(e),
+        }
+    }
+}
+
+impl From<BlockImportError> for LightClientError {
+    fn from(e: BlockImportError) -> Self {
+        match e {
+            BlockImportError::Other(e) => e.into(),
+            e => LightClientError::BlockImport(e),
+        }
+    }
+}
+
+impl From<BlockchainError> for LightClientError {
+    fn from(e: BlockchainError) -> Self {
+        LightClientError::Blockchain(e)
+    }
+}
+
+impl From<ConsensusError> for LightClientError {
+    fn from(e: ConsensusError) -> Self {
+        match e {
+            // Storage errors surface as such, whichever stage of import they come from
+            ConsensusError::Blockchain(e) => LightClientError::Blockchain(*e),
+            e => LightClientError::Consensus(e),
+        }
+    }
+}
+
+impl Display for LightClientError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            LightClientError::BlockImport(e) => write!(f, "Block import: {}", e),
+            LightClientError::Blockchain(e) => write!(f, "{}", e),
+            LightClientError::Consensus(e) => write!(f, "Consensus: {}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::common::types::block_import_error::BlockImportError;
+    use crate::common::types::blockchain_error::BlockchainError;
+    use crate::common::types::consensus_error::ConsensusError;
+    use crate::common::types::light_client_error::LightClientError;
+
+    #[test]
+    fn test_error_codes() {
+        let error: LightClientError = BlockImportError::UnknownParent.into();
+        assert_eq!(error.code(), 104);
+        assert_eq!(
+            error.to_string(),
+            "Block import: Block has an unknown parent"
+        );
+
+        let error: LightClientError =
+            BlockImportError::Other(ConsensusError::ClientImport("failed".into())).into();
+        assert!(matches!(
+            error,
+            LightClientError::Consensus(ConsensusError::ClientImport(_))
+        ));
+        assert_eq!(error.code(), 309);
+
+        let error: LightClientError =
+            BlockImportError::from(BlockchainError::NotInFinalizedChain).into();
+        assert!(matches!(
+            error,
+            LightClientError::Blockchain(BlockchainError::NotInFinalizedChain)
+        ));
+        assert_eq!(error.code(), 209);
+
+        let error: LightClientError = BlockchainError::BadJustification("invalid".into()).into();
+        assert_eq!(error.code(), 205);
+
+        let error: LightClientError = BlockchainError::from_blockchain(Box::new(
+            BlockchainError::UnknownBlock("pruned".into()),
+        ))
+        .into();
+        assert_eq!(error.code(), 202);
+    }
+}
@@ -1,20 +1,50 @@
+#[cfg(feature = "std")]
+pub(crate) mod archived_authority_set;
+#[cfg(feature = "std")]
 pub(crate) mod block_check_params;
+#[cfg(feature = "std")]
 pub(crate) mod block_import_error;
+#[cfg(feature = "std")]
 pub(crate) mod block_import_params;
+#[cfg(feature = "std")]
 pub(crate) mod block_import_result;
+#[cfg(feature = "std")]
 pub(crate) mod block_import_status;
+#[cfg(feature = "std")]
 pub(crate) mod block_origin;
+#[cfg(feature = "std")]
 pub(crate) mod block_status;
 pub(crate) mod blockchain_error;
+#[cfg(feature = "std")]
 pub(crate) mod blockchain_info;
 pub(crate) mod blockchain_result;
+#[cfg(feature = "std")]
 pub(crate) mod cached_header_metadata;
+#[cfg(feature = "std")]
+pub(crate) mod client_snapshot;
+#[cfg(feature = "std")]
 pub(crate) mod client_status;
+#[cfg(feature = "std")]
 pub(crate) mod consensus_error;
+#[cfg(feature = "std")]
 pub(crate) mod fork_choice_strategy;
+#[cfg(feature = "std")]
 pub(crate) mod import_result;
+#[cfg(feature = "std")]
 pub(crate) mod imported_aux;
+#[cfg(feature = "std")]
 pub(crate) mod incoming_block;
+#[cfg(feature = "std")]
+pub(crate) mod ingested_header;
+#[cfg(feature = "std")]
+pub(crate) mod justification_verification;
 pub(crate) mod light_authority_set;
+#[cfg(feature = "std")]
+pub(crate) mod light_client_error;
+#[cfg(feature = "std")]
 pub(crate) mod new_block_state;
 pub(crate) mod next_change_in_authority;
+#[cfg(feature = "std")]
+pub(crate) mod retention_policy;
+#[cfg(feature = "std")]
+pub(crate) mod storage_window;
@@ -0,0 +1,120 @@
+use parity_scale_codec::{Decode, Encode};
+use sp_runtime::traits::AtLeast32BitUnsigned;
+
+/// Amount of headers kept outside of the window, unless policy sets it.
+pub const DEFAULT_MAX_KEPT_HEADERS: u64 = 1000;
+
+/// Decides which headers are kept once they fall out of the window of the
+/// latest `max_headers_allowed_to_store` headers, which are always kept.
+/// Only finalized headers are kept outside of the window, unfinalized
+/// ones are dropped. Default policy keeps nothing outside of the window.
+#[derive(Debug, Clone, PartialEq, Encode, Decode)]
+pub struct RetentionPolicy<N> {
+    /// Keep headers signaling change of GRANDPA authority set,
+    /// either `ScheduledChange` or `ForcedChange`.
+    pub keep_authority_set_changes: bool,
+    /// Keep finalized headers whose number is a multiple of the interval.
+    pub possible_finalized_header_interval: Option<N>,
+    /// Keep finalized headers less than this many blocks older than
+    /// the last finalized header.
+    pub possible_finalized_headers_depth: Option<N>,
+    /// Maximum amount of headers kept outside of the window, the oldest
+    /// ones are dropped first once there are more of them.
+    pub max_kept_headers: u64,
+}
+
+impl<N> Default for RetentionPolicy<N> {
+    fn default() -> Self {
+        Self {
+            keep_authority_set_changes: false,
+            possible_finalized_header_interval: None,
+            possible_finalized_headers_depth: None,
+            max_kept_headers: DEFAULT_MAX_KEPT_HEADERS,
+        }
+    }
+}
+
+impl<N> RetentionPolicy<N>
+where
+    N: AtLeast32BitUnsigned + Copy,
+{
+    /// Whether header, which fell out of the window, is kept.
+    pub fn keeps(
+        &self,
+        number: N,
+        signals_authority_set_change: bool,
+        possible_finalized_number: Option<N>,
+    ) -> bool {
+        self.keeps_regardless_of_age(
+            number,
+            signals_authority_set_change,
+            possible_finalized_number,
+        ) || self.keeps_recent(number, possible_finalized_number)
+    }
+
+    /// Whether header, which fell out of the window, is kept for signaling
+    /// authority set change or for its number, no matter how old it gets.
+    pub fn keeps_regardless_of_age(
+        &self,
+        number: N,
+        signals_authority_set_change: bool,
+        possible_finalized_number: Option<N>,
+    ) -> bool {
+        match possible_finalized_number {
+            Some(finalized_number) if number <= finalized_number => {}
+            _ => return false,
+        }
+
+        (self.keep_authority_set_changes && signals_authority_set_change)
+            || self
+                .possible_finalized_header_interval
+                .map_or(false, |interval| (number % interval).is_zero())
+    }
+
+    /// Whether header, which fell out of the window, is kept for being
+    /// finalized recently. It stops being kept once it gets too old.
+    pub fn keeps_recent(&self, number: N, possible_finalized_number: Option<N>) -> bool {
+        match possible_finalized_number {
+            Some(finalized_number) if number <= finalized_number => self
+                .possible_finalized_headers_depth
+                .map_or(false, |depth| number + depth > finalized_number),
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::common::types::retention_policy::RetentionPolicy;
+
+    #[test]
+    fn test_retention_policy() {
+        let policy = RetentionPolicy::<u32>::default();
+        assert!(!policy.keeps(4, true, Some(10)));
+
+        let policy = RetentionPolicy::<u32> {
+            keep_authority_set_changes: true,
+            possible_finalized_header_interval: Some(4),
+            possible_finalized_headers_depth: Some(3),
+            max_kept_headers: 10,
+        };
+        // Unfinalized headers are never kept
+        assert!(!policy.keeps(4, true, None));
+        assert!(!policy.keeps(12, true, Some(10)));
+
+        assert!(policy.keeps(3, true, Some(10)));
+        assert!(!policy.keeps(3, false, Some(10)));
+        assert!(policy.keeps(4, false, Some(10)));
+        assert!(!policy.keeps(7, false, Some(10)));
+        assert!(policy.keeps(8, false, Some(10)));
+        assert!(policy.keeps(9, false, Some(10)));
+        assert!(policy.keeps(10, false, Some(10)));
+
+        // Headers kept for being finalized recently are told apart,
+        // as they stop being kept once they get too old
+        assert!(policy.keeps_regardless_of_age(8, false, Some(10)));
+        assert!(!policy.keeps_regardless_of_age(9, false, Some(10)));
+        assert!(policy.keeps_recent(9, Some(10)));
+        assert!(!policy.keeps_recent(9, Some(12)));
+    }
+}
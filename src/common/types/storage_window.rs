@@ -0,0 +1,14 @@
+/// Range of headers currently retained in the storage.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StorageWindow<N> {
+    /// Number of the oldest retained header.
+    pub oldest_number: N,
+    /// Number of the best header, which is the newest retained one.
+    pub best_number: N,
+    /// Number of the last finalized header, or `None` if nothing is finalized yet.
+    pub possible_finalized_number: Option<N>,
+    /// Amount of headers retained in the window.
+    pub total_stored: u64,
+    /// Numbers of older headers kept by retention policy, in ascending order.
+    pub kept_numbers: Vec<N>,
+}
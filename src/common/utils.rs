@@ -1,5 +1,8 @@
+use crate::beefy::BeefyValidatorSet;
 use crate::common::traits::aux_store::AuxStore;
+use crate::common::types::archived_authority_set::ArchivedAuthoritySet;
 use crate::common::types::blockchain_error::BlockchainError;
+use crate::common::types::justification_verification::JustificationVerification;
 use crate::common::types::light_authority_set::LightAuthoritySet;
 use crate::common::types::next_change_in_authority::NextChangeInAuthority;
 use crate::db;
@@ -15,6 +18,21 @@ pub static GRANDPA_AUTHORITY_CHANGE_INTERMEDIATE_KEY: &[u8] = b"grandpa_aci";
 /// LightAuthoritySet is saved under this key in aux storage.
 pub const LIGHT_AUTHORITY_SET_KEY: &[u8] = b"grandpa_voters";
 
+/// ArchivedAuthoritySets are saved under this prefix followed by SCALE encoded set id.
+pub const AUTHORITY_SET_ARCHIVE_PREFIX: &[u8] = b"grandpa_archive";
+
+/// BeefyValidatorSet is saved under this key in aux storage,
+/// only for clients finalizing headers with BEEFY.
+pub const BEEFY_VALIDATOR_SET_KEY: &[u8] = b"beefy_validators";
+
+/// JustificationVerification is saved under this key in aux storage,
+/// `Full` verification is used if it isn't set.
+pub const JUSTIFICATION_VERIFICATION_KEY: &[u8] = b"grandpa_verification";
+
+/// Next BeefyValidatorSet, announced by MMR leaf of a finalized header, is saved
+/// under this key in aux storage until it signs a commitment.
+pub const BEEFY_NEXT_VALIDATOR_SET_KEY: &[u8] = b"beefy_next_validators";
+
 // Columns supported in our in memory db
 pub const NUM_COLUMNS: u32 = 11;
 
@@ -120,3 +138,170 @@ where
 
     Ok(Some(light_authority_set))
 }
+
+fn archived_authority_set_key(set_id: u64) -> Vec<u8> {
+    [AUTHORITY_SET_ARCHIVE_PREFIX, set_id.encode().as_slice()].concat()
+}
+
+pub fn archive_light_authority_set<AS, Block>(
+    aux_store: Arc<AS>,
+    archived_authority_set: &ArchivedAuthoritySet<Block>,
+) -> Result<(), BlockchainError>
+where
+    AS: AuxStore,
+    Block: BlockT,
+{
+    aux_store.insert_aux(
+        &[(
+            archived_authority_set_key(archived_authority_set.authority_set.set_id()).as_slice(),
+            archived_authority_set.encode().as_slice(),
+        )],
+        &[],
+    )
+}
+
+pub fn fetch_archived_authority_set<AS, Block>(
+    aux_store: Arc<AS>,
+    set_id: u64,
+) -> Result<Option<ArchivedAuthoritySet<Block>>, BlockchainError>
+where
+    AS: AuxStore,
+    Block: BlockT,
+{
+    let possible_encoded_authority_set =
+        aux_store.get_aux(archived_authority_set_key(set_id).as_slice())?;
+
+    match possible_encoded_authority_set {
+        None => Ok(None),
+        Some(encoded_authority_set) => {
+            ArchivedAuthoritySet::decode(&mut encoded_authority_set.as_slice())
+                .map(Some)
+                .map_err(|err| {
+                    BlockchainError::Backend(format!(
+                "Unable to decode archived authority set. DB might be corrupted. Underlying Error: {}",
+                err.what()
+            ))
+                })
+        }
+    }
+}
+
+pub fn insert_beefy_validator_set<AS>(
+    aux_store: Arc<AS>,
+    beefy_validator_set: BeefyValidatorSet,
+) -> Result<(), BlockchainError>
+where
+    AS: AuxStore,
+{
+    aux_store.insert_aux(
+        &[(
+            BEEFY_VALIDATOR_SET_KEY,
+            beefy_validator_set.encode().as_slice(),
+        )],
+        &[],
+    )
+}
+
+pub fn fetch_beefy_validator_set<AS>(
+    aux_store: Arc<AS>,
+) -> Result<Option<BeefyValidatorSet>, BlockchainError>
+where
+    AS: AuxStore,
+{
+    let possible_encoded_validator_set = aux_store.get_aux(BEEFY_VALIDATOR_SET_KEY)?;
+
+    match possible_encoded_validator_set {
+        None => Ok(None),
+        Some(encoded_validator_set) => {
+            BeefyValidatorSet::decode(&mut encoded_validator_set.as_slice())
+                .map(Some)
+                .map_err(|err| {
+                    BlockchainError::Backend(format!(
+                "Unable to decode BEEFY validator set. DB might be corrupted. Underlying Error: {}",
+                err.what()
+            ))
+                })
+        }
+    }
+}
+
+pub fn insert_next_beefy_validator_set<AS>(
+    aux_store: Arc<AS>,
+    possible_beefy_validator_set: Option<BeefyValidatorSet>,
+) -> Result<(), BlockchainError>
+where
+    AS: AuxStore,
+{
+    match possible_beefy_validator_set {
+        Some(beefy_validator_set) => aux_store.insert_aux(
+            &[(
+                BEEFY_NEXT_VALIDATOR_SET_KEY,
+                beefy_validator_set.encode().as_slice(),
+            )],
+            &[],
+        ),
+        None => aux_store.insert_aux(&[], &[BEEFY_NEXT_VALIDATOR_SET_KEY]),
+    }
+}
+
+pub fn fetch_next_beefy_validator_set<AS>(
+    aux_store: Arc<AS>,
+) -> Result<Option<BeefyValidatorSet>, BlockchainError>
+where
+    AS: AuxStore,
+{
+    let possible_encoded_validator_set = aux_store.get_aux(BEEFY_NEXT_VALIDATOR_SET_KEY)?;
+
+    match possible_encoded_validator_set {
+        None => Ok(None),
+        Some(encoded_validator_set) => {
+            BeefyValidatorSet::decode(&mut encoded_validator_set.as_slice())
+                .map(Some)
+                .map_err(|err| {
+                    BlockchainError::Backend(format!(
+                "Unable to decode next BEEFY validator set. DB might be corrupted. Underlying Error: {}",
+                err.what()
+            ))
+                })
+        }
+    }
+}
+
+pub fn insert_justification_verification<AS>(
+    aux_store: Arc<AS>,
+    justification_verification: JustificationVerification,
+) -> Result<(), BlockchainError>
+where
+    AS: AuxStore,
+{
+    aux_store.insert_aux(
+        &[(
+            JUSTIFICATION_VERIFICATION_KEY,
+            justification_verification.encode().as_slice(),
+        )],
+        &[],
+    )
+}
+
+pub fn fetch_justification_verification<AS>(
+    aux_store: Arc<AS>,
+) -> Result<JustificationVerification, BlockchainError>
+where
+    AS: AuxStore,
+{
+    let possible_encoded_verification = aux_store.get_aux(JUSTIFICATION_VERIFICATION_KEY)?;
+
+    match possible_encoded_verification {
+        None => Ok(JustificationVerification::default()),
+        Some(encoded_verification) => {
+            JustificationVerification::decode(&mut encoded_verification.as_slice()).map_err(
+                |err| {
+                    BlockchainError::Backend(format!(
+                "Unable to decode justification verification. DB might be corrupted. Underlying Error: {}",
+                err.what()
+            ))
+                },
+            )
+        }
+    }
+}
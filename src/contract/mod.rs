@@ -1,44 +1,180 @@
 pub mod msg;
 mod state;
 
-use cosmwasm_std::{log, Env};
-use cosmwasm_std::{to_vec, Binary};
-use cosmwasm_std::{Api, Extern, ReadonlyStorage, Storage};
+use cosmwasm_std::{from_binary, to_vec, Binary};
+use cosmwasm_std::{log, CanonicalAddr, Env, HumanAddr};
+use cosmwasm_std::{Api, Extern, Order, ReadonlyStorage, Storage};
 use cosmwasm_std::{
     HandleResponse, HandleResult, InitResponse, InitResult, Querier, QueryResult, StdError,
 };
-use cosmwasm_storage::{singleton, singleton_read, ReadonlySingleton, Singleton};
-use parity_scale_codec::Decode;
-use sp_finality_grandpa::AuthorityList;
-use sp_runtime::traits::Header as HeaderT;
+use cosmwasm_storage::{bucket, bucket_read, Bucket, PrefixedStorage, ReadonlyBucket};
+use parity_scale_codec::{Decode, Encode};
+use sp_finality_grandpa::{AuthorityId, AuthorityList};
+use sp_runtime::traits::{BlakeTwo256, Block as BlockT, Hash as HashT, Header as HeaderT};
+use sp_runtime::Justification;
 
+use crate::beefy::{BeefyFinalityProof, BeefyValidatorSet};
+use crate::common::types::block_import_result::BlockImportResult;
+use crate::common::types::client_snapshot::ClientSnapshot;
+use crate::common::types::justification_verification::JustificationVerification;
 use crate::common::types::light_authority_set::LightAuthoritySet;
-use crate::contract::state::{ContractState, H256};
-use crate::light_state::{current_status, ingest_finalized_header, initialize_state};
-use crate::msg::{HandleMsg, InitMsg, LatestHeightResponse, QueryMsg};
-use crate::types::{Block, SignedBlock};
+use crate::common::types::light_client_error::LightClientError;
+use crate::common::types::retention_policy::{RetentionPolicy, DEFAULT_MAX_KEPT_HEADERS};
+use crate::contract::state::{
+    default_max_consensus_states_to_store, ContractState, ParachainState, SnapshotConfig,
+    VersionedSnapshot, H256,
+};
+use crate::light_state::{
+    current_status, export_snapshot, extract_equivocation_proofs, finalized_header_at,
+    ingest_beefy_finalized_header, ingest_finalized_header, initialize_state, read_state_value,
+    report_justification, resize_storage, restore_state, retained_headers, storage_window,
+    verify_archived_justification, verify_event_proof, verify_extrinsic_inclusion,
+    verify_header_inclusion, verify_initial_state, verify_parachain_head,
+};
+use crate::mmr::HeaderMmr;
+use crate::msg::{
+    BeefyValidatorSetInfo, ConfigResponse, ConsensusState, ConsensusStatesResponse,
+    EquivocationsResponse, ExportSnapshotResponse, FinalityGadget, HandleMsg, HeaderInclusionProof,
+    HeaderSummary, HeadersResponse, InitMsg, JustificationReportResponse,
+    JustificationVerificationMode, LatestHeightResponse, ParachainHead, ParachainHeadResponse,
+    QueryMsg, RelayerStats, RetentionPolicyInfo, StorageWindowResponse, UpdateClientResponse,
+    VerifiedWeightInfo, VerifyEventResponse, VerifyExtrinsicResponse, VerifyHeaderResponse,
+    VerifyJustificationResponse, VerifyParachainStorageResponse,
+};
+use crate::types::{Block, BlockNumber, Header, SignedBlock};
+
+pub const PREFIX_CLIENTS: &[u8] = b"clients";
+pub const PREFIX_PARACHAIN_CLIENTS: &[u8] = b"parachain_clients";
+pub const PREFIX_CONSENSUS_STATES: &[u8] = b"consensus_states";
+pub const PREFIX_RELAYER_STATS: &[u8] = b"relayer_stats";
+
+pub const DEFAULT_HEADERS_LIMIT: u32 = 10;
+pub const MAX_HEADERS_LIMIT: u32 = 100;
+
+/// Version of snapshots returned by `ExportSnapshot`
+pub const SNAPSHOT_VERSION: u8 = 1;
+
+fn contract_states<S: Storage>(storage: &mut S) -> Bucket<'_, S, ContractState> {
+    bucket(PREFIX_CLIENTS, storage)
+}
+
+fn read_only_contract_states<S: ReadonlyStorage>(
+    storage: &S,
+) -> ReadonlyBucket<'_, S, ContractState> {
+    bucket_read(PREFIX_CLIENTS, storage)
+}
+
+fn parachain_states<S: Storage>(storage: &mut S) -> Bucket<'_, S, ParachainState> {
+    bucket(PREFIX_PARACHAIN_CLIENTS, storage)
+}
+
+fn read_only_parachain_states<S: ReadonlyStorage>(
+    storage: &S,
+) -> ReadonlyBucket<'_, S, ParachainState> {
+    bucket_read(PREFIX_PARACHAIN_CLIENTS, storage)
+}
 
-pub const PREFIX_CONFIG: &[u8] = b"config";
-pub const PREFIX_MESSAGES: &[u8] = b"messages";
+/// Consensus states of client, keyed by big endian height.
+fn consensus_states<'a, S: Storage>(
+    storage: &'a mut S,
+    client_id: &str,
+) -> Bucket<'a, S, ConsensusState> {
+    Bucket::multilevel(&[PREFIX_CONSENSUS_STATES, client_id.as_bytes()], storage)
+}
+
+fn read_only_consensus_states<'a, S: ReadonlyStorage>(
+    storage: &'a S,
+    client_id: &str,
+) -> ReadonlyBucket<'a, S, ConsensusState> {
+    ReadonlyBucket::multilevel(&[PREFIX_CONSENSUS_STATES, client_id.as_bytes()], storage)
+}
+
+/// Submission stats of relayers of client, keyed by canonical address.
+fn relayer_stats<'a, S: Storage>(
+    storage: &'a mut S,
+    client_id: &str,
+) -> Bucket<'a, S, RelayerStats> {
+    Bucket::multilevel(&[PREFIX_RELAYER_STATS, client_id.as_bytes()], storage)
+}
+
+fn read_only_relayer_stats<'a, S: ReadonlyStorage>(
+    storage: &'a S,
+    client_id: &str,
+) -> ReadonlyBucket<'a, S, RelayerStats> {
+    ReadonlyBucket::multilevel(&[PREFIX_RELAYER_STATS, client_id.as_bytes()], storage)
+}
 
-pub const KEY_STATE_CONS: &[u8] = b"consensus_state";
-pub const KEY_STATE_CLIENT: &[u8] = b"client_state";
+fn load_parachain_state<S: ReadonlyStorage>(
+    storage: &S,
+    client_id: &str,
+) -> Result<ParachainState, StdError> {
+    read_only_parachain_states(storage)
+        .may_load(client_id.as_bytes())?
+        .ok_or_else(|| StdError::GenericErr {
+            msg: format!("Parachain client {} doesn't exist", client_id),
+            backtrace: None,
+        })
+}
 
-fn contract_state<S: Storage>(storage: &mut S) -> Singleton<S, ContractState> {
-    singleton(storage, KEY_STATE_CLIENT)
+/// Checks whether client id is taken, either by
+/// relay chain client or by parachain client.
+fn client_exists<S: ReadonlyStorage>(storage: &S, client_id: &str) -> Result<bool, StdError> {
+    Ok(read_only_contract_states(storage)
+        .may_load(client_id.as_bytes())?
+        .is_some()
+        || read_only_parachain_states(storage)
+            .may_load(client_id.as_bytes())?
+            .is_some())
 }
 
-fn read_only_contract_state<S: ReadonlyStorage>(
+fn load_contract_state<S: ReadonlyStorage>(
     storage: &S,
-) -> ReadonlySingleton<S, ContractState> {
-    singleton_read(storage, KEY_STATE_CLIENT)
+    client_id: &str,
+) -> Result<ContractState, StdError> {
+    read_only_contract_states(storage)
+        .may_load(client_id.as_bytes())?
+        .ok_or_else(|| StdError::GenericErr {
+            msg: format!("Client {} doesn't exist", client_id),
+            backtrace: None,
+        })
 }
 
 pub(crate) fn init<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
-    _env: Env,
+    env: Env,
     msg: InitMsg,
 ) -> InitResult {
+    let admin = env.message.sender;
+    match &msg.snapshot {
+        Some(snapshot) => restore_client(&mut deps.storage, &msg.client_id, snapshot, admin)?,
+        None => create_client(&mut deps.storage, msg, admin)?,
+    }
+
+    Ok(InitResponse::default())
+}
+
+/// Creates new light client under `msg.client_id`, independent of
+/// all other clients of this contract instance, administered by `admin`.
+fn create_client<S: Storage>(
+    storage: &mut S,
+    msg: InitMsg,
+    admin: CanonicalAddr,
+) -> Result<(), StdError> {
+    if !is_valid_client_id(&msg.client_id) {
+        return Err(StdError::ParseErr {
+            target: "msg.client_id".to_string(),
+            msg: "Client id is not in the expected format (1-64 alphanumeric ASCII bytes, '-' or '_')".to_string(),
+            backtrace: None,
+        });
+    }
+
+    if client_exists(storage, &msg.client_id)? {
+        return Err(StdError::GenericErr {
+            msg: format!("Client {} already exists", msg.client_id),
+            backtrace: None,
+        });
+    }
+
     // Check name, symbol, decimals
     if !is_valid_identifier(&msg.name) {
         return Err(StdError::ParseErr {
@@ -56,17 +192,24 @@ pub(crate) fn init<S: Storage, A: Api, Q: Querier>(
         });
     }
 
-    let block_bytes = match hex::decode(&msg.block[2..]) {
-        Ok(bytes) => bytes,
-        Err(e) => {
-            return Err(StdError::ParseErr {
-                target: "msg.block".to_string(),
-                msg: e.to_string(),
-                backtrace: None,
-            })
-        }
+    let max_consensus_states_to_store = msg
+        .max_consensus_states_to_store
+        .unwrap_or_else(default_max_consensus_states_to_store);
+    if max_consensus_states_to_store == 0 {
+        return Err(StdError::ParseErr {
+            target: "msg.max_consensus_states_to_store".to_string(),
+            msg: "max_consensus_states_to_store need to be at least 1".to_string(),
+            backtrace: None,
+        });
+    }
+
+    let retention_policy = match &msg.retention_policy {
+        Some(retention_policy) => decode_retention_policy(retention_policy)?,
+        None => RetentionPolicy::default(),
     };
 
+    let block_bytes = decode_bytes("msg.block", &msg.block)?;
+
     let block = match SignedBlock::decode(&mut block_bytes.as_slice()) {
         Ok(block) => block,
         Err(e) => {
@@ -78,16 +221,7 @@ pub(crate) fn init<S: Storage, A: Api, Q: Querier>(
         }
     };
 
-    let auth_bytes = match hex::decode(&msg.authority_set[2..]) {
-        Ok(bytes) => bytes,
-        Err(e) => {
-            return Err(StdError::ParseErr {
-                target: "msg.authority_set".to_string(),
-                msg: e.to_string(),
-                backtrace: None,
-            })
-        }
-    };
+    let auth_bytes = decode_bytes("msg.authority_set", &msg.authority_set)?;
 
     let authset = match AuthorityList::decode(&mut auth_bytes.as_slice()) {
         Ok(authset) => authset,
@@ -103,18 +237,73 @@ pub(crate) fn init<S: Storage, A: Api, Q: Querier>(
 
     let authority_set = LightAuthoritySet::new(msg.set_id, authset);
 
+    // In trust-minimized mode, authority set needs to prove that it finalized the header
+    match (&msg.justification, &msg.authority_set_proof) {
+        (Some(justification), authority_set_proof) => {
+            let justification = decode_bytes("msg.justification", justification)?;
+            let possible_authority_set_proof = match authority_set_proof {
+                Some(authority_set_proof) => {
+                    let mut proof_nodes = vec![];
+                    for node in authority_set_proof {
+                        proof_nodes.push(decode_bytes("msg.authority_set_proof", node)?);
+                    }
+                    Some(proof_nodes)
+                }
+                None => None,
+            };
+            verify_initial_state(
+                &head,
+                &authority_set,
+                &justification,
+                possible_authority_set_proof,
+            )
+            .map_err(|e| light_client_error("Unable to verify initial state", e))?;
+        }
+        (None, Some(_)) => {
+            return Err(StdError::ParseErr {
+                target: "msg.authority_set_proof".to_string(),
+                msg: "Authority set proof can only be used along with justification".to_string(),
+                backtrace: None,
+            });
+        }
+        (None, None) => {}
+    }
+
+    let finality_gadget = msg.finality_gadget.unwrap_or_default();
+    let possible_beefy_validator_set = match (finality_gadget, &msg.beefy_validator_set) {
+        (FinalityGadget::Beefy, Some(validator_set)) => {
+            Some(decode_beefy_validator_set(validator_set)?)
+        }
+        (FinalityGadget::Beefy, None) => {
+            return Err(StdError::ParseErr {
+                target: "msg.beefy_validator_set".to_string(),
+                msg: "BEEFY validator set is required by BEEFY clients".to_string(),
+                backtrace: None,
+            });
+        }
+        (FinalityGadget::Grandpa, Some(_)) => {
+            return Err(StdError::ParseErr {
+                target: "msg.beefy_validator_set".to_string(),
+                msg: "BEEFY validator set can only be used by BEEFY clients".to_string(),
+                backtrace: None,
+            });
+        }
+        (FinalityGadget::Grandpa, None) => None,
+    };
+
     let light_client_data = match initialize_state(
         head.clone(),
         authority_set,
+        possible_beefy_validator_set,
+        retention_policy,
+        match msg.justification_verification.unwrap_or_default() {
+            JustificationVerificationMode::Full => JustificationVerification::Full,
+            JustificationVerificationMode::Bounded => JustificationVerification::Bounded,
+        },
         msg.max_headers_allowed_to_store,
     ) {
         Ok(state_bytes) => state_bytes,
-        Err(e) => {
-            return Err(StdError::GenericErr {
-                msg: format!("unable to initialize light client. Error: {}", e),
-                backtrace: None,
-            })
-        }
+        Err(e) => return Err(light_client_error("Unable to initialize light client", e)),
     };
 
     let new_contract_state = ContractState {
@@ -123,11 +312,216 @@ pub(crate) fn init<S: Storage, A: Api, Q: Querier>(
         max_headers_allowed_to_store: msg.max_headers_allowed_to_store,
         max_headers_allowed_between_justifications: msg.max_headers_allowed_between_justifications,
         headers_ingested_after_last_justification: 1,
+        finality_gadget,
+        max_consensus_states_to_store,
+        possible_consensus_states_window: None,
+        possible_admin: Some(admin),
+        possible_relayer_allowlist: None,
     };
 
-    contract_state(&mut deps.storage).save(&new_contract_state)?;
+    contract_states(storage).save(msg.client_id.as_bytes(), &new_contract_state)
+}
 
-    Ok(InitResponse::default())
+/// Creates parachain client under `client_id`, which follows heads of
+/// parachain `para_id` proven against relay chain client `relay_client_id`.
+fn create_parachain_client<S: Storage>(
+    storage: &mut S,
+    client_id: &str,
+    name: String,
+    relay_client_id: String,
+    para_id: u32,
+) -> Result<(), StdError> {
+    if !is_valid_client_id(client_id) {
+        return Err(StdError::ParseErr {
+            target: "client_id".to_string(),
+            msg: "Client id is not in the expected format (1-64 alphanumeric ASCII bytes, '-' or '_')".to_string(),
+            backtrace: None,
+        });
+    }
+
+    if client_exists(storage, client_id)? {
+        return Err(StdError::GenericErr {
+            msg: format!("Client {} already exists", client_id),
+            backtrace: None,
+        });
+    }
+
+    if !is_valid_identifier(&name) {
+        return Err(StdError::ParseErr {
+            target: "name".to_string(),
+            msg: "Name is not in the expected format (8-20 lowercase UTF-8 bytes)".to_string(),
+            backtrace: None,
+        });
+    }
+
+    // Relay chain client needs to exist, parachain heads can't be proven otherwise
+    load_contract_state(storage, &relay_client_id)?;
+
+    let new_parachain_state = ParachainState {
+        name,
+        relay_client_id,
+        para_id,
+        possible_latest_head: None,
+    };
+
+    parachain_states(storage).save(client_id.as_bytes(), &new_parachain_state)
+}
+
+/// Restores client under `client_id` from snapshot returned by `ExportSnapshot`,
+/// after checking its version and integrity hash. Admin of the client isn't
+/// carried by snapshots, restored client is administered by `admin`.
+fn restore_client<S: Storage>(
+    storage: &mut S,
+    client_id: &str,
+    snapshot: &str,
+    admin: CanonicalAddr,
+) -> Result<(), StdError> {
+    if !is_valid_client_id(client_id) {
+        return Err(StdError::ParseErr {
+            target: "client_id".to_string(),
+            msg: "Client id is not in the expected format (1-64 alphanumeric ASCII bytes, '-' or '_')".to_string(),
+            backtrace: None,
+        });
+    }
+
+    if client_exists(storage, client_id)? {
+        return Err(StdError::GenericErr {
+            msg: format!("Client {} already exists", client_id),
+            backtrace: None,
+        });
+    }
+
+    let snapshot_bytes = decode_bytes("snapshot", snapshot)?;
+    // Version is checked first, as later versions may not decode as the current one
+    match u8::decode(&mut snapshot_bytes.as_slice()) {
+        Ok(SNAPSHOT_VERSION) => {}
+        Ok(version) => {
+            return Err(StdError::ParseErr {
+                target: "snapshot".to_string(),
+                msg: format!(
+                    "Unsupported snapshot version {}, expected {}",
+                    version, SNAPSHOT_VERSION
+                ),
+                backtrace: None,
+            })
+        }
+        Err(e) => {
+            return Err(StdError::ParseErr {
+                target: "snapshot".to_string(),
+                msg: format!("Unable to construct snapshot from bytes. Error: {}", e),
+                backtrace: None,
+            })
+        }
+    }
+    let snapshot = match VersionedSnapshot::decode(&mut snapshot_bytes.as_slice()) {
+        Ok(snapshot) => snapshot,
+        Err(e) => {
+            return Err(StdError::ParseErr {
+                target: "snapshot".to_string(),
+                msg: format!("Unable to construct snapshot from bytes. Error: {}", e),
+                backtrace: None,
+            })
+        }
+    };
+    if snapshot_integrity_hash(snapshot.version, &snapshot.config, &snapshot.client)
+        != snapshot.integrity_hash
+    {
+        return Err(StdError::ParseErr {
+            target: "snapshot".to_string(),
+            msg: "Snapshot doesn't match its integrity hash".to_string(),
+            backtrace: None,
+        });
+    }
+
+    let config = snapshot.config;
+    let light_client_data = restore_state(snapshot.client, config.max_headers_allowed_to_store)
+        .map_err(|e| light_client_error("Unable to restore light client", e))?;
+
+    let new_contract_state = ContractState {
+        name: config.name,
+        light_client_data,
+        max_headers_allowed_to_store: config.max_headers_allowed_to_store,
+        max_headers_allowed_between_justifications: config
+            .max_headers_allowed_between_justifications,
+        headers_ingested_after_last_justification: config.headers_ingested_after_last_justification,
+        finality_gadget: config.finality_gadget,
+        max_consensus_states_to_store: config.max_consensus_states_to_store,
+        possible_consensus_states_window: None,
+        possible_admin: Some(admin),
+        possible_relayer_allowlist: None,
+    };
+
+    contract_states(storage).save(client_id.as_bytes(), &new_contract_state)
+}
+
+fn snapshot_integrity_hash(
+    version: u8,
+    config: &SnapshotConfig,
+    client: &ClientSnapshot<Block>,
+) -> sp_core::H256 {
+    BlakeTwo256::hash_of(&(version, config, client))
+}
+
+fn decode_retention_policy(
+    retention_policy: &RetentionPolicyInfo,
+) -> Result<RetentionPolicy<BlockNumber>, StdError> {
+    if retention_policy.finalized_header_interval == Some(0) {
+        return Err(StdError::ParseErr {
+            target: "msg.retention_policy.finalized_header_interval".to_string(),
+            msg: "finalized_header_interval need to be at least 1".to_string(),
+            backtrace: None,
+        });
+    }
+    if retention_policy.finalized_headers_depth == Some(0) {
+        return Err(StdError::ParseErr {
+            target: "msg.retention_policy.finalized_headers_depth".to_string(),
+            msg: "finalized_headers_depth need to be at least 1".to_string(),
+            backtrace: None,
+        });
+    }
+
+    if retention_policy.max_kept_headers == Some(0) {
+        return Err(StdError::ParseErr {
+            target: "msg.retention_policy.max_kept_headers".to_string(),
+            msg: "max_kept_headers need to be at least 1".to_string(),
+            backtrace: None,
+        });
+    }
+
+    Ok(RetentionPolicy {
+        keep_authority_set_changes: retention_policy.keep_authority_set_changes,
+        possible_finalized_header_interval: retention_policy.finalized_header_interval,
+        possible_finalized_headers_depth: retention_policy.finalized_headers_depth,
+        max_kept_headers: retention_policy
+            .max_kept_headers
+            .unwrap_or(DEFAULT_MAX_KEPT_HEADERS),
+    })
+}
+
+fn decode_beefy_validator_set(
+    validator_set: &BeefyValidatorSetInfo,
+) -> Result<BeefyValidatorSet, StdError> {
+    if validator_set.len == 0 {
+        return Err(StdError::ParseErr {
+            target: "msg.beefy_validator_set.len".to_string(),
+            msg: "BEEFY validator set can't be empty".to_string(),
+            backtrace: None,
+        });
+    }
+    let root_bytes = decode_bytes("msg.beefy_validator_set.root", &validator_set.root)?;
+    if root_bytes.len() != 32 {
+        return Err(StdError::ParseErr {
+            target: "msg.beefy_validator_set.root".to_string(),
+            msg: format!("Expected 32 bytes, got {}", root_bytes.len()),
+            backtrace: None,
+        });
+    }
+
+    Ok(BeefyValidatorSet {
+        id: validator_set.id,
+        len: validator_set.len,
+        root: sp_core::H256::from_slice(&root_bytes),
+    })
 }
 
 pub(crate) fn handle<S: Storage, A: Api, Q: Querier>(
@@ -136,10 +530,139 @@ pub(crate) fn handle<S: Storage, A: Api, Q: Querier>(
     msg: HandleMsg,
 ) -> HandleResult {
     match msg {
+        HandleMsg::CreateClient {
+            client_id,
+            name,
+            block,
+            set_id,
+            authority_set,
+            max_headers_allowed_to_store,
+            max_headers_allowed_between_justifications,
+            justification,
+            authority_set_proof,
+            finality_gadget,
+            beefy_validator_set,
+            max_consensus_states_to_store,
+            retention_policy,
+            justification_verification,
+        } => {
+            create_client(
+                &mut deps.storage,
+                InitMsg {
+                    client_id: client_id.clone(),
+                    name,
+                    block,
+                    set_id,
+                    authority_set,
+                    max_headers_allowed_to_store,
+                    max_headers_allowed_between_justifications,
+                    justification,
+                    authority_set_proof,
+                    finality_gadget,
+                    beefy_validator_set,
+                    max_consensus_states_to_store,
+                    retention_policy,
+                    justification_verification,
+                    snapshot: None,
+                },
+                env.message.sender,
+            )?;
+
+            Ok(HandleResponse {
+                messages: vec![],
+                log: vec![log("action", "create_client"), log("client_id", client_id)],
+                data: None,
+            })
+        }
         HandleMsg::UpdateClient {
+            client_id,
             block,
             authority_set,
-        } => try_block(deps, env, &block, &authority_set),
+        } => try_block(deps, env, &client_id, &block, &authority_set),
+        HandleMsg::UpdateHeader {
+            client_id,
+            header,
+            justification,
+        } => try_header_with_justification(deps, env, &client_id, &header, justification.as_ref()),
+        HandleMsg::UpdateBeefy {
+            client_id,
+            header,
+            finality_proof,
+        } => try_header_with_beefy_proof(deps, env, &client_id, &header, &finality_proof),
+        HandleMsg::CreateParachainClient {
+            client_id,
+            name,
+            relay_client_id,
+            para_id,
+        } => {
+            create_parachain_client(
+                &mut deps.storage,
+                &client_id,
+                name,
+                relay_client_id.clone(),
+                para_id,
+            )?;
+
+            Ok(HandleResponse {
+                messages: vec![],
+                log: vec![
+                    log("action", "create_parachain_client"),
+                    log("client_id", client_id),
+                    log("relay_client_id", relay_client_id),
+                    log("para_id", para_id.to_string()),
+                ],
+                data: None,
+            })
+        }
+        HandleMsg::UpdateParachain {
+            client_id,
+            relay_height,
+            relay_header_proof,
+            header,
+            proof,
+        } => try_parachain_header(
+            deps,
+            &client_id,
+            relay_height,
+            relay_header_proof.as_ref(),
+            &header,
+            &proof,
+        ),
+        HandleMsg::RestoreClient {
+            client_id,
+            snapshot,
+        } => {
+            restore_client(&mut deps.storage, &client_id, &snapshot, env.message.sender)?;
+
+            Ok(HandleResponse {
+                messages: vec![],
+                log: vec![log("action", "restore_client"), log("client_id", client_id)],
+                data: None,
+            })
+        }
+        HandleMsg::UpdateConfig {
+            client_id,
+            max_headers_allowed_to_store,
+            max_headers_allowed_between_justifications,
+            max_consensus_states_to_store,
+            new_admin,
+            renounce_admin,
+        } => try_update_config(
+            deps,
+            env,
+            &client_id,
+            max_headers_allowed_to_store,
+            max_headers_allowed_between_justifications,
+            max_consensus_states_to_store,
+            new_admin.as_ref(),
+            renounce_admin,
+        ),
+        HandleMsg::UpdateRelayers {
+            client_id,
+            allowlist_enabled,
+            add,
+            remove,
+        } => try_update_relayers(deps, env, &client_id, allowlist_enabled, &add, &remove),
     }
 }
 
@@ -148,16 +671,11 @@ pub(crate) fn query<S: Storage, A: Api, Q: Querier>(
     msg: QueryMsg,
 ) -> QueryResult {
     match msg {
-        QueryMsg::LatestHeight {} => {
-            let state = read_only_contract_state(&deps.storage).load()?;
-
-            let light_client_status =
-                current_status::<Block>(state.light_client_data).map_err(|e| {
-                    StdError::GenericErr {
-                        msg: format!("Unable to get current status. Error: {}", e),
-                        backtrace: None,
-                    }
-                })?;
+        QueryMsg::LatestHeight { client_id } => {
+            let state = load_contract_state(&deps.storage, &client_id)?;
+
+            let light_client_status = current_status::<Block>(state.light_client_data)
+                .map_err(|e| light_client_error("Unable to get current status", e))?;
 
             let best_header_number = light_client_status
                 .possible_best_header
@@ -188,233 +706,3121 @@ pub(crate) fn query<S: Storage, A: Api, Q: Querier>(
             })?);
             Ok(out)
         }
-    }
-}
+        QueryMsg::Headers {
+            client_id,
+            start_height,
+            limit,
+            reverse,
+        } => query_headers(
+            deps,
+            &client_id,
+            start_height,
+            limit,
+            reverse.unwrap_or(false),
+        ),
+        QueryMsg::StorageWindow { client_id } => query_storage_window(deps, &client_id),
+        QueryMsg::VerifyHeader {
+            client_id,
+            header_proof,
+        } => query_header(deps, &client_id, &header_proof),
+        QueryMsg::Equivocations {
+            client_id,
+            justifications,
+        } => query_equivocations(deps, &client_id, &justifications),
+        QueryMsg::VerifyJustification {
+            client_id,
+            set_id,
+            justification,
+        } => query_justification(deps, &client_id, set_id, &justification),
+        QueryMsg::JustificationReport {
+            client_id,
+            set_id,
+            justification,
+        } => query_justification_report(deps, &client_id, set_id, &justification),
+        QueryMsg::VerifyExtrinsic {
+            client_id,
+            height,
+            header_proof,
+            extrinsic_index,
+            extrinsic,
+            proof,
+        } => query_extrinsic(
+            deps,
+            &client_id,
+            height,
+            header_proof.as_ref(),
+            extrinsic_index,
+            &extrinsic,
+            &proof,
+        ),
+        QueryMsg::ParachainHead { client_id } => {
+            let parachain_state = load_parachain_state(&deps.storage, &client_id)?;
 
-fn try_block<S: Storage, A: Api, Q: Querier>(
-    deps: &mut Extern<S, A, Q>,
-    _env: Env,
-    block: &String,
-    _authority_set: &String,
-) -> HandleResult {
-    let state = contract_state(&mut deps.storage).load()?;
-    let block_bytes = match hex::decode(&block[2..]) {
-        Ok(bytes) => bytes,
-        Err(e) => {
-            return Err(StdError::ParseErr {
-                target: "block".to_string(),
-                msg: e.to_string(),
+            Ok(Binary(to_vec(&ParachainHeadResponse {
+                para_id: parachain_state.para_id,
+                relay_client_id: parachain_state.relay_client_id,
+                head: parachain_state.possible_latest_head,
+            })?))
+        }
+        QueryMsg::VerifyParachainStorage {
+            client_id,
+            key,
+            proof,
+        } => query_parachain_storage(deps, &client_id, &key, &proof),
+        QueryMsg::VerifyEvent {
+            client_id,
+            height,
+            header_proof,
+            proof,
+            event_index,
+            record_lengths,
+            pallet_index,
+            variant_index,
+        } => query_event(
+            deps,
+            &client_id,
+            height,
+            header_proof.as_ref(),
+            &proof,
+            event_index,
+            &record_lengths,
+            pallet_index,
+            variant_index,
+        ),
+        QueryMsg::ConsensusState { client_id, height } => {
+            load_contract_state(&deps.storage, &client_id)?;
+            let possible_consensus_state = read_only_consensus_states(&deps.storage, &client_id)
+                .may_load(&height.to_be_bytes())?;
+            let consensus_state = possible_consensus_state.ok_or_else(|| StdError::GenericErr {
+                msg: format!(
+                    "Client {} has no consensus state at height {}",
+                    client_id, height
+                ),
                 backtrace: None,
-            })
+            })?;
+
+            Ok(Binary(to_vec(&consensus_state)?))
         }
-    };
+        QueryMsg::ConsensusStates {
+            client_id,
+            start_height,
+            limit,
+            reverse,
+        } => query_consensus_states(
+            deps,
+            &client_id,
+            start_height,
+            limit,
+            reverse.unwrap_or(false),
+        ),
+        QueryMsg::ExportSnapshot { client_id } => query_snapshot(deps, &client_id),
+        QueryMsg::Config { client_id } => {
+            let state = load_contract_state(&deps.storage, &client_id)?;
+            let admin = match &state.possible_admin {
+                Some(admin) => Some(deps.api.human_address(admin)?),
+                None => None,
+            };
 
-    let block = match SignedBlock::decode(&mut block_bytes.as_slice()) {
-        Ok(block) => block,
-        Err(e) => {
-            return Err(StdError::ParseErr {
-                target: "block".to_string(),
-                msg: format!("Unable to construct block from bytes. Error: {}", e),
-                backtrace: None,
-            })
+            let relayer_allowlist = match &state.possible_relayer_allowlist {
+                Some(allowlist) => Some(
+                    allowlist
+                        .iter()
+                        .map(|relayer| deps.api.human_address(relayer))
+                        .collect::<Result<Vec<_>, _>>()?,
+                ),
+                None => None,
+            };
+
+            Ok(Binary(to_vec(&ConfigResponse {
+                admin,
+                max_headers_allowed_to_store: state.max_headers_allowed_to_store,
+                max_headers_allowed_between_justifications: state
+                    .max_headers_allowed_between_justifications,
+                max_consensus_states_to_store: state.max_consensus_states_to_store,
+                finality_gadget: state.finality_gadget,
+                relayer_allowlist,
+            })?))
         }
-    };
+        QueryMsg::RelayerStats { client_id, relayer } => {
+            load_contract_state(&deps.storage, &client_id)?;
+            let relayer = deps.api.canonical_address(&relayer)?;
+            let stats = read_only_relayer_stats(&deps.storage, &client_id)
+                .may_load(relayer.as_slice())?
+                .unwrap_or_default();
 
-    if block.justification.is_none()
-        && state.headers_ingested_after_last_justification
-            >= state.max_headers_allowed_between_justifications
-    {
-        return Err(StdError::GenericErr {
-            msg: format!(
-                "Cannot ingest header without justification. Max headers allowed: {}, Already ingested: {}",
-                state.max_headers_allowed_between_justifications,
-                state.headers_ingested_after_last_justification
-            ),
-            backtrace: None,
-        });
+            Ok(Binary(to_vec(&stats)?))
+        }
     }
+}
 
-    let headers_ingested_after_last_justification = if block.justification.is_some() {
-        0
-    } else {
-        state.headers_ingested_after_last_justification + 1
-    };
+fn query_headers<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    client_id: &str,
+    start_height: Option<BlockNumber>,
+    limit: Option<u32>,
+    reverse: bool,
+) -> QueryResult {
+    let state = load_contract_state(&deps.storage, client_id)?;
+    let limit = limit
+        .unwrap_or(DEFAULT_HEADERS_LIMIT)
+        .min(MAX_HEADERS_LIMIT);
 
-    let header = block.block.header.clone();
+    let (headers, window) = retained_headers(state.light_client_data, start_height, limit, reverse)
+        .map_err(|e| light_client_error("Unable to get headers", e))?;
 
-    let (_result, updated_light_client_data) = match ingest_finalized_header(
-        state.light_client_data.clone(),
-        header.clone(),
-        block.justification,
-        state.max_headers_allowed_to_store,
-    ) {
-        Ok(result) => result,
-        Err(e) => {
-            return Err(StdError::GenericErr {
-                msg: format!("Unable to ingest header. Error: {}", e),
-                backtrace: None,
-            })
+    // Walk can continue if page is full and hasn't reached the end of the window
+    let next_height = match headers.last() {
+        Some(last) if headers.len() == limit as usize => {
+            if reverse && last.number > window.oldest_number {
+                Some(last.number - 1)
+            } else if !reverse && last.number < window.best_number {
+                Some(last.number + 1)
+            } else {
+                None
+            }
         }
+        _ => None,
     };
 
-    let new_contract_state = ContractState {
-        name: state.name,
-        light_client_data: updated_light_client_data,
-        headers_ingested_after_last_justification,
-        ..state
-    };
+    Ok(Binary(to_vec(&HeadersResponse {
+        headers: headers
+            .iter()
+            .map(|header| HeaderSummary {
+                number: header.number,
+                hash: header.hash().as_bytes().to_vec(),
+                parent_hash: header.parent_hash.as_bytes().to_vec(),
+                state_root: header.state_root.as_bytes().to_vec(),
+                finalized: Some(header.number) <= window.possible_finalized_number,
+            })
+            .collect(),
+        next_height,
+    })?))
+}
 
-    contract_state(&mut deps.storage).save(&new_contract_state)?;
+fn query_consensus_states<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    client_id: &str,
+    start_height: Option<BlockNumber>,
+    limit: Option<u32>,
+    reverse: bool,
+) -> QueryResult {
+    let state = load_contract_state(&deps.storage, client_id)?;
+    let limit = limit
+        .unwrap_or(DEFAULT_HEADERS_LIMIT)
+        .min(MAX_HEADERS_LIMIT);
+    if state.possible_consensus_states_window.is_none() {
+        return Ok(Binary(to_vec(&ConsensusStatesResponse {
+            consensus_states: vec![],
+            next_height: None,
+        })?));
+    }
 
-    let res = HandleResponse {
-        messages: vec![],
-        log: vec![
-            log("action", "block"),
-            log("height", header.number.to_string()),
-        ],
-        data: None,
+    // Heights of pruned headers which got finalized are missing within the window,
+    // so stored consensus states are ranged over instead of every height. Keys are
+    // big endian heights, whose byte order is the order of heights.
+    let bucket = read_only_consensus_states(&deps.storage, client_id);
+    let consensus_states_range = if reverse {
+        let end_key = start_height
+            .and_then(|height| height.checked_add(1))
+            .map(|height| height.to_be_bytes());
+        bucket.range(
+            None,
+            end_key.as_ref().map(|key| &key[..]),
+            Order::Descending,
+        )?
+    } else {
+        let start_key = start_height.map(|height| height.to_be_bytes());
+        bucket.range(
+            start_key.as_ref().map(|key| &key[..]),
+            None,
+            Order::Ascending,
+        )?
     };
-    Ok(res)
+    let mut consensus_states = vec![];
+    let mut next_height = None;
+    for item in consensus_states_range {
+        let (_, consensus_state) = item?;
+        if consensus_states.len() == limit as usize {
+            next_height = Some(consensus_state.height);
+            break;
+        }
+        consensus_states.push(consensus_state);
+    }
+
+    Ok(Binary(to_vec(&ConsensusStatesResponse {
+        consensus_states,
+        next_height,
+    })?))
 }
 
-fn is_valid_identifier(name: &str) -> bool {
-    let bytes = name.as_bytes();
-    if bytes.len() < 8 || bytes.len() > 20 {
-        return false; // length invalid
+fn query_storage_window<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    client_id: &str,
+) -> QueryResult {
+    let state = load_contract_state(&deps.storage, client_id)?;
+    let window = storage_window(state.light_client_data)
+        .map_err(|e| light_client_error("Unable to get storage window", e))?;
+
+    Ok(Binary(to_vec(&StorageWindowResponse {
+        oldest_height: window.oldest_number,
+        newest_height: window.best_number,
+        last_finalized_height: window.possible_finalized_number,
+        total_stored: window.total_stored,
+        capacity: state.max_headers_allowed_to_store,
+        kept_heights: window.kept_numbers,
+    })?))
+}
+
+fn query_header<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    client_id: &str,
+    header_proof: &HeaderInclusionProof,
+) -> QueryResult {
+    let state = load_contract_state(&deps.storage, client_id)?;
+    let (header, header_mmr) = verify_header_proof(state.light_client_data, header_proof)?;
+
+    Ok(Binary(to_vec(&VerifyHeaderResponse {
+        height: header.number,
+        header_hash: header.hash().as_bytes().to_vec(),
+        state_root: header.state_root.as_bytes().to_vec(),
+        mmr_root: header_mmr
+            .root::<BlakeTwo256>()
+            .map_or(H256::default(), |r| r.as_bytes().to_vec()),
+        mmr_leaf_count: header_mmr.leaf_count(),
+    })?))
+}
+
+fn query_equivocations<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    client_id: &str,
+    justifications: &[String],
+) -> QueryResult {
+    let state = load_contract_state(&deps.storage, client_id)?;
+
+    let mut encoded_justifications = vec![];
+    for justification in justifications {
+        encoded_justifications.push(decode_bytes("justifications", justification)?);
     }
-    for byte in bytes {
-        if byte > &122 || byte < &97 {
-            return false; // not lowercase ascii
+
+    let equivocation_proofs =
+        extract_equivocation_proofs(state.light_client_data, encoded_justifications)
+            .map_err(|e| light_client_error("Unable to extract equivocations", e))?;
+
+    Ok(Binary(to_vec(&EquivocationsResponse {
+        equivocation_proofs: equivocation_proofs
+            .iter()
+            .map(|proof| "0x".to_string() + hex::encode(proof.encode()).as_ref())
+            .collect(),
+    })?))
+}
+
+fn query_justification<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    client_id: &str,
+    set_id: u64,
+    justification: &str,
+) -> QueryResult {
+    let state = load_contract_state(&deps.storage, client_id)?;
+    let justification = decode_bytes("justification", justification)?;
+    let (archived_authority_set, grandpa_justification) =
+        verify_archived_justification(state.light_client_data, set_id, &justification)
+            .map_err(|e| light_client_error("Unable to verify justification", e))?;
+
+    Ok(Binary(to_vec(&VerifyJustificationResponse {
+        set_id,
+        activation_height: archived_authority_set.activation_number,
+        activation_hash: archived_authority_set.activation_hash.as_bytes().to_vec(),
+        target_height: grandpa_justification.commit.target_number,
+        target_hash: grandpa_justification.commit.target_hash.as_bytes().to_vec(),
+    })?))
+}
+
+fn query_justification_report<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    client_id: &str,
+    set_id: Option<u64>,
+    justification: &str,
+) -> QueryResult {
+    let state = load_contract_state(&deps.storage, client_id)?;
+    let justification = decode_bytes("justification", justification)?;
+    let (archived_authority_set, report) =
+        report_justification(state.light_client_data, set_id, &justification)
+            .map_err(|e| light_client_error("Unable to report justification", e))?;
+
+    let encode_ids = |ids: &[AuthorityId]| -> Vec<String> {
+        ids.iter()
+            .map(|id| "0x".to_string() + hex::encode(id).as_ref())
+            .collect()
+    };
+    Ok(Binary(to_vec(&JustificationReportResponse {
+        set_id: archived_authority_set.authority_set.set_id(),
+        valid: report.is_valid(),
+        round: report.round,
+        target_height: report.target_number,
+        target_hash: report.target_hash.as_bytes().to_vec(),
+        signing_authorities: encode_ids(&report.signing_authorities),
+        signed_weight: report.signed_weight,
+        required_weight: report.required_weight,
+        total_weight: report.total_weight,
+        duplicate_voters: encode_ids(&report.duplicate_voters),
+        unknown_voters: encode_ids(&report.unknown_voters),
+        invalid_signatures: report
+            .invalid_signatures
+            .iter()
+            .map(|index| *index as u64)
+            .collect(),
+        unrelated_precommits: report
+            .unrelated_precommits
+            .iter()
+            .map(|index| *index as u64)
+            .collect(),
+        unused_ancestry_headers: report
+            .unused_ancestry_headers
+            .iter()
+            .map(|hash| hash.as_bytes().to_vec())
+            .collect(),
+    })?))
+}
+
+#[allow(clippy::too_many_arguments)]
+fn query_event<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    client_id: &str,
+    height: BlockNumber,
+    header_proof: Option<&HeaderInclusionProof>,
+    proof: &[String],
+    event_index: u32,
+    record_lengths: &[u32],
+    pallet_index: Option<u8>,
+    variant_index: Option<u8>,
+) -> QueryResult {
+    let state = load_contract_state(&deps.storage, client_id)?;
+    let header = finalized_header(state.light_client_data, height, header_proof)?;
+
+    let mut proof_nodes = vec![];
+    for node in proof {
+        proof_nodes.push(decode_bytes("proof", node)?);
+    }
+
+    let event_record = verify_event_proof(
+        &header,
+        proof_nodes,
+        event_index,
+        record_lengths,
+        pallet_index,
+        variant_index,
+    )
+    .map_err(|e| light_client_error("Unable to verify event", e))?;
+
+    Ok(Binary(to_vec(&VerifyEventResponse {
+        event_record: "0x".to_string() + hex::encode(event_record).as_ref(),
+    })?))
+}
+
+fn query_extrinsic<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    client_id: &str,
+    height: BlockNumber,
+    header_proof: Option<&HeaderInclusionProof>,
+    extrinsic_index: u32,
+    extrinsic: &str,
+    proof: &[String],
+) -> QueryResult {
+    let state = load_contract_state(&deps.storage, client_id)?;
+    let header = finalized_header(state.light_client_data, height, header_proof)?;
+
+    let extrinsic = decode_bytes("extrinsic", extrinsic)?;
+    let mut proof_nodes = vec![];
+    for node in proof {
+        proof_nodes.push(decode_bytes("proof", node)?);
+    }
+
+    verify_extrinsic_inclusion(&header, proof_nodes, extrinsic_index, &extrinsic)
+        .map_err(|e| light_client_error("Unable to verify extrinsic", e))?;
+
+    Ok(Binary(to_vec(&VerifyExtrinsicResponse {
+        header_hash: header.hash().as_bytes().to_vec(),
+        extrinsics_root: header.extrinsics_root.as_bytes().to_vec(),
+        extrinsic_hash: BlakeTwo256::hash(&extrinsic).as_bytes().to_vec(),
+    })?))
+}
+
+fn query_parachain_storage<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    client_id: &str,
+    key: &str,
+    proof: &[String],
+) -> QueryResult {
+    let parachain_state = load_parachain_state(&deps.storage, client_id)?;
+    let head = parachain_state
+        .possible_latest_head
+        .ok_or_else(|| StdError::GenericErr {
+            msg: format!("Parachain client {} has no head yet", client_id),
+            backtrace: None,
+        })?;
+
+    let key = decode_bytes("key", key)?;
+    let mut proof_nodes = vec![];
+    for node in proof {
+        proof_nodes.push(decode_bytes("proof", node)?);
+    }
+
+    let state_root = sp_core::H256::from_slice(&head.state_root);
+    let possible_value = read_state_value(&state_root, proof_nodes, &key)
+        .map_err(|e| light_client_error("Unable to verify parachain storage", e))?;
+
+    Ok(Binary(to_vec(&VerifyParachainStorageResponse {
+        head,
+        value: possible_value.map(|value| "0x".to_string() + hex::encode(value).as_ref()),
+    })?))
+}
+
+fn query_snapshot<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    client_id: &str,
+) -> QueryResult {
+    let state = load_contract_state(&deps.storage, client_id)?;
+    let client = export_snapshot(state.light_client_data)
+        .map_err(|e| light_client_error("Unable to export snapshot", e))?;
+    let config = SnapshotConfig {
+        name: state.name,
+        max_headers_allowed_to_store: state.max_headers_allowed_to_store,
+        max_headers_allowed_between_justifications: state
+            .max_headers_allowed_between_justifications,
+        headers_ingested_after_last_justification: state.headers_ingested_after_last_justification,
+        finality_gadget: state.finality_gadget,
+        max_consensus_states_to_store: state.max_consensus_states_to_store,
+    };
+    let integrity_hash = snapshot_integrity_hash(SNAPSHOT_VERSION, &config, &client);
+    let snapshot = VersionedSnapshot {
+        version: SNAPSHOT_VERSION,
+        config,
+        client,
+        integrity_hash,
+    };
+
+    Ok(Binary(to_vec(&ExportSnapshotResponse {
+        snapshot: "0x".to_string() + hex::encode(snapshot.encode()).as_ref(),
+    })?))
+}
+
+/// Fetches finalized header at given height. Headers which are pruned from
+/// storage can still be used with inclusion proof against MMR of finalized headers.
+fn finalized_header(
+    light_client_data: Vec<u8>,
+    height: BlockNumber,
+    header_proof: Option<&HeaderInclusionProof>,
+) -> Result<Header, StdError> {
+    match header_proof {
+        Some(header_proof) => {
+            let (header, _) = verify_header_proof(light_client_data, header_proof)?;
+            if header.number != height {
+                return Err(StdError::GenericErr {
+                    msg: format!(
+                        "Header in header proof is at height {}, expected {}",
+                        header.number, height
+                    ),
+                    backtrace: None,
+                });
+            }
+            Ok(header)
         }
+        None => finalized_header_at(light_client_data, height)
+            .map_err(|e| light_client_error("Unable to get finalized header", e)),
     }
-    return true;
 }
 
-#[cfg(test)]
-mod tests {
-    use crate::contract::msg::{LatestHeightResponse, QueryMsg};
-    use crate::contract::{handle, init, query, read_only_contract_state};
-    use crate::msg::{HandleMsg, InitMsg};
-    use crate::types::SignedBlock;
-    use cosmwasm_std::testing::{MockApi, MockQuerier, MockStorage};
-    use cosmwasm_std::{from_binary, Env};
-    use cosmwasm_std::{Extern, StdError};
-    use hex::encode;
-    use parity_scale_codec::{Decode, Encode};
-    use serde_json::{from_str, to_string};
-    use sp_finality_grandpa::{AuthorityList, VersionedAuthorityList};
+fn verify_header_proof(
+    light_client_data: Vec<u8>,
+    header_proof: &HeaderInclusionProof,
+) -> Result<(Header, HeaderMmr<<Block as BlockT>::Hash>), StdError> {
+    let header_bytes = decode_bytes("header_proof.header", &header_proof.header)?;
+    let header = match Header::decode(&mut header_bytes.as_slice()) {
+        Ok(header) => header,
+        Err(e) => {
+            return Err(StdError::ParseErr {
+                target: "header_proof.header".to_string(),
+                msg: format!("Unable to construct header from bytes. Error: {}", e),
+                backtrace: None,
+            })
+        }
+    };
+
+    let mut proof = vec![];
+    for item in header_proof.proof.iter() {
+        let item_bytes = decode_bytes("header_proof.proof", item)?;
+        match <Block as BlockT>::Hash::decode(&mut item_bytes.as_slice()) {
+            Ok(hash) => proof.push(hash),
+            Err(e) => {
+                return Err(StdError::ParseErr {
+                    target: "header_proof.proof".to_string(),
+                    msg: e.to_string(),
+                    backtrace: None,
+                })
+            }
+        }
+    }
+
+    let header_mmr =
+        verify_header_inclusion(light_client_data, &header, header_proof.leaf_index, &proof)
+            .map_err(|e| light_client_error("Unable to verify header inclusion", e))?;
+
+    Ok((header, header_mmr))
+}
+
+/// Converts light client error into contract error, prefixing the message
+/// with stable error code in square brackets, e.g. `[104] Unable to ingest header...`.
+fn light_client_error<E: Into<LightClientError>>(context: &str, error: E) -> StdError {
+    let error = error.into();
+    StdError::GenericErr {
+        msg: format!("[{}] {}. Error: {}", error.code(), context, error),
+        backtrace: None,
+    }
+}
+
+/// Decodes binary input, which is either `0x` prefixed hex or base64
+/// (as `cosmwasm_std::Binary` is serialized). Base64 is preferred for large
+/// inputs like justifications, as it is a third shorter than hex. Base64 may
+/// start with `0x` as well, so it is tried whenever input isn't valid hex.
+fn decode_bytes(target: &str, value: &str) -> Result<Vec<u8>, StdError> {
+    let hex_error = match value.strip_prefix("0x").map(hex::decode) {
+        Some(Ok(bytes)) => return Ok(bytes),
+        Some(Err(e)) => Some(e),
+        None => None,
+    };
+    match Binary::from_base64(value) {
+        Ok(binary) => Ok(binary.0),
+        Err(StdError::InvalidBase64 { msg, .. }) => Err(StdError::ParseErr {
+            target: target.to_string(),
+            msg: match hex_error {
+                Some(e) => format!("Invalid hex: {}, nor valid base64: {}", e, msg),
+                None => format!("Input is neither 0x prefixed hex nor valid base64: {}", msg),
+            },
+            backtrace: None,
+        }),
+        Err(e) => Err(e),
+    }
+}
+
+fn try_block<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    client_id: &str,
+    block: &String,
+    _authority_set: &String,
+) -> HandleResult {
+    let block_bytes = decode_bytes("block", block)?;
+
+    let block = match SignedBlock::decode(&mut block_bytes.as_slice()) {
+        Ok(block) => block,
+        Err(e) => {
+            return Err(StdError::ParseErr {
+                target: "block".to_string(),
+                msg: format!("Unable to construct block from bytes. Error: {}", e),
+                backtrace: None,
+            })
+        }
+    };
+
+    // Body is always checked, even if empty, so that it can't be stripped
+    // from the block. Headers without body are submitted with `UpdateHeader`
+    try_relayed_header(
+        deps,
+        env,
+        client_id,
+        block.block.header,
+        Some(block.block.extrinsics),
+        block.justification.map(FinalityProof::Grandpa),
+    )
+}
+
+/// Same as `try_block`, but without block body, which isn't used by
+/// the light client anyway.
+fn try_header_with_justification<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    client_id: &str,
+    header: &str,
+    justification: Option<&String>,
+) -> HandleResult {
+    let header = decode_header(header)?;
+    let justification = match justification {
+        Some(justification) => Some(decode_bytes("justification", justification)?),
+        None => None,
+    };
+
+    try_relayed_header(
+        deps,
+        env,
+        client_id,
+        header,
+        None,
+        justification.map(FinalityProof::Grandpa),
+    )
+}
+
+/// Same as `try_header_with_justification`, but header is finalized with
+/// BEEFY finality proof instead of GRANDPA justification.
+fn try_header_with_beefy_proof<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    client_id: &str,
+    header: &str,
+    finality_proof: &str,
+) -> HandleResult {
+    let header = decode_header(header)?;
+    let finality_proof_bytes = decode_bytes("finality_proof", finality_proof)?;
+    let finality_proof = match BeefyFinalityProof::decode(&mut finality_proof_bytes.as_slice()) {
+        Ok(finality_proof) => finality_proof,
+        Err(e) => {
+            return Err(StdError::ParseErr {
+                target: "finality_proof".to_string(),
+                msg: format!(
+                    "Unable to construct BEEFY finality proof from bytes. Error: {}",
+                    e
+                ),
+                backtrace: None,
+            })
+        }
+    };
+
+    try_relayed_header(
+        deps,
+        env,
+        client_id,
+        header,
+        None,
+        Some(FinalityProof::Beefy(Box::new(finality_proof))),
+    )
+}
+
+fn decode_header(header: &str) -> Result<Header, StdError> {
+    let header_bytes = decode_bytes("header", header)?;
+    Header::decode(&mut header_bytes.as_slice()).map_err(|e| StdError::ParseErr {
+        target: "header".to_string(),
+        msg: format!("Unable to construct header from bytes. Error: {}", e),
+        backtrace: None,
+    })
+}
+
+/// Proof of finality of ingested header, which needs to be
+/// produced by the finality gadget of the client.
+enum FinalityProof {
+    Grandpa(Justification),
+    Beefy(Box<BeefyFinalityProof>),
+}
+
+impl FinalityProof {
+    fn finality_gadget(&self) -> FinalityGadget {
+        match self {
+            FinalityProof::Grandpa(_) => FinalityGadget::Grandpa,
+            FinalityProof::Beefy(_) => FinalityGadget::Beefy,
+        }
+    }
+}
+
+/// Ingests header submitted by relayer, who needs to be on relayer allowlist
+/// of the client if it has one, and records the submission in relayer stats.
+/// Failed messages revert all their storage writes, stats included, so only
+/// submissions processed by the light client are recorded, with or without
+/// allowlist. Submissions which didn't change the client are recorded as
+/// ineffective: headers which were already known and didn't finalize anything,
+/// and finality proofs which didn't finalize new headers.
+fn try_relayed_header<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    client_id: &str,
+    header: Header,
+    body: Option<Vec<<Block as BlockT>::Extrinsic>>,
+    possible_finality_proof: Option<FinalityProof>,
+) -> HandleResult {
+    let state = load_contract_state(&deps.storage, client_id)?;
+    let relayer = env.message.sender.clone();
+    if let Some(allowlist) = &state.possible_relayer_allowlist {
+        if !allowlist.contains(&relayer) {
+            return Err(StdError::Unauthorized { backtrace: None });
+        }
+    }
+
+    let with_finality_proof = possible_finality_proof.is_some();
+    let response = try_header(deps, env, client_id, header, body, possible_finality_proof)?;
+    let update: UpdateClientResponse = match response.data.as_ref() {
+        Some(data) => from_binary(data)?,
+        None => {
+            return Err(StdError::GenericErr {
+                msg: "Update of the client didn't return its outcome".to_string(),
+                backtrace: None,
+            })
+        }
+    };
+
+    let mut stats = read_only_relayer_stats(&deps.storage, client_id)
+        .may_load(relayer.as_slice())?
+        .unwrap_or_default();
+    if update.is_new_best || update.finalized {
+        stats.accepted_headers += 1;
+    } else {
+        stats.ineffective_headers += 1;
+    }
+    match (with_finality_proof, update.finalized) {
+        (false, _) => {}
+        (true, true) => stats.accepted_justifications += 1,
+        (true, false) => stats.ineffective_justifications += 1,
+    }
+    relayer_stats(&mut deps.storage, client_id).save(relayer.as_slice(), &stats)?;
+
+    Ok(response)
+}
+
+fn try_header<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    client_id: &str,
+    header: Header,
+    body: Option<Vec<<Block as BlockT>::Extrinsic>>,
+    possible_finality_proof: Option<FinalityProof>,
+) -> HandleResult {
+    let state = load_contract_state(&deps.storage, client_id)?;
+
+    if let Some(finality_proof) = possible_finality_proof.as_ref() {
+        if finality_proof.finality_gadget() != state.finality_gadget {
+            return Err(StdError::GenericErr {
+                msg: format!(
+                    "Client {} finalizes headers with {:?}, not {:?}",
+                    client_id,
+                    state.finality_gadget,
+                    finality_proof.finality_gadget()
+                ),
+                backtrace: None,
+            });
+        }
+    }
+
+    if possible_finality_proof.is_none()
+        && state.headers_ingested_after_last_justification
+            >= state.max_headers_allowed_between_justifications
+    {
+        return Err(StdError::GenericErr {
+            msg: format!(
+                "Cannot ingest header without justification. Max headers allowed: {}, Already ingested: {}",
+                state.max_headers_allowed_between_justifications,
+                state.headers_ingested_after_last_justification
+            ),
+            backtrace: None,
+        });
+    }
+
+    let headers_ingested_after_last_justification = if possible_finality_proof.is_some() {
+        0
+    } else {
+        state.headers_ingested_after_last_justification + 1
+    };
+
+    let ingestion_result = match possible_finality_proof {
+        Some(FinalityProof::Beefy(finality_proof)) => ingest_beefy_finalized_header(
+            state.light_client_data.clone(),
+            header.clone(),
+            *finality_proof,
+            state.max_headers_allowed_to_store,
+        ),
+        possible_justification => ingest_finalized_header(
+            state.light_client_data.clone(),
+            header.clone(),
+            body,
+            possible_justification.and_then(|proof| match proof {
+                FinalityProof::Grandpa(justification) => Some(justification),
+                FinalityProof::Beefy(_) => None,
+            }),
+            state.max_headers_allowed_to_store,
+        ),
+    };
+    let (ingested_header, updated_light_client_data) = match ingestion_result {
+        Ok(result) => result,
+        Err(e) => return Err(light_client_error("Unable to ingest header", e)),
+    };
+
+    let mut new_contract_state = ContractState {
+        name: state.name,
+        light_client_data: updated_light_client_data,
+        headers_ingested_after_last_justification,
+        ..state
+    };
+
+    if let Some((finalized_number, _)) = ingested_header.possible_finalized {
+        record_consensus_states(
+            &mut deps.storage,
+            &env,
+            client_id,
+            &mut new_contract_state,
+            ingested_header
+                .possible_previous_finalized
+                .map(|(number, _)| number),
+            finalized_number,
+        )?;
+    }
+
+    contract_states(&mut deps.storage).save(client_id.as_bytes(), &new_contract_state)?;
+
+    let (is_new_best, possible_verified_weight) = match &ingested_header.block_import_result {
+        BlockImportResult::ImportedKnown(_) => (false, None),
+        BlockImportResult::ImportedUnknown(_, aux) => {
+            (aux.is_new_best, aux.possible_verified_weight)
+        }
+    };
+    let scheduled_change_at = ingested_header
+        .possible_scheduled_change
+        .as_ref()
+        .map(|c| c.next_change_at);
+
+    let response = UpdateClientResponse {
+        height: header.number,
+        header_hash: header.hash().as_bytes().to_vec(),
+        is_new_best,
+        finalized: ingested_header.finalized(),
+        last_finalized_header_height: ingested_header
+            .possible_finalized
+            .map_or(0, |(number, _)| number),
+        last_finalized_header_hash: ingested_header
+            .possible_finalized
+            .map_or(H256::default(), |(_, hash)| hash.as_bytes().to_vec()),
+        scheduled_change_detected: scheduled_change_at.is_some(),
+        scheduled_change_at,
+        authority_set_enacted: ingested_header.validator_set_enacted(),
+        set_id: ingested_header.set_id,
+        headers_ingested_after_last_justification,
+        verified_weight: possible_verified_weight.map(|w| VerifiedWeightInfo {
+            verified_weight: w.verified_weight,
+            threshold: w.threshold,
+            total_weight: w.total_weight,
+            signatures_checked: w.signatures_checked as u64,
+        }),
+    };
+
+    let mut logs = vec![
+        log("action", "block"),
+        log("client_id", client_id),
+        log("height", response.height.to_string()),
+        log("header_hash", hex::encode(&response.header_hash)),
+        log("is_new_best", response.is_new_best.to_string()),
+        log("finalized", response.finalized.to_string()),
+    ];
+    if response.finalized {
+        logs.push(log(
+            "finalized_height",
+            response.last_finalized_header_height.to_string(),
+        ));
+        logs.push(log(
+            "finalized_hash",
+            hex::encode(&response.last_finalized_header_hash),
+        ));
+    }
+    logs.push(log(
+        "scheduled_change_detected",
+        response.scheduled_change_detected.to_string(),
+    ));
+    if let Some(scheduled_change_at) = response.scheduled_change_at {
+        logs.push(log("scheduled_change_at", scheduled_change_at.to_string()));
+    }
+    logs.push(log(
+        "authority_set_enacted",
+        response.authority_set_enacted.to_string(),
+    ));
+    logs.push(log("set_id", response.set_id.to_string()));
+    logs.push(log(
+        "headers_ingested_after_last_justification",
+        response
+            .headers_ingested_after_last_justification
+            .to_string(),
+    ));
+    if let Some(verified_weight) = response.verified_weight.as_ref() {
+        logs.push(log(
+            "verified_weight",
+            verified_weight.verified_weight.to_string(),
+        ));
+        logs.push(log(
+            "signatures_checked",
+            verified_weight.signatures_checked.to_string(),
+        ));
+    }
+
+    let res = HandleResponse {
+        messages: vec![],
+        log: logs,
+        data: Some(Binary(to_vec(&response)?)),
+    };
+    Ok(res)
+}
+
+/// Records consensus states of headers finalized after the previously
+/// finalized height, up to `finalized_height`, and prunes consensus states
+/// of heights which fall out of retention of the client. Headers pruned
+/// before getting finalized have no consensus state.
+fn record_consensus_states<S: Storage>(
+    storage: &mut S,
+    env: &Env,
+    client_id: &str,
+    state: &mut ContractState,
+    possible_previous_finalized_height: Option<BlockNumber>,
+    finalized_height: BlockNumber,
+) -> Result<(), StdError> {
+    if possible_previous_finalized_height >= Some(finalized_height) {
+        return Ok(());
+    }
+    let retained_since = (finalized_height as u64 + 1)
+        .saturating_sub(state.max_consensus_states_to_store)
+        as BlockNumber;
+    let start_height =
+        possible_previous_finalized_height.map_or(retained_since, |h| (h + 1).max(retained_since));
+    let (headers, _) = retained_headers(
+        state.light_client_data.clone(),
+        Some(start_height),
+        finalized_height - start_height + 1,
+        false,
+    )
+    .map_err(|e| light_client_error("Unable to get finalized headers", e))?;
+
+    for header in headers
+        .iter()
+        .filter(|header| header.number <= finalized_height)
+    {
+        consensus_states(storage, client_id).save(
+            &header.number.to_be_bytes(),
+            &ConsensusState {
+                height: header.number,
+                hash: header.hash().as_bytes().to_vec(),
+                state_root: header.state_root.as_bytes().to_vec(),
+                extrinsics_root: header.extrinsics_root.as_bytes().to_vec(),
+                processed_time: env.block.time,
+                processed_height: env.block.height,
+            },
+        )?;
+    }
+
+    let oldest_height = match state.possible_consensus_states_window {
+        Some((oldest_height, _)) => {
+            remove_consensus_states_below(storage, client_id, retained_since)?;
+            oldest_height.max(retained_since)
+        }
+        None => start_height,
+    };
+    state.possible_consensus_states_window = Some((oldest_height, finalized_height));
+
+    Ok(())
+}
+
+/// Updates config of client on behalf of its admin. Shrinking limits prunes
+/// headers and consensus states right away. Window of headers can't shrink to
+/// the point of pruning headers ingested after the last justification, as
+/// they couldn't be finalized anymore.
+#[allow(clippy::too_many_arguments)]
+fn try_update_config<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    client_id: &str,
+    possible_max_headers_allowed_to_store: Option<u64>,
+    possible_max_headers_allowed_between_justifications: Option<u64>,
+    possible_max_consensus_states_to_store: Option<u64>,
+    possible_new_admin: Option<&HumanAddr>,
+    renounce_admin: bool,
+) -> HandleResult {
+    let mut state = load_contract_state(&deps.storage, client_id)?;
+    check_admin(&state, &env)?;
+
+    let mut logs = vec![log("action", "update_config"), log("client_id", client_id)];
+
+    if let Some(max_headers_allowed_between_justifications) =
+        possible_max_headers_allowed_between_justifications
+    {
+        if max_headers_allowed_between_justifications == 0 {
+            return Err(StdError::ParseErr {
+                target: "max_headers_allowed_between_justifications".to_string(),
+                msg: "max_headers_allowed_between_justification need to be at least 1".to_string(),
+                backtrace: None,
+            });
+        }
+        state.max_headers_allowed_between_justifications =
+            max_headers_allowed_between_justifications;
+        logs.push(log(
+            "max_headers_allowed_between_justifications",
+            max_headers_allowed_between_justifications.to_string(),
+        ));
+    }
+
+    if let Some(max_headers_allowed_to_store) = possible_max_headers_allowed_to_store {
+        if max_headers_allowed_to_store <= state.headers_ingested_after_last_justification {
+            return Err(StdError::ParseErr {
+                target: "max_headers_allowed_to_store".to_string(),
+                msg: format!(
+                    "max_headers_allowed_to_store need to be more than {} headers ingested after last justification",
+                    state.headers_ingested_after_last_justification
+                ),
+                backtrace: None,
+            });
+        }
+        if max_headers_allowed_to_store != state.max_headers_allowed_to_store {
+            state.light_client_data =
+                resize_storage(state.light_client_data, max_headers_allowed_to_store)
+                    .map_err(|e| light_client_error("Unable to resize light client storage", e))?;
+            state.max_headers_allowed_to_store = max_headers_allowed_to_store;
+        }
+        logs.push(log(
+            "max_headers_allowed_to_store",
+            max_headers_allowed_to_store.to_string(),
+        ));
+    }
+
+    if let Some(max_consensus_states_to_store) = possible_max_consensus_states_to_store {
+        if max_consensus_states_to_store == 0 {
+            return Err(StdError::ParseErr {
+                target: "max_consensus_states_to_store".to_string(),
+                msg: "max_consensus_states_to_store need to be at least 1".to_string(),
+                backtrace: None,
+            });
+        }
+        state.max_consensus_states_to_store = max_consensus_states_to_store;
+        prune_consensus_states(&mut deps.storage, client_id, &mut state)?;
+        logs.push(log(
+            "max_consensus_states_to_store",
+            max_consensus_states_to_store.to_string(),
+        ));
+    }
+
+    match (possible_new_admin, renounce_admin) {
+        (Some(_), true) => {
+            return Err(StdError::ParseErr {
+                target: "new_admin".to_string(),
+                msg: "Admin rights can't be both transferred and renounced".to_string(),
+                backtrace: None,
+            });
+        }
+        (Some(new_admin), false) => {
+            state.possible_admin = Some(deps.api.canonical_address(new_admin)?);
+            logs.push(log("new_admin", new_admin.as_str()));
+        }
+        (None, true) => {
+            state.possible_admin = None;
+            logs.push(log("admin_renounced", "true"));
+        }
+        (None, false) => {}
+    }
+
+    contract_states(&mut deps.storage).save(client_id.as_bytes(), &state)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: logs,
+        data: None,
+    })
+}
+
+/// Turns relayer allowlist of client on or off and adds relayers
+/// to it or removes them from it, on behalf of admin of the client.
+fn try_update_relayers<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    client_id: &str,
+    possible_allowlist_enabled: Option<bool>,
+    add: &[HumanAddr],
+    remove: &[HumanAddr],
+) -> HandleResult {
+    let mut state = load_contract_state(&deps.storage, client_id)?;
+    check_admin(&state, &env)?;
+
+    match possible_allowlist_enabled {
+        Some(true) => {
+            state
+                .possible_relayer_allowlist
+                .get_or_insert_with(Vec::new);
+        }
+        Some(false) => state.possible_relayer_allowlist = None,
+        None => {}
+    }
+
+    let mut logs = vec![
+        log("action", "update_relayers"),
+        log("client_id", client_id),
+    ];
+    if !add.is_empty() || !remove.is_empty() {
+        let allowlist =
+            state
+                .possible_relayer_allowlist
+                .as_mut()
+                .ok_or_else(|| StdError::GenericErr {
+                    msg: format!("Client {} has no relayer allowlist", client_id),
+                    backtrace: None,
+                })?;
+        for relayer in add {
+            let relayer_address = deps.api.canonical_address(relayer)?;
+            if !allowlist.contains(&relayer_address) {
+                allowlist.push(relayer_address);
+            }
+            logs.push(log("added_relayer", relayer.as_str()));
+        }
+        for relayer in remove {
+            let relayer_address = deps.api.canonical_address(relayer)?;
+            allowlist.retain(|r| *r != relayer_address);
+            logs.push(log("removed_relayer", relayer.as_str()));
+        }
+    }
+    logs.push(log(
+        "allowlist_enabled",
+        state.possible_relayer_allowlist.is_some().to_string(),
+    ));
+
+    contract_states(&mut deps.storage).save(client_id.as_bytes(), &state)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: logs,
+        data: None,
+    })
+}
+
+fn check_admin(state: &ContractState, env: &Env) -> Result<(), StdError> {
+    if state.possible_admin.as_ref() != Some(&env.message.sender) {
+        return Err(StdError::Unauthorized { backtrace: None });
+    }
+
+    Ok(())
+}
+
+/// Drops consensus states older than the latest `max_consensus_states_to_store`
+/// ones, for when the limit is lowered.
+fn prune_consensus_states<S: Storage>(
+    storage: &mut S,
+    client_id: &str,
+    state: &mut ContractState,
+) -> Result<(), StdError> {
+    if let Some((oldest_height, newest_height)) = state.possible_consensus_states_window {
+        let retained_since = (newest_height as u64 + 1)
+            .saturating_sub(state.max_consensus_states_to_store)
+            as BlockNumber;
+        remove_consensus_states_below(storage, client_id, retained_since)?;
+        state.possible_consensus_states_window =
+            Some((oldest_height.max(retained_since), newest_height));
+    }
+
+    Ok(())
+}
+
+/// Removes consensus states of client below `end_height`. Stored heights are
+/// ranged over rather than every height, as finalized heights can be far apart.
+fn remove_consensus_states_below<S: Storage>(
+    storage: &mut S,
+    client_id: &str,
+    end_height: BlockNumber,
+) -> Result<(), StdError> {
+    // Buckets can't remove values, but they share namespaces with prefixed storage
+    let mut prefixed_storage =
+        PrefixedStorage::multilevel(&[PREFIX_CONSENSUS_STATES, client_id.as_bytes()], storage);
+    let keys = prefixed_storage
+        .range(None, Some(&end_height.to_be_bytes()), Order::Ascending)?
+        .map(|item| item.map(|(key, _)| key))
+        .collect::<Result<Vec<_>, StdError>>()?;
+    for key in keys {
+        prefixed_storage.remove(&key)?;
+    }
+
+    Ok(())
+}
+
+/// Updates head of parachain client, after verifying that the header is
+/// the head of the parachain at finalized header of relay chain client.
+/// Heads can only move forward.
+fn try_parachain_header<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    client_id: &str,
+    relay_height: BlockNumber,
+    relay_header_proof: Option<&HeaderInclusionProof>,
+    header: &str,
+    proof: &[String],
+) -> HandleResult {
+    let parachain_state = load_parachain_state(&deps.storage, client_id)?;
+    let relay_state = load_contract_state(&deps.storage, &parachain_state.relay_client_id)?;
+    let relay_header = finalized_header(
+        relay_state.light_client_data,
+        relay_height,
+        relay_header_proof,
+    )?;
+
+    let header = decode_header(header)?;
+    let mut proof_nodes = vec![];
+    for node in proof {
+        proof_nodes.push(decode_bytes("proof", node)?);
+    }
+
+    if let Some(latest_head) = parachain_state.possible_latest_head.as_ref() {
+        if header.number <= latest_head.number {
+            return Err(StdError::GenericErr {
+                msg: format!(
+                    "Parachain header at height {} isn't newer than the latest head at height {}",
+                    header.number, latest_head.number
+                ),
+                backtrace: None,
+            });
+        }
+    }
+
+    verify_parachain_head(&relay_header, parachain_state.para_id, proof_nodes, &header)
+        .map_err(|e| light_client_error("Unable to verify parachain head", e))?;
+
+    let latest_head = ParachainHead {
+        number: header.number,
+        hash: header.hash().as_bytes().to_vec(),
+        state_root: header.state_root.as_bytes().to_vec(),
+        relay_height: relay_header.number,
+        relay_hash: relay_header.hash().as_bytes().to_vec(),
+    };
+    let new_parachain_state = ParachainState {
+        possible_latest_head: Some(latest_head.clone()),
+        ..parachain_state
+    };
+    parachain_states(&mut deps.storage).save(client_id.as_bytes(), &new_parachain_state)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![
+            log("action", "update_parachain"),
+            log("client_id", client_id),
+            log("height", latest_head.number.to_string()),
+            log("header_hash", hex::encode(&latest_head.hash)),
+            log("relay_height", latest_head.relay_height.to_string()),
+        ],
+        data: Some(Binary(to_vec(&ParachainHeadResponse {
+            para_id: new_parachain_state.para_id,
+            relay_client_id: new_parachain_state.relay_client_id,
+            head: Some(latest_head),
+        })?)),
+    })
+}
+
+fn is_valid_identifier(name: &str) -> bool {
+    let bytes = name.as_bytes();
+    if bytes.len() < 8 || bytes.len() > 20 {
+        return false; // length invalid
+    }
+    for byte in bytes {
+        if byte > &122 || byte < &97 {
+            return false; // not lowercase ascii
+        }
+    }
+    return true;
+}
+
+fn is_valid_client_id(client_id: &str) -> bool {
+    let bytes = client_id.as_bytes();
+    if bytes.is_empty() || bytes.len() > 64 {
+        return false; // length invalid
+    }
+    bytes
+        .iter()
+        .all(|byte| byte.is_ascii_alphanumeric() || *byte == b'-' || *byte == b'_')
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::contract::msg::{
+        BeefyValidatorSetInfo, ConfigResponse, ConsensusState, ConsensusStatesResponse,
+        ExportSnapshotResponse, FinalityGadget, HeaderSummary, HeadersResponse,
+        JustificationReportResponse, LatestHeightResponse, ParachainHeadResponse, QueryMsg,
+        RelayerStats, StorageWindowResponse, UpdateClientResponse, VerifyExtrinsicResponse,
+        VerifyParachainStorageResponse,
+    };
+    use crate::contract::{decode_bytes, handle, init, load_contract_state, query};
+    use crate::msg::{HandleMsg, InitMsg};
+    use crate::state_proof::paras_heads_key;
+    use crate::test_utils::{
+        create_finality_proof, create_next_header, make_ids, validator_keys, validator_set,
+        ChainSimulator,
+    };
+    use crate::types::{Block, Header, SignedBlock};
+    use cosmwasm_std::testing::{mock_env, MockApi, MockQuerier, MockStorage};
+    use cosmwasm_std::{from_binary, log, Binary, Env, HumanAddr};
+    use cosmwasm_std::{Extern, StdError};
+    use hex::encode;
+    use parity_scale_codec::{Compact, Decode, Encode};
+    use secp256k1::SecretKey;
+    use serde_json::{from_str, to_string};
+    use sp_core::H256;
+    use sp_finality_grandpa::{AuthorityList, VersionedAuthorityList};
+    use sp_keyring::Ed25519Keyring;
+    use sp_runtime::traits::{BlakeTwo256, Block as BlockT, Hash as HashT, Header as HeaderT};
+    use sp_trie::{trie_types::TrieDBMut, MemoryDB, TrieMut};
+
+    /// Chain of headers numbered from 1 up to `last`, with distinct state roots.
+    fn relay_headers(last: u32) -> Vec<Header> {
+        let mut headers = vec![Header::new(
+            1,
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+        )];
+        for number in 2..=last {
+            let parent_hash = headers.last().unwrap().hash();
+            headers.push(Header::new(
+                number,
+                Default::default(),
+                H256::repeat_byte(number as u8),
+                parent_hash,
+                Default::default(),
+            ));
+        }
+        headers
+    }
+
+    /// Creates relay chain client finalizing headers with BEEFY from `init_header`,
+    /// with validator set 0 made of `keys`.
+    fn beefy_init_msg(client_id: &str, init_header: &Header, keys: &[SecretKey]) -> InitMsg {
+        let beefy_set = validator_set(0, keys);
+        InitMsg {
+            client_id: client_id.to_string(),
+            name: "testtesttest".into(),
+            block: "0x".to_string()
+                + encode(
+                    SignedBlock {
+                        block: Block::new(init_header.clone(), vec![]),
+                        justification: None,
+                    }
+                    .encode(),
+                )
+                .as_ref(),
+            authority_set:
+                "0x0488dc3417d5058ec4b4503e0c12ea1a0a89be200fe98922423d4334014fa6b0ee0100000000000000"
+                    .to_string(),
+            max_headers_allowed_to_store: 256,
+            set_id: 1,
+            max_headers_allowed_between_justifications: 2,
+            justification: None,
+            authority_set_proof: None,
+            finality_gadget: Some(FinalityGadget::Beefy),
+            beefy_validator_set: Some(BeefyValidatorSetInfo {
+                id: beefy_set.id,
+                len: beefy_set.len,
+                root: format!("{:?}", beefy_set.root),
+            }),
+            max_consensus_states_to_store: None,
+            retention_policy: None,
+            justification_verification: None,
+            snapshot: None,
+        }
+    }
+
+    /// Finalizes `header` with BEEFY commitment signed by `signers` of validator set 0.
+    fn beefy_update_msg(
+        client_id: &str,
+        header: &Header,
+        keys: &[SecretKey],
+        signers: &[usize],
+    ) -> HandleMsg {
+        let finality_proof = create_finality_proof(header, 0, keys, signers, Default::default());
+        HandleMsg::UpdateBeefy {
+            client_id: client_id.to_string(),
+            header: "0x".to_string() + encode(header.encode()).as_ref(),
+            finality_proof: Binary(finality_proof.encode()).to_base64(),
+        }
+    }
+
+    #[test]
+    fn test_contract_init_and_update() {
+        let storage = MockStorage::new();
+        let api = MockApi::new(5);
+        let querier = MockQuerier::default();
+        let mut extern_dep = Extern {
+            storage,
+            api,
+            querier,
+        };
+
+        let init_msg = InitMsg{
+            client_id: "testclient".to_string(),
+            name: "testtesttest".into(),
+            block: "0x5e9fc49076803d0ba88c719252ede5ae713d09367162d344e9b79ef3aac2efa03e620300fe518cc595e8f5ede8010cf6d26352f6a089ee52f992153a540c7b5d9b659ea272c9c1e535cf5ca49ab2d72059671d80f69c6dba7e6c0dca1e27c3832e873f2b08066175726120448dd10f0000000005617572610101fe734978fa3cb9804346988424124add53316e68e9dcd96a5dfc5a576fe61262031463e0e3a1cdb15538a763dddfbbdf2d3c47e3ecc72deebb3ba5ec59b1168204280402000bc0e95ebf720100".into(),
+            authority_set: "0x0488dc3417d5058ec4b4503e0c12ea1a0a89be200fe98922423d4334014fa6b0ee0100000000000000".to_string(),
+            max_headers_allowed_to_store: 256,
+            set_id: 1,
+            max_headers_allowed_between_justifications: 2,
+            justification: None,
+            authority_set_proof: None,
+            finality_gadget: None,
+            beefy_validator_set: None,
+            max_consensus_states_to_store: None,
+            retention_policy: None,
+            justification_verification: None,
+            snapshot: None,
+        };
+        let init_header_hash =
+            hex::decode("f157283bcfe5ace5f3258bdb595ee8c6761394a56c8e73b6aaf734e6fb1e7c92")
+                .expect("Hex decoding of init header hash failed");
+        let init_header_number: u32 = 55439;
+        let init_authority_set = "LightAuthoritySet { set_id: 1, authorities: [(Public(88dc3417d5058ec4b4503e0c12ea1a0a89be200fe98922423d4334014fa6b0ee (5FA9nQDV...)), 1)] }";
+        let result = init(&mut extern_dep, Env::default(), init_msg);
+        assert!(result.is_ok());
+
+        let contract_state = load_contract_state(&extern_dep.storage, "testclient")
+            .expect("Contract state should exists");
+        assert_eq!(contract_state.name, "testtesttest");
+        assert!(contract_state.light_client_data.len() > 0);
+
+        let result = query(
+            &extern_dep,
+            QueryMsg::LatestHeight {
+                client_id: "testclient".to_string(),
+            },
+        );
+        assert!(result.is_ok());
+        let query_response: LatestHeightResponse =
+            from_binary(&result.unwrap()).expect("Deserializing Query response failed");
+        assert_eq!(query_response.last_finalized_header_hash.len(), 0);
+        assert_eq!(query_response.best_header_hash, init_header_hash);
+        assert_eq!(query_response.best_header_height, init_header_number);
+        assert_eq!(query_response.current_authority_set, init_authority_set);
+
+        let update_msg = HandleMsg::UpdateClient {
+            client_id: "testclient".to_string(),
+            block: "0xf157283bcfe5ace5f3258bdb595ee8c6761394a56c8e73b6aaf734e6fb1e7c92426203000ad92ba15285e38e29472d35c29a8e0097e0748fa66fca1b4c834e13f0604de6f7e776ac0632a86d967e1fc4694d51b15c06dadf6c2d0f60a0c661993ffa6d5308066175726120458dd10f00000000056175726101019c9a0a6afd95ff9b8a479bab6676867d19f388b187534394661f0b9ca540b86cd5847174d8b1075f61c01f3b0f5dfa8c643b15c226ebace6aa5aca43cd12ce8504280402000b30015fbf720100".to_string(),
+            authority_set: "0x0488dc3417d5058ec4b4503e0c12ea1a0a89be200fe98922423d4334014fa6b0ee0100000000000000".to_string(),
+        };
+        let next_header_hash =
+            hex::decode("b17ad1a298edb7fa902ce240358ced980a1a1f9febe163152be5e66c377fa38c")
+                .expect("Hex decoding of next header hash failed");
+        let next_header_number = init_header_number + 1;
+        let next_authority_set = init_authority_set;
+        let result = handle(&mut extern_dep, Env::default(), update_msg);
+        assert!(result.is_ok());
+
+        let contract_state = load_contract_state(&extern_dep.storage, "testclient")
+            .expect("Contract state should exists");
+        assert_eq!(contract_state.name, "testtesttest");
+        assert!(contract_state.light_client_data.len() > 0);
+
+        let result = query(
+            &extern_dep,
+            QueryMsg::LatestHeight {
+                client_id: "testclient".to_string(),
+            },
+        );
+        assert!(result.is_ok());
+        let query_response: LatestHeightResponse =
+            from_binary(&result.unwrap()).expect("Deserializing Query response failed");
+        assert_eq!(query_response.last_finalized_header_hash.len(), 0);
+        assert_eq!(query_response.best_header_hash, next_header_hash);
+        assert_eq!(query_response.best_header_height, next_header_number);
+        assert_eq!(query_response.current_authority_set, next_authority_set);
+
+        let update_msg = HandleMsg::UpdateClient {
+            client_id: "testclient".to_string(),
+            block: "0xf157283bcfe5ace5f3258bdb595ee8c6761394a56c8e73b6aaf734e6fb1e7c92426203000ad92ba15285e38e29472d35c29a8e0097e0748fa66fca1b4c834e13f0604de6f7e776ac0632a86d967e1fc4694d51b15c06dadf6c2d0f60a0c661993ffa6d5308066175726120458dd10f00000000056175726101019c9a0a6afd95ff9b8a479bab6676867d19f388b187534394661f0b9ca540b86cd5847174d8b1075f61c01f3b0f5dfa8c643b15c226ebace6aa5aca43cd12ce8504280402000b30015fbf720100".to_string(),
+            authority_set: "0x0488dc3417d5058ec4b4503e0c12ea1a0a89be200fe98922423d4334014fa6b0ee0100000000000000".to_string(),
+        };
+        // This wil fail as at max only two headers are allowed between justification
+        let result = handle(&mut extern_dep, Env::default(), update_msg);
+        assert!(result.is_err());
+        assert_eq!(
+            result.err().unwrap(),
+            StdError::GenericErr {
+                msg: "Cannot ingest header without justification. Max headers allowed: 2, Already ingested: 2".to_string(),
+                backtrace: None,
+            }
+        );
+    }
+
+    /// The block and justification data here are from Kusama (websocket url: wss://kusama-rpc.polkadot.io/)
+    #[test]
+    fn test_contract_init_and_update_with_justification() {
+        let encoded_versioned_authority_list = "017905f45066c35babc6ba27b7ca056df70f04dc877dbcc0bd9ac8c9d82d56856b15000100000000000000fa831eba1c67bc9cb97e75feaf03a739a3aaa8c117e4fa59c345be08600ae1d30100000000000000381bb2f0ef2a9384e93fe56774bd6417277dc3a5a76d15ac55bfcfdd0f907787010000000000000036c3ba9b05f6814e0de9a3ab1e108141416c95d784122b76ee52e3b0e8c86aba01000000000000008f3fb12a535077f6c94870193fded721b054cf633ff4d29c6711ec52167f63ac0100000000000000105452ff0261a21edc823a2d494ba2d1eb1b326375b8019c93b1ae79a4fa65240100000000000000baff92b53651ee2bade8d835e16b9285175db0f478c73c4f15167941ba3ea6350100000000000000f888102b2e5195efc88308bd13c581fefa7e3c8aeb0b4412bcf3dea4b607c9eb010000000000000073213e29b78192bfc1b1213ddcf88e7b8f046335e4b55d598a6e05ea8bf8bd1d01000000000000004000fa80428185da7fe6ee159d305f9cd32fd116f14ecce4cef35e2cef745191010000000000000024a1be39c97ef5992f94efcb0f4511801a5abdeb3e813fb464d43eb31bc925c9010000000000000053198b1b024cfde1fd068b8f6d7712517eb13c3e3416d01c2cba50409d4ca0fa01000000000000001d39bb34a39491970775350c5d5f87cea4eb9cc6fe14783b5d84cd7d47eaafdc01000000000000002e779c8535a7689ddf4389240060cedd9e9a3c559eadc572e3b797d8c49ac79901000000000000001057c0fc416d3f60a00bc7f87bd55f0ceb27fdd3f3af1bd5864b159c1adba39a0100000000000000b6deccb07c975c85ee8c2437dc3ad2184bc69494faf34741b1c3c79cefba5e3c0100000000000000ce5498f545700388a50b77d713fbb8a2c684cc73b9e41bbe988facb6217ccbce0100000000000000d8553266d4b46dfed6f9b6c1d75008e19e75538942de4928c697c445d26fcdd50100000000000000ec189cd23dfbcd51661cae853e6dc2733391c4375b75fec40b37ab56bb1a781c01000000000000008cf418512b7d4d2453b0da53b7e9de3e32862164b62511a303d84f04fc334ce4010000000000000093fa19a580853381cf03a0b187d8e576863593ef8c03f56be100ba50e1cb72cc01000000000000003618acc2b02a85775e66fcf72597dbc404bfb54cfc72292ff66d8cafc49df61a0100000000000000942b9ef351d184a1c83a7ddbea3b5e2af6c71486752b66284c07def606bdfad40100000000000000c5cba14eb175337dd7ac5ed02519fcc374f9e5c60b6fdae28cb5eaa2942403b2010000000000000032f8bf0f37b35d9db2a00fba4ca20051eea49ad9d2edd99d5744bd963b2c6f66010000000000000029fc78475997bca058872b83f5961972e9771f78f0fae0dce918e430df83aa2a01000000000000005a1e880ccc2a0615d6655c342ca9c83f06e891bf492766d2a33e5afc8fa667000100000000000000089f8365f265c12c58d6eb95cb58d4e67626dc7ca10ac223424b7624012c1bb501000000000000008916cb4c79579d42bdde503a8ef073c082edd220a5851a75ed23049d033d1c6d01000000000000008d7acd36aedce52ae891b72848aad41cfd32a755d56cb8bf4cc2af7d472ef50f0100000000000000874296ca163e65856a696c94521bb35ff82d1bd34b8c3194ff02a2663c87d9050100000000000000f26c8b40fe23a64d3bf0fbad27ea08edfe070db5f65fb28ec26fa743ad6df6f601000000000000003e05f4e13173752ab99613f6794c3c2d7ad2d57f8ab66a851675ebcb002cd8330100000000000000f5e73f61a88d305f9f88d3efe06e964140f87505ebd5f644a4b92ae52e57971301000000000000007c3c0fbe707bab436497cadb9f6f4f688b0adb05e0c6199554350872da2e28780100000000000000492d9ab341f2f61101ac7c76a7df3125d26bf84467f97e4270faa4b05ca528a001000000000000008a21215143c1f631a129345a440e14c4a335089ef351c361da036b589dd3d90f0100000000000000182da31370a90a76902f45222b29432a72474fe3aafa7c62d368111f6214205c0100000000000000d6426bcfa77ab6050d7a4e4930c934da18913247fa5c3467a6c26ab5867ceaf901000000000000003cfc7d049205605bcb7bc5891454fe91da9a5968ecd8a0380e868813db0691b60100000000000000e6ef360eb109b0e730c734d487480bb5159b1e7edf32dcb2d6724fc33474630601000000000000001df7948b12cb7be4d5d9aee93b74658d0c8444a0c35f3ffa4aedc5c3539c31bf01000000000000009e8179ce4ad83026545dc2c99c27c45c22509e47bbf3325a6977674c7f9bfe130100000000000000aa76e585eb097575c3f4307c88a41ff10dd55e14cf9a1e14903cda53d017f1950100000000000000c1f633c7f4c65a703def23d07e85e650e7a124383b9f4e71dadf6e938f2cc429010000000000000075fc688b5010455b94c34ed273b95a7f3f3ad1e3aebdffe5c0c58662b3a6fe720100000000000000de3b03117c0592c1bb6156cf5a89b62db5bee5c1a96172a83c057a962b055d580100000000000000f2d5b666dff660ed586000cb5b2236c78b3e1ce465ba8c2e7647baa49a1df2df0100000000000000dd3af5175ce2aae92be020aab462082fbaf1424e8fc5f1510f3dba6e24c10b970100000000000000c95dc9d2a070e5350cdd9a87d07896207618b8b5fa2e5473e10e94a0c70b9e240100000000000000e53eefa1ae52754ea106e4a32ed14ce7194a0ecdbdb55b4675303a230c027b750100000000000000a72534ec118b3f23b92418fa062faed297c81173457982ac5fa51105946dd4c90100000000000000506ebe725128d4a8ab89714f8141b3962fff06d8982f50ec5cc2a6e562c587f601000000000000007e969e559f860e6eea31bf88d3a5837e2053f8bc8e393159701369a402ed227201000000000000000cd4bbd993c4e75ca06ff72f96cda5730ba0de11938891cc6dbdacb0898f333c010000000000000006d80f029c6fa63e5b61cb69f20830e5ffc0632b82d1be929d10100b95f42b4b01000000000000007a021d6a700bd688713c3683d9d9cc08f30471231d7292eb6ca63204069d904301000000000000006a98fcba174345cbef21cf2390349028093f1c485c559afa25e73b4c7585dd4b0100000000000000378ebee99365bccb2752137df5b004e03557b02d73065e62ce8ef4f2ff2067f801000000000000009b36d04062b635e7b7c37d73151e7745e670c7e550908b491d27de6e4b623f040100000000000000c98bacb2b0b5e88da24060475191f4ed37af1401b2bb037ce499e3243dc3c9cc0100000000000000e05f66d40ae6f502292883ff61bee60abe9f631976a3a788992c02ae08ee21ed01000000000000009f04069dcb9f5aa255ab23343eb49f98935ee04aca33f95aee0d7183e1ae3d2c01000000000000008646f71df17c78d27591e9dd7c1965e42a22047470a55ed860ce82dcb41845790100000000000000b4295abb37df4a3bd35b74e4fd436154812c12f136945c6a6e745d438c22598d0100000000000000713a3f722486fd089759973a0eaa0dcd4fb5586f5c10353342eb3c8208b120e8010000000000000013388b602dd1ce53bf76e9f5ee28aff93012b2fec3971579efb52e350eb29d2c01000000000000001a55785628300bfebee9bbf23ef1f2eb6d3b5cc32ad68ff92e7b34a5ac9f6b840100000000000000e769015f204f7871fd1e608ec251fb64a464f41a5ef58a2a5971d3dc4190714e01000000000000008f2606887fa0a1a14dcfbbb2aaf096b9158eb7ab45e2af0cae8ea2160799ba260100000000000000aa34de0e713619e9556866d06da2001e4e19fdb69208e1884eaac15873e8f9a90100000000000000f2c1d36192b098a94ebaf7f4d5e9cfb5d35086573366ad9e0f57873477f9ef840100000000000000abc45e18c26cd2d210c18a92de9dd8cab28b8db5372f383d7c65958cb3f82bdc0100000000000000097072e8a104ee7d892f0741d72122f494ee4fc69240bcc20a30d4429086ae7c01000000000000008e7c7dbc66a9350fa0ca4fc743f243139a913bec3e37368856e86e5e395b4a5e010000000000000098f77837dce08dce233e9ed774678f599c2f206a106f6df95eaa2b9dbfd3f5170100000000000000e89ea9a8e5efa09504c45f1bd577bde06ec1fc2ae6e58f86a1613bf0fecdf3a80100000000000000c403d780ed2fa2b90c8bb652b66aaf526f60b6b00963b03d2123a43794c3956201000000000000003b4324156feff0b3513fcf8dbfd639a997ac502a314b862dfb9165f60d661b07010000000000000032675ca04f169d19b7ae0983dc4056bdd3abab820d4cbbfdbb4e824275dc38d50100000000000000de45ccfd8087b1a632fca9a233755241733c5a8a342b5271302566edc94b603a0100000000000000d264dbd69b8980edddcabc30a6b8e1c36bc14076f2e2bf5233377daa2f05fb17010000000000000022aa15840840af192b8687ba12347ffd6decf2fe47514155df8f7437154ead5c0100000000000000c68682b0e8dfcfe2cbe3444d1b540582e5776aca58229449356f6a5ffe4fde080100000000000000ee37ff73d973d47c403f77ae91d1811d82b683bd1d1b4af7d27a822121517ff201000000000000005eeb0d83d17abbc37d017e363d107f8c007cbe9b97a6357cee0238c4dbfcdc840100000000000000e132b03de854b7b302c10abb6a9873a34487fb8204a0aa26915553e48f10101401000000000000000ffedc01157425fb072cbc2abf8378eb3d7d46e4016010ff8d0d11e5df839c1a0100000000000000068045d78f1b1e7344710db2ca3c49e83135b47e16490c95c7ce27f4a4dc4c3801000000000000008f12c455abf3b12832d51f812f6e253209df8bc73c37e6588866a2b74fa7cf3c0100000000000000ebd9ab9165223f21e698077cffb73285f78ae81a457733a2c313ebf5abc19bf60100000000000000482eeb642c5551c08aaf74a23b4807bc601e010169a2378979e7ce75e4d4301d010000000000000078cf063bee982d58db18b7e808d271795231d1663a009ee29761aa2eb794ba280100000000000000efe303a467020c77d306ce27bf1153f606ed3682a23d3a03afb365c1ed12dc4701000000000000002a6090e8a56138fe641f3f5736604af7ab738ee5006fb73912362421c23813e401000000000000008992aa070f0095bdb0c95c257a068a41679b3e260c428e6ad2c474cbfa8d39bb01000000000000001b0e920e57b8399b1a43f0b31d76e8fad3b460bc266af1fe0e5b6a48336784c201000000000000000c35a3794adf5d49e2ee3aa54a3562260cc34864c1f33109e106412e299393760100000000000000585a8780999aaf32c05c0f408b4dcad6d910e36b6acf33f89b54cf057e6bf0db0100000000000000014d5d19494d1d22478f2e3e829a90116047e0afd9b89e56b02769b98be30e5e0100000000000000d3a13dcf17b11259f890f81956b2a5f7aa0ab07da653eb401f1366adc8f9fcd00100000000000000ada51d21398b4c315aef94950ea5573af9cd0360b699d069f231384449c46aee0100000000000000356fb1225fbf58ed0907cf975d408cf34539e2cdf4bb9c72e602a3e7cd99681401000000000000000b420f891b94e13c0150e13e4a20af8b6b7820c4d70cef08df6535e37b2012b40100000000000000b89e2b89530d98bd762b0a093d42831c5002e60410e101a41e05ee3b8161be8f0100000000000000fc4139e17ca3e3a05a43aaa3b203285a1d718e336fb5c60c8b20abb4f63612480100000000000000d70ebcafd6db9a843aa35a374e11b9643e72a856f6516533a6e6f933338eb7860100000000000000b4a014e2cbefde8d6041f3453418d0cfc70cd8501c88750bf838731d0e870ae0010000000000000036c9e70a4bf38aacccb494774032935645988915a6b2bc7894b73e302c36fd680100000000000000dd2c383912dbe4d2e258aadb2a01d5aaf218e6eceb5b6662348fea0f44069cdf0100000000000000740b80430e293a008b5df8f64653a67b87c434dc5954551815ff361f4eea8a0a0100000000000000c5aae938580da881f9a387cb7590f23be7709c812399023f9310213d502fb2910100000000000000511b3859b7e56c18f6487e9559a93ec30f4b604264f667564914af50c79038750100000000000000298fc14716807110d276344377e6247272492961f3afd52cdee5df85978ef4ac0100000000000000697037b1bcfe6cf9e4a15fe04ec04f3f83f81a41f5c928788eacacb9329f8cfa010000000000000055438be4c877264e5683a872317e756ebb1d3fbaa44d0735330f6aedd984f3630100000000000000596d81e0039b9ef6266345878349fde921c94c20d16b7692ffc1b089807cbd9201000000000000003343d776c28de327fe9c2d9b743f452ecbff61fe4d5260eae99a1950f4f222b1010000000000000023db9e93f55c14c67a9fd7a60fa2f6f51e4e8c24baa097776ce27c4fc22075830100000000000000a50b02be05aa426aa7a41c73b0abc96b57bddd0f4a9705c2dec6aec9f3eaa7ac0100000000000000425e378a41fe25ef93e1837dffe3558e348a4362518d794f282560406958a5a30100000000000000bec6303ea9181751ae7248552a55439b698be53e8143ca2c0ed329105c24fd380100000000000000ac1f4668798391a7d3a998c1cb0801d8dde95d31b5f2b6d8c3cfa1fe2d135248010000000000000012540171172468cce33a3932d0e0a282d2592519cc207a184bb4bf15a359d835010000000000000014903a1932f729eec51c64c8652f5a7bdf17dfa359799e550f1083e16457ff270100000000000000e730ab9c10d10d5bb70c423199fa6292e42caa8898b97edff42e7139ee0d09ed0100000000000000704c69917fe59c82dfa6044e9817e3f2d7fa708b2c2b761ad0f0095ce1d939800100000000000000459f99c8be2412acad0f7cf2552d8501e60e6f16614d67d04640776e57b04f89010000000000000073243180ab26ae71ba0aabc39680c3d1742df12a5125f45327340fffd5a534780100000000000000104854d7e85ff9a6825dc4de7e668d5345432b747522f73d8c2613ec8c82808d01000000000000006a559723e601438aa63a5de649b460b9f7c282dfda4ecb9dbec4ab40bbc25acb01000000000000000b4e8a517108b5a96cd560fc9116cb955b7e64e82e757a00d9ac544ceca1e3720100000000000000d0d66b9e138e72362fc8ac99947a2f81b7d3a16ea2e5e7b7e873a749770aca5e01000000000000009a267c5d8dfa9520342b47566e186b24ea3cd125970950d7d98d06fd649a21220100000000000000eede4d531722b3763067b729de2f8af8abac018f967a64bd9b6907396f2ff65b0100000000000000adaca7859de8e13deda4287976cd78a23fe2c0c6a2cc365791beb3ace94bb8fa0100000000000000849380a0c141656ae2f38bc74958656a7699156dae391a2f0671b3575fa158990100000000000000a0cdfd574e0d73d60e77b03c8abca97619d0b9da4a242c46c36b5da85b90bc8401000000000000001f4deb80d81471c9fd5b1d86af7c6fac61c659aee7d49fdde64c63edc48233550100000000000000a60fa8a9f80d2f4e232108a28bac749559836458371e314871ddb84a9769ce780100000000000000f8890ba455213f3a15cf14c05d5b72d5f9e8fde423eb52455bc3cdc55f29f1fc01000000000000001c9b2bf5fa39c9f6b033e0393255457711343723ed4e8cfd8031a57098f583090100000000000000d30b3b1727758441aa4d3bc89182eec6cd29f8de97b2ad78b059d64e5369ec9a0100000000000000bbfcfcf1c9a6a576d76559e3ef69d5a13a2751f32661c0798d9b32ab017aad520100000000000000f3d58d0f267316d55c08caf4638bfdf4e781a44ba0165a6589a6102370490d89010000000000000057e2f8546be780091a2ff07c027bea462ace6cd201ad624c076b3b78a17a94ba01000000000000001de55e80321186a8263712b501015b7ada9e8b1b4061b64f8d7aa78d1e736e3a0100000000000000172a68338d9b2a719685d13531d6cfa59fd9f9108a6c4ae2369e0b84322ec3410100000000000000a453853db2a712f86cae675230847e9bb74a537e102c3e9ebc3dcf94f4f1f6ff01000000000000000653b109f7fdc95b7c73997eb664d5c299d3fcdcf8e3af674d034b87e9e6efe301000000000000009f051fa2838e35661b7e3ec6fe5b8bd3eeb23d191c6e2c908b150170426c3b5501000000000000006021bd7676d7aefd3ba1542c657faa3b87c15113fe1138aa1ad61341ad855603010000000000000057d0adcf969a39a7d0a25a898db1925d413b53895492dc48231ccfc50b672edc01000000000000007be9a15295a120bd330f07e9295800e06cbc2165decc736f9d5f8ec580efad1501000000000000007c53deb88b1a9f772fd0d61556d267783db7e3765e22fe6540048c580ebb92a101000000000000000bbf8f4a346f74964fc07987fe00053d72f1f132d4964e7e5012bc9fa460a5030100000000000000f745129714167d9e647da677b8d453135d9e75b3efb4e86a757c6274fdf365e4010000000000000036cd1eebeeaea926936837b65de98409c469d2438b9cd13f9aecd9828f2320f80100000000000000bbd226a3ad42d0bdff594f898826402d087baca25d12703f115c4559f359698e01000000000000005e7b52eec0c156214b2bbfba7c059cebd6db3c45835a665e0f6aa35621d7788c010000000000000017ce4bb41bf33a073d81a253307d363d9074a387abc3cd76ac52e7fae11ae92701000000000000009a8f12b9d85318cf7f93c0599edb3e397306670f5a59b11071c9529f87d8732601000000000000000e7833314b4a21425a183b725241cb3ae89ea9ce0cf56572e61daf13157ce9bb01000000000000004c107dfb960bb93c6e36528fd6d23e67427768965fea0a77f75f70a0fa1064a20100000000000000bac15cb3005a8818d1fbd07f2792f27a84de4ca75bd602172b8a10cf8ccde95e01000000000000004e44d1267a3e1653c564459a980b21db4bdfedb65e6d79654145361e00c388040100000000000000ad0dbd33e3334319efae4c0df8171d8a6dfa5f36cbdc08f4f6654c034bd74cd80100000000000000be9022566f2fc0ede57413f11519aaa52218638de70d2be8ba2dc7fa4a6b4f5f0100000000000000ecddcef3ef4929a20ab4c2ab4d252fc638cd016658ce1591698cd5f11e6ad5dd0100000000000000a730a48dd7de96d724dd78a9ba528d34c673603a2cb4b1a7dc3b14e92eeded050100000000000000302ffda71edb5b383bf5970f15a0181f84eb0282cca90100fc454953ce4f7fef010000000000000030b1b9dbb27657148a780f8139cc13076cf1c33b71ff382555966cad0d683d7f0100000000000000dc54c257868f60eab20780954fb72a866b21f643308f7c0ab3b6e2a9f4f0010601000000000000009c5f840ff005b42fdf54c665a331f6c9a3ba67d38ca9512b23e2646c420ce1be0100000000000000f60bf2ec0a60ee1109c5cf1e3e10765345a060bb767a455182bead1b067d045d01000000000000002503da32b9aabba2d73cb4b64554c9dc71a0c5e5c1391cbab2ae98fc3d4a232a010000000000000096d36c9288d08375b3d547c3dc8f952bb1137fd5c3087bb8bbcbd5b8fbe3accc01000000000000003b2180b3d5f1fcd150f9856b763da49c50357c1d528ecc9d36932adcb062d69b01000000000000002daa75813c73ca5543def4d0891dbc76e450b9fd4802a0bf80811db845f927640100000000000000d63d680bb4a3c20227315afb95862ba264aa21121515b2937dd058367e415a0c0100000000000000a6e5b8159fa9df398d4c29f27f855ec83f56ed1615fc69c9bb5b88541e5d931d010000000000000064ae58236a9c646382b8fb7d27e209cdd9fdfe4a3c42cf03502785bbfc25d77001000000000000002868883051b17c28d5cc53dde5a69a210208402bcead13086cd43d19aaf820d4010000000000000087095a48bb621b2372a752154b552567908a749df830635885410f1e705ca2a70100000000000000114dd622bf78f8a90be24c86a7aaec2c84d7c65d193d2e26f4946b777e80ebaa0100000000000000b0af63a6ed5a364cf4935fc6a0714232accc624dee5e6bcb9170ffedbf99963b0100000000000000c23728fa40b2ec3c8b236d169ff398d770a8e2566bed4f3416a9ac100f40f0630100000000000000910f68ac0457d60460cda9bbd8559cc0c4a6290feb03ca79988a9b0f3e0a03d801000000000000006b5ef74df17ca662c870c489a6e6c57f10d41f50a6cc558400a61854e66d14830100000000000000699ae4589dce260bd0f51a05208cd5612fe7bdbe87cae8be548fd168fa8b182501000000000000003644d6c7c0e89a4edf6331e17a147c174d06facf891a036242d8e8a0633bfe6a01000000000000009f55a42822b1af64e031f12a4d913d3e8065434d8985b37c5b74010a847c1eb30100000000000000661e02ac790a3a7af9bff79a2cc94a75059bb5ed2eac8b5a27bf0185d72c99570100000000000000671dd4b02fbfd0e956fdc31b9fbc0062d3236a1fa47a3294c5dd62764680e817010000000000000059e46cf5d030c3732b88be1204e5d591a62ce46606ac50c94a913e20c707ddc40100000000000000e3c7a9f80edb077b21973485ee16663abf722cff55af6e44a167b861dc8fa7b40100000000000000aed46cbb3b81671ec8a7a40b966b95cc8808045f134d6635e0057dac0825d7ae0100000000000000c932bc15eaa5fd19e9f5391c1f1269230840b9f05978893dda0c7db786ace416010000000000000097967f7ddf99c59a0310744dd022cbc83c71ec3bb8ad474044d71c4f5d4f4778010000000000000054e2c364543153ec959bfda6c5c69efa58f8eacc05d419688b78614478545346010000000000000091a1abeae7dc21b2366932b6c0b3fd603913b201127f343bd052b8e940bae2aa01000000000000008e52731d172ccf0cd0e68e349b20f9a30a1bcfdcc383869bca0b122be2f0e67f0100000000000000a20452bd4a6b29292fa8725d551691b04c2b53dca8892774c82513b2595de93d0100000000000000ff185b43021488634315977358d816c5da8520702359b6f1d2b6fdb8c0a754ca0100000000000000539c6a9cf39788feef973f48f54c2dc4863d3b28b5af4e08579471056a262c600100000000000000d88ed486432b11ec49c247f82dbe6ec74ce42287a1f96f4ecc4ddffba1bdb00e0100000000000000c157da1148e3fec0098ea52d10a8970b6f4c5506faa14133cfeecc0820e5d67a0100000000000000d9efc3ef1a2abf8dd1b2d0cb501c7b2bf666eaa21379d5b0ca15592d72d987df0100000000000000f447fddce496dec679b75110d504ff4e1dc13320ad9ada36550efc31c59c275c010000000000000022bc8f9a49096e770c936cf6aaa0a5e99861494b064d169e7c03b2ad514ba83001000000000000007aa1deb2d5e45cf5ef28520c6e08049c0f8ca8cc3d797590bf72fd8f1327ad0101000000000000005c91c617258e745c2641f64b623b0ddedfb444f7eacd8289f75736336b3e61190100000000000000b7bef95acc1121104ca1b37f3c9f79bb464d7f564e310573c763abe245fba844010000000000000009dd221a3be84881d5583a03ad02ac3f3add6eabc861415fec997dc15b9b003801000000000000000c4c60dc788f9f77639b871426a2aa85a6455d28df1a78d8486245d8f31927040100000000000000e4f9dc5be5b81e8960440426837ed657cf4c6197d25bc11f4cb12318400f89f20100000000000000ba237906be10ac8e9c209307ca33f73258b048e2684cfa95ddd149ac278d62c001000000000000007ba9cfd9f113e110ab434ef6678b464d114bfe6f970db26357c3dfca149bf9250100000000000000a577519c486f98ce81578c5e96c64cc0c29c5febd868b0e3804b55242febd09001000000000000003d8874311525da57093fb1ee2ef4529f16401edec15377f17215fbae51683ad801000000000000003943ac233778ed04db43307a720a56717f1f76332f0440df1befc92770db9df601000000000000009290deb6334f7a3e9a7ec43a8d85c42da689e63021d40cedacc539db0d660e600100000000000000abb004827c680cbd3dcfa92189c1dcc91500e57254a54285b3cea52540858102010000000000000064c99d806bec03038aa17580cf1437e43db68727821747b89ac0510a60699ba70100000000000000d61dbba3b78963d3aacab3051b8ed5345e97a12877833e53d340ae34b5934dac01000000000000008818f43c172fa7d37344b0df074fe7795da61113634ede5585525e491d528a06010000000000000078cfb0b5d7df6741e1a2399c33c42ca8c9ce29c93b3d308ba3ec5116dcfb393101000000000000002c709a8dfb482ef308243eddeb503a4395ed0410f04a201a3e1cf4cf74d83901010000000000000079e5bac7bf14d0ffc9a2d0eac55aa2827d7f28445974626d9efbeaef56a44f1501000000000000003c9aa43e2709356cf6003654cc40afd5b33b7840f5c1a4fc132f370dd325acad0100000000000000dbb6a0dafd2cab7af574101286ea000f110506fa5faa974f94921207db8912d30100000000000000898ee658b00aab03dfc508c2039e90f69cc4c33e93be14dad73f42c91d2dcfa40100000000000000edf71b81d6765e54f3570986c687ab52270ca0c88491fc6d4cecd32a045847af0100000000000000f9f3a99f52a9991475dbe0140739f816e8ee43a3ee0534d8e1c168d744293bda0100000000000000de590edce038302a478d35462299b4c0505daaf5f41ba29a17908116e038dd4c0100000000000000fd7c31202bb5673c345380538f1f431d2c8c9d2bbc892fdf8ab6a8ff79c050e90100000000000000340f9100df01977e8e7e1a950405082dc0fd8a2864743f73505ce779806938c10100000000000000938a0a6a1c32e7352518e591891b542e54221ece6cd43bea11a3362ae63f6db2010000000000000014480a4ce2ff1192a9adc0284e98efa480037efac4f315a19e39a82198188bd50100000000000000d1e9fb415e53bfeed0b1d3691cf780d547fcc291963d045bcc38cad420ff4f5801000000000000004f82dc9418d43695b2b1a0dde38098de6da47bc3b496d4a8cec02e5a1eb9d6e7010000000000000007c99b07a496bd4a6702ec9dae942860a2e3a11c1b7864f057d8b91e194d178a0100000000000000ecdcb4dc4e0a912e4191a5921ae28dbad4a50eca41084e26fe9920450e989684010000000000000035e400a7fa21cd5b3d33f0fbccd4fed4a1e15bbd7857f2bb87dfc28c9ad5fad60100000000000000546715dd806e6cc12930c73d5ba29d957b94e6ddd8be4332025a94aaa67e138f01000000000000000337babc46b44e58b15a2f9932a002e79176ea68ac4f0ddc83bff4ad24ac4473010000000000000053077ad3e81700fdc7a8843f07a7b9e1bdefc8969316eb12effc762aebb7c2b2010000000000000015478d96bccae266214004d67aa3b6a7242ea77ca9da0b95c9858ed70c4e33980100000000000000363a99c82a7e0b1166f5c657fbfb4acc0f85607463604406b07e0c847ba7fa01010000000000000051b9cdc1329db7a66e364983888744783c44430f6e66b79a9090a4d96bd9fd0501000000000000000d3f2775617b62bde57ba6ead8d4cc81c7ed2f2e986fa3cb6772b92098667a0701000000000000002025e3f1c19bcf5c38bced4d1436bd42c4f6d696935edd7668d9f013f4fe58140100000000000000b305c5ff4dae3dc6a37300f48da85fc7e812cf100d30e368d9b4e607ba9cd6970100000000000000663bc85589190c716bf3a13d579e703994a887136896a5c33d79469f9cc793e0010000000000000017b0496f81a343c5a70f8be4aea6b672b87b390096f3bec0436462d8b395f0330100000000000000303b9ec383edc3588e6908c606272594af38ca91ef8afec5a93cb1c981b6b87d0100000000000000734c354b0bd3405b0fc5a9279b3d76e27202f4acc6f3db4743045dcfdb4adfc1010000000000000081a2d42998fdc7ca4a0d7d222e6db074b360609e96c1fe97e9d72d4852990024010000000000000013fce414bea13a7004c9002f75852d5cda84df895e9b7e52172386622e02027801000000000000000a7e67d1787b44f57095c96c4d5ec8fcaa00f60a3a81d5c6e6afbd0e7b91764701000000000000001f81141a8c46fe442e4555cc9d2da4e61c4412c27e6938683eec4bbd43e8879c01000000000000001b4f3f8329e00910838ebb786487dec1303da4a4676fb39b49100ecebc67e93e010000000000000032649d7f5f5d7a5480c9988d0e10cf099674760c1799d1d9d286c65b6a089cb2010000000000000089b141bc8a8a191f46f334b8f3e9be2847823c90571f1fb991ee4ad26ec4ca670100000000000000e807218f271d4f6df7fc4fd462962038dd07576fbfedaf8c3a1fc4203b68d03d01000000000000008a3849c6cabe5082e90ec44d3b8d9816b01bd577d5fecfb109a547a5cb10d03301000000000000000698bd71dcdad99aec0135bfd67990e7c7098b64cf8a4e60a3e60baf963f155101000000000000009991974d92238276f33623d914d786c604e0022210b942aff8709c43c741abce0100000000000000661a7a0c09c2c95efcf1b702cde4856f81b7ed2f96355e97b62b8c25cea7e23b01000000000000009c5cab7f76e5aa7945c71bf3396f72c5691e24c97ebb2f54adfd2a4abd58edfe01000000000000005ce13b8a5efc21c9fd13a8bd474154d52bef1560fccab16adee9803ccfac107a01000000000000009ccae3655ff1e0e1e24c773158cb8e9df4e3bf8c82e4801b38f11f89a58a73ee0100000000000000c662283d9f7d948be4ea0264aff66034a4408abd2a268b7c65fbca3cc4e9e9fd01000000000000009069fe7c096d0ad1cde77c884707683c32ad769ef63b2ac4362df593767d3c510100000000000000106acb7e95a971f93eb4b04c9734fbf0b843fdf72305c6bf91b2a107bd88369b0100000000000000cf641b6776631560b647681ddec2fd628220f50772cb054fd46a487aeece2c9d010000000000000072584021095324b7e330d5432ed1c5d243792024871c8b7c0efa47da681110110100000000000000c08ac272c273336e48055a262d8994e48e4d2d29aee52e718e57dcc8cc410eb1010000000000000025c11d23f84fb922baca0183556b991c685a8027c6381b7aff51d775c08ff66f01000000000000007867c23ab1bc7a93082ddc309920226614ad82109d789b53e6fc06975a12171d01000000000000009b5fe41ad1c51f30c188d2425e210b5ede18af172bad6bb1241a0db87dc866ad010000000000000054b3af8299f6b939f6891887c8bb565a5aeb8cdb602612341f36ebecb565a2e1010000000000000096085f27f62625487d72d5a7136ffde1d7893dc527c3a9d356889cc3a36200a801000000000000003b049747c16458b8aa28bc371d6c7dd682fa3d550ebec106e9da18cd869d84f701000000000000008ae56a7f764b81a89dfcce9bb5388fc26dc3cb302f91c0b7d9923f81f404201d01000000000000000f3d2e4e137de175ec06abc592f6fc70375f49e67a23746887a74dc6e81e182601000000000000006b5d2ed89a94234d3a4bf918a5ddc201b74b130c7357894478f3f584cf395b1c0100000000000000606430051fdc771566c8810592ccfe826bebe78158034e9dd32325c40eb37bf70100000000000000ba104520cab9a9230eed557ffc3e8da57e700bb314880f2dedb10b4ec1bb2e6d010000000000000074806a0eb19c96f5940e20341aa53a15b8e0446f2e9fa0dce8ac8328f5c05e93010000000000000099802e5ce1e01b97b91bf8b8f5c6f18ec206e81b4a5b75642466db51e65912a00100000000000000be146351435791ac5e09f1009f24d3deeb701a46054a93b800e1b89b09a5a5c50100000000000000a7874632409602506850eb78c1e51d0d90b456b0f7a84ae2d3f53d6d9f3c910501000000000000008997db137aa2aec72c715759c19b1fbb85285e5f703b63629ff9d36220d4c2fa01000000000000004c76ae91cb8357b3eca70b8eb0cb0c80ce09c43a8a6a8b89e1faa8404344b5c501000000000000003a5ef3ca58df56def71e6f37cbe34ebafdcc0fab1cada6d708a3edccc10e6e0a01000000000000000db25baf0d49341b8b7de7b91d7a6d139aa62d693124724539d88677655edcd301000000000000000fd4f7941562ee92fb9fce140fb7b045bc4aa12aea490061d05d171eb4f80081010000000000000076f524b45977ad44790c75904183077340b843e80817902cfc8a8f6d5c00b6780100000000000000ce6e57c4979e6e0759b274d0d4495994d3b9ee902f80501103f0e834026e8be1010000000000000034f57117f72eb65ccc867f16044b6e9e5e0c30c432a3dd443513db39645020620100000000000000142427ce7c87d257ebe1761bbe7672fba09268d3213c8d26c36fb4df1cc21bd90100000000000000b594cadae4ac5f40a0fe6b834a70756324955af9d8d70c25c94dfcf3fec49743010000000000000085c832363e832abf7a711a60c8b114f03b923da64af0386d21f5495f706fdc040100000000000000fe787732d7a1ce9769e4063351bb058a123be12e5d4ef4f5d93dd0ca348f2f2c0100000000000000b5cfda4d2876bcf99538a4861278260738117ca5f251b2e7f3d15a8476272a9101000000000000000acc7024c10d76dcc968a5f2482d7c5be47abe879e95a2fae7887206fd1e681f01000000000000006eb677c427e7f426c7f0c1b18034d9b3ed2778fe0e1c7c35b2aca7776b41b8720100000000000000152373151474d68bfaf80d5afc281c11a2063e7549e5a203b1dd3a03d6ce55af01000000000000005ccf60dd4e7ab47906f45d71046ee5fcd7c9fd83dc4657852caff29d7381523d0100000000000000e9e6f84ec1ddf8381a2e07ab4607ed640532b2e9e3afa6129b345ffa679f6fda01000000000000002567eead1b79eade19ff3101e539ba2b607f4f8f0d50827767b368fc17172b6e01000000000000004e05e2f383987e2ea50e4b44fa9b03e982c86e63f4a745563a61f3fc4bff0b7e0100000000000000ca4e5b60dde2ec87a31fe0436798e86c3cf4d66e96aeec7bab5280b66347e4bc01000000000000000037acf41acd143038de38d22b0c9e1a71b530953e78969c4601430eb29bbe0f0100000000000000cac959d88c030df36da2b6106b4325b2bda9d30c9b76b3d3afbbc9b1b2d1954b0100000000000000c42ea844b49eb458b775f307dbebb9f29830a98ba85a7ca722a9297d8eb9511d01000000000000008508fec6ee8b9effbcc14ec08d39b7ece13137987f14529e2d6b3495dc01e3c001000000000000008fef8dad7915c9bb3693440286389516b297c9164fc8aeba2efbbf7b67d9570a0100000000000000dfb4e70faf37e0c3122e956280437080743a05c30ed0835f7a571581b04c77290100000000000000340928ec9ca251728a06f3b5d5e773d41754d4603eeff0fe052f464d4cd8660301000000000000002ac100c9edec32ec109e63a1c8ace437facde2e12d796a9dffb6d971414f378901000000000000000dec13d4b8393c0f712d31955c82c1397cc59c7b641ce309f7e00e5e800a55ca010000000000000051cbcf3c493583f34f4bbebcd8313e5b7a8bae7da8eec4e7effa536d86476a2d0100000000000000dff478e845e134f827b5e8845be956695f6769ff6dd68626267a032d61162408010000000000000076c3003d7ec387de2822f45df32fd18c1cc18224d381c0b720dac2108add4d3b0100000000000000bb8c12244c2ae20d31775b6b8002a558887be9c468d503b583a4730bbaefac9d0100000000000000a3ac7579c92e98b5e774ffebc0c43b115b5748c00c5fe93e9adaceda6d7ed6e10100000000000000ba5b4e2e810ee7984f9d9be220b046e36b12a32bf4637a3e0d3ff4ab9c2420bb0100000000000000570fe760a3340189708ad335107114e4a64e02180c19577efc359812f79a5fb901000000000000006dd5ee873e7630279f7bfbc3e212868973c107ad5ccea8a698aa4c2ab4735c830100000000000000740c72e955fec776fb23768873e1710a89d6aec2dcea5d624341651aca9fcd460100000000000000bb33c9f11c7986db92ea1ce85444aac8eb1c18faaa443d2df8d6aa7a1e608213010000000000000093e276d7f8b042c4a18130b085eb2bb6a4c06095934b9cdb8d5883642ee9b6bc010000000000000051a0d4023b87bf0998957fa638dd3960c3b96a753f52cc4e4c57ee2c5295a2a701000000000000001fbb46dc9dabfad0d6df5b081731b48cf9d2b3a525d81f729921a359100849e20100000000000000bb74154689a4725aa1a2ef790d2f1fe6cafe6decf7c04b344b03777836de3ce80100000000000000120fba7ddaa09646826b69e9fc039b75858e7ad47a4f7427108d5bd888c0a16001000000000000003248125508698a238f53403cfdc7627f672ce9f8cf93fa72ce8dc8776b3fcfa001000000000000000a09fadb607e53682fcc706f101a6ab9f295c1e27ab32bdc6875baad163cf0e8010000000000000009e4ae032e54a6b4d2d8e0e30e1ec1b42c2eb5255def58b64280c39a7ae2a2230100000000000000a81e678f0adeaa9c5ab7d96a80381e47f601d9bc6d410b970e58c175e254d74e01000000000000007cb589ac52163016e157a3c01102e21f7540072721ab1ae15f06da8e531532f701000000000000006534178809a1ffd07aadc00b73226c701748ddc694935aaf3360e8690bb5404b0100000000000000ceceb7fdb6a93ea913ace600128652043dcace10928b4a24fde635f1c72b812c010000000000000072737c3205224b49df92fba1fd9f9f8e8dc7797b6e2694f31cad8e8d3e6282370100000000000000a2847de5b17cc8c8865e560770814dd352d6bf25f315b3a39f26ebd7688109960100000000000000bf9c3af8647f294e82d4544d0fb1165eaf9c33b0bc4e34cb12996d557055a5c60100000000000000c84c3f3ef8e02d435684bbd362371b4dcbe8821d78ca64efe76e2d85302d79cd0100000000000000eff02b3b76c33319976a8438a2a5b4b68392ed910c01dfd5d2b81226d521acde0100000000000000";
+        let versioned_authority_list_binary =
+            hex::decode(encoded_versioned_authority_list).unwrap();
+        let versioned_authority_list =
+            VersionedAuthorityList::decode(&mut versioned_authority_list_binary.as_slice())
+                .unwrap();
+        let authority_list: AuthorityList = versioned_authority_list.into();
+        println!("{}", to_string(&authority_list).unwrap());
+        let encoded_authority_list = "0x".to_string() + encode(authority_list.encode()).as_ref();
+        let set_id: u64 = 1101;
+        let encoded_first_block = r##"{"block":{"extrinsics":["0x280402000b101cdedb7201","0x1004140000"],"header":{"digest":{"logs":["0x064241424534023801000087c4d20f00000000","0x05424142450101987e387ab4bae9ff75775ac9f443f3b0c67a67b882971f17f935342027b9574cb02d317ed673e140fb427484110f719f160bbbd895cc012e5fc4131a2d980484"]},"extrinsicsRoot":"0x54949d175590963a8da7086d93db24b4ef8fc293ec9f78fac437018f471cf3e7","number":"0x2b99fe","parentHash":"0x1bfe3c8897a158a06c852452d14e79e076a4546427f8820006dce967caa2ab8a","stateRoot":"0x7406b1be4a2f70a42fe9aee585634400d59fee9fb8825d26800143208bebeac6"}},"justification":null}"##;
+        let first_block: SignedBlock = from_str(encoded_first_block).unwrap();
+        let first_block_hex_encoded = "0x".to_string() + encode(first_block.encode()).as_ref();
+        let encoded_second_block = r##"{"block":{"extrinsics":["0x280402000b8033dedb7201","0x1c040900f267ae00","0x1004140000"],"header":{"digest":{"logs":["0x0642414245b501014101000088c4d20f0000000066c6306a31a925f5f947011a7a3e7ccc1ce5571d85b01d32c8b5ae1557a3ed67974a2e5084adf965f248f2e19a21296c3ae133607df506223c0204fea5dfa6014b0c037a04a90774cad4d093934d7c3876a4aa627eac4d762c7903abae1b240e","0x05424142450101e68d322738413ed45f63e3c7f66511f2940b3d600a6dd475c8ad9fad77f07b5fed210d2e0efe90cb8c85f8b67e739854366300b6717a58c899f781ce7f11318c"]},"extrinsicsRoot":"0x7b5c52f0d6f125e81560f41876712815562bc2cce43a42280fa1d973c9f8bf5b","number":"0x2b99ff","parentHash":"0x93cd6c9490d544eed4efa0e90a0d13f0bb981fec55878042b69ba9ae54fa9b0f","stateRoot":"0xf71e199c3a505d47416aff00d933a26bcf9b8ab47dd8a98e4c3316e06527ec38"}},"justification":null}"##;
+        let second_block: SignedBlock = from_str(encoded_second_block).unwrap();
+        let second_block_hex_encoded = "0x".to_string() + encode(second_block.encode()).as_ref();
+        let encoded_third_block = r##"{"block":{"extrinsics":["0x280402000bf04adedb7201","0x1004140000"],"header":{"digest":{"logs":["0x064241424534020b00000089c4d20f00000000","0x05424142450101e2ae609365dcfbd928d8337c8d15759a6afba0b26ccbfb14f24407920395cb7ac0ac26364b43edde72072a016bad47a254271968e7cde0d6788f2e0c81be7888"]},"extrinsicsRoot":"0x25c0be3d9c0f752ea58db649735fa0cd1d61f7f2b88ba395cce76e306c132708","number":"0x2b9a00","parentHash":"0xb1c66cb4c220d9495af76befe39c08ac3b2bb8d97e324288b7ce6d9ba26e32e6","stateRoot":"0xe63104de515d8cd95d00fe3380afefbb6fcfaef1b9e01fb12df6f5e0148d8a60"}},"justification":[72,1,0,0,0,0,0,0,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,169,3,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,52,74,218,176,171,227,104,148,119,151,24,166,141,89,192,176,194,218,144,15,2,157,110,192,180,220,147,232,153,232,5,233,158,165,164,153,23,150,236,189,97,217,230,45,40,0,186,170,80,2,139,48,205,33,138,254,124,106,31,130,18,232,149,12,0,55,172,244,26,205,20,48,56,222,56,210,43,12,158,26,113,181,48,149,62,120,150,156,70,1,67,14,178,155,190,15,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,151,33,222,88,210,194,239,74,169,129,30,202,84,143,253,193,86,126,67,118,28,159,190,73,143,145,133,179,141,245,212,193,233,197,153,194,69,57,161,109,159,37,28,78,104,201,130,162,57,3,107,177,88,45,51,239,43,192,179,87,230,65,245,11,6,128,69,215,143,27,30,115,68,113,13,178,202,60,73,232,49,53,180,126,22,73,12,149,199,206,39,244,164,220,76,56,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,213,87,65,87,75,215,171,192,142,138,75,20,37,40,186,47,17,181,72,192,252,194,239,68,65,113,147,22,73,54,4,25,255,34,202,40,221,36,233,196,88,116,177,207,224,72,199,195,199,251,155,157,10,135,141,2,59,200,242,223,121,141,112,8,6,152,189,113,220,218,217,154,236,1,53,191,214,121,144,231,199,9,139,100,207,138,78,96,163,230,11,175,150,63,21,81,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,56,222,155,70,78,219,172,150,245,132,195,71,149,157,192,243,173,225,215,116,49,17,55,15,11,59,227,191,249,240,181,90,241,129,62,126,6,195,47,117,107,201,65,179,172,198,109,169,104,231,46,191,53,97,148,236,126,202,81,60,89,226,88,12,7,201,155,7,164,150,189,74,103,2,236,157,174,148,40,96,162,227,161,28,27,120,100,240,87,216,185,30,25,77,23,138,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,15,134,137,92,207,195,217,221,116,197,51,237,126,170,159,246,88,54,220,208,15,55,5,239,83,241,234,77,187,84,253,207,93,205,34,13,18,128,115,136,29,145,226,32,67,35,12,54,127,152,24,101,154,36,44,164,94,21,140,127,244,191,56,11,8,159,131,101,242,101,193,44,88,214,235,149,203,88,212,230,118,38,220,124,161,10,194,35,66,75,118,36,1,44,27,181,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,58,226,41,96,213,188,212,238,39,20,239,115,195,60,103,99,77,227,143,72,16,14,38,102,38,209,61,150,134,175,132,166,241,180,172,71,176,3,206,87,91,63,35,185,14,63,142,78,88,132,178,68,223,243,106,12,99,199,115,61,218,91,21,2,9,112,114,232,161,4,238,125,137,47,7,65,215,33,34,244,148,238,79,198,146,64,188,194,10,48,212,66,144,134,174,124,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,43,197,73,215,118,181,170,72,58,168,108,230,248,64,121,44,233,180,139,120,138,95,220,55,227,55,159,226,160,104,11,21,26,101,125,49,19,153,130,22,132,145,51,77,127,127,193,95,94,133,93,16,179,156,13,161,175,19,24,195,36,73,57,12,9,228,174,3,46,84,166,180,210,216,224,227,14,30,193,180,44,46,181,37,93,239,88,182,66,128,195,154,122,226,162,35,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,219,107,143,163,173,148,214,156,233,208,106,141,72,124,80,103,135,165,126,110,102,153,187,154,142,182,244,155,216,108,229,43,234,85,184,97,195,114,129,167,109,105,234,241,172,172,64,111,107,158,18,150,180,66,9,246,35,48,115,74,69,157,156,10,10,9,250,219,96,126,83,104,47,204,112,111,16,26,106,185,242,149,193,226,122,179,43,220,104,117,186,173,22,60,240,232,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,98,193,27,191,133,54,72,239,104,122,109,244,201,160,190,196,196,111,137,33,47,186,137,99,186,125,236,127,35,86,31,66,135,187,211,56,105,157,245,59,254,248,244,139,198,166,102,45,50,88,233,234,155,156,211,57,160,188,125,55,29,173,103,6,10,126,103,209,120,123,68,245,112,149,201,108,77,94,200,252,170,0,246,10,58,129,213,198,230,175,189,14,123,145,118,71,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,250,56,211,19,147,71,236,52,60,104,210,35,109,6,149,213,137,245,70,146,253,92,18,147,120,51,132,197,223,19,247,101,82,73,24,14,211,173,161,154,29,117,181,20,31,21,228,84,87,254,132,45,184,104,99,94,108,219,21,136,208,112,30,9,11,66,15,137,27,148,225,60,1,80,225,62,74,32,175,139,107,120,32,196,215,12,239,8,223,101,53,227,123,32,18,180,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,204,203,10,230,31,84,38,252,127,204,113,17,215,225,208,0,132,23,4,35,233,53,245,173,176,120,198,217,127,51,67,125,168,175,185,117,177,80,57,67,58,192,142,7,176,85,212,217,3,68,55,189,59,252,208,31,230,75,30,189,200,91,20,12,11,78,138,81,113,8,181,169,108,213,96,252,145,22,203,149,91,126,100,232,46,117,122,0,217,172,84,76,236,161,227,114,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,125,9,44,26,37,64,154,182,234,179,232,237,105,90,183,45,49,99,47,149,51,194,182,53,37,37,240,14,207,172,30,26,17,232,236,194,138,77,37,147,33,116,114,87,230,15,129,247,97,28,159,181,251,126,161,182,55,238,7,106,86,85,102,12,12,53,163,121,74,223,93,73,226,238,58,165,74,53,98,38,12,195,72,100,193,243,49,9,225,6,65,46,41,147,147,118,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,157,58,194,120,222,121,234,61,164,37,236,96,127,152,46,135,191,120,115,226,183,115,191,26,79,146,93,136,13,75,72,190,184,244,8,35,213,123,122,121,182,220,34,174,232,224,254,97,24,243,225,128,3,121,146,244,164,58,76,119,63,186,221,1,13,63,39,117,97,123,98,189,229,123,166,234,216,212,204,129,199,237,47,46,152,111,163,203,103,114,185,32,152,102,122,7,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,252,93,104,45,230,100,202,118,254,184,234,61,144,94,164,232,214,11,58,45,172,27,8,178,109,225,173,31,223,87,237,239,10,185,203,91,33,155,168,149,144,102,76,100,11,198,190,113,5,156,191,153,199,124,211,248,249,126,90,85,5,202,174,8,13,178,91,175,13,73,52,27,139,125,231,185,29,122,109,19,154,166,45,105,49,36,114,69,57,216,134,119,101,94,220,211,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,63,34,39,104,136,134,7,187,32,198,160,142,184,73,100,15,152,117,101,251,189,85,0,88,78,205,16,234,49,102,100,134,177,36,203,237,178,25,51,41,51,67,193,107,45,55,215,218,161,183,110,230,133,193,118,137,24,140,243,158,152,56,179,0,13,236,19,212,184,57,60,15,113,45,49,149,92,130,193,57,124,197,156,123,100,28,227,9,247,224,14,94,128,10,85,202,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,142,195,129,2,70,146,17,171,210,13,50,233,172,172,105,250,11,163,120,184,225,200,116,222,99,78,36,94,128,144,14,95,234,254,25,3,110,193,0,37,204,188,48,45,205,194,171,190,157,68,45,127,181,206,234,100,138,73,37,61,59,53,178,9,15,61,46,78,19,125,225,117,236,6,171,197,146,246,252,112,55,95,73,230,122,35,116,104,135,167,77,198,232,30,24,38,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,159,185,16,83,227,176,170,38,131,29,139,224,55,97,46,119,228,101,202,68,230,208,129,95,132,69,153,212,111,166,29,199,137,4,133,215,88,62,54,228,29,117,238,18,125,108,12,200,17,96,103,248,233,85,217,237,140,54,167,194,245,131,245,6,15,212,247,148,21,98,238,146,251,159,206,20,15,183,176,69,188,74,161,42,234,73,0,97,208,93,23,30,180,248,0,129,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,202,57,217,72,110,193,84,172,135,106,65,145,21,162,164,134,60,92,46,19,244,69,141,161,126,58,15,103,250,72,177,7,56,71,131,104,2,97,123,146,168,159,204,199,37,106,103,139,10,249,25,201,166,183,38,59,243,10,134,103,137,76,110,0,15,254,220,1,21,116,37,251,7,44,188,42,191,131,120,235,61,125,70,228,1,96,16,255,141,13,17,229,223,131,156,26,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,52,150,145,197,58,111,203,208,146,115,32,47,65,230,101,87,211,130,3,160,241,69,178,251,36,99,28,29,98,55,152,172,96,83,192,86,252,197,158,36,103,243,103,129,194,170,67,245,120,11,143,255,190,62,35,116,195,134,187,103,95,1,43,5,16,84,82,255,2,97,162,30,220,130,58,45,73,75,162,209,235,27,50,99,117,184,1,156,147,177,174,121,164,250,101,36,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,79,164,15,85,11,33,246,117,149,243,154,127,188,172,5,50,163,86,115,249,109,99,133,24,128,85,225,186,143,202,198,22,72,70,233,200,165,168,252,233,126,161,44,61,160,189,113,51,247,204,240,197,213,14,12,10,126,105,149,71,123,160,5,14,16,87,192,252,65,109,63,96,160,11,199,248,123,213,95,12,235,39,253,211,243,175,27,213,134,75,21,156,26,219,163,154,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,135,215,123,149,131,228,161,66,192,223,162,216,254,6,81,130,117,144,166,208,146,73,203,191,196,66,175,114,81,205,192,31,167,203,136,205,153,158,79,1,245,50,132,254,85,84,126,154,105,202,74,49,252,162,64,86,78,254,8,238,246,53,253,6,16,106,203,126,149,169,113,249,62,180,176,76,151,52,251,240,184,67,253,247,35,5,198,191,145,178,161,7,189,136,54,155,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,246,191,115,65,148,60,35,217,19,161,103,167,154,134,228,98,248,135,0,7,84,151,235,250,195,181,82,185,152,190,244,157,30,129,93,251,106,145,35,237,245,37,1,54,185,29,161,112,67,7,214,20,232,7,49,89,211,56,225,83,30,191,237,1,17,77,214,34,191,120,248,169,11,226,76,134,167,170,236,44,132,215,198,93,25,61,46,38,244,148,107,119,126,128,235,170,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,146,63,104,193,220,10,112,55,175,35,178,170,144,7,97,19,241,173,25,223,44,203,58,51,91,167,48,235,226,141,21,252,138,199,101,14,76,19,113,116,228,51,66,237,7,123,114,86,241,11,198,131,80,253,130,84,189,44,37,69,217,248,201,7,18,15,186,125,218,160,150,70,130,107,105,233,252,3,155,117,133,142,122,212,122,79,116,39,16,141,91,216,136,192,161,96,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,199,64,137,175,100,103,188,128,206,34,224,198,176,182,220,13,224,134,235,19,186,246,89,147,182,229,79,94,111,242,25,57,161,153,255,97,92,153,27,114,129,41,86,112,186,214,94,198,140,78,85,69,3,35,16,98,14,36,184,39,76,166,77,3,19,252,228,20,190,161,58,112,4,201,0,47,117,133,45,92,218,132,223,137,94,155,126,82,23,35,134,98,46,2,2,120,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,159,51,70,30,166,188,199,220,105,95,134,36,103,7,89,140,124,129,22,90,235,20,63,72,25,58,112,208,180,2,108,162,185,246,138,139,230,207,44,53,197,116,171,98,241,52,117,131,212,249,79,241,122,229,242,10,108,190,40,228,228,10,59,15,20,36,39,206,124,135,210,87,235,225,118,27,190,118,114,251,160,146,104,211,33,60,141,38,195,111,180,223,28,194,27,217,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,34,2,164,221,234,12,60,194,104,78,168,156,200,223,26,72,83,151,52,98,196,112,83,238,9,182,51,2,66,190,56,131,178,67,11,128,192,232,24,250,95,151,22,105,117,56,141,145,146,77,216,95,42,244,204,33,29,201,222,111,234,199,213,5,20,72,10,76,226,255,17,146,169,173,192,40,78,152,239,164,128,3,126,250,196,243,21,161,158,57,168,33,152,24,139,213,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,233,113,231,224,166,219,148,223,70,103,168,15,126,96,177,222,108,139,219,203,20,37,75,47,227,61,2,222,196,196,133,144,221,0,248,239,145,254,123,232,143,173,236,126,146,20,116,148,130,7,238,172,173,9,212,158,170,176,53,188,127,206,6,8,20,144,58,25,50,247,41,238,197,28,100,200,101,47,90,123,223,23,223,163,89,121,158,85,15,16,131,225,100,87,255,39,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,171,177,111,91,176,129,236,176,48,119,52,96,72,139,11,235,55,175,144,241,59,90,236,57,113,202,151,99,137,233,226,200,70,22,122,254,198,252,114,157,213,69,167,93,56,162,75,22,186,28,32,131,145,244,245,7,239,72,5,113,31,231,88,10,21,71,141,150,188,202,226,102,33,64,4,214,122,163,182,167,36,46,167,124,169,218,11,149,201,133,142,215,12,78,51,152,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,87,136,182,37,146,5,113,202,143,163,110,59,18,95,145,228,20,163,59,238,49,130,213,72,133,194,225,233,88,167,164,104,254,141,229,35,255,176,104,108,33,221,113,60,134,27,31,209,115,221,67,34,132,65,59,38,208,160,253,83,228,62,37,2,23,176,73,111,129,163,67,197,167,15,139,228,174,166,182,114,184,123,57,0,150,243,190,192,67,100,98,216,179,149,240,51,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,100,183,35,51,209,218,253,124,110,98,244,181,250,67,46,21,96,14,203,13,40,245,249,88,249,203,70,26,143,22,151,202,175,221,175,106,150,98,52,36,198,86,188,163,94,2,81,105,110,47,26,59,168,84,163,237,119,0,10,222,205,24,176,0,23,206,75,180,27,243,58,7,61,129,162,83,48,125,54,61,144,116,163,135,171,195,205,118,172,82,231,250,225,26,233,39,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,148,250,13,134,223,125,167,125,152,104,188,146,201,16,66,87,194,116,187,183,5,20,21,85,203,78,121,184,213,212,143,191,76,129,122,78,11,20,165,41,109,253,168,157,205,5,252,74,185,152,163,218,86,233,27,143,12,232,109,95,177,86,219,12,24,45,163,19,112,169,10,118,144,47,69,34,43,41,67,42,114,71,79,227,170,250,124,98,211,104,17,31,98,20,32,92,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,17,179,117,236,99,209,31,229,255,249,20,64,216,46,186,27,208,162,111,73,129,140,171,201,187,214,18,232,116,206,236,254,202,76,116,248,69,235,93,180,142,189,116,232,154,10,0,117,28,152,124,137,65,156,137,30,21,108,72,213,187,224,159,7,26,85,120,86,40,48,11,254,190,233,187,242,62,241,242,235,109,59,92,195,42,214,143,249,46,123,52,165,172,159,107,132,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,110,249,201,192,28,14,195,143,202,2,101,222,200,22,9,122,9,197,223,158,73,195,88,179,76,30,134,249,237,233,161,205,58,26,27,218,36,144,109,38,32,229,253,35,101,133,241,45,169,211,19,71,82,154,247,244,66,13,181,94,232,118,196,15,27,79,63,131,41,224,9,16,131,142,187,120,100,135,222,193,48,61,164,164,103,111,179,155,73,16,14,206,188,103,233,62,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,7,72,125,164,108,141,109,203,219,64,146,107,211,43,120,66,83,206,131,185,160,228,207,67,194,84,238,58,218,64,17,155,115,201,61,84,80,41,155,194,136,62,123,193,254,79,15,185,149,57,169,63,226,75,33,251,207,186,194,152,98,62,131,3,29,229,94,128,50,17,134,168,38,55,18,181,1,1,91,122,218,158,139,27,64,97,182,79,141,122,167,141,30,115,110,58,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,87,22,239,125,46,110,225,99,93,24,96,201,7,85,194,104,43,2,10,35,47,32,4,230,194,127,104,83,178,185,201,135,231,176,74,33,130,105,106,71,95,228,210,114,64,46,217,49,12,182,151,40,160,228,151,166,0,119,12,77,71,0,56,3,29,247,148,139,18,203,123,228,213,217,174,233,59,116,101,141,12,132,68,160,195,95,63,250,74,237,197,195,83,156,49,191,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,70,72,59,138,47,57,196,157,19,221,9,62,3,46,77,176,56,255,209,238,168,144,62,72,30,140,15,29,39,140,246,222,200,188,163,86,169,115,16,182,229,25,175,132,143,144,240,135,57,186,116,240,3,13,190,27,213,64,38,244,87,138,240,5,31,129,20,26,140,70,254,68,46,69,85,204,157,45,164,230,28,68,18,194,126,105,56,104,62,236,75,189,67,232,135,156,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,209,141,0,213,187,68,178,210,118,40,154,114,45,79,99,97,245,147,158,101,113,96,89,31,181,58,238,133,96,139,139,237,67,210,242,168,140,201,25,9,122,184,98,148,216,69,237,214,161,232,167,79,152,8,109,247,43,73,18,243,253,100,57,1,32,37,227,241,193,155,207,92,56,188,237,77,20,54,189,66,196,246,214,150,147,94,221,118,104,217,240,19,244,254,88,20,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,179,70,125,252,103,21,119,231,94,50,24,4,161,161,16,175,69,207,199,1,8,177,214,211,248,114,175,110,41,229,92,244,175,123,142,184,123,110,182,245,57,166,240,193,22,243,93,210,181,187,215,232,51,105,3,107,221,178,145,109,245,118,17,0,34,170,21,132,8,64,175,25,43,134,135,186,18,52,127,253,109,236,242,254,71,81,65,85,223,143,116,55,21,78,173,92,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,90,99,80,74,0,223,193,142,121,243,253,41,88,179,157,34,73,22,145,42,110,88,25,124,37,136,119,104,28,85,63,151,123,78,34,165,160,46,127,229,197,41,100,76,31,108,113,172,155,236,27,219,108,11,198,215,249,46,26,83,90,112,44,9,35,219,158,147,245,92,20,198,122,159,215,166,15,162,246,245,30,78,140,36,186,160,151,119,108,226,124,79,194,32,117,131,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,156,74,165,241,31,148,135,97,88,195,57,48,95,255,142,5,244,137,21,250,26,213,126,10,189,91,196,106,252,86,221,235,46,185,134,183,45,221,202,169,21,70,88,83,177,247,13,176,224,161,46,250,149,95,65,84,28,245,92,240,64,125,40,12,36,161,190,57,201,126,245,153,47,148,239,203,15,69,17,128,26,90,189,235,62,129,63,180,100,212,62,179,27,201,37,201,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,105,128,171,132,18,225,194,53,23,137,108,227,47,55,101,56,241,65,247,34,107,94,185,135,11,90,66,125,43,38,175,184,133,115,61,127,11,253,246,228,16,57,163,88,37,146,223,203,20,180,46,131,236,194,112,61,214,145,209,125,59,132,127,5,37,103,238,173,27,121,234,222,25,255,49,1,229,57,186,43,96,127,79,143,13,80,130,119,103,179,104,252,23,23,43,110,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,144,165,140,225,119,26,120,90,226,237,57,184,12,137,212,192,161,186,141,85,108,113,33,113,232,94,218,178,73,121,179,207,170,203,78,221,87,72,78,75,3,56,47,254,12,104,114,150,125,113,66,136,67,126,40,128,241,123,241,221,6,201,115,10,37,193,29,35,248,79,185,34,186,202,1,131,85,107,153,28,104,90,128,39,198,56,27,122,255,81,215,117,192,143,246,111,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,248,238,74,148,23,176,95,169,49,253,122,248,90,91,7,52,34,203,55,236,183,249,159,93,87,210,110,173,203,252,74,205,203,23,232,210,84,79,60,41,149,216,80,43,195,25,221,48,20,145,98,126,53,165,178,180,163,89,150,111,93,141,246,3,41,143,193,71,22,128,113,16,210,118,52,67,119,230,36,114,114,73,41,97,243,175,213,44,222,229,223,133,151,142,244,172,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,151,222,171,50,116,41,242,6,90,157,201,57,34,27,123,58,234,55,128,8,24,192,234,33,193,214,169,72,83,210,146,199,160,223,12,31,109,159,131,205,190,193,85,167,164,126,102,164,65,224,199,19,130,31,127,190,154,36,136,79,87,30,16,11,41,252,120,71,89,151,188,160,88,135,43,131,245,150,25,114,233,119,31,120,240,250,224,220,233,24,228,48,223,131,170,42,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,7,150,193,223,10,67,49,233,252,11,66,34,76,170,126,159,28,79,200,180,32,248,195,13,174,55,164,111,97,78,198,130,253,35,173,70,207,7,231,89,178,142,63,109,62,139,154,64,196,17,238,193,198,163,82,47,120,180,91,213,52,8,91,1,42,96,144,232,165,97,56,254,100,31,63,87,54,96,74,247,171,115,142,229,0,111,183,57,18,54,36,33,194,56,19,228,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,143,178,175,59,90,223,1,132,210,159,164,26,64,183,103,41,249,131,114,5,209,81,68,97,231,21,108,190,51,229,173,228,41,144,181,78,109,47,109,98,0,98,16,239,224,72,111,87,205,255,239,77,138,148,2,23,112,164,23,167,43,252,89,9,42,193,0,201,237,236,50,236,16,158,99,161,200,172,228,55,250,205,226,225,45,121,106,157,255,182,217,113,65,79,55,137,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,218,230,112,220,183,41,124,124,218,75,15,138,17,240,139,12,12,116,71,180,136,21,224,246,183,130,248,220,91,175,74,125,50,208,232,182,9,177,140,167,97,60,246,188,88,243,75,37,0,118,92,104,108,185,180,105,72,158,170,159,113,86,223,0,44,112,154,141,251,72,46,243,8,36,62,221,235,80,58,67,149,237,4,16,240,74,32,26,62,28,244,207,116,216,57,1,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,98,191,157,223,253,93,9,31,51,216,32,113,230,27,232,211,48,52,175,125,253,21,226,169,101,110,190,162,206,59,48,159,84,54,182,110,245,157,193,17,79,9,197,201,237,102,171,9,75,238,249,36,170,170,94,213,111,166,219,91,203,222,235,4,45,170,117,129,60,115,202,85,67,222,244,208,137,29,188,118,228,80,185,253,72,2,160,191,128,129,29,184,69,249,39,100,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,115,78,211,166,89,155,16,109,139,53,67,77,86,21,148,122,61,63,162,131,76,152,117,148,2,19,158,238,99,115,54,134,111,235,207,33,199,9,23,83,74,44,206,52,41,138,252,197,145,187,81,102,52,129,82,172,165,69,156,221,50,252,26,3,46,119,156,133,53,167,104,157,223,67,137,36,0,96,206,221,158,154,60,85,158,173,197,114,227,183,151,216,196,154,199,153,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,242,17,46,155,239,203,26,144,136,175,183,180,27,250,41,208,186,118,23,85,80,41,119,154,235,195,27,64,119,191,132,6,89,62,225,45,156,220,217,238,168,36,104,242,254,8,42,86,99,83,138,248,52,98,6,2,38,47,67,49,126,204,230,4,48,59,158,195,131,237,195,88,142,105,8,198,6,39,37,148,175,56,202,145,239,138,254,197,169,60,177,201,129,182,184,125,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,78,18,149,61,56,215,127,199,76,81,186,38,103,140,3,225,56,174,163,20,82,154,151,167,99,156,235,182,249,104,33,212,125,172,61,13,184,9,62,22,1,11,138,234,235,167,209,52,158,191,120,105,124,55,14,3,110,209,79,92,42,81,246,7,48,177,185,219,178,118,87,20,138,120,15,129,57,204,19,7,108,241,195,59,113,255,56,37,85,150,108,173,13,104,61,127,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,58,174,55,82,3,54,248,32,213,244,56,181,240,152,32,214,69,4,231,251,4,202,244,145,153,248,203,249,73,121,86,171,87,171,171,66,33,50,179,95,126,56,157,140,72,176,139,166,1,115,98,41,168,123,200,179,20,113,22,30,134,79,58,15,50,72,18,85,8,105,138,35,143,83,64,60,253,199,98,127,103,44,233,248,207,147,250,114,206,141,200,119,107,63,207,160,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,139,79,223,41,74,188,83,35,93,144,33,29,62,174,252,74,101,100,184,16,173,137,79,143,245,209,134,58,162,137,185,190,87,204,136,162,195,202,180,57,37,15,46,159,98,120,221,160,250,106,172,227,153,239,232,79,173,244,231,251,169,35,45,3,50,248,191,15,55,179,93,157,178,160,15,186,76,162,0,81,238,164,154,217,210,237,217,157,87,68,189,150,59,44,111,102,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,176,134,235,12,37,45,144,54,59,160,247,26,202,120,187,200,234,60,98,202,120,229,181,200,54,78,98,81,111,55,211,44,91,198,3,147,11,127,131,54,132,18,7,252,222,66,35,78,8,128,81,17,23,31,219,213,82,222,167,84,124,52,243,7,52,9,40,236,156,162,81,114,138,6,243,181,213,231,115,212,23,84,212,96,62,239,240,254,5,47,70,77,76,216,102,3,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,178,159,138,244,250,124,183,205,236,67,0,126,160,85,78,203,189,255,62,121,63,181,216,50,139,47,73,95,102,31,176,36,152,89,55,240,199,211,28,71,184,208,248,80,123,91,51,147,85,65,131,80,107,72,251,151,68,16,101,113,95,89,51,10,52,15,145,0,223,1,151,126,142,126,26,149,4,5,8,45,192,253,138,40,100,116,63,115,80,92,231,121,128,105,56,193,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,231,250,233,58,89,226,66,132,59,11,83,191,145,59,6,28,14,110,9,123,106,186,207,253,168,194,194,62,95,26,73,30,19,109,136,40,67,164,107,107,36,174,92,153,157,116,100,140,44,8,213,222,213,253,238,175,56,111,144,180,146,218,126,3,52,245,113,23,247,46,182,92,204,134,127,22,4,75,110,158,94,12,48,196,50,163,221,68,53,19,219,57,100,80,32,98,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,139,165,89,73,139,31,86,96,209,229,98,90,204,38,86,138,66,102,225,153,146,243,221,249,115,68,27,84,108,246,22,107,213,210,131,50,14,98,127,163,60,161,140,42,180,14,15,76,129,215,179,185,141,71,201,166,159,4,48,226,72,144,172,4,53,111,177,34,95,191,88,237,9,7,207,151,93,64,140,243,69,57,226,205,244,187,156,114,230,2,163,231,205,153,104,20,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,38,224,193,15,44,235,232,4,100,228,96,119,128,169,170,39,122,110,253,179,200,180,43,86,174,157,39,163,151,88,222,57,116,74,39,253,128,96,239,25,72,92,193,78,87,89,249,28,12,152,125,220,127,15,55,247,51,207,112,204,218,213,187,8,53,228,0,167,250,33,205,91,61,51,240,251,204,212,254,212,161,225,91,189,120,87,242,187,135,223,194,140,154,213,250,214,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,126,145,255,245,98,46,34,228,10,223,137,111,39,118,191,44,198,239,153,49,219,221,143,190,74,59,158,232,35,59,196,210,215,172,82,238,41,77,54,75,126,246,117,228,84,5,90,184,237,167,218,108,176,135,35,104,74,202,61,37,32,184,207,0,54,68,214,199,192,232,154,78,223,99,49,225,122,20,124,23,77,6,250,207,137,26,3,98,66,216,232,160,99,59,254,106,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,36,137,167,34,142,6,1,185,209,56,49,49,113,132,72,94,97,242,198,85,73,203,61,25,48,185,48,231,185,93,130,91,198,175,117,169,64,11,5,98,251,161,48,192,245,126,244,218,134,142,29,60,27,210,147,89,187,97,200,120,224,116,61,2,54,195,186,155,5,246,129,78,13,233,163,171,30,16,129,65,65,108,149,215,132,18,43,118,238,82,227,176,232,200,106,186,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,232,16,33,6,134,194,248,211,142,66,51,164,223,234,7,77,174,119,121,254,56,175,144,216,195,89,150,20,162,41,66,186,58,6,49,170,188,151,188,136,234,193,130,44,89,206,255,40,122,20,26,147,244,145,174,166,143,217,21,84,60,51,86,13,54,201,231,10,75,243,138,172,204,180,148,119,64,50,147,86,69,152,137,21,166,178,188,120,148,183,62,48,44,54,253,104,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,225,226,74,120,206,59,148,11,163,15,143,12,103,37,19,61,72,84,156,5,190,157,1,155,238,179,107,109,45,117,178,5,202,62,94,235,127,143,106,178,100,79,192,136,207,70,38,177,228,247,127,79,239,93,149,137,15,34,140,52,194,195,80,12,54,205,30,235,238,174,169,38,147,104,55,182,93,233,132,9,196,105,210,67,139,156,209,63,154,236,217,130,143,35,32,248,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,154,39,227,247,232,224,93,158,158,46,93,131,170,148,240,151,60,124,173,175,250,76,178,55,33,157,29,42,180,180,11,146,152,132,48,223,33,237,186,33,162,70,191,227,175,148,94,22,223,92,83,8,113,43,185,75,223,222,235,174,217,181,213,10,55,142,190,233,147,101,188,203,39,82,19,125,245,176,4,224,53,87,176,45,115,6,94,98,206,142,244,242,255,32,103,248,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,175,203,34,6,133,243,85,69,234,163,80,211,211,75,239,58,23,129,43,6,233,252,240,15,232,43,223,68,117,241,249,246,134,157,34,191,24,220,164,185,53,66,83,50,99,61,34,187,27,176,48,203,0,36,61,10,124,52,62,254,251,236,1,7,59,4,151,71,193,100,88,184,170,40,188,55,29,108,125,214,130,250,61,85,14,190,193,6,233,218,24,205,134,157,132,247,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,114,98,50,243,22,116,166,7,72,206,85,193,195,8,115,220,135,42,135,39,51,168,192,124,26,167,69,21,243,227,167,248,214,56,151,26,114,70,224,248,242,95,237,98,251,11,77,189,22,186,106,94,29,240,107,191,222,204,120,24,16,138,195,8,59,67,36,21,111,239,240,179,81,63,207,141,191,214,57,169,151,172,80,42,49,75,134,45,251,145,101,246,13,102,27,7,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,28,114,231,93,88,137,149,108,112,202,37,183,31,151,30,180,199,213,175,52,177,102,165,30,54,62,21,69,239,139,44,249,103,240,11,49,251,135,232,243,72,39,113,93,231,186,200,60,1,16,33,5,165,237,187,250,137,110,139,142,108,152,208,6,60,252,125,4,146,5,96,91,203,123,197,137,20,84,254,145,218,154,89,104,236,216,160,56,14,134,136,19,219,6,145,182,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,132,50,251,45,101,211,48,128,180,114,188,95,243,5,129,36,232,95,244,170,35,162,51,7,230,101,227,185,205,108,73,218,24,244,88,247,106,52,104,195,130,196,59,11,89,65,52,74,69,194,88,85,223,170,182,32,84,35,247,80,98,19,16,5,61,136,116,49,21,37,218,87,9,63,177,238,46,244,82,159,22,64,30,222,193,83,119,241,114,21,251,174,81,104,58,216,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,106,199,27,46,240,212,197,23,8,168,48,239,140,0,164,152,92,134,105,115,250,25,238,64,55,156,154,121,254,168,12,246,170,185,37,125,197,207,113,253,53,231,10,80,108,103,52,7,214,8,48,187,139,241,64,40,185,135,154,128,246,203,116,8,62,5,244,225,49,115,117,42,185,150,19,246,121,76,60,45,122,210,213,127,138,182,106,133,22,117,235,203,0,44,216,51,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,149,121,199,203,0,228,64,62,140,51,174,216,0,148,105,248,17,111,217,203,230,9,196,105,144,218,203,192,32,237,182,38,43,156,150,155,148,225,68,178,178,216,27,172,77,90,152,116,199,45,197,133,248,176,22,31,243,110,136,254,219,48,26,10,66,94,55,138,65,254,37,239,147,225,131,125,255,227,85,142,52,138,67,98,81,141,121,79,40,37,96,64,105,88,165,163,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,62,43,67,54,113,178,221,72,109,22,220,241,42,125,18,246,89,122,165,7,27,141,180,132,55,12,2,211,130,144,249,6,252,115,76,17,58,43,96,146,253,199,158,21,82,237,210,16,188,163,155,250,244,3,200,15,173,195,165,178,0,178,66,7,69,159,153,200,190,36,18,172,173,15,124,242,85,45,133,1,230,14,111,22,97,77,103,208,70,64,119,110,87,176,79,137,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,86,179,160,67,168,11,195,129,206,248,88,105,49,206,122,109,134,111,180,16,66,35,133,52,47,95,204,242,93,21,179,39,150,170,92,229,103,187,218,171,60,152,102,163,139,169,227,21,6,60,251,121,241,103,118,164,60,91,249,208,130,169,196,7,72,46,235,100,44,85,81,192,138,175,116,162,59,72,7,188,96,30,1,1,105,162,55,137,121,231,206,117,228,212,48,29,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,200,215,177,113,109,222,241,19,51,129,241,149,95,207,137,0,89,75,84,51,224,153,31,102,154,237,117,166,238,165,131,60,86,38,182,153,29,117,106,205,31,85,143,101,29,224,139,5,15,65,101,83,240,51,229,27,118,32,234,183,40,227,242,7,76,118,174,145,203,131,87,179,236,167,11,142,176,203,12,128,206,9,196,58,138,106,139,137,225,250,168,64,67,68,181,197,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,39,222,224,242,13,114,250,183,3,8,56,188,252,175,220,148,181,5,95,8,158,52,95,81,152,13,79,197,228,129,207,241,24,217,174,128,184,193,78,10,223,248,111,44,18,251,21,77,231,209,242,205,31,112,28,220,21,88,105,119,78,94,20,14,78,5,226,243,131,152,126,46,165,14,75,68,250,155,3,233,130,200,110,99,244,167,69,86,58,97,243,252,75,255,11,126,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,76,140,128,89,195,64,89,11,118,125,100,233,45,13,86,254,235,143,86,175,163,39,101,251,129,95,211,163,149,230,248,212,46,121,81,198,6,9,123,206,79,37,119,62,143,219,27,7,65,26,18,134,14,65,130,162,52,30,182,192,245,196,234,8,78,68,209,38,122,62,22,83,197,100,69,154,152,11,33,219,75,223,237,182,94,109,121,101,65,69,54,30,0,195,136,4,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,92,147,34,55,140,47,154,219,62,58,49,180,3,230,191,156,22,230,100,162,14,8,134,57,18,145,50,177,181,102,148,224,55,132,182,130,207,245,135,27,121,16,93,184,205,142,167,174,228,113,236,203,255,221,49,172,224,22,196,235,11,146,115,1,79,130,220,148,24,212,54,149,178,177,160,221,227,128,152,222,109,164,123,195,180,150,212,168,206,192,46,90,30,185,214,231,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,102,98,87,141,211,248,142,139,215,108,198,188,149,179,92,224,205,206,7,146,137,99,34,1,42,109,170,69,115,12,95,38,40,81,32,2,157,64,10,37,87,144,232,152,15,191,70,51,15,55,185,250,223,191,130,54,47,170,49,91,174,185,162,8,80,110,190,114,81,40,212,168,171,137,113,79,129,65,179,150,47,255,6,216,152,47,80,236,92,194,166,229,98,197,135,246,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,126,234,221,91,255,164,123,204,1,55,234,37,115,162,156,139,201,184,194,96,174,72,126,23,148,117,98,59,176,222,226,5,152,158,46,179,179,184,186,120,74,191,215,183,212,64,253,33,210,61,121,93,124,143,61,79,135,30,168,56,203,43,182,9,81,160,212,2,59,135,191,9,152,149,127,166,56,221,57,96,195,185,106,117,63,82,204,78,76,87,238,44,82,149,162,167,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,212,205,183,81,173,148,234,212,167,240,54,33,74,67,6,68,115,43,84,200,102,201,220,95,29,149,202,104,173,48,70,245,80,144,10,249,120,191,27,45,46,186,123,103,93,10,246,114,196,41,64,211,127,67,54,24,236,220,128,58,3,32,192,11,81,185,205,193,50,157,183,166,110,54,73,131,136,135,68,120,60,68,67,15,110,102,183,154,144,144,164,217,107,217,253,5,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,9,195,54,56,149,75,129,103,253,250,95,87,168,135,216,189,101,246,220,140,163,12,255,118,75,75,112,161,225,251,244,179,192,226,147,67,87,245,254,123,29,150,83,27,7,135,158,205,73,127,131,111,125,202,141,250,238,3,50,139,63,222,228,10,83,156,106,156,243,151,136,254,239,151,63,72,245,76,45,196,134,61,59,40,181,175,78,8,87,148,113,5,106,38,44,96,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,9,191,158,40,202,242,195,251,238,51,49,49,120,47,109,144,166,109,250,246,162,131,104,113,232,13,32,44,48,157,44,97,7,25,7,173,191,143,20,65,81,25,238,222,54,83,59,6,71,225,128,171,155,154,149,76,84,138,86,72,28,10,200,11,84,103,21,221,128,110,108,193,41,48,199,61,91,162,157,149,123,148,230,221,216,190,67,50,2,90,148,170,166,126,19,143,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,32,104,109,98,251,189,100,28,114,7,204,26,182,182,220,242,27,77,177,167,198,1,200,136,242,18,185,6,191,90,197,174,245,145,95,226,7,31,138,205,132,180,39,121,202,100,9,189,170,48,151,62,201,56,27,160,168,192,28,131,20,57,228,15,85,67,139,228,200,119,38,78,86,131,168,114,49,126,117,110,187,29,63,186,164,77,7,53,51,15,106,237,217,132,243,99,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,144,27,55,193,141,38,207,246,164,218,156,157,155,112,50,65,226,4,61,51,89,40,157,212,169,143,31,91,207,100,92,73,35,28,210,9,160,173,56,186,250,211,242,148,223,68,136,14,95,30,128,86,169,185,224,175,56,250,227,65,36,255,206,7,87,15,231,96,163,52,1,137,112,138,211,53,16,113,20,228,166,78,2,24,12,25,87,126,252,53,152,18,247,154,95,185,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,221,19,153,251,125,238,150,211,102,48,237,139,174,130,5,52,102,236,209,180,66,156,70,37,185,236,211,98,194,33,227,235,128,187,160,205,136,104,21,221,88,57,165,98,97,133,121,80,243,113,178,189,42,175,36,136,44,202,203,185,112,42,200,9,87,208,173,207,150,154,57,167,208,162,90,137,141,177,146,93,65,59,83,137,84,146,220,72,35,28,207,197,11,103,46,220,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,56,160,98,5,128,201,182,16,11,59,216,203,251,97,33,181,27,196,221,172,144,161,57,52,143,30,110,131,147,156,201,171,134,171,202,138,255,112,242,174,59,202,111,67,235,122,226,246,138,22,44,42,65,214,47,39,165,72,7,22,78,245,197,13,88,90,135,128,153,154,175,50,192,92,15,64,139,77,202,214,217,16,227,107,106,207,51,248,155,84,207,5,126,107,240,219,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,230,117,10,96,202,98,26,47,172,19,149,34,70,112,59,241,115,90,59,142,140,224,58,168,130,102,205,34,22,224,183,67,249,123,193,86,217,92,146,214,2,254,235,134,118,179,45,78,122,118,138,124,140,232,237,110,47,103,229,1,52,131,223,2,89,109,129,224,3,155,158,246,38,99,69,135,131,73,253,233,33,201,76,32,209,107,118,146,255,193,176,137,128,124,189,146,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,26,169,179,91,107,44,22,252,91,136,16,180,162,31,95,182,63,52,14,80,104,7,217,77,44,90,233,79,251,49,182,17,125,26,200,15,220,43,109,219,41,216,130,21,42,155,202,200,237,163,146,115,49,118,53,219,156,122,69,190,138,255,138,0,89,228,108,245,208,48,195,115,43,136,190,18,4,229,213,145,166,44,228,102,6,172,80,201,74,145,62,32,199,7,221,196,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,4,77,16,222,55,139,59,35,146,204,122,43,119,21,190,8,181,133,101,136,17,232,97,217,150,91,178,232,238,216,119,1,34,232,207,5,86,220,18,153,34,79,121,153,117,193,10,125,224,44,126,153,117,133,97,105,240,160,128,68,73,218,125,3,90,30,136,12,204,42,6,21,214,101,92,52,44,169,200,63,6,232,145,191,73,39,102,210,163,62,90,252,143,166,103,0,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,169,247,105,175,195,220,212,152,51,146,12,116,218,215,245,95,55,213,94,251,150,218,123,176,38,206,172,217,195,235,165,233,217,58,6,237,113,38,94,12,217,35,13,251,106,132,187,117,227,45,246,27,185,57,178,237,21,4,19,183,193,9,57,1,92,145,198,23,37,142,116,92,38,65,246,75,98,59,13,222,223,180,68,247,234,205,130,137,247,87,54,51,107,62,97,25,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,159,159,231,44,23,52,165,72,57,252,224,113,185,29,39,211,243,201,149,103,130,135,208,98,104,14,7,143,158,226,101,209,154,26,162,41,197,225,233,49,65,118,31,134,210,213,101,237,32,51,178,167,182,196,241,225,8,133,71,218,182,199,139,10,92,207,96,221,78,122,180,121,6,244,93,113,4,110,229,252,215,201,253,131,220,70,87,133,44,175,242,157,115,129,82,61,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,55,102,105,197,176,64,226,103,105,80,72,70,106,149,245,97,215,93,147,126,187,128,212,104,250,133,60,50,114,5,46,51,14,69,115,122,113,6,106,165,8,31,218,107,189,136,79,64,182,238,23,81,121,39,16,167,30,104,163,254,35,86,127,8,92,225,59,138,94,252,33,201,253,19,168,189,71,65,84,213,43,239,21,96,252,202,177,106,222,233,128,60,207,172,16,122,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,22,33,163,69,115,37,46,82,14,146,34,143,219,255,24,207,85,5,226,241,254,164,20,239,82,12,107,70,97,253,40,67,169,50,15,73,186,111,44,151,69,202,61,187,231,187,49,26,20,163,21,101,25,28,168,237,244,235,59,61,107,106,80,2,94,123,82,238,192,193,86,33,75,43,191,186,124,5,156,235,214,219,60,69,131,90,102,94,15,106,163,86,33,215,120,140,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,42,190,91,88,129,165,37,69,149,212,72,246,166,125,177,59,132,79,149,255,173,100,218,223,159,158,214,121,37,115,110,103,138,26,155,220,6,146,208,66,29,215,170,217,250,190,33,59,184,201,38,118,145,16,200,46,83,55,193,52,129,173,194,4,100,174,88,35,106,156,100,99,130,184,251,125,39,226,9,205,217,253,254,74,60,66,207,3,80,39,133,187,252,37,215,112,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,52,144,223,76,33,168,190,206,161,68,171,86,130,95,93,69,143,206,34,185,99,21,161,158,248,69,68,209,239,35,232,62,10,2,237,25,150,103,235,9,220,88,3,100,214,156,210,157,18,9,22,74,198,195,91,156,193,182,163,227,141,15,97,8,100,201,157,128,107,236,3,3,138,161,117,128,207,20,55,228,61,182,135,39,130,23,71,184,154,192,81,10,96,105,155,167,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,140,88,217,50,140,135,9,250,241,235,180,208,228,67,80,74,152,234,163,11,96,184,172,14,118,129,105,169,153,215,101,53,185,54,207,92,12,18,97,215,114,189,26,15,208,185,26,155,18,76,183,48,68,245,249,60,126,78,172,47,55,3,235,8,101,52,23,136,9,161,255,208,122,173,192,11,115,34,108,112,23,72,221,198,148,147,90,175,51,96,232,105,11,181,64,75,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,246,185,119,52,109,240,200,198,14,193,73,58,225,229,168,160,186,228,150,161,101,5,209,253,64,154,6,160,42,215,188,231,182,252,125,26,145,160,47,88,8,28,18,53,233,8,25,222,192,155,243,236,111,210,53,164,132,22,142,191,66,219,18,12,102,30,2,172,121,10,58,122,249,191,247,154,44,201,74,117,5,155,181,237,46,172,139,90,39,191,1,133,215,44,153,87,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,252,69,105,39,10,47,168,33,26,174,193,4,18,6,209,211,91,9,147,241,23,17,56,14,55,200,166,157,197,176,231,157,205,112,70,106,95,15,220,189,9,55,197,5,49,221,230,14,29,76,206,220,60,235,244,187,222,152,11,159,157,33,131,3,102,59,200,85,137,25,12,113,107,243,161,61,87,158,112,57,148,168,135,19,104,150,165,195,61,121,70,159,156,199,147,224,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,47,200,206,12,56,225,2,18,176,75,248,151,148,212,56,62,246,12,183,213,136,239,23,63,65,124,31,86,196,182,210,81,81,76,150,82,207,132,203,46,159,117,33,113,202,78,39,103,146,151,208,69,150,99,187,49,238,197,149,31,224,88,200,7,105,112,55,177,188,254,108,249,228,161,95,224,78,192,79,63,131,248,26,65,245,201,40,120,142,172,172,185,50,159,140,250,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,204,236,96,61,104,60,84,59,69,240,86,243,147,43,160,36,132,202,15,252,192,177,28,146,11,208,36,98,83,47,186,81,164,231,2,149,247,164,200,63,112,120,217,180,4,196,228,232,249,161,0,98,148,130,246,133,255,50,228,100,168,138,118,13,106,85,151,35,230,1,67,138,166,58,93,230,73,180,96,185,247,194,130,223,218,78,203,157,190,196,171,64,187,194,90,203,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,191,154,187,79,249,43,87,0,32,68,41,3,232,170,195,60,152,10,50,221,234,91,71,103,230,170,140,106,249,213,144,197,77,73,50,138,163,226,194,105,211,182,172,66,74,26,36,196,246,146,55,70,39,2,108,19,122,239,82,229,73,56,71,1,107,93,46,216,154,148,35,77,58,75,249,24,165,221,194,1,183,75,19,12,115,87,137,68,120,243,245,132,207,57,91,28,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,95,123,117,255,144,183,5,10,118,68,179,191,33,211,36,37,141,94,15,103,165,252,124,244,99,133,231,3,11,102,219,171,199,238,104,82,231,178,252,32,51,143,138,41,128,240,140,50,11,46,144,93,19,238,61,9,150,127,134,167,175,68,234,1,110,182,119,196,39,231,244,38,199,240,193,177,128,52,217,179,237,39,120,254,14,28,124,53,178,172,167,119,107,65,184,114,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,96,139,47,42,91,37,76,44,136,155,0,89,208,76,53,228,183,223,204,230,20,14,97,11,83,172,24,29,111,217,124,177,215,124,246,0,58,189,208,105,215,138,84,81,220,54,106,90,111,85,180,56,82,170,26,231,161,239,164,13,230,101,76,2,113,58,63,114,36,134,253,8,151,89,151,58,14,170,13,205,79,181,88,111,92,16,53,51,66,235,60,130,8,177,32,232,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,90,81,89,118,208,148,154,133,247,123,149,255,180,139,18,200,28,139,252,115,3,147,179,190,245,132,95,219,87,89,167,15,148,199,11,187,205,198,151,133,48,0,161,80,32,131,174,0,160,93,44,124,41,204,119,70,85,247,13,196,169,32,133,2,114,88,64,33,9,83,36,183,227,48,213,67,46,209,197,210,67,121,32,36,135,28,139,124,14,250,71,218,104,17,16,17,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,53,19,230,250,19,230,182,41,124,119,163,9,50,149,106,161,148,17,97,237,17,208,186,124,170,191,143,86,21,135,115,12,35,131,40,184,66,163,203,142,248,177,124,188,101,205,120,143,10,248,204,133,32,149,94,133,152,81,9,179,67,27,240,10,114,115,124,50,5,34,75,73,223,146,251,161,253,159,159,142,141,199,121,123,110,38,148,243,28,173,142,141,62,98,130,55,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,9,219,182,253,3,240,203,251,69,62,247,157,134,8,5,127,130,33,6,201,239,154,206,250,153,119,247,161,153,252,29,12,206,132,54,105,69,88,154,137,154,43,121,74,153,164,73,196,97,41,33,84,136,163,44,66,18,107,203,182,247,188,2,6,115,36,49,128,171,38,174,113,186,10,171,195,150,128,195,209,116,45,241,42,81,37,244,83,39,52,15,255,213,165,52,120,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,149,164,110,160,232,138,122,27,184,38,104,72,176,74,30,67,198,89,14,155,134,249,211,13,81,27,18,163,84,126,170,140,108,24,66,239,250,25,139,148,250,196,139,186,83,35,108,195,164,172,120,144,58,140,230,188,23,19,192,222,38,83,252,12,115,76,53,75,11,211,64,91,15,197,169,39,155,61,118,226,114,2,244,172,198,243,219,71,67,4,93,207,219,74,223,193,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,184,54,9,144,201,189,234,175,2,157,164,114,188,251,37,214,47,163,246,254,127,91,95,135,1,103,106,208,66,86,38,91,212,69,106,240,76,170,146,236,116,71,117,142,116,65,22,38,145,177,11,53,46,136,120,235,132,187,29,213,243,186,219,13,116,12,114,233,85,254,199,118,251,35,118,136,115,225,113,10,137,214,174,194,220,234,93,98,67,65,101,26,202,159,205,70,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,145,132,128,213,74,25,160,51,115,86,155,1,230,35,143,166,50,197,77,59,26,239,13,237,198,160,150,113,161,244,45,177,247,250,47,72,95,76,146,48,19,85,126,1,72,112,138,49,199,248,155,173,131,223,153,184,148,76,13,78,186,95,48,8,116,128,106,14,177,156,150,245,148,14,32,52,26,165,58,21,184,224,68,111,46,159,160,220,232,172,131,40,245,192,94,147,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,25,21,3,42,79,40,183,47,29,105,59,236,112,109,134,202,215,13,245,233,105,45,152,84,163,114,154,14,254,87,233,34,36,225,37,59,129,149,43,160,14,15,167,56,175,35,120,154,36,161,88,105,227,225,107,75,191,137,16,236,85,145,105,5,117,252,104,139,80,16,69,91,148,195,78,210,115,185,90,127,63,58,209,227,174,189,255,229,192,197,134,98,179,166,254,114,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,69,97,178,205,213,210,169,189,14,86,54,188,184,52,247,89,245,29,183,197,151,127,14,58,176,228,0,7,179,156,144,28,178,17,221,170,86,221,144,111,38,146,243,178,79,142,7,53,24,33,170,55,53,152,17,218,215,220,227,88,117,167,11,0,118,195,0,61,126,195,135,222,40,34,244,93,243,47,209,140,28,193,130,36,211,129,192,183,32,218,194,16,138,221,77,59,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,246,210,84,251,199,247,94,198,152,139,248,218,207,56,233,2,205,111,76,21,78,11,244,137,228,6,13,63,214,170,223,232,233,226,27,213,187,171,19,214,72,112,206,242,83,101,253,77,144,155,239,34,123,241,8,29,137,88,249,24,116,58,106,0,118,245,36,180,89,119,173,68,121,12,117,144,65,131,7,115,64,184,67,232,8,23,144,44,252,138,143,109,92,0,182,120,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,142,30,173,163,38,85,44,10,205,4,194,71,170,55,103,251,134,68,170,232,236,170,183,130,57,143,187,161,88,38,221,96,69,202,177,80,30,20,77,236,174,53,238,141,27,125,19,138,36,124,73,199,29,186,40,213,45,254,65,91,209,222,171,15,121,229,186,199,191,20,208,255,201,162,208,234,197,90,162,130,125,127,40,68,89,116,98,109,158,251,234,239,86,164,79,21,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,3,122,164,151,20,179,239,199,182,90,125,221,65,12,105,138,35,111,68,119,224,119,238,75,32,24,158,239,103,182,187,47,249,156,76,241,148,228,142,118,223,182,70,239,125,11,2,34,102,232,253,26,109,14,233,29,137,100,170,243,185,163,45,13,122,2,29,106,112,11,214,136,113,60,54,131,217,217,204,8,243,4,113,35,29,114,146,235,108,166,50,4,6,157,144,67,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,181,206,240,145,161,16,90,30,54,110,168,222,3,48,171,44,207,79,160,156,40,196,212,61,71,178,136,44,29,226,48,23,218,124,35,117,88,83,172,198,48,55,50,228,4,249,24,110,0,20,122,53,100,23,67,126,221,192,66,183,93,206,116,3,123,169,207,217,241,19,225,16,171,67,78,246,103,139,70,77,17,75,254,111,151,13,178,99,87,195,223,202,20,155,249,37,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,24,227,97,255,85,109,157,60,213,39,106,226,120,107,239,3,118,174,214,34,224,47,172,111,91,85,192,251,164,249,37,245,57,67,58,61,49,160,166,105,71,238,237,14,240,152,56,37,231,221,205,154,91,178,105,148,221,121,174,246,129,44,50,9,123,233,161,82,149,161,32,189,51,15,7,233,41,88,0,224,108,188,33,101,222,204,115,111,157,95,142,197,128,239,173,21,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,129,61,81,212,129,123,19,125,255,167,19,87,152,222,14,9,196,113,39,38,47,214,62,48,138,53,234,47,118,128,211,79,39,2,248,255,59,4,0,95,189,175,255,213,21,52,239,117,230,184,208,106,15,68,243,7,220,141,242,235,61,220,7,10,124,83,222,184,139,26,159,119,47,208,214,21,86,210,103,120,61,183,227,118,94,34,254,101,64,4,140,88,14,187,146,161,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,195,230,239,177,23,92,182,241,155,74,155,114,79,118,116,240,104,155,141,126,191,196,106,219,2,232,77,97,120,183,14,146,108,76,215,84,253,1,78,223,185,58,95,72,193,14,20,233,96,227,234,108,88,181,210,255,18,124,46,161,22,212,244,11,124,181,137,172,82,22,48,22,225,87,163,192,17,2,226,31,117,64,7,39,33,171,26,225,95,6,218,142,83,21,50,247,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,0,229,219,104,255,14,137,101,53,95,55,121,245,103,7,251,215,225,190,141,17,30,100,148,144,117,210,84,68,18,250,250,197,202,241,228,214,136,138,190,172,193,87,229,73,56,131,39,22,221,170,91,99,157,123,120,207,116,135,69,134,142,4,14,126,150,158,85,159,134,14,110,234,49,191,136,211,165,131,126,32,83,248,188,142,57,49,89,112,19,105,164,2,237,34,114,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,85,242,195,108,184,201,23,91,60,118,200,27,58,41,28,131,105,251,198,70,46,125,232,23,209,47,246,72,15,14,203,177,138,145,5,214,141,199,184,242,191,100,2,86,253,58,110,210,203,177,93,10,19,49,152,38,154,63,174,156,121,13,110,4,129,162,212,41,152,253,199,202,74,13,125,34,46,109,176,116,179,96,96,158,150,193,254,151,233,215,45,72,82,153,0,36,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,76,37,154,189,174,114,41,218,210,114,24,51,140,233,40,238,110,203,59,197,114,87,28,57,59,193,50,64,83,228,25,147,140,171,118,175,218,44,132,11,97,38,49,133,39,152,136,165,68,25,65,134,116,14,242,18,249,213,226,4,28,197,173,10,133,8,254,198,238,139,158,255,188,193,78,192,141,57,183,236,225,49,55,152,127,20,82,158,45,107,52,149,220,1,227,192,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,210,224,82,102,94,201,64,79,223,45,59,255,160,164,144,16,253,190,4,129,182,107,206,250,78,150,4,198,57,51,136,220,185,106,212,135,151,71,60,69,250,130,136,182,204,29,235,88,252,150,121,129,49,208,218,126,67,166,173,76,101,153,36,5,133,200,50,54,62,131,42,191,122,113,26,96,200,177,20,240,59,146,61,166,74,240,56,109,33,245,73,95,112,111,220,4,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,185,78,1,94,49,221,213,210,113,152,124,81,164,230,196,227,217,177,125,75,67,169,72,91,214,160,82,199,219,179,196,142,13,127,225,2,221,21,212,108,165,31,194,211,101,154,227,111,180,213,175,1,247,247,239,248,246,178,118,104,148,205,75,4,134,70,247,29,241,124,120,210,117,145,233,221,124,25,101,228,42,34,4,116,112,165,94,216,96,206,130,220,180,24,69,121,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,58,131,227,183,138,119,233,55,218,170,197,173,42,50,199,67,40,195,10,68,149,200,41,225,88,4,8,49,210,62,214,21,126,71,156,149,161,201,73,194,147,75,70,236,65,189,50,76,75,125,64,49,165,160,237,157,2,63,251,180,45,202,227,14,135,9,90,72,187,98,27,35,114,167,82,21,75,85,37,103,144,138,116,157,248,48,99,88,133,65,15,30,112,92,162,167,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,150,157,118,49,65,177,2,130,181,85,69,54,68,217,2,167,252,179,12,173,115,51,122,1,158,222,87,233,168,22,85,3,228,90,57,32,145,126,44,166,144,240,83,158,44,92,6,208,104,65,205,123,120,90,101,72,36,59,246,4,153,97,196,9,136,24,244,60,23,47,167,211,115,68,176,223,7,79,231,121,93,166,17,19,99,78,222,85,133,82,94,73,29,82,138,6,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,23,196,123,78,93,95,106,95,206,38,218,34,198,72,127,6,193,81,135,204,23,185,153,53,56,224,16,74,150,240,70,22,42,77,185,156,17,208,191,6,74,242,190,52,2,222,103,231,136,105,100,166,94,194,220,61,20,151,78,66,144,88,212,8,137,22,203,76,121,87,157,66,189,222,80,58,142,240,115,192,130,237,210,32,165,133,26,117,237,35,4,157,3,61,28,109,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,155,150,158,80,59,117,203,237,203,200,126,33,46,61,76,195,238,78,95,152,78,70,55,253,140,134,10,129,144,161,62,229,82,80,64,53,30,155,185,9,18,195,213,111,93,121,155,227,46,228,89,110,118,9,163,127,117,33,104,219,82,31,68,11,137,142,230,88,176,10,171,3,223,197,8,194,3,158,144,246,156,196,195,62,147,190,20,218,215,63,66,201,29,45,207,164,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,68,22,227,217,235,241,218,121,184,252,246,245,150,118,211,252,45,138,156,207,98,150,205,44,17,248,165,6,152,69,40,30,176,241,51,240,201,195,235,71,94,17,182,2,126,173,57,49,76,11,20,94,167,178,199,81,179,150,114,134,72,63,150,1,137,146,170,7,15,0,149,189,176,201,92,37,122,6,138,65,103,155,62,38,12,66,142,106,210,196,116,203,250,141,57,187,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,62,65,59,75,86,28,144,202,3,58,226,57,158,107,28,175,126,217,81,181,117,178,77,196,135,39,100,155,174,132,95,82,146,228,27,230,207,121,52,202,162,209,164,230,212,236,39,53,122,235,214,80,122,116,247,100,140,118,164,195,16,77,124,10,137,151,219,19,122,162,174,199,44,113,87,89,193,155,31,187,133,40,94,95,112,59,99,98,159,249,211,98,32,212,194,250,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,68,47,38,49,0,218,22,246,180,29,103,171,126,178,88,56,193,217,2,195,244,140,187,177,254,117,173,239,19,41,155,175,23,100,140,94,232,142,86,138,64,127,144,253,111,238,247,11,122,183,156,83,76,221,52,7,11,228,42,111,249,75,68,0,137,177,65,188,138,138,25,31,70,243,52,184,243,233,190,40,71,130,60,144,87,31,31,185,145,238,74,210,110,196,202,103,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,19,110,208,141,126,240,215,234,128,151,178,68,147,91,6,90,162,2,192,167,194,116,139,50,159,77,9,189,34,10,31,93,187,179,123,2,19,58,8,112,75,171,158,219,86,197,100,190,115,2,4,7,181,35,215,67,123,51,82,179,157,3,14,8,138,56,73,198,202,190,80,130,233,14,196,77,59,141,152,22,176,27,213,119,213,254,207,177,9,165,71,165,203,16,208,51,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,167,112,139,106,168,180,249,107,155,58,217,70,239,13,90,114,88,98,242,47,58,87,152,138,225,26,217,177,89,107,44,13,218,109,208,74,214,143,130,122,248,97,3,138,49,225,191,138,132,157,115,252,29,232,127,51,84,156,232,151,81,109,56,5,138,229,106,127,118,75,129,168,157,252,206,155,181,56,143,194,109,195,203,48,47,145,192,183,217,146,63,129,244,4,32,29,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,174,182,31,55,23,93,68,74,179,207,25,85,97,219,176,144,7,176,186,76,253,146,102,153,92,208,226,133,177,97,244,215,204,126,16,97,21,161,34,227,178,223,1,10,204,78,227,76,201,121,102,154,6,97,214,121,110,182,9,221,162,148,135,8,140,244,24,81,43,125,77,36,83,176,218,83,183,233,222,62,50,134,33,100,182,37,17,163,3,216,79,4,252,51,76,228,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,22,209,99,252,69,214,181,191,139,25,176,146,218,216,194,50,241,239,147,74,124,114,105,167,242,56,63,106,167,149,19,33,199,150,218,241,236,131,158,33,169,54,29,249,108,9,229,144,157,133,76,217,89,179,244,192,106,166,17,173,198,151,52,15,141,122,205,54,174,220,229,42,232,145,183,40,72,170,212,28,253,50,167,85,213,108,184,191,76,194,175,125,71,46,245,15,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,49,206,132,54,127,242,211,121,66,49,107,207,215,91,41,166,209,190,16,6,22,245,77,201,108,211,253,14,177,90,185,194,68,24,123,207,58,238,248,227,84,40,53,252,204,16,62,2,209,32,100,127,30,68,80,223,13,146,85,29,24,149,182,4,142,124,125,188,102,169,53,15,160,202,79,199,67,242,67,19,154,145,59,236,62,55,54,136,86,232,110,94,57,91,74,94,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,47,103,233,27,134,55,122,83,81,196,186,153,15,103,205,23,59,232,205,202,66,69,8,244,113,5,161,172,35,173,231,212,184,22,197,18,74,107,111,114,142,49,129,161,127,132,10,179,55,165,196,82,138,122,157,50,76,189,33,71,177,156,61,0,143,18,196,85,171,243,177,40,50,213,31,129,47,110,37,50,9,223,139,199,60,55,230,88,136,102,162,183,79,167,207,60,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,88,188,217,198,36,108,212,34,41,74,13,94,33,120,144,1,152,240,210,103,126,50,153,146,252,19,85,125,240,181,139,163,109,134,212,72,20,15,130,84,114,239,144,70,142,32,235,140,162,48,177,162,34,139,213,234,36,113,202,204,74,203,120,9,143,38,6,136,127,160,161,161,77,207,187,178,170,240,150,185,21,142,183,171,69,226,175,12,174,142,162,22,7,153,186,38,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,79,184,56,223,186,129,97,37,217,34,26,3,61,220,45,48,133,249,46,60,3,76,80,155,193,246,224,43,31,40,7,80,181,58,241,201,114,188,239,125,190,25,175,109,43,174,165,203,121,71,74,166,157,61,88,120,151,161,204,124,88,112,232,15,144,105,254,124,9,109,10,209,205,231,124,136,71,7,104,60,50,173,118,158,246,59,42,196,54,45,245,147,118,125,60,81,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,71,116,14,147,83,83,31,197,128,59,23,158,223,249,209,241,63,163,242,190,148,98,240,109,95,111,92,39,87,54,146,179,213,122,157,63,249,189,235,82,37,237,159,136,235,82,24,244,235,150,107,21,107,131,210,129,128,251,193,225,187,103,130,4,145,15,104,172,4,87,214,4,96,205,169,187,216,85,156,192,196,166,41,15,235,3,202,121,152,138,155,15,62,10,3,216,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,215,4,237,42,221,32,44,216,143,157,123,33,143,250,202,25,162,177,109,161,149,116,111,230,35,179,114,157,203,204,10,71,209,163,108,59,43,138,114,74,90,39,85,10,236,79,171,38,138,221,90,191,118,219,153,92,58,232,193,244,93,1,88,15,146,144,222,182,51,79,122,62,154,126,196,58,141,133,196,45,166,137,230,48,33,212,12,237,172,197,57,219,13,102,14,96,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,109,15,112,188,144,104,13,84,232,113,121,30,80,32,151,87,142,212,48,175,117,89,80,182,188,209,59,176,8,234,85,189,105,225,23,147,197,182,230,199,79,194,58,143,68,208,219,30,216,102,223,95,55,101,252,39,109,220,191,197,124,56,244,9,147,138,10,106,28,50,231,53,37,24,229,145,137,27,84,46,84,34,30,206,108,212,59,234,17,163,54,42,230,63,109,178,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,65,255,83,208,131,158,187,72,239,80,83,80,37,68,192,110,135,47,232,86,79,97,91,170,66,247,181,24,212,195,168,12,69,177,157,249,92,224,74,246,97,133,123,227,124,207,96,53,187,3,90,199,208,38,228,182,140,6,93,236,217,107,181,15,147,226,118,215,248,176,66,196,161,129,48,176,133,235,43,182,164,192,96,149,147,75,156,219,141,88,131,100,46,233,182,188,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,58,51,248,221,6,39,157,79,176,28,214,28,17,23,252,78,89,41,134,202,5,27,56,242,254,55,75,148,39,252,89,79,202,178,203,219,18,5,127,163,105,98,186,16,198,215,213,87,94,204,254,63,124,34,229,217,2,97,117,169,121,140,232,0,150,8,95,39,246,38,37,72,125,114,213,167,19,111,253,225,215,137,61,197,39,195,169,211,86,136,156,195,163,98,0,168,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,211,48,177,57,237,180,222,151,206,50,77,70,132,34,254,22,221,96,84,183,187,114,54,101,80,187,100,69,174,207,234,246,175,11,238,180,166,22,174,47,204,127,120,84,184,214,5,21,10,71,198,165,67,159,131,136,52,188,154,230,29,246,130,0,150,211,108,146,136,208,131,117,179,213,71,195,220,143,149,43,177,19,127,213,195,8,123,184,187,203,213,184,251,227,172,204,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,120,247,45,92,91,80,129,228,157,253,90,95,223,139,180,57,44,223,107,14,166,86,152,216,96,18,81,45,171,59,114,90,133,216,131,225,18,76,183,177,169,49,235,121,91,176,241,176,125,62,194,101,45,129,94,30,43,115,126,80,69,117,142,2,151,150,127,125,223,153,197,154,3,16,116,77,208,34,203,200,60,113,236,59,184,173,71,64,68,215,28,79,93,79,71,120,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,145,244,65,200,25,61,104,21,163,89,61,212,229,150,242,208,117,177,142,56,210,18,44,209,222,202,138,128,184,76,39,29,122,46,38,250,95,45,205,10,110,122,29,64,51,198,196,214,114,184,153,15,203,143,77,157,171,141,38,92,104,185,143,2,153,128,46,92,225,224,27,151,185,27,248,184,245,198,241,142,194,6,232,27,74,91,117,100,36,102,219,81,230,89,18,160,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,52,242,168,190,71,53,240,102,73,133,128,192,115,78,219,162,16,182,29,134,62,20,167,184,134,78,241,17,43,3,54,220,51,177,128,200,195,59,255,32,241,17,247,102,189,90,149,95,197,133,100,172,162,96,89,195,198,166,43,93,133,82,169,8,153,145,151,77,146,35,130,118,243,54,35,217,20,215,134,198,4,224,2,34,16,185,66,175,248,112,156,67,199,65,171,206,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,164,253,141,247,213,204,109,179,88,50,64,213,192,29,189,145,195,43,194,120,107,211,120,238,164,165,253,184,200,96,121,159,169,94,235,66,194,134,12,149,174,21,19,123,255,26,157,166,118,133,110,249,136,8,202,235,127,234,21,47,210,202,175,5,154,143,18,185,216,83,24,207,127,147,192,89,158,219,62,57,115,6,103,15,90,89,177,16,113,201,82,159,135,216,115,38,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,63,255,128,212,173,1,227,68,43,207,228,170,32,255,54,91,89,240,43,247,202,18,203,18,24,209,72,57,126,60,13,49,172,83,230,123,86,40,179,221,8,105,181,221,130,170,178,125,52,173,118,158,104,27,15,222,40,64,62,59,73,204,254,0,155,95,228,26,209,197,31,48,193,136,210,66,94,33,11,94,222,24,175,23,43,173,107,177,36,26,13,184,125,200,102,173,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,254,147,110,254,70,74,64,124,5,144,190,58,68,30,90,244,153,170,211,248,219,100,98,67,131,17,224,233,46,99,185,13,121,16,191,172,193,38,66,132,73,224,169,212,182,18,166,46,197,235,194,36,22,37,13,13,211,110,177,69,68,179,25,11,156,92,171,127,118,229,170,121,69,199,27,243,57,111,114,197,105,30,36,201,126,187,47,84,173,253,42,74,189,88,237,254,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,248,83,119,220,199,81,229,253,88,55,125,46,253,94,0,166,51,42,7,202,86,240,100,110,228,97,184,11,162,51,178,45,10,48,242,192,242,241,215,119,179,229,9,119,62,140,135,37,43,187,42,213,213,65,236,36,142,121,216,122,248,143,195,14,156,95,132,15,240,5,180,47,223,84,198,101,163,49,246,201,163,186,103,211,140,169,81,43,35,226,100,108,66,12,225,190,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,122,115,188,64,228,210,221,243,79,199,186,26,110,173,122,12,81,135,169,235,33,225,252,236,63,23,159,2,9,48,56,6,195,145,70,207,241,244,38,151,235,209,184,53,20,56,60,48,237,69,110,181,5,29,251,217,212,204,198,239,170,161,31,5,156,202,227,101,95,241,224,225,226,76,119,49,88,203,142,157,244,227,191,140,130,228,128,27,56,241,31,137,165,138,115,238,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,60,154,131,48,59,0,129,159,149,230,194,3,245,90,186,168,80,136,212,80,57,196,60,240,133,112,174,177,160,13,32,100,50,116,121,191,249,45,228,136,214,36,52,47,218,235,94,88,69,151,79,233,41,189,96,204,204,70,37,254,202,91,204,10,158,129,121,206,74,216,48,38,84,93,194,201,156,39,196,92,34,80,158,71,187,243,50,90,105,119,103,76,127,155,254,19,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,247,161,73,64,160,60,252,165,174,183,199,245,224,127,55,175,101,249,20,93,148,178,222,172,138,73,226,100,37,5,63,85,219,213,207,75,162,73,147,220,135,246,246,87,187,197,118,181,22,249,154,6,243,139,82,204,218,136,240,177,15,71,113,0,159,4,6,157,203,159,90,162,85,171,35,52,62,180,159,152,147,94,224,74,202,51,249,90,238,13,113,131,225,174,61,44,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,168,72,109,35,230,223,68,6,5,88,97,17,146,149,250,250,178,23,176,103,19,44,12,116,57,98,190,81,37,107,59,108,78,168,178,132,116,4,57,135,44,241,207,242,199,54,78,123,88,34,73,194,227,132,132,84,250,5,8,222,166,36,210,3,159,5,31,162,131,142,53,102,27,126,62,198,254,91,139,211,238,178,61,25,28,110,44,144,139,21,1,112,66,108,59,85,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,201,89,46,95,72,86,89,202,207,17,144,232,116,170,232,197,214,188,69,136,3,26,231,33,144,173,165,154,124,182,175,115,2,33,2,135,41,96,5,5,154,45,188,185,178,89,128,211,24,212,112,252,29,107,40,16,223,130,60,129,26,62,45,9,160,205,253,87,78,13,115,214,14,119,176,60,138,188,169,118,25,208,185,218,74,36,44,70,195,107,93,168,91,144,188,132,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,22,198,52,219,166,251,191,245,111,109,19,99,116,142,24,141,46,54,124,237,209,248,36,78,61,148,188,64,183,179,32,44,140,238,68,201,52,179,81,145,67,96,216,173,61,191,158,205,55,175,137,12,183,65,148,89,187,86,181,157,13,117,94,9,162,132,125,229,177,124,200,200,134,94,86,7,112,129,77,211,82,214,191,37,243,21,179,163,159,38,235,215,104,129,9,150,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,143,163,208,238,202,24,175,171,243,67,82,123,3,190,121,19,96,167,71,254,227,227,66,33,177,150,99,111,104,209,6,254,89,195,78,0,112,57,59,202,44,140,214,53,9,215,117,200,91,6,41,98,106,98,33,84,232,116,111,229,120,163,139,0,163,172,117,121,201,46,152,181,231,116,255,235,192,196,59,17,91,87,72,192,12,95,233,62,154,218,206,218,109,126,214,225,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,40,2,9,186,0,165,39,37,52,161,231,248,232,48,148,156,90,177,165,30,1,222,223,24,147,88,209,214,3,29,132,60,145,173,141,204,30,183,146,111,29,195,193,170,58,78,54,7,59,88,245,179,69,126,92,241,39,106,147,95,28,124,166,9,164,83,133,61,178,167,18,248,108,174,103,82,48,132,126,155,183,74,83,126,16,44,62,158,188,61,207,148,244,241,246,255,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,194,30,61,237,0,18,250,15,159,16,173,39,112,200,139,133,176,171,239,78,120,56,97,226,209,153,238,89,107,114,151,252,144,153,194,36,103,209,152,219,175,88,213,239,56,182,216,164,76,130,33,140,32,163,250,31,162,69,234,173,13,5,1,8,165,11,2,190,5,170,66,106,167,164,28,115,176,171,201,107,87,189,221,15,74,151,5,194,222,198,174,201,243,234,167,172,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,19,208,198,98,99,229,4,244,130,225,5,96,233,202,50,28,247,174,250,219,150,161,6,227,35,220,161,219,70,80,94,233,212,160,103,106,56,51,97,145,178,42,112,63,47,146,31,224,129,234,34,224,28,8,212,100,210,86,40,223,229,129,31,7,165,119,81,156,72,111,152,206,129,87,140,94,150,198,76,192,194,156,95,235,216,104,176,227,128,75,85,36,47,235,208,144,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,122,40,185,191,234,186,223,157,79,169,241,72,57,78,77,127,144,81,167,96,226,251,104,22,251,138,113,14,161,99,254,159,50,53,232,38,20,68,233,72,222,241,91,9,8,0,233,74,67,196,26,232,181,88,215,157,216,90,51,165,226,141,120,1,166,15,168,169,248,13,47,78,35,33,8,162,139,172,116,149,89,131,100,88,55,30,49,72,113,221,184,74,151,105,206,120,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,252,126,142,20,56,122,221,185,157,104,53,70,248,171,161,219,89,139,50,208,66,146,212,250,0,136,65,100,57,155,197,15,91,107,79,150,66,12,7,237,110,36,116,246,58,199,218,104,33,93,179,22,34,47,142,106,246,56,47,25,89,47,198,5,166,229,184,21,159,169,223,57,141,76,41,242,127,133,94,200,63,86,237,22,21,252,105,201,187,91,136,84,30,93,147,29,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,227,41,216,231,15,99,29,157,76,154,24,23,192,202,74,96,177,141,67,148,135,197,227,204,198,102,194,44,7,116,74,240,47,185,98,205,7,34,254,177,238,44,110,102,227,234,113,45,219,63,125,39,167,202,220,142,29,83,24,226,125,133,130,13,167,37,52,236,17,139,63,35,185,36,24,250,6,47,174,210,151,200,17,115,69,121,130,172,95,165,17,5,148,109,212,201,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,151,122,205,63,74,88,71,39,175,232,108,83,114,217,155,85,75,225,5,64,134,187,225,237,251,169,108,17,244,224,174,162,110,104,251,199,18,50,23,136,228,2,101,8,241,192,8,146,132,66,177,250,118,51,18,9,144,69,62,112,56,107,222,15,167,135,70,50,64,150,2,80,104,80,235,120,193,229,29,13,144,180,86,176,247,168,74,226,211,245,61,109,159,60,145,5,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,154,13,217,57,74,87,238,158,5,100,128,76,223,25,170,19,21,15,186,206,249,136,41,97,237,222,190,168,212,219,96,245,206,90,84,251,141,127,253,153,6,81,15,71,99,77,66,240,94,192,15,99,229,105,13,136,199,44,212,235,150,241,5,5,168,30,103,143,10,222,170,156,90,183,217,106,128,56,30,71,246,1,217,188,109,65,11,151,14,88,193,117,226,84,215,78,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,249,221,176,19,155,104,50,244,239,111,23,21,183,70,99,21,30,215,216,185,100,77,206,194,244,81,126,127,223,86,44,53,60,248,217,182,73,210,204,245,7,245,14,111,34,251,200,247,32,37,216,232,171,70,231,196,13,46,106,88,63,192,194,4,171,176,4,130,124,104,12,189,61,207,169,33,137,193,220,201,21,0,229,114,84,165,66,133,179,206,165,37,64,133,129,2,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,2,193,228,128,139,159,231,33,124,97,147,142,226,118,221,165,110,41,179,103,169,154,104,109,206,98,150,117,48,107,114,130,212,255,19,49,229,67,122,235,111,88,107,58,89,10,241,95,131,0,43,56,82,231,170,89,253,239,211,190,245,247,6,13,171,196,94,24,194,108,210,210,16,193,138,146,222,157,216,202,178,139,141,181,55,47,56,61,124,101,149,140,179,248,43,220,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,98,85,171,121,67,207,56,243,242,165,140,221,196,54,149,3,86,137,87,221,155,88,192,98,30,111,243,213,37,180,107,211,57,180,175,174,57,119,174,241,38,3,95,187,164,158,211,34,95,163,151,84,210,97,108,18,194,81,228,194,122,186,7,4,172,31,70,104,121,131,145,167,211,169,152,193,203,8,1,216,221,233,93,49,181,242,182,216,195,207,161,254,45,19,82,72,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,71,174,86,234,215,208,254,89,55,174,216,200,248,40,236,137,96,106,120,66,235,189,160,226,181,165,239,187,32,109,236,182,192,110,2,17,184,236,141,236,221,237,85,143,86,59,243,232,86,61,71,122,132,144,180,63,15,169,43,33,178,26,53,3,173,165,29,33,57,139,76,49,90,239,148,149,14,165,87,58,249,205,3,96,182,153,208,105,242,49,56,68,73,196,106,238,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,5,197,230,243,225,128,103,102,60,82,214,236,142,67,208,173,23,7,145,70,236,32,117,114,36,194,135,45,74,33,74,32,108,124,208,194,223,20,233,237,55,121,186,198,170,230,16,167,96,32,247,42,147,166,200,178,232,53,105,66,226,234,28,1,173,172,167,133,157,232,225,61,237,164,40,121,118,205,120,162,63,226,192,198,162,204,54,87,145,190,179,172,233,75,184,250,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,96,154,223,100,145,168,162,172,176,133,13,135,7,38,185,75,217,33,27,13,160,5,192,112,160,73,41,135,35,193,20,154,206,124,102,194,71,69,78,158,249,170,101,12,178,13,254,110,218,133,236,215,115,17,104,17,148,205,164,42,106,188,109,0,176,175,99,166,237,90,54,76,244,147,95,198,160,113,66,50,172,204,98,77,238,94,107,203,145,112,255,237,191,153,150,59,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,167,90,14,108,197,102,159,54,9,63,38,254,230,105,181,210,7,251,130,26,201,92,206,52,49,66,80,229,249,55,120,79,81,14,165,11,151,218,209,15,225,102,19,166,1,62,117,87,50,153,206,71,70,10,117,121,172,136,98,47,76,244,108,0,179,5,197,255,77,174,61,198,163,115,0,244,141,168,95,199,232,18,207,16,13,48,227,104,217,180,230,7,186,156,214,151,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,150,216,4,79,32,213,149,14,28,112,118,38,115,194,106,111,191,128,45,215,145,190,193,128,97,116,193,118,248,96,172,215,168,164,43,212,135,234,92,229,218,178,153,145,63,113,204,168,23,21,140,186,204,200,248,213,8,187,201,105,235,194,105,14,180,41,90,187,55,223,74,59,211,91,116,228,253,67,97,84,129,44,18,241,54,148,92,106,110,116,93,67,140,34,89,141,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,236,81,234,109,82,17,177,152,245,6,22,36,19,101,52,139,54,114,143,114,167,131,20,184,51,210,185,9,90,167,190,162,235,220,225,238,61,120,184,47,33,43,251,180,193,227,227,12,165,150,105,13,147,18,89,162,242,31,247,88,94,78,239,7,181,207,218,77,40,118,188,249,149,56,164,134,18,120,38,7,56,17,124,165,242,81,178,231,243,209,90,132,118,39,42,145,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,64,46,158,220,173,159,10,144,147,98,176,67,191,48,137,246,28,222,240,18,145,50,9,33,34,24,135,182,150,79,99,219,179,240,217,220,38,184,188,18,134,43,100,178,150,81,143,116,221,67,151,47,6,118,70,148,124,105,138,229,0,16,132,4,182,222,204,176,124,151,92,133,238,140,36,55,220,58,210,24,75,198,148,148,250,243,71,65,177,195,199,156,239,186,94,60,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,110,75,75,170,114,98,75,92,201,176,80,101,205,86,47,151,33,5,46,5,69,246,215,140,246,52,243,11,220,136,208,48,240,254,218,94,228,245,213,223,157,72,242,143,186,142,149,160,196,168,22,173,13,195,173,43,77,33,121,39,145,238,135,13,184,158,43,137,83,13,152,189,118,43,10,9,61,66,131,28,80,2,230,4,16,225,1,164,30,5,238,59,129,97,190,143,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,40,161,113,226,207,44,178,7,67,128,235,166,188,174,164,216,82,198,218,182,209,41,173,161,222,240,8,128,98,254,216,147,63,65,232,203,224,171,198,150,179,72,95,1,204,134,86,105,181,65,158,244,23,4,20,73,211,14,130,196,112,2,32,14,186,35,121,6,190,16,172,142,156,32,147,7,202,51,247,50,88,176,72,226,104,76,250,149,221,209,73,172,39,141,98,192,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,45,99,180,65,36,55,2,125,64,78,13,112,13,45,188,59,195,45,236,17,108,6,158,5,118,149,145,111,102,128,51,188,162,75,255,38,127,62,69,226,173,129,56,10,86,98,226,215,46,199,54,132,40,79,169,141,145,70,75,81,180,109,29,12,186,91,78,46,129,14,231,152,79,157,155,226,32,176,70,227,107,18,163,43,244,99,122,62,13,63,244,171,156,36,32,187,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,190,159,169,17,43,244,218,194,130,212,106,199,85,66,22,147,36,162,189,200,164,253,99,81,104,108,184,104,117,73,122,72,143,31,188,151,80,9,169,232,241,108,90,27,156,24,126,130,146,96,132,206,91,245,185,18,151,46,20,4,51,43,221,15,186,193,92,179,0,90,136,24,209,251,208,127,39,146,242,122,132,222,76,167,91,214,2,23,43,138,16,207,140,205,233,94,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,34,25,59,189,93,66,186,144,99,242,187,62,161,239,168,30,197,13,196,253,226,83,54,18,14,103,135,149,157,198,67,150,16,218,38,67,140,21,244,227,125,159,148,156,220,130,240,36,226,214,86,95,204,4,32,245,56,114,25,106,12,197,246,0,187,51,201,241,28,121,134,219,146,234,28,232,84,68,170,200,235,28,24,250,170,68,61,45,248,214,170,122,30,96,130,19,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,99,139,131,108,17,65,11,231,216,96,83,14,224,188,2,196,2,194,91,24,199,138,139,134,235,32,57,96,3,18,34,183,47,211,5,195,87,10,57,34,253,72,132,70,83,50,21,166,216,74,11,51,93,38,140,214,19,27,14,143,243,244,131,12,187,116,21,70,137,164,114,90,161,162,239,121,13,47,31,230,202,254,109,236,247,192,75,52,75,3,119,120,54,222,60,232,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,199,176,44,114,176,171,47,159,181,176,236,93,91,52,194,222,31,179,65,130,153,243,50,65,55,57,192,229,31,20,128,175,253,36,93,197,147,247,162,254,219,117,62,14,169,82,167,161,146,97,216,229,187,10,168,106,71,75,181,33,85,74,100,13,187,140,18,36,76,42,226,13,49,119,91,107,128,2,165,88,136,123,233,196,104,213,3,181,131,164,115,11,186,239,172,157,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,207,206,185,193,224,146,226,40,234,169,111,147,141,121,187,37,35,126,87,197,215,226,167,146,132,206,218,54,222,237,212,35,136,78,209,58,0,84,220,189,10,3,20,131,6,207,170,177,235,86,247,113,133,53,36,192,8,164,191,42,24,60,27,2,187,210,38,163,173,66,208,189,255,89,79,137,136,38,64,45,8,123,172,162,93,18,112,63,17,92,69,89,243,89,105,142,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,58,9,250,180,127,118,19,16,84,240,162,248,222,96,227,246,200,206,167,68,102,160,229,93,77,116,124,46,68,137,202,196,179,167,52,140,65,58,101,7,67,228,40,74,80,14,121,99,140,115,29,93,245,175,212,137,105,128,151,129,228,32,40,3,190,20,99,81,67,87,145,172,94,9,241,0,159,36,211,222,235,112,26,70,5,74,147,184,0,225,184,155,9,165,165,197,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,7,116,154,26,95,251,183,241,255,97,26,213,85,109,63,81,4,92,84,43,190,13,8,193,77,144,142,52,25,34,249,25,74,35,163,229,70,127,54,148,64,239,232,152,31,155,19,11,237,176,82,204,71,46,38,123,167,72,225,71,143,233,118,9,190,144,34,86,111,47,192,237,229,116,19,241,21,25,170,165,34,24,99,141,231,13,43,232,186,45,199,250,74,107,79,95,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,238,186,146,163,21,18,215,164,29,22,76,158,169,121,218,43,223,223,162,217,113,31,54,153,221,226,47,230,147,248,155,44,202,223,255,30,83,67,137,41,49,8,132,50,167,5,179,195,201,94,97,13,116,43,59,127,137,67,114,138,240,170,135,14,190,198,48,62,169,24,23,81,174,114,72,85,42,85,67,155,105,139,229,62,129,67,202,44,14,211,41,16,92,36,253,56,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,23,17,157,97,100,12,211,166,109,66,87,253,144,237,245,86,157,66,78,154,95,135,79,185,157,92,36,212,118,41,159,24,180,127,124,46,84,219,166,172,27,121,51,104,144,202,218,219,229,12,146,73,216,129,206,157,67,57,80,41,104,248,41,13,191,156,58,248,100,127,41,78,130,212,84,77,15,177,22,94,175,156,51,176,188,78,52,203,18,153,109,85,112,85,165,198,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,159,190,54,245,142,201,171,63,184,68,101,216,82,137,160,111,238,181,173,157,103,132,27,13,145,122,39,170,197,37,31,40,32,59,120,43,14,2,140,247,96,87,48,245,214,12,221,142,79,241,55,60,180,55,145,45,170,223,4,0,62,59,33,13,192,138,194,114,194,115,51,110,72,5,90,38,45,137,148,228,142,77,45,41,174,229,46,113,142,87,220,200,204,65,14,177,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,46,24,141,59,182,197,65,106,253,214,107,207,4,221,160,106,34,194,245,86,72,227,138,10,133,22,246,192,160,67,166,160,210,158,39,95,11,217,108,47,223,115,44,65,203,177,126,239,67,189,90,122,176,171,242,121,75,107,49,212,86,190,7,0,193,87,218,17,72,227,254,192,9,142,165,45,16,168,151,11,111,76,85,6,250,161,65,51,207,238,204,8,32,229,214,122,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,248,37,98,245,211,238,226,116,79,247,218,48,251,107,120,213,148,36,123,38,209,241,55,43,103,189,204,142,175,102,141,237,105,16,166,24,137,60,176,225,70,97,103,57,103,228,75,55,236,253,14,94,88,193,197,94,242,41,65,116,40,0,33,9,196,3,215,128,237,47,162,185,12,139,182,82,182,106,175,82,111,96,182,176,9,99,176,61,33,35,164,55,148,195,149,98,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,83,38,152,240,1,109,57,182,14,233,174,96,194,224,7,159,166,54,240,252,88,28,197,5,242,118,179,59,85,120,79,12,58,118,185,116,163,91,204,210,69,174,201,223,250,134,93,154,120,114,187,21,242,80,220,163,6,57,57,116,99,181,220,13,196,46,168,68,180,158,180,88,183,117,243,7,219,235,185,242,152,48,169,139,168,90,124,167,34,169,41,125,142,185,81,29,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,56,223,113,228,254,79,113,238,174,206,20,27,193,42,140,55,86,189,115,158,186,218,124,159,208,73,124,122,73,32,230,2,85,68,67,142,178,232,22,148,254,238,141,1,46,70,148,179,57,99,151,7,180,53,100,209,51,207,205,100,63,24,233,12,197,170,233,56,88,13,168,129,249,163,135,203,117,144,242,59,231,112,156,129,35,153,2,63,147,16,33,61,80,47,178,145,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,78,151,115,163,22,217,127,92,106,12,105,214,10,101,235,252,178,244,104,169,87,208,33,50,9,204,46,226,27,150,174,70,253,9,164,59,225,10,150,189,219,21,146,208,248,202,224,154,61,95,115,40,147,139,194,173,8,245,169,252,242,108,27,11,197,203,161,78,177,117,51,125,215,172,94,208,37,25,252,195,116,249,229,198,11,111,218,226,140,181,234,162,148,36,3,178,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,178,11,36,84,11,72,69,9,152,152,191,249,120,97,101,5,29,222,7,71,106,148,113,173,199,188,1,124,90,57,212,254,246,138,246,101,163,25,59,238,103,162,222,236,35,105,109,24,119,179,48,213,25,95,125,42,246,50,145,51,175,7,68,2,200,76,63,62,248,224,45,67,86,132,187,211,98,55,27,77,203,232,130,29,120,202,100,239,231,110,45,133,48,45,121,205,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,221,179,9,130,148,36,3,94,70,82,214,4,249,23,25,39,103,52,60,243,238,213,223,42,14,198,116,113,84,82,252,105,2,171,64,216,83,118,125,112,68,191,223,46,150,100,68,87,166,55,89,236,117,237,54,54,2,167,139,134,84,238,167,15,201,50,188,21,234,165,253,25,233,245,57,28,31,18,105,35,8,64,185,240,89,120,137,61,218,12,125,183,134,172,228,22,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,40,103,190,117,26,211,100,155,254,250,94,217,186,63,70,163,20,111,197,232,150,153,118,11,59,249,231,175,133,183,246,6,238,194,99,86,22,160,208,161,138,30,103,176,211,147,44,115,105,44,129,113,44,81,133,18,165,195,41,28,104,134,82,12,201,93,201,210,160,112,229,53,12,221,154,135,208,120,150,32,118,24,184,181,250,46,84,115,225,14,148,160,199,11,158,36,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,11,51,9,69,93,12,191,90,185,193,80,138,199,88,177,134,148,63,24,223,175,206,228,238,209,216,9,75,126,141,140,185,249,187,100,81,98,166,222,148,104,156,166,118,103,21,174,137,110,84,252,148,197,119,58,7,100,242,230,218,203,192,14,9,202,78,91,96,221,226,236,135,163,31,224,67,103,152,232,108,60,244,214,110,150,174,236,123,171,82,128,182,99,71,228,188,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,213,174,163,129,143,115,71,54,218,134,43,123,155,161,131,128,187,109,120,120,111,212,91,190,241,48,23,143,165,24,184,64,122,224,74,107,99,226,227,237,15,99,149,51,44,49,231,74,75,8,222,192,211,125,216,45,72,5,27,73,207,35,122,10,206,110,87,196,151,158,110,7,89,178,116,208,212,73,89,148,211,185,238,144,47,128,80,17,3,240,232,52,2,110,139,225,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,37,249,119,189,18,100,221,63,96,107,106,165,179,31,197,192,51,26,106,111,61,179,109,163,27,141,228,156,124,76,209,107,255,130,48,53,70,125,197,18,28,197,138,72,213,196,197,221,10,85,86,19,225,139,119,138,20,89,98,45,81,13,163,6,206,206,183,253,182,169,62,169,19,172,230,0,18,134,82,4,61,202,206,16,146,139,74,36,253,230,53,241,199,43,129,44,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,171,32,51,101,123,79,5,152,107,29,116,7,184,121,251,62,25,199,243,43,196,46,211,28,83,53,108,203,214,212,210,13,81,20,253,240,90,56,161,3,110,176,178,128,184,112,165,26,28,204,176,247,53,220,204,29,174,58,228,147,247,98,207,13,207,100,27,103,118,99,21,96,182,71,104,29,222,194,253,98,130,32,245,7,114,203,5,79,212,106,72,122,238,206,44,157,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,187,211,140,11,97,132,17,150,56,80,148,86,94,154,60,145,160,82,40,152,233,156,247,68,162,212,186,143,201,179,132,119,141,10,248,165,231,44,111,210,220,252,35,156,200,178,132,227,165,27,117,68,90,78,129,163,108,181,100,90,141,248,192,4,208,214,107,158,19,142,114,54,47,200,172,153,148,122,47,129,183,211,161,110,162,229,231,183,232,115,167,73,119,10,202,94,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,64,217,233,160,110,234,153,47,106,123,137,77,213,96,133,109,46,175,230,182,46,240,219,77,139,214,47,120,172,169,249,199,186,134,11,213,245,7,161,146,219,45,115,48,100,13,154,71,3,212,178,12,5,99,38,253,30,56,209,93,222,2,184,5,211,11,59,23,39,117,132,65,170,77,59,200,145,130,238,198,205,41,248,222,151,178,173,120,176,89,214,78,83,105,236,154,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,18,183,234,249,213,91,85,219,106,146,24,148,242,142,162,149,62,10,138,230,29,228,220,11,58,192,15,79,88,42,206,93,35,255,61,227,110,184,190,153,163,26,245,129,214,161,33,87,69,1,133,49,231,129,153,96,134,116,239,15,96,227,166,13,211,161,61,207,23,177,18,89,248,144,248,25,86,178,165,247,170,10,176,125,166,83,235,64,31,19,102,173,200,249,252,208,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,112,27,173,134,122,138,197,209,91,240,101,97,195,124,176,39,233,66,247,76,230,186,115,221,187,192,171,131,229,144,210,75,188,97,146,54,118,255,69,173,68,2,83,113,131,8,162,129,135,223,187,196,149,93,189,119,181,122,228,141,25,7,47,1,214,29,187,163,183,137,99,211,170,202,179,5,27,142,213,52,94,151,161,40,119,131,62,83,211,64,174,52,181,147,77,172,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,241,24,146,144,84,24,133,64,59,6,158,158,10,146,232,131,71,15,118,238,58,37,147,116,252,239,162,37,36,210,89,15,214,180,130,253,12,221,250,70,102,115,230,81,207,80,115,75,45,108,221,230,174,193,197,10,128,56,250,62,35,189,231,0,214,61,104,11,180,163,194,2,39,49,90,251,149,134,43,162,100,170,33,18,21,21,178,147,125,208,88,54,126,65,90,12,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,251,227,78,123,101,60,213,66,26,169,184,92,203,21,201,92,67,145,12,180,145,152,23,35,85,36,39,26,23,4,247,208,61,90,149,212,197,216,41,157,247,175,154,99,82,199,209,90,220,149,236,135,133,18,150,25,242,148,28,191,202,113,96,4,214,66,107,207,167,122,182,5,13,122,78,73,48,201,52,218,24,145,50,71,250,92,52,103,166,194,106,181,134,124,234,249,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,111,248,123,91,250,7,135,63,174,13,130,94,165,85,138,123,173,62,99,131,34,218,169,127,108,214,192,182,10,118,104,11,183,110,66,250,51,148,208,55,219,56,61,96,234,98,245,141,203,249,23,242,64,206,59,170,170,124,81,110,255,119,37,9,215,14,188,175,214,219,154,132,58,163,90,55,78,17,185,100,62,114,168,86,246,81,101,51,166,230,249,51,51,142,183,134,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,205,213,12,147,106,3,10,188,108,77,20,118,66,105,244,68,191,206,57,66,227,159,136,68,143,45,202,170,254,239,45,26,65,99,227,195,191,7,0,61,42,129,135,95,207,133,93,205,34,110,237,15,219,48,21,20,58,101,107,104,61,137,201,8,216,142,212,134,67,43,17,236,73,194,71,248,45,190,110,199,76,228,34,135,161,249,111,78,204,77,223,251,161,189,176,14,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,23,154,253,193,79,75,86,210,162,117,220,152,54,165,52,81,200,48,172,141,188,87,245,52,111,238,208,246,115,213,92,124,15,128,42,162,159,119,124,54,158,93,250,129,65,212,66,9,45,242,29,8,87,162,215,166,243,103,8,131,58,154,246,8,220,84,194,87,134,143,96,234,178,7,128,149,79,183,42,134,107,33,246,67,48,143,124,10,179,182,226,169,244,240,1,6,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,50,146,235,92,106,239,185,200,23,222,109,107,150,183,225,144,210,206,209,125,169,194,70,46,188,170,189,103,184,221,5,132,250,127,201,27,81,198,236,127,90,251,5,35,7,9,181,7,235,77,224,247,202,93,196,114,145,35,63,31,20,236,100,10,221,44,56,57,18,219,228,210,226,88,170,219,42,1,213,170,242,24,230,236,235,91,102,98,52,143,234,15,68,6,156,223,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,252,70,149,35,189,75,159,51,107,96,198,164,231,197,62,155,97,72,131,139,229,148,215,237,84,187,168,63,102,47,210,116,207,234,11,207,235,62,153,218,226,156,161,196,0,83,142,27,191,163,125,21,188,90,168,164,65,10,255,125,216,133,10,3,222,59,3,17,124,5,146,193,187,97,86,207,90,137,182,45,181,190,229,193,169,97,114,168,60,5,122,150,43,5,93,88,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,177,67,193,225,109,233,118,215,29,215,205,207,68,26,65,62,141,246,233,27,188,255,251,93,46,196,178,72,49,141,244,107,120,13,22,130,103,248,252,44,34,113,223,132,149,66,2,192,248,19,53,79,83,194,194,32,183,223,56,55,39,150,11,13,222,89,14,220,224,56,48,42,71,141,53,70,34,153,180,192,80,93,170,245,244,27,162,154,23,144,129,22,224,56,221,76,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,58,17,142,45,130,54,242,118,215,27,206,154,156,124,164,111,140,56,222,98,69,33,228,29,62,18,32,9,36,216,57,31,183,50,159,29,21,128,141,29,231,146,84,81,106,215,100,70,27,189,34,29,162,34,121,69,130,240,49,79,45,189,140,5,223,180,231,15,175,55,224,195,18,46,149,98,128,67,112,128,116,58,5,195,14,208,131,95,122,87,21,129,176,76,119,41,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,6,223,166,47,42,47,245,111,156,135,47,153,89,58,51,163,161,112,21,82,160,211,182,37,143,207,232,110,117,156,31,232,70,85,204,135,64,56,239,0,179,134,36,156,64,64,51,24,201,173,233,203,244,87,173,46,213,172,128,10,133,49,225,7,224,95,102,212,10,230,245,2,41,40,131,255,97,190,230,10,190,159,99,25,118,163,167,136,153,44,2,174,8,238,33,237,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,97,199,226,117,139,248,3,219,123,181,129,199,50,235,114,56,80,232,52,237,220,90,246,252,16,246,159,90,83,23,34,103,166,142,240,135,160,1,222,165,43,195,35,120,76,224,129,140,253,40,216,89,180,155,59,189,67,215,218,49,188,51,157,5,225,50,176,61,232,84,183,179,2,193,10,187,106,152,115,163,68,135,251,130,4,160,170,38,145,85,83,228,143,16,16,20,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,244,192,20,125,152,58,84,79,26,152,79,226,16,163,84,67,110,157,82,105,61,165,66,204,118,168,139,80,216,45,208,117,206,19,220,183,56,61,16,193,47,85,9,32,66,61,57,181,217,20,56,6,127,205,24,123,25,237,37,194,156,185,115,10,227,199,169,248,14,219,7,123,33,151,52,133,238,22,102,58,191,114,44,255,85,175,110,68,161,103,184,97,220,143,167,180,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,184,7,240,196,141,205,161,186,111,182,69,96,9,254,238,132,94,247,121,2,31,9,202,168,253,156,197,34,246,187,214,62,248,3,120,136,220,229,33,82,91,155,148,105,190,95,145,126,47,194,46,139,153,98,119,124,42,206,127,233,37,130,208,1,228,249,220,91,229,184,30,137,96,68,4,38,131,126,214,87,207,76,97,151,210,91,193,31,76,177,35,24,64,15,137,242,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,227,195,184,140,95,78,73,146,29,64,72,106,146,251,127,112,243,64,130,137,116,106,116,115,169,233,164,204,183,121,22,101,28,23,29,152,108,28,190,122,91,179,169,124,155,134,170,79,20,222,23,20,37,109,244,252,42,192,122,156,30,6,17,14,229,62,239,161,174,82,117,78,161,6,228,163,46,209,76,231,25,74,14,205,189,181,91,70,117,48,58,35,12,2,123,117,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,123,236,123,106,152,173,229,39,16,88,11,86,29,54,128,148,70,28,76,77,31,183,35,180,244,99,213,222,12,140,73,75,108,206,162,148,139,11,64,108,78,12,2,247,137,232,22,10,181,199,178,248,32,31,165,218,96,128,160,247,167,175,211,13,230,239,54,14,177,9,176,231,48,199,52,212,135,72,11,181,21,155,30,126,223,50,220,178,214,114,79,195,52,116,99,6,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,58,212,99,51,217,233,125,40,206,5,196,187,114,0,237,222,161,171,250,15,123,132,225,67,109,92,122,89,96,221,212,156,121,108,237,187,120,216,165,110,221,148,142,43,16,118,59,129,133,50,147,22,149,156,205,222,146,76,17,26,95,95,58,5,232,158,169,168,229,239,160,149,4,196,95,27,213,119,189,224,110,193,252,42,230,229,143,134,161,97,59,240,254,205,243,168,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,250,137,37,181,168,53,33,19,34,36,9,241,101,18,24,231,58,111,22,203,75,42,202,56,206,88,189,59,103,102,99,197,3,208,90,164,193,117,7,221,126,150,173,211,70,239,176,171,214,89,0,16,137,134,131,26,58,63,157,187,150,27,221,15,236,24,156,210,61,251,205,81,102,28,174,133,62,109,194,115,51,145,196,55,91,117,254,196,11,55,171,86,187,26,120,28,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,100,180,230,69,164,251,164,5,187,244,157,248,252,218,48,126,105,7,200,109,19,126,169,73,11,77,118,10,131,230,115,20,87,62,242,70,157,72,191,115,131,218,128,68,93,39,80,90,126,180,161,230,76,104,198,184,219,91,23,197,129,51,202,3,236,220,180,220,78,10,145,46,65,145,165,146,26,226,141,186,212,165,14,202,65,8,78,38,254,153,32,69,14,152,150,132,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,54,39,116,18,45,226,14,227,53,248,222,179,110,37,199,112,228,0,238,184,101,99,80,241,74,247,169,193,139,43,11,147,15,123,118,62,15,7,238,164,60,89,194,42,210,108,212,59,134,71,243,77,145,80,110,109,68,81,220,33,75,50,6,6,236,221,206,243,239,73,41,162,10,180,194,171,77,37,47,198,56,205,1,102,88,206,21,145,105,140,213,241,30,106,213,221,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,171,118,150,241,72,101,122,172,111,9,143,142,19,68,222,190,54,82,241,247,162,221,177,57,167,27,163,37,146,143,144,67,227,117,3,72,154,195,153,185,119,98,247,222,68,40,13,7,99,125,214,197,205,226,20,132,171,28,223,197,153,97,44,6,239,240,43,59,118,195,51,25,151,106,132,56,162,165,180,182,131,146,237,145,12,1,223,213,210,184,18,38,213,33,172,222,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,70,74,82,86,19,142,112,20,252,155,230,65,42,68,101,51,121,8,91,46,191,34,138,116,213,33,24,140,34,98,168,110,215,212,60,109,70,165,155,228,150,211,241,66,229,157,33,192,31,189,192,26,80,113,64,41,81,2,85,106,217,113,136,10,242,108,139,64,254,35,166,77,59,240,251,173,39,234,8,237,254,7,13,181,246,95,178,142,194,111,167,67,173,109,246,246,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,153,181,82,97,232,71,173,155,68,19,229,253,136,89,239,249,254,12,177,92,218,34,38,207,127,19,221,154,254,92,117,50,8,8,6,11,41,93,218,161,145,107,148,55,121,223,196,233,183,108,175,223,149,138,251,31,234,198,213,178,84,192,164,13,242,213,182,102,223,246,96,237,88,96,0,203,91,34,54,199,139,62,28,228,101,186,140,46,118,71,186,164,154,29,242,223,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,204,151,110,154,8,6,177,209,126,181,233,163,86,82,91,246,230,87,92,90,123,99,40,195,118,116,203,139,143,151,123,234,35,29,137,138,35,192,77,188,76,210,144,31,227,73,130,238,206,113,51,175,121,154,166,150,145,220,243,17,233,114,127,2,243,213,141,15,38,115,22,213,92,8,202,244,99,139,253,244,231,129,164,75,160,22,90,101,137,166,16,35,112,73,13,137,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,33,205,226,221,103,4,158,42,36,250,81,171,241,209,179,151,215,228,176,31,45,169,159,129,205,126,5,15,98,50,189,156,144,240,226,155,160,218,252,38,154,194,7,91,247,232,98,0,88,168,57,134,126,129,238,21,44,181,235,52,95,86,21,14,244,80,102,195,91,171,198,186,39,183,202,5,109,247,15,4,220,135,125,188,192,189,154,200,201,216,45,86,133,107,21,0,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,243,35,196,84,224,189,82,248,6,168,104,69,167,224,243,116,104,39,155,57,113,228,195,108,9,176,92,89,47,9,175,215,124,177,184,123,13,91,3,156,150,230,38,29,33,253,43,68,31,42,55,58,163,162,192,194,145,148,111,161,230,138,225,9,247,69,18,151,20,22,125,158,100,125,166,119,184,212,83,19,93,158,117,179,239,180,232,106,117,124,98,116,253,243,101,228,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,223,229,200,107,182,171,104,83,225,99,168,22,177,174,45,33,239,2,135,242,88,61,223,206,141,135,12,19,229,117,82,178,7,131,235,42,119,236,80,39,13,147,154,17,92,247,227,101,1,93,35,9,160,98,112,17,158,143,175,73,107,99,117,3,248,137,11,164,85,33,63,58,21,207,20,192,93,91,114,213,249,232,253,228,35,235,82,69,91,195,205,197,95,41,241,252,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,183,213,53,187,1,24,143,4,84,141,204,49,144,153,193,113,100,246,117,81,5,73,219,134,218,130,191,65,128,40,3,129,107,204,236,228,200,221,98,226,27,6,96,246,3,103,169,205,9,13,24,127,213,167,113,66,213,164,26,45,38,170,82,5,249,243,169,159,82,169,153,20,117,219,224,20,7,57,248,22,232,238,67,163,238,5,52,216,225,193,104,215,68,41,59,218,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,232,32,108,96,81,179,23,33,26,144,217,117,62,247,50,42,28,17,6,6,134,63,189,206,156,16,169,73,104,75,199,72,235,28,236,250,185,117,108,158,241,212,56,6,126,127,70,51,162,137,51,201,145,32,253,232,80,119,163,131,160,138,28,9,250,131,30,186,28,103,188,156,185,126,117,254,175,3,167,57,163,170,168,193,23,228,250,89,195,69,190,8,96,10,225,211,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,108,5,203,9,104,232,241,243,226,218,224,174,99,191,102,12,153,209,176,246,88,81,46,192,172,20,119,170,219,54,237,227,195,3,212,15,216,48,223,249,208,100,85,208,183,166,24,92,146,243,145,166,211,76,37,35,100,36,107,176,147,115,252,4,253,124,49,32,43,181,103,60,52,83,128,83,143,31,67,29,44,140,157,43,188,137,47,223,138,182,168,255,121,192,80,233,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,213,227,147,175,97,112,106,81,3,225,88,247,128,216,74,180,140,125,254,75,97,57,226,139,182,195,241,120,55,94,248,228,3,124,143,22,95,233,193,3,91,162,138,26,67,223,149,240,23,41,199,11,53,51,146,90,152,47,46,217,69,13,140,0,254,120,119,50,215,161,206,151,105,228,6,51,81,187,5,138,18,59,225,46,93,78,244,245,217,61,208,202,52,143,47,44,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,188,174,91,27,222,220,125,129,147,234,11,42,48,67,246,178,194,27,132,254,88,254,94,74,6,208,52,153,254,130,51,229,55,141,196,31,45,166,144,5,206,185,205,218,102,104,70,8,57,151,196,208,14,1,186,7,227,202,24,54,95,84,46,15,255,24,91,67,2,20,136,99,67,21,151,115,88,216,22,197,218,133,32,112,35,89,182,241,210,182,253,184,192,167,84,202,0]}"##;
+        let third_block: SignedBlock = from_str(encoded_third_block).unwrap();
+        let third_block_hex_encoded = "0x".to_string() + encode(third_block.encode()).as_ref();
+        let encoded_fourth_block = r##"{"block":{"extrinsics":["0x280402000b6062dedb7201","0x1c040900f667ae00","0x1004140000"],"header":{"digest":{"logs":["0x06424142453402500000008ac4d20f00000000","0x05424142450101480d0f8bbf3adab95a70c18487da84d18931bfaf1f1bb447eaa442ad442de938ab51df401cace75a6b8863193cc6948203f19ba4533a7a9039802e08718d1e8c"]},"extrinsicsRoot":"0x040226b4b8fa49f1ca610eeed78e4f597026dcc94a12af0efbdf472f5083d932","number":"0x2b9a01","parentHash":"0x6029ee56c47d84ce5a5616d58181dc7e2de37df20f2d23323bcfd720d65db29a","stateRoot":"0x03ceb2aae98c16376d50403ab166577ac63ab4f76d44012e920e78cbc5b3b65b"}},"justification":null}"##;
+        let fourth_block: SignedBlock = from_str(encoded_fourth_block).unwrap();
+        let fourth_block_hex_encoded = "0x".to_string() + encode(fourth_block.encode()).as_ref();
+
+        let storage = MockStorage::new();
+        let api = MockApi::new(5);
+        let querier = MockQuerier::default();
+        let mut extern_dep = Extern {
+            storage,
+            api,
+            querier,
+        };
+
+        let init_msg = InitMsg {
+            client_id: "testclient".to_string(),
+            name: "testtesttest".into(),
+            block: first_block_hex_encoded.clone(),
+            authority_set: encoded_authority_list.to_string(),
+            max_headers_allowed_to_store: 256,
+            set_id,
+            max_headers_allowed_between_justifications: 2,
+            justification: None,
+            authority_set_proof: None,
+            finality_gadget: None,
+            beefy_validator_set: None,
+            max_consensus_states_to_store: None,
+            retention_policy: None,
+            justification_verification: None,
+            snapshot: None,
+        };
+
+        let result = init(&mut extern_dep, Env::default(), init_msg);
+        assert!(result.is_ok());
+
+        let result = query(
+            &extern_dep,
+            QueryMsg::LatestHeight {
+                client_id: "testclient".to_string(),
+            },
+        );
+        assert!(result.is_ok());
+        let query_response: LatestHeightResponse =
+            from_binary(&result.unwrap()).expect("Deserializing Query response failed");
+        assert_eq!(query_response.last_finalized_header_hash.len(), 0);
+        assert_eq!(
+            query_response.best_header_hash,
+            first_block.block.header.hash().0
+        );
+        assert_eq!(
+            query_response.best_header_height,
+            first_block.block.header.number
+        );
+
+        let contract_state = load_contract_state(&extern_dep.storage, "testclient")
+            .expect("Contract state should exists");
+        assert_eq!(contract_state.name, "testtesttest");
+        assert!(contract_state.light_client_data.len() > 0);
+        assert_eq!(contract_state.headers_ingested_after_last_justification, 1);
+        assert_eq!(contract_state.max_headers_allowed_between_justifications, 2);
+
+        // Second header
+        let update_msg = HandleMsg::UpdateClient {
+            client_id: "testclient".to_string(),
+            block: second_block_hex_encoded.to_string(),
+            authority_set: encoded_authority_list.to_string(),
+        };
+        let result = handle(&mut extern_dep, Env::default(), update_msg);
+        assert!(result.is_ok());
+
+        let result = query(
+            &extern_dep,
+            QueryMsg::LatestHeight {
+                client_id: "testclient".to_string(),
+            },
+        );
+        assert!(result.is_ok());
+        let query_response: LatestHeightResponse =
+            from_binary(&result.unwrap()).expect("Deserializing Query response failed");
+        assert_eq!(query_response.last_finalized_header_hash.len(), 0);
+        assert_eq!(
+            query_response.best_header_hash,
+            second_block.block.header.hash().0
+        );
+        assert_eq!(
+            query_response.best_header_height,
+            second_block.block.header.number
+        );
+
+        let contract_state = load_contract_state(&extern_dep.storage, "testclient")
+            .expect("Contract state should exists");
+        assert_eq!(contract_state.name, "testtesttest");
+        assert!(contract_state.light_client_data.len() > 0);
+        assert_eq!(contract_state.headers_ingested_after_last_justification, 2);
+        assert_eq!(contract_state.max_headers_allowed_between_justifications, 2);
+
+        // Third block
+        let update_msg = HandleMsg::UpdateClient {
+            client_id: "testclient".to_string(),
+            block: third_block_hex_encoded.to_string(),
+            authority_set: encoded_authority_list.to_string(),
+        };
+        let result = handle(&mut extern_dep, Env::default(), update_msg);
+        assert!(result.is_ok());
+        let handle_response = result.unwrap();
+        let update_response: UpdateClientResponse =
+            from_binary(&handle_response.data.unwrap()).expect("Deserializing data failed");
+        assert_eq!(update_response.height, third_block.block.header.number);
+        assert!(update_response.is_new_best);
+        assert!(update_response.finalized);
+        assert_eq!(
+            update_response.last_finalized_header_hash,
+            third_block.block.header.hash().0
+        );
+        assert!(!update_response.scheduled_change_detected);
+        assert!(!update_response.authority_set_enacted);
+        assert_eq!(update_response.set_id, set_id);
+        assert_eq!(update_response.headers_ingested_after_last_justification, 0);
+        assert!(handle_response.log.contains(&log(
+            "finalized_height",
+            third_block.block.header.number.to_string()
+        )));
+
+        let result = query(
+            &extern_dep,
+            QueryMsg::LatestHeight {
+                client_id: "testclient".to_string(),
+            },
+        );
+        assert!(result.is_ok());
+        let query_response: LatestHeightResponse =
+            from_binary(&result.unwrap()).expect("Deserializing Query response failed");
+        assert_eq!(
+            query_response.last_finalized_header_hash,
+            third_block.block.header.hash().0
+        );
+        assert_eq!(
+            query_response.best_header_hash,
+            third_block.block.header.hash().0
+        );
+        assert_eq!(
+            query_response.best_header_height,
+            third_block.block.header.number
+        );
+
+        let contract_state = load_contract_state(&extern_dep.storage, "testclient")
+            .expect("Contract state should exists");
+        assert_eq!(contract_state.name, "testtesttest");
+        assert!(contract_state.light_client_data.len() > 0);
+        assert_eq!(contract_state.headers_ingested_after_last_justification, 0);
+        assert_eq!(contract_state.max_headers_allowed_between_justifications, 2);
+
+        // Body which doesn't match extrinsics root of the header is rejected
+        let mut tampered_fourth_block = fourth_block.clone();
+        tampered_fourth_block.block.extrinsics.pop();
+        let update_msg = HandleMsg::UpdateClient {
+            client_id: "testclient".to_string(),
+            block: "0x".to_string() + encode(tampered_fourth_block.encode()).as_ref(),
+            authority_set: encoded_authority_list.to_string(),
+        };
+        let result = handle(&mut extern_dep, Env::default(), update_msg);
+        assert!(result
+            .err()
+            .unwrap()
+            .to_string()
+            .contains("[101] Unable to ingest header. Error: Block import: Block verification failed: Block body doesn't match extrinsics root"));
+
+        // Stripped body is checked as well, header alone goes with UpdateHeader
+        let mut stripped_fourth_block = fourth_block.clone();
+        stripped_fourth_block.block.extrinsics.clear();
+        let update_msg = HandleMsg::UpdateClient {
+            client_id: "testclient".to_string(),
+            block: "0x".to_string() + encode(stripped_fourth_block.encode()).as_ref(),
+            authority_set: encoded_authority_list.to_string(),
+        };
+        let result = handle(&mut extern_dep, Env::default(), update_msg);
+        assert!(result
+            .err()
+            .unwrap()
+            .to_string()
+            .contains("Block body doesn't match extrinsics root"));
+
+        // Fourth block
+        let update_msg = HandleMsg::UpdateClient {
+            client_id: "testclient".to_string(),
+            block: fourth_block_hex_encoded.to_string(),
+            authority_set: encoded_authority_list.to_string(),
+        };
+        let result = handle(&mut extern_dep, Env::default(), update_msg);
+        assert!(result.is_ok());
+        let handle_response = result.unwrap();
+        let update_response: UpdateClientResponse =
+            from_binary(&handle_response.data.unwrap()).expect("Deserializing data failed");
+        assert!(!update_response.finalized);
+        assert_eq!(
+            update_response.last_finalized_header_hash,
+            third_block.block.header.hash().0
+        );
+        assert_eq!(update_response.headers_ingested_after_last_justification, 1);
+        assert!(handle_response.log.contains(&log("finalized", "false")));
+
+        let result = query(
+            &extern_dep,
+            QueryMsg::LatestHeight {
+                client_id: "testclient".to_string(),
+            },
+        );
+        assert!(result.is_ok());
+        let query_response: LatestHeightResponse =
+            from_binary(&result.unwrap()).expect("Deserializing Query response failed");
+        assert_eq!(
+            query_response.last_finalized_header_hash,
+            third_block.block.header.hash().0
+        );
+        assert_eq!(
+            query_response.best_header_hash,
+            fourth_block.block.header.hash().0
+        );
+        assert_eq!(
+            query_response.best_header_height,
+            fourth_block.block.header.number
+        );
+
+        let contract_state = load_contract_state(&extern_dep.storage, "testclient")
+            .expect("Contract state should exists");
+        assert_eq!(contract_state.name, "testtesttest");
+        assert!(contract_state.light_client_data.len() > 0);
+        assert_eq!(contract_state.headers_ingested_after_last_justification, 1);
+        assert_eq!(contract_state.max_headers_allowed_between_justifications, 2);
+
+        // Retained headers can be walked in pages in both directions
+        let headers_query = |start_height, reverse| {
+            let result = query(
+                &extern_dep,
+                QueryMsg::Headers {
+                    client_id: "testclient".to_string(),
+                    start_height,
+                    limit: Some(3),
+                    reverse,
+                },
+            );
+            from_binary::<HeadersResponse>(&result.unwrap())
+                .expect("Deserializing Query response failed")
+        };
+        let first_number = first_block.block.header.number;
+        let headers_response = headers_query(None, None);
+        assert_eq!(headers_response.headers.len(), 3);
+        assert_eq!(headers_response.next_height, Some(first_number + 3));
+        assert_eq!(
+            headers_response.headers[1],
+            HeaderSummary {
+                number: first_number + 1,
+                hash: second_block.block.header.hash().0.to_vec(),
+                parent_hash: first_block.block.header.hash().0.to_vec(),
+                state_root: second_block.block.header.state_root.0.to_vec(),
+                finalized: true,
+            }
+        );
+        let headers_response = headers_query(headers_response.next_height, None);
+        assert_eq!(headers_response.headers.len(), 1);
+        assert_eq!(
+            headers_response.headers[0].hash,
+            fourth_block.block.header.hash().0.to_vec()
+        );
+        assert!(!headers_response.headers[0].finalized);
+        assert_eq!(headers_response.next_height, None);
+
+        let headers_response = headers_query(None, Some(true));
+        let numbers: Vec<_> = headers_response.headers.iter().map(|h| h.number).collect();
+        assert_eq!(
+            numbers,
+            vec![first_number + 3, first_number + 2, first_number + 1]
+        );
+        assert_eq!(headers_response.next_height, Some(first_number));
+        let headers_response = headers_query(Some(first_number), Some(true));
+        assert_eq!(headers_response.headers.len(), 1);
+        assert_eq!(headers_response.next_height, None);
+
+        let result = query(
+            &extern_dep,
+            QueryMsg::StorageWindow {
+                client_id: "testclient".to_string(),
+            },
+        );
+        let storage_window_response: StorageWindowResponse =
+            from_binary(&result.unwrap()).expect("Deserializing Query response failed");
+        assert_eq!(
+            storage_window_response,
+            StorageWindowResponse {
+                oldest_height: first_number,
+                newest_height: first_number + 3,
+                last_finalized_height: Some(first_number + 2),
+                total_stored: 4,
+                capacity: 256,
+                kept_heights: vec![],
+            }
+        );
+
+        // Client can be created in trust-minimized mode, where authority set
+        // proves that it finalized the initial header.
+        let third_block_justification =
+            "0x".to_string() + encode(third_block.justification.clone().unwrap()).as_ref();
+        let create_msg = |client_id: &str, set_id| HandleMsg::CreateClient {
+            client_id: client_id.to_string(),
+            name: "testtesttest".into(),
+            block: third_block_hex_encoded.clone(),
+            set_id,
+            authority_set: encoded_authority_list.to_string(),
+            max_headers_allowed_to_store: 256,
+            max_headers_allowed_between_justifications: 2,
+            justification: Some(third_block_justification.clone()),
+            authority_set_proof: None,
+            finality_gadget: None,
+            beefy_validator_set: None,
+            max_consensus_states_to_store: None,
+            retention_policy: None,
+            justification_verification: None,
+        };
+        let result = handle(
+            &mut extern_dep,
+            Env::default(),
+            create_msg("wrongsetid", set_id + 1),
+        );
+        assert!(result
+            .err()
+            .unwrap()
+            .to_string()
+            .contains("[205] Unable to verify initial state"));
+        let result = handle(
+            &mut extern_dep,
+            Env::default(),
+            create_msg("verified", set_id),
+        );
+        assert!(result.is_ok());
+        let contract_state = load_contract_state(&extern_dep.storage, "verified")
+            .expect("Contract state should exists");
+        assert_eq!(contract_state.headers_ingested_after_last_justification, 1);
+
+        // Justification can be dry-run against authority set of the client
+        let report_query = |set_id, justification: &str| {
+            query(
+                &extern_dep,
+                QueryMsg::JustificationReport {
+                    client_id: "verified".to_string(),
+                    set_id,
+                    justification: justification.to_string(),
+                },
+            )
+        };
+        let report_response: JustificationReportResponse =
+            from_binary(&report_query(None, &third_block_justification).unwrap())
+                .expect("Deserializing Query response failed");
+        assert!(report_response.valid);
+        assert_eq!(report_response.set_id, set_id);
+        assert_eq!(
+            report_response.target_hash,
+            third_block.block.header.hash().0
+        );
+        assert!(report_response.signed_weight >= report_response.required_weight);
+        assert!(report_response.invalid_signatures.is_empty());
+        assert!(report_response.unknown_voters.is_empty());
+        let result = report_query(Some(set_id + 1), &third_block_justification);
+        assert!(result
+            .err()
+            .unwrap()
+            .to_string()
+            .contains(&format!("Authority set {} isn't archived", set_id + 1)));
+
+        // Justification of another header doesn't finalize the initial header
+        let mut create_msg = create_msg("mismatched", set_id);
+        if let HandleMsg::CreateClient { block, .. } = &mut create_msg {
+            *block = first_block_hex_encoded.clone();
+        }
+        let result = handle(&mut extern_dep, Env::default(), create_msg);
+        assert!(result.is_err());
+
+        // Headers can be ingested without block bodies
+        let result = handle(
+            &mut extern_dep,
+            Env::default(),
+            HandleMsg::CreateClient {
+                client_id: "headeronly".to_string(),
+                name: "testtesttest".into(),
+                block: first_block_hex_encoded.clone(),
+                set_id,
+                authority_set: encoded_authority_list.to_string(),
+                max_headers_allowed_to_store: 256,
+                max_headers_allowed_between_justifications: 2,
+                justification: None,
+                authority_set_proof: None,
+                finality_gadget: None,
+                beefy_validator_set: None,
+                max_consensus_states_to_store: None,
+                retention_policy: None,
+                justification_verification: None,
+            },
+        );
+        assert!(result.is_ok());
+        for block in &[&second_block, &third_block] {
+            let update_msg = HandleMsg::UpdateHeader {
+                client_id: "headeronly".to_string(),
+                header: Binary(block.block.header.encode()).to_base64(),
+                justification: block
+                    .justification
+                    .as_ref()
+                    .map(|j| Binary(j.clone()).to_base64()),
+            };
+            let result = handle(&mut extern_dep, Env::default(), update_msg);
+            assert!(result.is_ok());
+        }
+        let result = query(
+            &extern_dep,
+            QueryMsg::LatestHeight {
+                client_id: "headeronly".to_string(),
+            },
+        );
+        let query_response: LatestHeightResponse =
+            from_binary(&result.unwrap()).expect("Deserializing Query response failed");
+        assert_eq!(
+            query_response.last_finalized_header_hash,
+            third_block.block.header.hash().0
+        );
+        let contract_state = load_contract_state(&extern_dep.storage, "headeronly")
+            .expect("Contract state should exists");
+        assert_eq!(contract_state.headers_ingested_after_last_justification, 0);
+
+        let update_msg = HandleMsg::UpdateHeader {
+            client_id: "headeronly".to_string(),
+            header: "0x".to_string() + encode(fourth_block.block.header.encode()).as_ref(),
+            justification: None,
+        };
+        assert!(handle(&mut extern_dep, Env::default(), update_msg).is_ok());
+        let contract_state = load_contract_state(&extern_dep.storage, "headeronly")
+            .expect("Contract state should exists");
+        assert_eq!(contract_state.headers_ingested_after_last_justification, 1);
+
+        let update_msg = HandleMsg::UpdateHeader {
+            client_id: "headeronly".to_string(),
+            header: "0x00".to_string(),
+            justification: None,
+        };
+        match handle(&mut extern_dep, Env::default(), update_msg)
+            .err()
+            .unwrap()
+        {
+            StdError::ParseErr { target, .. } => assert_eq!(target, "header"),
+            e => panic!("Unexpected error: {}", e),
+        }
+
+        // Extrinsics of finalized blocks can be proven against their extrinsics root
+        let extrinsics: Vec<Vec<u8>> = second_block
+            .block
+            .extrinsics
+            .iter()
+            .map(|e| e.encode())
+            .collect();
+        let mut db = MemoryDB::<BlakeTwo256>::default();
+        let mut extrinsics_root = H256::default();
+        {
+            let mut trie = TrieDBMut::<BlakeTwo256>::new(&mut db, &mut extrinsics_root);
+            for (index, extrinsic) in extrinsics.iter().enumerate() {
+                trie.insert(&Compact(index as u32).encode(), extrinsic)
+                    .unwrap();
+            }
+        }
+        assert_eq!(extrinsics_root, second_block.block.header.extrinsics_root);
+        let proof: Vec<String> = db
+            .drain()
+            .into_iter()
+            .map(|(_, (node, _))| Binary(node).to_base64())
+            .collect();
+        let extrinsic_query = |extrinsic_index, extrinsic: &[u8]| {
+            query(
+                &extern_dep,
+                QueryMsg::VerifyExtrinsic {
+                    client_id: "testclient".to_string(),
+                    height: second_block.block.header.number,
+                    header_proof: None,
+                    extrinsic_index,
+                    extrinsic: Binary(extrinsic.to_vec()).to_base64(),
+                    proof: proof.clone(),
+                },
+            )
+        };
+        let result = extrinsic_query(1, &extrinsics[1]);
+        let extrinsic_response: VerifyExtrinsicResponse =
+            from_binary(&result.unwrap()).expect("Deserializing Query response failed");
+        assert_eq!(
+            extrinsic_response.header_hash,
+            second_block.block.header.hash().0
+        );
+        assert_eq!(extrinsic_response.extrinsics_root, extrinsics_root.0);
+        assert_eq!(
+            extrinsic_response.extrinsic_hash,
+            BlakeTwo256::hash(&extrinsics[1]).0
+        );
+        assert!(extrinsic_query(0, &extrinsics[1]).is_err());
+        assert!(extrinsic_query(extrinsics.len() as u32, &extrinsics[1]).is_err());
+    }
+
+    #[test]
+    fn test_multiple_clients() {
+        let storage = MockStorage::new();
+        let api = MockApi::new(5);
+        let querier = MockQuerier::default();
+        let mut extern_dep = Extern {
+            storage,
+            api,
+            querier,
+        };
+        let init_block = "0x5e9fc49076803d0ba88c719252ede5ae713d09367162d344e9b79ef3aac2efa03e620300fe518cc595e8f5ede8010cf6d26352f6a089ee52f992153a540c7b5d9b659ea272c9c1e535cf5ca49ab2d72059671d80f69c6dba7e6c0dca1e27c3832e873f2b08066175726120448dd10f0000000005617572610101fe734978fa3cb9804346988424124add53316e68e9dcd96a5dfc5a576fe61262031463e0e3a1cdb15538a763dddfbbdf2d3c47e3ecc72deebb3ba5ec59b1168204280402000bc0e95ebf720100";
+        let update_block = "0xf157283bcfe5ace5f3258bdb595ee8c6761394a56c8e73b6aaf734e6fb1e7c92426203000ad92ba15285e38e29472d35c29a8e0097e0748fa66fca1b4c834e13f0604de6f7e776ac0632a86d967e1fc4694d51b15c06dadf6c2d0f60a0c661993ffa6d5308066175726120458dd10f00000000056175726101019c9a0a6afd95ff9b8a479bab6676867d19f388b187534394661f0b9ca540b86cd5847174d8b1075f61c01f3b0f5dfa8c643b15c226ebace6aa5aca43cd12ce8504280402000b30015fbf720100";
+        let authority_set =
+            "0x0488dc3417d5058ec4b4503e0c12ea1a0a89be200fe98922423d4334014fa6b0ee0100000000000000";
+
+        let init_msg = InitMsg {
+            client_id: "first".to_string(),
+            name: "testtesttest".into(),
+            block: init_block.to_string(),
+            authority_set: authority_set.to_string(),
+            max_headers_allowed_to_store: 256,
+            set_id: 1,
+            max_headers_allowed_between_justifications: 2,
+            justification: None,
+            authority_set_proof: None,
+            finality_gadget: None,
+            beefy_validator_set: None,
+            max_consensus_states_to_store: None,
+            retention_policy: None,
+            justification_verification: None,
+            snapshot: None,
+        };
+        let result = init(&mut extern_dep, Env::default(), init_msg);
+        assert!(result.is_ok());
+
+        let create_msg = |client_id: &str| HandleMsg::CreateClient {
+            client_id: client_id.to_string(),
+            name: "anothertest".into(),
+            block: init_block.to_string(),
+            authority_set: authority_set.to_string(),
+            max_headers_allowed_to_store: 256,
+            set_id: 1,
+            max_headers_allowed_between_justifications: 2,
+            justification: None,
+            authority_set_proof: None,
+            finality_gadget: None,
+            beefy_validator_set: None,
+            max_consensus_states_to_store: None,
+            retention_policy: None,
+            justification_verification: None,
+        };
+        let result = handle(&mut extern_dep, Env::default(), create_msg("first"));
+        assert_eq!(
+            result.err().unwrap(),
+            StdError::GenericErr {
+                msg: "Client first already exists".to_string(),
+                backtrace: None,
+            }
+        );
+        let result = handle(&mut extern_dep, Env::default(), create_msg("invalid id"));
+        assert!(result.is_err());
+        let result = handle(&mut extern_dep, Env::default(), create_msg("second"));
+        assert!(result.is_ok());
+
+        // Updating second client doesn't affect the first one
+        let update_msg = HandleMsg::UpdateClient {
+            client_id: "second".to_string(),
+            block: update_block.to_string(),
+            authority_set: authority_set.to_string(),
+        };
+        let result = handle(&mut extern_dep, Env::default(), update_msg);
+        assert!(result.is_ok());
+
+        let latest_height = |client_id: &str| -> LatestHeightResponse {
+            let result = query(
+                &extern_dep,
+                QueryMsg::LatestHeight {
+                    client_id: client_id.to_string(),
+                },
+            );
+            from_binary(&result.unwrap()).expect("Deserializing Query response failed")
+        };
+        assert_eq!(latest_height("first").best_header_height, 55439);
+        assert_eq!(latest_height("second").best_header_height, 55440);
+
+        let first_state = load_contract_state(&extern_dep.storage, "first").unwrap();
+        let second_state = load_contract_state(&extern_dep.storage, "second").unwrap();
+        assert_eq!(first_state.name, "testtesttest");
+        assert_eq!(first_state.headers_ingested_after_last_justification, 1);
+        assert_eq!(second_state.name, "anothertest");
+        assert_eq!(second_state.headers_ingested_after_last_justification, 2);
+
+        // Second client has already ingested max headers allowed without justification
+        let update_msg = |client_id: &str| HandleMsg::UpdateClient {
+            client_id: client_id.to_string(),
+            block: update_block.to_string(),
+            authority_set: authority_set.to_string(),
+        };
+        assert!(handle(&mut extern_dep, Env::default(), update_msg("second")).is_err());
+        assert!(handle(&mut extern_dep, Env::default(), update_msg("first")).is_ok());
+
+        let result = query(
+            &extern_dep,
+            QueryMsg::LatestHeight {
+                client_id: "third".to_string(),
+            },
+        );
+        assert_eq!(
+            result.err().unwrap(),
+            StdError::GenericErr {
+                msg: "Client third doesn't exist".to_string(),
+                backtrace: None,
+            }
+        );
+    }
+
+    #[test]
+    fn test_beefy_client() {
+        let storage = MockStorage::new();
+        let api = MockApi::new(5);
+        let querier = MockQuerier::default();
+        let mut extern_dep = Extern {
+            storage,
+            api,
+            querier,
+        };
+        let init_block = "0x5e9fc49076803d0ba88c719252ede5ae713d09367162d344e9b79ef3aac2efa03e620300fe518cc595e8f5ede8010cf6d26352f6a089ee52f992153a540c7b5d9b659ea272c9c1e535cf5ca49ab2d72059671d80f69c6dba7e6c0dca1e27c3832e873f2b08066175726120448dd10f0000000005617572610101fe734978fa3cb9804346988424124add53316e68e9dcd96a5dfc5a576fe61262031463e0e3a1cdb15538a763dddfbbdf2d3c47e3ecc72deebb3ba5ec59b1168204280402000bc0e95ebf720100";
+        let update_block = "0xf157283bcfe5ace5f3258bdb595ee8c6761394a56c8e73b6aaf734e6fb1e7c92426203000ad92ba15285e38e29472d35c29a8e0097e0748fa66fca1b4c834e13f0604de6f7e776ac0632a86d967e1fc4694d51b15c06dadf6c2d0f60a0c661993ffa6d5308066175726120458dd10f00000000056175726101019c9a0a6afd95ff9b8a479bab6676867d19f388b187534394661f0b9ca540b86cd5847174d8b1075f61c01f3b0f5dfa8c643b15c226ebace6aa5aca43cd12ce8504280402000b30015fbf720100";
+        let authority_set =
+            "0x0488dc3417d5058ec4b4503e0c12ea1a0a89be200fe98922423d4334014fa6b0ee0100000000000000";
+
+        let keys = validator_keys(4);
+        let beefy_set = validator_set(0, &keys);
+        let create_msg =
+            |client_id: &str, finality_gadget, beefy_validator_set| HandleMsg::CreateClient {
+                client_id: client_id.to_string(),
+                name: "testtesttest".into(),
+                block: init_block.to_string(),
+                authority_set: authority_set.to_string(),
+                max_headers_allowed_to_store: 256,
+                set_id: 1,
+                max_headers_allowed_between_justifications: 2,
+                justification: None,
+                authority_set_proof: None,
+                finality_gadget,
+                beefy_validator_set,
+                max_consensus_states_to_store: None,
+                retention_policy: None,
+                justification_verification: None,
+            };
+        let beefy_set_info = BeefyValidatorSetInfo {
+            id: beefy_set.id,
+            len: beefy_set.len,
+            root: format!("{:?}", beefy_set.root),
+        };
+
+        let result = handle(
+            &mut extern_dep,
+            Env::default(),
+            create_msg("beefy", Some(FinalityGadget::Beefy), None),
+        );
+        assert!(result.is_err());
+        let result = handle(
+            &mut extern_dep,
+            Env::default(),
+            create_msg("grandpa", None, Some(beefy_set_info.clone())),
+        );
+        assert!(result.is_err());
+        let result = handle(
+            &mut extern_dep,
+            Env::default(),
+            create_msg("grandpa", None, None),
+        );
+        assert!(result.is_ok());
+        let result = handle(
+            &mut extern_dep,
+            Env::default(),
+            create_msg("beefy", Some(FinalityGadget::Beefy), Some(beefy_set_info)),
+        );
+        assert!(result.is_ok());
+
+        let header = SignedBlock::decode(&mut hex::decode(&update_block[2..]).unwrap().as_slice())
+            .unwrap()
+            .block
+            .header;
+        let next_beefy_set = validator_set(1, &keys);
+        let finality_proof =
+            create_finality_proof(&header, 0, &keys, &[0, 2, 3], next_beefy_set.clone());
+        let update_msg = |client_id: &str| HandleMsg::UpdateBeefy {
+            client_id: client_id.to_string(),
+            header: "0x".to_string() + encode(header.encode()).as_ref(),
+            finality_proof: Binary(finality_proof.encode()).to_base64(),
+        };
+
+        let result = handle(&mut extern_dep, Env::default(), update_msg("grandpa"));
+        assert_eq!(
+            result.err().unwrap(),
+            StdError::GenericErr {
+                msg: "Client grandpa finalizes headers with Grandpa, not Beefy".to_string(),
+                backtrace: None,
+            }
+        );
+
+        let result = handle(&mut extern_dep, Env::default(), update_msg("beefy")).unwrap();
+        let response: UpdateClientResponse = from_binary(&result.data.unwrap()).unwrap();
+        assert_eq!(response.height, header.number);
+        assert!(response.finalized);
+        assert_eq!(response.last_finalized_header_height, header.number);
+        // Announced validator set becomes the current one once it signs a commitment
+        assert!(!response.authority_set_enacted);
+        assert_eq!(response.set_id, 0);
+        assert_eq!(response.headers_ingested_after_last_justification, 0);
+        let finality_proof =
+            create_finality_proof(&header, 1, &keys, &[0, 1, 2], Default::default());
+        let result = handle(
+            &mut extern_dep,
+            Env::default(),
+            HandleMsg::UpdateBeefy {
+                client_id: "beefy".to_string(),
+                header: "0x".to_string() + encode(header.encode()).as_ref(),
+                finality_proof: Binary(finality_proof.encode()).to_base64(),
+            },
+        )
+        .unwrap();
+        let response: UpdateClientResponse = from_binary(&result.data.unwrap()).unwrap();
+        assert!(response.authority_set_enacted);
+        assert_eq!(response.set_id, next_beefy_set.id);
+
+        // Proof signed by the previous validator set is rejected
+        let result = handle(&mut extern_dep, Env::default(), update_msg("beefy"));
+        assert!(result
+            .err()
+            .unwrap()
+            .to_string()
+            .contains("[205] Unable to ingest header"));
+    }
+
+    #[test]
+    fn test_snapshot() {
+        let new_extern = || Extern {
+            storage: MockStorage::new(),
+            api: MockApi::new(5),
+            querier: MockQuerier::default(),
+        };
+        let mut extern_dep = new_extern();
+        let keys = validator_keys(4);
+        let headers = relay_headers(4);
+        let init_msg = beefy_init_msg("relay", &headers[0], &keys);
+        assert!(init(&mut extern_dep, Env::default(), init_msg).is_ok());
+        let result = handle(
+            &mut extern_dep,
+            Env::default(),
+            beefy_update_msg("relay", &headers[1], &keys, &[0, 1, 2]),
+        );
+        assert!(result.is_ok());
+        let result = handle(
+            &mut extern_dep,
+            Env::default(),
+            HandleMsg::UpdateHeader {
+                client_id: "relay".to_string(),
+                header: "0x".to_string() + encode(headers[2].encode()).as_ref(),
+                justification: None,
+            },
+        );
+        assert!(result.is_ok());
+
+        let result = query(
+            &extern_dep,
+            QueryMsg::ExportSnapshot {
+                client_id: "relay".to_string(),
+            },
+        );
+        let snapshot = from_binary::<ExportSnapshotResponse>(&result.unwrap())
+            .unwrap()
+            .snapshot;
+
+        // Snapshot is restored both under another id and by another contract
+        let restore_msg = |client_id: &str, snapshot: &str| HandleMsg::RestoreClient {
+            client_id: client_id.to_string(),
+            snapshot: snapshot.to_string(),
+        };
+        let result = handle(
+            &mut extern_dep,
+            Env::default(),
+            restore_msg("relay", &snapshot),
+        );
+        assert_eq!(
+            result.err().unwrap(),
+            StdError::GenericErr {
+                msg: "Client relay already exists".to_string(),
+                backtrace: None,
+            }
+        );
+        let result = handle(
+            &mut extern_dep,
+            Env::default(),
+            restore_msg("copy", &snapshot),
+        );
+        assert!(result.is_ok());
+        let mut other_extern_dep = new_extern();
+        let init_msg = InitMsg {
+            client_id: "relay".to_string(),
+            snapshot: Some(snapshot.clone()),
+            name: String::new(),
+            block: String::new(),
+            set_id: 0,
+            authority_set: String::new(),
+            max_headers_allowed_to_store: 0,
+            max_headers_allowed_between_justifications: 0,
+            justification: None,
+            authority_set_proof: None,
+            finality_gadget: None,
+            beefy_validator_set: None,
+            max_consensus_states_to_store: None,
+            retention_policy: None,
+            justification_verification: None,
+        };
+        assert!(init(&mut other_extern_dep, Env::default(), init_msg).is_ok());
+
+        let client_view = |extern_dep: &Extern<_, _, _>, client_id: &str| {
+            let latest_height: LatestHeightResponse = from_binary(
+                &query(
+                    extern_dep,
+                    QueryMsg::LatestHeight {
+                        client_id: client_id.to_string(),
+                    },
+                )
+                .unwrap(),
+            )
+            .unwrap();
+            let storage_window: StorageWindowResponse = from_binary(
+                &query(
+                    extern_dep,
+                    QueryMsg::StorageWindow {
+                        client_id: client_id.to_string(),
+                    },
+                )
+                .unwrap(),
+            )
+            .unwrap();
+            let headers: HeadersResponse = from_binary(
+                &query(
+                    extern_dep,
+                    QueryMsg::Headers {
+                        client_id: client_id.to_string(),
+                        start_height: None,
+                        limit: None,
+                        reverse: None,
+                    },
+                )
+                .unwrap(),
+            )
+            .unwrap();
+            (latest_height, storage_window, headers)
+        };
+        let original = client_view(&extern_dep, "relay");
+        assert_eq!(original.0.best_header_height, 3);
+        assert_eq!(original.1.last_finalized_height, Some(2));
+        assert!(original == client_view(&extern_dep, "copy"));
+        assert!(original == client_view(&other_extern_dep, "relay"));
+
+        // Restored client carries on from where the original one stopped
+        let result = handle(
+            &mut other_extern_dep,
+            Env::default(),
+            beefy_update_msg("relay", &headers[3], &keys, &[0, 1, 2]),
+        )
+        .unwrap();
+        let response: UpdateClientResponse = from_binary(&result.data.unwrap()).unwrap();
+        assert_eq!(response.height, 4);
+        assert!(response.finalized);
+
+        let result = handle(
+            &mut extern_dep,
+            Env::default(),
+            restore_msg(
+                "tampered",
+                &(snapshot[..snapshot.len() - 2].to_string() + "00"),
+            ),
+        );
+        assert_eq!(
+            result.err().unwrap(),
+            StdError::ParseErr {
+                target: "snapshot".to_string(),
+                msg: "Snapshot doesn't match its integrity hash".to_string(),
+                backtrace: None,
+            }
+        );
+        let result = handle(
+            &mut extern_dep,
+            Env::default(),
+            restore_msg("versioned", &("0x02".to_string() + &snapshot[4..])),
+        );
+        assert_eq!(
+            result.err().unwrap(),
+            StdError::ParseErr {
+                target: "snapshot".to_string(),
+                msg: "Unsupported snapshot version 2, expected 1".to_string(),
+                backtrace: None,
+            }
+        );
+    }
+
+    #[test]
+    fn test_consensus_states() {
+        let storage = MockStorage::new();
+        let api = MockApi::new(5);
+        let querier = MockQuerier::default();
+        let mut extern_dep = Extern {
+            storage,
+            api,
+            querier,
+        };
+        let keys = validator_keys(4);
+        let headers = relay_headers(5);
+        let init_msg = InitMsg {
+            max_headers_allowed_between_justifications: 3,
+            max_consensus_states_to_store: Some(2),
+            ..beefy_init_msg("relay", &headers[0], &keys)
+        };
+        assert!(init(&mut extern_dep, Env::default(), init_msg).is_ok());
+        let env_at = |height: u64| {
+            let mut env = Env::default();
+            env.block.height = height;
+            env.block.time = height * 5;
+            env
+        };
+        let finalize = |extern_dep: &mut Extern<_, _, _>, header: &Header, env| {
+            let update_msg = beefy_update_msg("relay", header, &keys, &[0, 1, 2]);
+            assert!(handle(extern_dep, env, update_msg).is_ok());
+        };
+        let consensus_state = |extern_dep: &Extern<_, _, _>, height| {
+            query(
+                extern_dep,
+                QueryMsg::ConsensusState {
+                    client_id: "relay".to_string(),
+                    height,
+                },
+            )
+            .map(|result| from_binary::<ConsensusState>(&result).unwrap())
+        };
+        let consensus_states_from = |extern_dep: &Extern<_, _, _>, start_height, limit, reverse| {
+            let result = query(
+                extern_dep,
+                QueryMsg::ConsensusStates {
+                    client_id: "relay".to_string(),
+                    start_height,
+                    limit,
+                    reverse,
+                },
+            );
+            from_binary::<ConsensusStatesResponse>(&result.unwrap()).unwrap()
+        };
+        let consensus_states = |extern_dep: &Extern<_, _, _>, limit, reverse| {
+            consensus_states_from(extern_dep, None, limit, reverse)
+        };
+
+        // Initial header isn't finalized by creation
+        assert!(consensus_state(&extern_dep, 1).is_err());
+        assert!(consensus_states(&extern_dep, None, None)
+            .consensus_states
+            .is_empty());
+
+        finalize(&mut extern_dep, &headers[1], env_at(10));
+        let heights = |response: ConsensusStatesResponse| {
+            response
+                .consensus_states
+                .iter()
+                .map(|c| c.height)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            heights(consensus_states(&extern_dep, None, None)),
+            vec![1, 2]
+        );
+        assert_eq!(
+            consensus_state(&extern_dep, 2).unwrap(),
+            ConsensusState {
+                height: 2,
+                hash: headers[1].hash().as_bytes().to_vec(),
+                state_root: H256::repeat_byte(2).as_bytes().to_vec(),
+                extrinsics_root: H256::default().as_bytes().to_vec(),
+                processed_time: 50,
+                processed_height: 10,
+            }
+        );
+
+        // Consensus states outside of retention are pruned, though headers are still retained
+        for header in &headers[2..4] {
+            let result = handle(
+                &mut extern_dep,
+                env_at(11),
+                HandleMsg::UpdateHeader {
+                    client_id: "relay".to_string(),
+                    header: "0x".to_string() + encode(header.encode()).as_ref(),
+                    justification: None,
+                },
+            );
+            assert!(result.is_ok());
+        }
+        assert!(consensus_state(&extern_dep, 3).is_err());
+        finalize(&mut extern_dep, &headers[4], env_at(12));
+        for height in 1..=3 {
+            assert_eq!(
+                consensus_state(&extern_dep, height).err().unwrap(),
+                StdError::GenericErr {
+                    msg: format!("Client relay has no consensus state at height {}", height),
+                    backtrace: None,
+                }
+            );
+        }
+        assert_eq!(
+            consensus_state(&extern_dep, 4).unwrap().processed_height,
+            12
+        );
+        assert_eq!(
+            heights(consensus_states(&extern_dep, None, None)),
+            vec![4, 5]
+        );
+        let response = consensus_states(&extern_dep, Some(1), Some(true));
+        assert_eq!(response.next_height, Some(4));
+        assert_eq!(heights(response), vec![5]);
+        // Pages skip heights without consensus states
+        let response = consensus_states_from(&extern_dep, Some(1), Some(1), None);
+        assert_eq!(response.next_height, Some(5));
+        assert_eq!(heights(response), vec![4]);
+        let response = consensus_states_from(&extern_dep, Some(100), None, Some(true));
+        assert_eq!(response.next_height, None);
+        assert_eq!(heights(response), vec![5, 4]);
+    }
+
+    #[test]
+    fn test_update_config() {
+        let storage = MockStorage::new();
+        let api = MockApi::new(20);
+        let querier = MockQuerier::default();
+        let mut extern_dep = Extern {
+            storage,
+            api,
+            querier,
+        };
+        let keys = validator_keys(4);
+        let headers = relay_headers(5);
+        let init_msg = InitMsg {
+            max_headers_allowed_between_justifications: 3,
+            ..beefy_init_msg("relay", &headers[0], &keys)
+        };
+        let creator_env = mock_env(&extern_dep.api, "creator", &[]);
+        assert!(init(&mut extern_dep, creator_env.clone(), init_msg).is_ok());
+        for header in &headers[1..] {
+            let update_msg = beefy_update_msg("relay", header, &keys, &[0, 1, 2]);
+            assert!(handle(&mut extern_dep, creator_env.clone(), update_msg).is_ok());
+        }
+
+        let config = |extern_dep: &Extern<_, _, _>| {
+            let result = query(
+                extern_dep,
+                QueryMsg::Config {
+                    client_id: "relay".to_string(),
+                },
+            );
+            from_binary::<ConfigResponse>(&result.unwrap()).unwrap()
+        };
+        let update_msg = || HandleMsg::UpdateConfig {
+            client_id: "relay".to_string(),
+            max_headers_allowed_to_store: None,
+            max_headers_allowed_between_justifications: None,
+            max_consensus_states_to_store: None,
+            new_admin: None,
+            renounce_admin: false,
+        };
+        let with_limits = |max_headers_allowed_to_store, max_consensus_states_to_store| {
+            let mut update_msg = update_msg();
+            if let HandleMsg::UpdateConfig {
+                max_headers_allowed_to_store: headers_limit,
+                max_consensus_states_to_store: consensus_states_limit,
+                ..
+            } = &mut update_msg
+            {
+                *headers_limit = max_headers_allowed_to_store;
+                *consensus_states_limit = max_consensus_states_to_store;
+            }
+            update_msg
+        };
+        let with_admin = |new_admin: Option<&str>, renounce_admin| {
+            let mut update_msg = update_msg();
+            if let HandleMsg::UpdateConfig {
+                new_admin: admin,
+                renounce_admin: renounce,
+                ..
+            } = &mut update_msg
+            {
+                *admin = new_admin.map(HumanAddr::from);
+                *renounce = renounce_admin;
+            }
+            update_msg
+        };
+
+        // Admin is the creator of the client
+        assert_eq!(
+            config(&extern_dep),
+            ConfigResponse {
+                admin: Some(HumanAddr::from("creator")),
+                max_headers_allowed_to_store: 256,
+                max_headers_allowed_between_justifications: 3,
+                max_consensus_states_to_store: 1000,
+                finality_gadget: FinalityGadget::Beefy,
+                relayer_allowlist: None,
+            }
+        );
+        let stranger_env = mock_env(&extern_dep.api, "stranger", &[]);
+        match handle(
+            &mut extern_dep,
+            stranger_env.clone(),
+            with_limits(Some(2), None),
+        )
+        .err()
+        .unwrap()
+        {
+            StdError::Unauthorized { .. } => {}
+            e => panic!("Unexpected error: {}", e),
+        }
+
+        // Shrinking limits prunes headers and consensus states right away
+        let result = handle(
+            &mut extern_dep,
+            creator_env.clone(),
+            with_limits(Some(2), Some(2)),
+        );
+        assert!(result.is_ok());
+        let result = query(
+            &extern_dep,
+            QueryMsg::StorageWindow {
+                client_id: "relay".to_string(),
+            },
+        );
+        let storage_window_response: StorageWindowResponse =
+            from_binary(&result.unwrap()).expect("Deserializing Query response failed");
+        assert_eq!(
+            storage_window_response,
+            StorageWindowResponse {
+                oldest_height: 4,
+                newest_height: 5,
+                last_finalized_height: Some(5),
+                total_stored: 2,
+                capacity: 2,
+                kept_heights: vec![],
+            }
+        );
+        let result = query(
+            &extern_dep,
+            QueryMsg::ConsensusStates {
+                client_id: "relay".to_string(),
+                start_height: None,
+                limit: None,
+                reverse: None,
+            },
+        );
+        let consensus_states_response: ConsensusStatesResponse =
+            from_binary(&result.unwrap()).expect("Deserializing Query response failed");
+        assert_eq!(
+            consensus_states_response
+                .consensus_states
+                .iter()
+                .map(|c| c.height)
+                .collect::<Vec<_>>(),
+            vec![4, 5]
+        );
+        let result = query(
+            &extern_dep,
+            QueryMsg::ConsensusState {
+                client_id: "relay".to_string(),
+                height: 3,
+            },
+        );
+        assert!(result.is_err());
+
+        // Limits are validated
+        assert!(handle(
+            &mut extern_dep,
+            creator_env.clone(),
+            with_limits(Some(1), None)
+        )
+        .is_err());
+        assert!(handle(
+            &mut extern_dep,
+            creator_env.clone(),
+            with_limits(None, Some(0))
+        )
+        .is_err());
+        let mut update_msg = update_msg();
+        if let HandleMsg::UpdateConfig {
+            max_headers_allowed_between_justifications,
+            ..
+        } = &mut update_msg
+        {
+            *max_headers_allowed_between_justifications = Some(1);
+        }
+        assert!(handle(&mut extern_dep, creator_env.clone(), update_msg).is_ok());
+        assert_eq!(
+            config(&extern_dep),
+            ConfigResponse {
+                admin: Some(HumanAddr::from("creator")),
+                max_headers_allowed_to_store: 2,
+                max_headers_allowed_between_justifications: 1,
+                max_consensus_states_to_store: 2,
+                finality_gadget: FinalityGadget::Beefy,
+                relayer_allowlist: None,
+            }
+        );
+
+        // Ownership can be transferred and renounced
+        assert!(handle(
+            &mut extern_dep,
+            creator_env.clone(),
+            with_admin(Some("stranger"), true)
+        )
+        .is_err());
+        assert!(handle(
+            &mut extern_dep,
+            creator_env.clone(),
+            with_admin(Some("stranger"), false)
+        )
+        .is_ok());
+        assert_eq!(config(&extern_dep).admin, Some(HumanAddr::from("stranger")));
+        assert!(handle(&mut extern_dep, creator_env, with_limits(Some(3), None)).is_err());
+        assert!(handle(
+            &mut extern_dep,
+            stranger_env.clone(),
+            with_admin(None, true)
+        )
+        .is_ok());
+        assert_eq!(config(&extern_dep).admin, None);
+        assert!(handle(&mut extern_dep, stranger_env, with_limits(Some(3), None)).is_err());
+    }
 
     #[test]
-    fn test_contract_init_and_update() {
+    fn test_relayers() {
         let storage = MockStorage::new();
-        let api = MockApi::new(5);
+        let api = MockApi::new(20);
         let querier = MockQuerier::default();
         let mut extern_dep = Extern {
             storage,
             api,
             querier,
         };
+        let keys = validator_keys(4);
+        let headers = relay_headers(5);
+        let init_msg = InitMsg {
+            max_headers_allowed_between_justifications: 3,
+            ..beefy_init_msg("relay", &headers[0], &keys)
+        };
+        let creator_env = mock_env(&extern_dep.api, "creator", &[]);
+        let relayer_env = mock_env(&extern_dep.api, "relayer", &[]);
+        let stranger_env = mock_env(&extern_dep.api, "stranger", &[]);
+        assert!(init(&mut extern_dep, creator_env.clone(), init_msg).is_ok());
 
-        let init_msg = InitMsg{
-            name: "testtesttest".into(),
-            block: "0x5e9fc49076803d0ba88c719252ede5ae713d09367162d344e9b79ef3aac2efa03e620300fe518cc595e8f5ede8010cf6d26352f6a089ee52f992153a540c7b5d9b659ea272c9c1e535cf5ca49ab2d72059671d80f69c6dba7e6c0dca1e27c3832e873f2b08066175726120448dd10f0000000005617572610101fe734978fa3cb9804346988424124add53316e68e9dcd96a5dfc5a576fe61262031463e0e3a1cdb15538a763dddfbbdf2d3c47e3ecc72deebb3ba5ec59b1168204280402000bc0e95ebf720100".into(),
-            authority_set: "0x0488dc3417d5058ec4b4503e0c12ea1a0a89be200fe98922423d4334014fa6b0ee0100000000000000".to_string(),
-            max_headers_allowed_to_store: 256,
-            set_id: 1,
-            max_headers_allowed_between_justifications: 2
+        let beefy_msg =
+            |header: &Header, signers: &[usize]| beefy_update_msg("relay", header, &keys, signers);
+        let header_msg = |header: &Header| HandleMsg::UpdateHeader {
+            client_id: "relay".to_string(),
+            header: "0x".to_string() + encode(header.encode()).as_ref(),
+            justification: None,
+        };
+        let relayers_msg =
+            |allowlist_enabled, add: &[&str], remove: &[&str]| HandleMsg::UpdateRelayers {
+                client_id: "relay".to_string(),
+                allowlist_enabled,
+                add: add.iter().map(|r| HumanAddr::from(*r)).collect(),
+                remove: remove.iter().map(|r| HumanAddr::from(*r)).collect(),
+            };
+        let stats = |extern_dep: &Extern<_, _, _>, relayer: &str| {
+            let result = query(
+                extern_dep,
+                QueryMsg::RelayerStats {
+                    client_id: "relay".to_string(),
+                    relayer: HumanAddr::from(relayer),
+                },
+            );
+            from_binary::<RelayerStats>(&result.unwrap()).unwrap()
+        };
+        let allowlist = |extern_dep: &Extern<_, _, _>| {
+            let result = query(
+                extern_dep,
+                QueryMsg::Config {
+                    client_id: "relay".to_string(),
+                },
+            );
+            from_binary::<ConfigResponse>(&result.unwrap())
+                .unwrap()
+                .relayer_allowlist
         };
-        let init_header_hash =
-            hex::decode("f157283bcfe5ace5f3258bdb595ee8c6761394a56c8e73b6aaf734e6fb1e7c92")
-                .expect("Hex decoding of init header hash failed");
-        let init_header_number: u32 = 55439;
-        let init_authority_set = "LightAuthoritySet { set_id: 1, authorities: [(Public(88dc3417d5058ec4b4503e0c12ea1a0a89be200fe98922423d4334014fa6b0ee (5FA9nQDV...)), 1)] }";
-        let result = init(&mut extern_dep, Env::default(), init_msg);
-        assert!(result.is_ok());
 
-        let contract_state = read_only_contract_state(&extern_dep.storage)
-            .load()
-            .expect("Contract state should exists");
-        assert_eq!(contract_state.name, "testtesttest");
-        assert!(contract_state.light_client_data.len() > 0);
+        // Without allowlist anyone can update client, and only processed
+        // submissions are recorded, as rejected ones revert
+        assert!(handle(
+            &mut extern_dep,
+            stranger_env.clone(),
+            header_msg(&headers[1])
+        )
+        .is_ok());
+        assert!(handle(
+            &mut extern_dep,
+            stranger_env.clone(),
+            beefy_msg(&headers[2], &[0])
+        )
+        .is_err());
+        assert_eq!(
+            stats(&extern_dep, "stranger"),
+            RelayerStats {
+                accepted_headers: 1,
+                ..Default::default()
+            }
+        );
 
-        let result = query(&extern_dep, QueryMsg::LatestHeight {});
-        assert!(result.is_ok());
-        let query_response: LatestHeightResponse =
-            from_binary(&result.unwrap()).expect("Deserializing Query response failed");
-        assert_eq!(query_response.last_finalized_header_hash.len(), 0);
-        assert_eq!(query_response.best_header_hash, init_header_hash);
-        assert_eq!(query_response.best_header_height, init_header_number);
-        assert_eq!(query_response.current_authority_set, init_authority_set);
+        // Allowlist is managed by admin
+        assert!(handle(
+            &mut extern_dep,
+            relayer_env.clone(),
+            relayers_msg(Some(true), &["relayer"], &[])
+        )
+        .is_err());
+        assert!(handle(
+            &mut extern_dep,
+            creator_env.clone(),
+            relayers_msg(None, &["relayer"], &[])
+        )
+        .is_err());
+        assert!(handle(
+            &mut extern_dep,
+            creator_env.clone(),
+            relayers_msg(Some(true), &["relayer", "stranger"], &["stranger"])
+        )
+        .is_ok());
+        assert_eq!(
+            allowlist(&extern_dep),
+            Some(vec![HumanAddr::from("relayer")])
+        );
 
-        let update_msg = HandleMsg::UpdateClient {
-            block: "0xf157283bcfe5ace5f3258bdb595ee8c6761394a56c8e73b6aaf734e6fb1e7c92426203000ad92ba15285e38e29472d35c29a8e0097e0748fa66fca1b4c834e13f0604de6f7e776ac0632a86d967e1fc4694d51b15c06dadf6c2d0f60a0c661993ffa6d5308066175726120458dd10f00000000056175726101019c9a0a6afd95ff9b8a479bab6676867d19f388b187534394661f0b9ca540b86cd5847174d8b1075f61c01f3b0f5dfa8c643b15c226ebace6aa5aca43cd12ce8504280402000b30015fbf720100".to_string(),
-            authority_set: "0x0488dc3417d5058ec4b4503e0c12ea1a0a89be200fe98922423d4334014fa6b0ee0100000000000000".to_string(),
-        };
-        let next_header_hash =
-            hex::decode("b17ad1a298edb7fa902ce240358ced980a1a1f9febe163152be5e66c377fa38c")
-                .expect("Hex decoding of next header hash failed");
-        let next_header_number = init_header_number + 1;
-        let next_authority_set = init_authority_set;
-        let result = handle(&mut extern_dep, Env::default(), update_msg);
-        assert!(result.is_ok());
+        // Relayers out of allowlist can't update client
+        match handle(
+            &mut extern_dep,
+            stranger_env.clone(),
+            header_msg(&headers[2]),
+        )
+        .err()
+        .unwrap()
+        {
+            StdError::Unauthorized { .. } => {}
+            e => panic!("Unexpected error: {}", e),
+        }
 
-        let contract_state = read_only_contract_state(&extern_dep.storage)
-            .load()
-            .expect("Contract state should exists");
-        assert_eq!(contract_state.name, "testtesttest");
-        assert!(contract_state.light_client_data.len() > 0);
+        // Submissions rejected by the light client fail, just like without allowlist
+        let result = handle(
+            &mut extern_dep,
+            relayer_env.clone(),
+            beefy_msg(&headers[2], &[0]),
+        );
+        assert!(result
+            .err()
+            .unwrap()
+            .to_string()
+            .contains("Unable to ingest header"));
+        assert!(handle(
+            &mut extern_dep,
+            relayer_env.clone(),
+            beefy_msg(&headers[2], &[0, 1, 2])
+        )
+        .is_ok());
+        assert!(handle(
+            &mut extern_dep,
+            relayer_env.clone(),
+            header_msg(&headers[3])
+        )
+        .is_ok());
+        // Submission which doesn't change the client is recorded as ineffective
+        assert!(handle(
+            &mut extern_dep,
+            relayer_env.clone(),
+            beefy_msg(&headers[2], &[0, 1, 2])
+        )
+        .is_ok());
+        assert_eq!(
+            stats(&extern_dep, "relayer"),
+            RelayerStats {
+                accepted_headers: 2,
+                ineffective_headers: 1,
+                accepted_justifications: 1,
+                ineffective_justifications: 1,
+            }
+        );
+        assert_eq!(stats(&extern_dep, "creator"), RelayerStats::default());
 
-        let result = query(&extern_dep, QueryMsg::LatestHeight {});
-        assert!(result.is_ok());
-        let query_response: LatestHeightResponse =
-            from_binary(&result.unwrap()).expect("Deserializing Query response failed");
-        assert_eq!(query_response.last_finalized_header_hash.len(), 0);
-        assert_eq!(query_response.best_header_hash, next_header_hash);
-        assert_eq!(query_response.best_header_height, next_header_number);
-        assert_eq!(query_response.current_authority_set, next_authority_set);
+        // Allowlist can be turned off
+        assert!(handle(
+            &mut extern_dep,
+            creator_env,
+            relayers_msg(Some(false), &[], &[])
+        )
+        .is_ok());
+        assert_eq!(allowlist(&extern_dep), None);
+        assert!(handle(&mut extern_dep, stranger_env, header_msg(&headers[4])).is_ok());
+        assert_eq!(stats(&extern_dep, "stranger").accepted_headers, 2);
+    }
 
-        let update_msg = HandleMsg::UpdateClient {
-            block: "0xf157283bcfe5ace5f3258bdb595ee8c6761394a56c8e73b6aaf734e6fb1e7c92426203000ad92ba15285e38e29472d35c29a8e0097e0748fa66fca1b4c834e13f0604de6f7e776ac0632a86d967e1fc4694d51b15c06dadf6c2d0f60a0c661993ffa6d5308066175726120458dd10f00000000056175726101019c9a0a6afd95ff9b8a479bab6676867d19f388b187534394661f0b9ca540b86cd5847174d8b1075f61c01f3b0f5dfa8c643b15c226ebace6aa5aca43cd12ce8504280402000b30015fbf720100".to_string(),
-            authority_set: "0x0488dc3417d5058ec4b4503e0c12ea1a0a89be200fe98922423d4334014fa6b0ee0100000000000000".to_string(),
+    #[test]
+    fn test_parachain_client() {
+        let storage = MockStorage::new();
+        let api = MockApi::new(5);
+        let querier = MockQuerier::default();
+        let mut extern_dep = Extern {
+            storage,
+            api,
+            querier,
         };
-        // This wil fail as at max only two headers are allowed between justification
-        let result = handle(&mut extern_dep, Env::default(), update_msg);
+        let para_id = 2000;
+
+        let state_with = |key: &[u8], value: &[u8]| -> (H256, Vec<String>) {
+            let mut db = MemoryDB::<BlakeTwo256>::default();
+            let mut root = H256::default();
+            {
+                let mut trie = TrieDBMut::<BlakeTwo256>::new(&mut db, &mut root);
+                trie.insert(key, value).unwrap();
+                trie.insert(b":code", &[1, 2, 3]).unwrap();
+            }
+            let proof = db
+                .drain()
+                .into_iter()
+                .map(|(_, (node, _))| "0x".to_string() + encode(node).as_ref())
+                .collect();
+            (root, proof)
+        };
+        let (parachain_state_root, parachain_proof) = state_with(b"balance", &[42]);
+        let parachain_header = Header::new(
+            5,
+            Default::default(),
+            parachain_state_root,
+            H256::repeat_byte(5),
+            Default::default(),
+        );
+        let (relay_state_root, relay_proof) = state_with(
+            &paras_heads_key(para_id),
+            &parachain_header.encode().encode(),
+        );
+
+        // Relay chain client finalizes header holding the parachain head with BEEFY
+        let keys = validator_keys(4);
+        let relay_init_header = Header::new(
+            1,
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+        );
+        let relay_header = Header::new(
+            2,
+            Default::default(),
+            relay_state_root,
+            relay_init_header.hash(),
+            Default::default(),
+        );
+        let init_msg = beefy_init_msg("relay", &relay_init_header, &keys);
+        assert!(init(&mut extern_dep, Env::default(), init_msg).is_ok());
+        let update_msg = beefy_update_msg("relay", &relay_header, &keys, &[0, 1, 2]);
+        assert!(handle(&mut extern_dep, Env::default(), update_msg).is_ok());
+
+        let create_msg =
+            |client_id: &str, relay_client_id: &str| HandleMsg::CreateParachainClient {
+                client_id: client_id.to_string(),
+                name: "parachaintest".into(),
+                relay_client_id: relay_client_id.to_string(),
+                para_id,
+            };
+        let result = handle(
+            &mut extern_dep,
+            Env::default(),
+            create_msg("para", "unknown"),
+        );
         assert!(result.is_err());
+        let result = handle(
+            &mut extern_dep,
+            Env::default(),
+            create_msg("relay", "relay"),
+        );
         assert_eq!(
             result.err().unwrap(),
             StdError::GenericErr {
-                msg: "Cannot ingest header without justification. Max headers allowed: 2, Already ingested: 2".to_string(),
+                msg: "Client relay already exists".to_string(),
                 backtrace: None,
             }
         );
+        let result = handle(&mut extern_dep, Env::default(), create_msg("para", "relay"));
+        assert!(result.is_ok());
+
+        let parachain_head = |extern_dep: &Extern<_, _, _>| -> ParachainHeadResponse {
+            let result = query(
+                extern_dep,
+                QueryMsg::ParachainHead {
+                    client_id: "para".to_string(),
+                },
+            );
+            from_binary(&result.unwrap()).unwrap()
+        };
+        assert_eq!(parachain_head(&extern_dep).head, None);
+
+        let update_msg = |relay_height, header: &Header| HandleMsg::UpdateParachain {
+            client_id: "para".to_string(),
+            relay_height,
+            relay_header_proof: None,
+            header: "0x".to_string() + encode(header.encode()).as_ref(),
+            proof: relay_proof.clone(),
+        };
+
+        // Header doesn't match the head stored at relay chain
+        let mut wrong_header = parachain_header.clone();
+        wrong_header.number = 6;
+        let result = handle(
+            &mut extern_dep,
+            Env::default(),
+            update_msg(2, &wrong_header),
+        );
+        assert!(result
+            .err()
+            .unwrap()
+            .to_string()
+            .contains("[213] Unable to verify parachain head"));
+        // Proof doesn't match state root of another relay chain header
+        let result = handle(
+            &mut extern_dep,
+            Env::default(),
+            update_msg(1, &parachain_header),
+        );
+        assert!(result.is_err());
+
+        let result = handle(
+            &mut extern_dep,
+            Env::default(),
+            update_msg(2, &parachain_header),
+        );
+        let response: ParachainHeadResponse = from_binary(&result.unwrap().data.unwrap()).unwrap();
+        assert_eq!(response, parachain_head(&extern_dep));
+        let head = response.head.unwrap();
+        assert_eq!(head.number, 5);
+        assert_eq!(head.hash, parachain_header.hash().as_bytes().to_vec());
+        assert_eq!(head.relay_height, 2);
+
+        // Head can't move backwards or stay in place
+        let result = handle(
+            &mut extern_dep,
+            Env::default(),
+            update_msg(2, &parachain_header),
+        );
+        assert!(result.is_err());
+
+        // Parachain state is verified against state root of the tracked head
+        let storage_query = |key: &[u8]| {
+            query(
+                &extern_dep,
+                QueryMsg::VerifyParachainStorage {
+                    client_id: "para".to_string(),
+                    key: "0x".to_string() + encode(key).as_ref(),
+                    proof: parachain_proof.clone(),
+                },
+            )
+        };
+        let response: VerifyParachainStorageResponse =
+            from_binary(&storage_query(b"balance").unwrap()).unwrap();
+        assert_eq!(response.value, Some("0x2a".to_string()));
+        let response: VerifyParachainStorageResponse =
+            from_binary(&storage_query(b"missing").unwrap()).unwrap();
+        assert_eq!(response.value, None);
     }
 
-    /// The block and justification data here are from Kusama (websocket url: wss://kusama-rpc.polkadot.io/)
     #[test]
-    fn test_contract_init_and_update_with_justification() {
-        let encoded_versioned_authority_list = "017905f45066c35babc6ba27b7ca056df70f04dc877dbcc0bd9ac8c9d82d56856b15000100000000000000fa831eba1c67bc9cb97e75feaf03a739a3aaa8c117e4fa59c345be08600ae1d30100000000000000381bb2f0ef2a9384e93fe56774bd6417277dc3a5a76d15ac55bfcfdd0f907787010000000000000036c3ba9b05f6814e0de9a3ab1e108141416c95d784122b76ee52e3b0e8c86aba01000000000000008f3fb12a535077f6c94870193fded721b054cf633ff4d29c6711ec52167f63ac0100000000000000105452ff0261a21edc823a2d494ba2d1eb1b326375b8019c93b1ae79a4fa65240100000000000000baff92b53651ee2bade8d835e16b9285175db0f478c73c4f15167941ba3ea6350100000000000000f888102b2e5195efc88308bd13c581fefa7e3c8aeb0b4412bcf3dea4b607c9eb010000000000000073213e29b78192bfc1b1213ddcf88e7b8f046335e4b55d598a6e05ea8bf8bd1d01000000000000004000fa80428185da7fe6ee159d305f9cd32fd116f14ecce4cef35e2cef745191010000000000000024a1be39c97ef5992f94efcb0f4511801a5abdeb3e813fb464d43eb31bc925c9010000000000000053198b1b024cfde1fd068b8f6d7712517eb13c3e3416d01c2cba50409d4ca0fa01000000000000001d39bb34a39491970775350c5d5f87cea4eb9cc6fe14783b5d84cd7d47eaafdc01000000000000002e779c8535a7689ddf4389240060cedd9e9a3c559eadc572e3b797d8c49ac79901000000000000001057c0fc416d3f60a00bc7f87bd55f0ceb27fdd3f3af1bd5864b159c1adba39a0100000000000000b6deccb07c975c85ee8c2437dc3ad2184bc69494faf34741b1c3c79cefba5e3c0100000000000000ce5498f545700388a50b77d713fbb8a2c684cc73b9e41bbe988facb6217ccbce0100000000000000d8553266d4b46dfed6f9b6c1d75008e19e75538942de4928c697c445d26fcdd50100000000000000ec189cd23dfbcd51661cae853e6dc2733391c4375b75fec40b37ab56bb1a781c01000000000000008cf418512b7d4d2453b0da53b7e9de3e32862164b62511a303d84f04fc334ce4010000000000000093fa19a580853381cf03a0b187d8e576863593ef8c03f56be100ba50e1cb72cc01000000000000003618acc2b02a85775e66fcf72597dbc404bfb54cfc72292ff66d8cafc49df61a0100000000000000942b9ef351d184a1c83a7ddbea3b5e2af6c71486752b66284c07def606bdfad40100000000000000c5cba14eb175337dd7ac5ed02519fcc374f9e5c60b6fdae28cb5eaa2942403b2010000000000000032f8bf0f37b35d9db2a00fba4ca20051eea49ad9d2edd99d5744bd963b2c6f66010000000000000029fc78475997bca058872b83f5961972e9771f78f0fae0dce918e430df83aa2a01000000000000005a1e880ccc2a0615d6655c342ca9c83f06e891bf492766d2a33e5afc8fa667000100000000000000089f8365f265c12c58d6eb95cb58d4e67626dc7ca10ac223424b7624012c1bb501000000000000008916cb4c79579d42bdde503a8ef073c082edd220a5851a75ed23049d033d1c6d01000000000000008d7acd36aedce52ae891b72848aad41cfd32a755d56cb8bf4cc2af7d472ef50f0100000000000000874296ca163e65856a696c94521bb35ff82d1bd34b8c3194ff02a2663c87d9050100000000000000f26c8b40fe23a64d3bf0fbad27ea08edfe070db5f65fb28ec26fa743ad6df6f601000000000000003e05f4e13173752ab99613f6794c3c2d7ad2d57f8ab66a851675ebcb002cd8330100000000000000f5e73f61a88d305f9f88d3efe06e964140f87505ebd5f644a4b92ae52e57971301000000000000007c3c0fbe707bab436497cadb9f6f4f688b0adb05e0c6199554350872da2e28780100000000000000492d9ab341f2f61101ac7c76a7df3125d26bf84467f97e4270faa4b05ca528a001000000000000008a21215143c1f631a129345a440e14c4a335089ef351c361da036b589dd3d90f0100000000000000182da31370a90a76902f45222b29432a72474fe3aafa7c62d368111f6214205c0100000000000000d6426bcfa77ab6050d7a4e4930c934da18913247fa5c3467a6c26ab5867ceaf901000000000000003cfc7d049205605bcb7bc5891454fe91da9a5968ecd8a0380e868813db0691b60100000000000000e6ef360eb109b0e730c734d487480bb5159b1e7edf32dcb2d6724fc33474630601000000000000001df7948b12cb7be4d5d9aee93b74658d0c8444a0c35f3ffa4aedc5c3539c31bf01000000000000009e8179ce4ad83026545dc2c99c27c45c22509e47bbf3325a6977674c7f9bfe130100000000000000aa76e585eb097575c3f4307c88a41ff10dd55e14cf9a1e14903cda53d017f1950100000000000000c1f633c7f4c65a703def23d07e85e650e7a124383b9f4e71dadf6e938f2cc429010000000000000075fc688b5010455b94c34ed273b95a7f3f3ad1e3aebdffe5c0c58662b3a6fe720100000000000000de3b03117c0592c1bb6156cf5a89b62db5bee5c1a96172a83c057a962b055d580100000000000000f2d5b666dff660ed586000cb5b2236c78b3e1ce465ba8c2e7647baa49a1df2df0100000000000000dd3af5175ce2aae92be020aab462082fbaf1424e8fc5f1510f3dba6e24c10b970100000000000000c95dc9d2a070e5350cdd9a87d07896207618b8b5fa2e5473e10e94a0c70b9e240100000000000000e53eefa1ae52754ea106e4a32ed14ce7194a0ecdbdb55b4675303a230c027b750100000000000000a72534ec118b3f23b92418fa062faed297c81173457982ac5fa51105946dd4c90100000000000000506ebe725128d4a8ab89714f8141b3962fff06d8982f50ec5cc2a6e562c587f601000000000000007e969e559f860e6eea31bf88d3a5837e2053f8bc8e393159701369a402ed227201000000000000000cd4bbd993c4e75ca06ff72f96cda5730ba0de11938891cc6dbdacb0898f333c010000000000000006d80f029c6fa63e5b61cb69f20830e5ffc0632b82d1be929d10100b95f42b4b01000000000000007a021d6a700bd688713c3683d9d9cc08f30471231d7292eb6ca63204069d904301000000000000006a98fcba174345cbef21cf2390349028093f1c485c559afa25e73b4c7585dd4b0100000000000000378ebee99365bccb2752137df5b004e03557b02d73065e62ce8ef4f2ff2067f801000000000000009b36d04062b635e7b7c37d73151e7745e670c7e550908b491d27de6e4b623f040100000000000000c98bacb2b0b5e88da24060475191f4ed37af1401b2bb037ce499e3243dc3c9cc0100000000000000e05f66d40ae6f502292883ff61bee60abe9f631976a3a788992c02ae08ee21ed01000000000000009f04069dcb9f5aa255ab23343eb49f98935ee04aca33f95aee0d7183e1ae3d2c01000000000000008646f71df17c78d27591e9dd7c1965e42a22047470a55ed860ce82dcb41845790100000000000000b4295abb37df4a3bd35b74e4fd436154812c12f136945c6a6e745d438c22598d0100000000000000713a3f722486fd089759973a0eaa0dcd4fb5586f5c10353342eb3c8208b120e8010000000000000013388b602dd1ce53bf76e9f5ee28aff93012b2fec3971579efb52e350eb29d2c01000000000000001a55785628300bfebee9bbf23ef1f2eb6d3b5cc32ad68ff92e7b34a5ac9f6b840100000000000000e769015f204f7871fd1e608ec251fb64a464f41a5ef58a2a5971d3dc4190714e01000000000000008f2606887fa0a1a14dcfbbb2aaf096b9158eb7ab45e2af0cae8ea2160799ba260100000000000000aa34de0e713619e9556866d06da2001e4e19fdb69208e1884eaac15873e8f9a90100000000000000f2c1d36192b098a94ebaf7f4d5e9cfb5d35086573366ad9e0f57873477f9ef840100000000000000abc45e18c26cd2d210c18a92de9dd8cab28b8db5372f383d7c65958cb3f82bdc0100000000000000097072e8a104ee7d892f0741d72122f494ee4fc69240bcc20a30d4429086ae7c01000000000000008e7c7dbc66a9350fa0ca4fc743f243139a913bec3e37368856e86e5e395b4a5e010000000000000098f77837dce08dce233e9ed774678f599c2f206a106f6df95eaa2b9dbfd3f5170100000000000000e89ea9a8e5efa09504c45f1bd577bde06ec1fc2ae6e58f86a1613bf0fecdf3a80100000000000000c403d780ed2fa2b90c8bb652b66aaf526f60b6b00963b03d2123a43794c3956201000000000000003b4324156feff0b3513fcf8dbfd639a997ac502a314b862dfb9165f60d661b07010000000000000032675ca04f169d19b7ae0983dc4056bdd3abab820d4cbbfdbb4e824275dc38d50100000000000000de45ccfd8087b1a632fca9a233755241733c5a8a342b5271302566edc94b603a0100000000000000d264dbd69b8980edddcabc30a6b8e1c36bc14076f2e2bf5233377daa2f05fb17010000000000000022aa15840840af192b8687ba12347ffd6decf2fe47514155df8f7437154ead5c0100000000000000c68682b0e8dfcfe2cbe3444d1b540582e5776aca58229449356f6a5ffe4fde080100000000000000ee37ff73d973d47c403f77ae91d1811d82b683bd1d1b4af7d27a822121517ff201000000000000005eeb0d83d17abbc37d017e363d107f8c007cbe9b97a6357cee0238c4dbfcdc840100000000000000e132b03de854b7b302c10abb6a9873a34487fb8204a0aa26915553e48f10101401000000000000000ffedc01157425fb072cbc2abf8378eb3d7d46e4016010ff8d0d11e5df839c1a0100000000000000068045d78f1b1e7344710db2ca3c49e83135b47e16490c95c7ce27f4a4dc4c3801000000000000008f12c455abf3b12832d51f812f6e253209df8bc73c37e6588866a2b74fa7cf3c0100000000000000ebd9ab9165223f21e698077cffb73285f78ae81a457733a2c313ebf5abc19bf60100000000000000482eeb642c5551c08aaf74a23b4807bc601e010169a2378979e7ce75e4d4301d010000000000000078cf063bee982d58db18b7e808d271795231d1663a009ee29761aa2eb794ba280100000000000000efe303a467020c77d306ce27bf1153f606ed3682a23d3a03afb365c1ed12dc4701000000000000002a6090e8a56138fe641f3f5736604af7ab738ee5006fb73912362421c23813e401000000000000008992aa070f0095bdb0c95c257a068a41679b3e260c428e6ad2c474cbfa8d39bb01000000000000001b0e920e57b8399b1a43f0b31d76e8fad3b460bc266af1fe0e5b6a48336784c201000000000000000c35a3794adf5d49e2ee3aa54a3562260cc34864c1f33109e106412e299393760100000000000000585a8780999aaf32c05c0f408b4dcad6d910e36b6acf33f89b54cf057e6bf0db0100000000000000014d5d19494d1d22478f2e3e829a90116047e0afd9b89e56b02769b98be30e5e0100000000000000d3a13dcf17b11259f890f81956b2a5f7aa0ab07da653eb401f1366adc8f9fcd00100000000000000ada51d21398b4c315aef94950ea5573af9cd0360b699d069f231384449c46aee0100000000000000356fb1225fbf58ed0907cf975d408cf34539e2cdf4bb9c72e602a3e7cd99681401000000000000000b420f891b94e13c0150e13e4a20af8b6b7820c4d70cef08df6535e37b2012b40100000000000000b89e2b89530d98bd762b0a093d42831c5002e60410e101a41e05ee3b8161be8f0100000000000000fc4139e17ca3e3a05a43aaa3b203285a1d718e336fb5c60c8b20abb4f63612480100000000000000d70ebcafd6db9a843aa35a374e11b9643e72a856f6516533a6e6f933338eb7860100000000000000b4a014e2cbefde8d6041f3453418d0cfc70cd8501c88750bf838731d0e870ae0010000000000000036c9e70a4bf38aacccb494774032935645988915a6b2bc7894b73e302c36fd680100000000000000dd2c383912dbe4d2e258aadb2a01d5aaf218e6eceb5b6662348fea0f44069cdf0100000000000000740b80430e293a008b5df8f64653a67b87c434dc5954551815ff361f4eea8a0a0100000000000000c5aae938580da881f9a387cb7590f23be7709c812399023f9310213d502fb2910100000000000000511b3859b7e56c18f6487e9559a93ec30f4b604264f667564914af50c79038750100000000000000298fc14716807110d276344377e6247272492961f3afd52cdee5df85978ef4ac0100000000000000697037b1bcfe6cf9e4a15fe04ec04f3f83f81a41f5c928788eacacb9329f8cfa010000000000000055438be4c877264e5683a872317e756ebb1d3fbaa44d0735330f6aedd984f3630100000000000000596d81e0039b9ef6266345878349fde921c94c20d16b7692ffc1b089807cbd9201000000000000003343d776c28de327fe9c2d9b743f452ecbff61fe4d5260eae99a1950f4f222b1010000000000000023db9e93f55c14c67a9fd7a60fa2f6f51e4e8c24baa097776ce27c4fc22075830100000000000000a50b02be05aa426aa7a41c73b0abc96b57bddd0f4a9705c2dec6aec9f3eaa7ac0100000000000000425e378a41fe25ef93e1837dffe3558e348a4362518d794f282560406958a5a30100000000000000bec6303ea9181751ae7248552a55439b698be53e8143ca2c0ed329105c24fd380100000000000000ac1f4668798391a7d3a998c1cb0801d8dde95d31b5f2b6d8c3cfa1fe2d135248010000000000000012540171172468cce33a3932d0e0a282d2592519cc207a184bb4bf15a359d835010000000000000014903a1932f729eec51c64c8652f5a7bdf17dfa359799e550f1083e16457ff270100000000000000e730ab9c10d10d5bb70c423199fa6292e42caa8898b97edff42e7139ee0d09ed0100000000000000704c69917fe59c82dfa6044e9817e3f2d7fa708b2c2b761ad0f0095ce1d939800100000000000000459f99c8be2412acad0f7cf2552d8501e60e6f16614d67d04640776e57b04f89010000000000000073243180ab26ae71ba0aabc39680c3d1742df12a5125f45327340fffd5a534780100000000000000104854d7e85ff9a6825dc4de7e668d5345432b747522f73d8c2613ec8c82808d01000000000000006a559723e601438aa63a5de649b460b9f7c282dfda4ecb9dbec4ab40bbc25acb01000000000000000b4e8a517108b5a96cd560fc9116cb955b7e64e82e757a00d9ac544ceca1e3720100000000000000d0d66b9e138e72362fc8ac99947a2f81b7d3a16ea2e5e7b7e873a749770aca5e01000000000000009a267c5d8dfa9520342b47566e186b24ea3cd125970950d7d98d06fd649a21220100000000000000eede4d531722b3763067b729de2f8af8abac018f967a64bd9b6907396f2ff65b0100000000000000adaca7859de8e13deda4287976cd78a23fe2c0c6a2cc365791beb3ace94bb8fa0100000000000000849380a0c141656ae2f38bc74958656a7699156dae391a2f0671b3575fa158990100000000000000a0cdfd574e0d73d60e77b03c8abca97619d0b9da4a242c46c36b5da85b90bc8401000000000000001f4deb80d81471c9fd5b1d86af7c6fac61c659aee7d49fdde64c63edc48233550100000000000000a60fa8a9f80d2f4e232108a28bac749559836458371e314871ddb84a9769ce780100000000000000f8890ba455213f3a15cf14c05d5b72d5f9e8fde423eb52455bc3cdc55f29f1fc01000000000000001c9b2bf5fa39c9f6b033e0393255457711343723ed4e8cfd8031a57098f583090100000000000000d30b3b1727758441aa4d3bc89182eec6cd29f8de97b2ad78b059d64e5369ec9a0100000000000000bbfcfcf1c9a6a576d76559e3ef69d5a13a2751f32661c0798d9b32ab017aad520100000000000000f3d58d0f267316d55c08caf4638bfdf4e781a44ba0165a6589a6102370490d89010000000000000057e2f8546be780091a2ff07c027bea462ace6cd201ad624c076b3b78a17a94ba01000000000000001de55e80321186a8263712b501015b7ada9e8b1b4061b64f8d7aa78d1e736e3a0100000000000000172a68338d9b2a719685d13531d6cfa59fd9f9108a6c4ae2369e0b84322ec3410100000000000000a453853db2a712f86cae675230847e9bb74a537e102c3e9ebc3dcf94f4f1f6ff01000000000000000653b109f7fdc95b7c73997eb664d5c299d3fcdcf8e3af674d034b87e9e6efe301000000000000009f051fa2838e35661b7e3ec6fe5b8bd3eeb23d191c6e2c908b150170426c3b5501000000000000006021bd7676d7aefd3ba1542c657faa3b87c15113fe1138aa1ad61341ad855603010000000000000057d0adcf969a39a7d0a25a898db1925d413b53895492dc48231ccfc50b672edc01000000000000007be9a15295a120bd330f07e9295800e06cbc2165decc736f9d5f8ec580efad1501000000000000007c53deb88b1a9f772fd0d61556d267783db7e3765e22fe6540048c580ebb92a101000000000000000bbf8f4a346f74964fc07987fe00053d72f1f132d4964e7e5012bc9fa460a5030100000000000000f745129714167d9e647da677b8d453135d9e75b3efb4e86a757c6274fdf365e4010000000000000036cd1eebeeaea926936837b65de98409c469d2438b9cd13f9aecd9828f2320f80100000000000000bbd226a3ad42d0bdff594f898826402d087baca25d12703f115c4559f359698e01000000000000005e7b52eec0c156214b2bbfba7c059cebd6db3c45835a665e0f6aa35621d7788c010000000000000017ce4bb41bf33a073d81a253307d363d9074a387abc3cd76ac52e7fae11ae92701000000000000009a8f12b9d85318cf7f93c0599edb3e397306670f5a59b11071c9529f87d8732601000000000000000e7833314b4a21425a183b725241cb3ae89ea9ce0cf56572e61daf13157ce9bb01000000000000004c107dfb960bb93c6e36528fd6d23e67427768965fea0a77f75f70a0fa1064a20100000000000000bac15cb3005a8818d1fbd07f2792f27a84de4ca75bd602172b8a10cf8ccde95e01000000000000004e44d1267a3e1653c564459a980b21db4bdfedb65e6d79654145361e00c388040100000000000000ad0dbd33e3334319efae4c0df8171d8a6dfa5f36cbdc08f4f6654c034bd74cd80100000000000000be9022566f2fc0ede57413f11519aaa52218638de70d2be8ba2dc7fa4a6b4f5f0100000000000000ecddcef3ef4929a20ab4c2ab4d252fc638cd016658ce1591698cd5f11e6ad5dd0100000000000000a730a48dd7de96d724dd78a9ba528d34c673603a2cb4b1a7dc3b14e92eeded050100000000000000302ffda71edb5b383bf5970f15a0181f84eb0282cca90100fc454953ce4f7fef010000000000000030b1b9dbb27657148a780f8139cc13076cf1c33b71ff382555966cad0d683d7f0100000000000000dc54c257868f60eab20780954fb72a866b21f643308f7c0ab3b6e2a9f4f0010601000000000000009c5f840ff005b42fdf54c665a331f6c9a3ba67d38ca9512b23e2646c420ce1be0100000000000000f60bf2ec0a60ee1109c5cf1e3e10765345a060bb767a455182bead1b067d045d01000000000000002503da32b9aabba2d73cb4b64554c9dc71a0c5e5c1391cbab2ae98fc3d4a232a010000000000000096d36c9288d08375b3d547c3dc8f952bb1137fd5c3087bb8bbcbd5b8fbe3accc01000000000000003b2180b3d5f1fcd150f9856b763da49c50357c1d528ecc9d36932adcb062d69b01000000000000002daa75813c73ca5543def4d0891dbc76e450b9fd4802a0bf80811db845f927640100000000000000d63d680bb4a3c20227315afb95862ba264aa21121515b2937dd058367e415a0c0100000000000000a6e5b8159fa9df398d4c29f27f855ec83f56ed1615fc69c9bb5b88541e5d931d010000000000000064ae58236a9c646382b8fb7d27e209cdd9fdfe4a3c42cf03502785bbfc25d77001000000000000002868883051b17c28d5cc53dde5a69a210208402bcead13086cd43d19aaf820d4010000000000000087095a48bb621b2372a752154b552567908a749df830635885410f1e705ca2a70100000000000000114dd622bf78f8a90be24c86a7aaec2c84d7c65d193d2e26f4946b777e80ebaa0100000000000000b0af63a6ed5a364cf4935fc6a0714232accc624dee5e6bcb9170ffedbf99963b0100000000000000c23728fa40b2ec3c8b236d169ff398d770a8e2566bed4f3416a9ac100f40f0630100000000000000910f68ac0457d60460cda9bbd8559cc0c4a6290feb03ca79988a9b0f3e0a03d801000000000000006b5ef74df17ca662c870c489a6e6c57f10d41f50a6cc558400a61854e66d14830100000000000000699ae4589dce260bd0f51a05208cd5612fe7bdbe87cae8be548fd168fa8b182501000000000000003644d6c7c0e89a4edf6331e17a147c174d06facf891a036242d8e8a0633bfe6a01000000000000009f55a42822b1af64e031f12a4d913d3e8065434d8985b37c5b74010a847c1eb30100000000000000661e02ac790a3a7af9bff79a2cc94a75059bb5ed2eac8b5a27bf0185d72c99570100000000000000671dd4b02fbfd0e956fdc31b9fbc0062d3236a1fa47a3294c5dd62764680e817010000000000000059e46cf5d030c3732b88be1204e5d591a62ce46606ac50c94a913e20c707ddc40100000000000000e3c7a9f80edb077b21973485ee16663abf722cff55af6e44a167b861dc8fa7b40100000000000000aed46cbb3b81671ec8a7a40b966b95cc8808045f134d6635e0057dac0825d7ae0100000000000000c932bc15eaa5fd19e9f5391c1f1269230840b9f05978893dda0c7db786ace416010000000000000097967f7ddf99c59a0310744dd022cbc83c71ec3bb8ad474044d71c4f5d4f4778010000000000000054e2c364543153ec959bfda6c5c69efa58f8eacc05d419688b78614478545346010000000000000091a1abeae7dc21b2366932b6c0b3fd603913b201127f343bd052b8e940bae2aa01000000000000008e52731d172ccf0cd0e68e349b20f9a30a1bcfdcc383869bca0b122be2f0e67f0100000000000000a20452bd4a6b29292fa8725d551691b04c2b53dca8892774c82513b2595de93d0100000000000000ff185b43021488634315977358d816c5da8520702359b6f1d2b6fdb8c0a754ca0100000000000000539c6a9cf39788feef973f48f54c2dc4863d3b28b5af4e08579471056a262c600100000000000000d88ed486432b11ec49c247f82dbe6ec74ce42287a1f96f4ecc4ddffba1bdb00e0100000000000000c157da1148e3fec0098ea52d10a8970b6f4c5506faa14133cfeecc0820e5d67a0100000000000000d9efc3ef1a2abf8dd1b2d0cb501c7b2bf666eaa21379d5b0ca15592d72d987df0100000000000000f447fddce496dec679b75110d504ff4e1dc13320ad9ada36550efc31c59c275c010000000000000022bc8f9a49096e770c936cf6aaa0a5e99861494b064d169e7c03b2ad514ba83001000000000000007aa1deb2d5e45cf5ef28520c6e08049c0f8ca8cc3d797590bf72fd8f1327ad0101000000000000005c91c617258e745c2641f64b623b0ddedfb444f7eacd8289f75736336b3e61190100000000000000b7bef95acc1121104ca1b37f3c9f79bb464d7f564e310573c763abe245fba844010000000000000009dd221a3be84881d5583a03ad02ac3f3add6eabc861415fec997dc15b9b003801000000000000000c4c60dc788f9f77639b871426a2aa85a6455d28df1a78d8486245d8f31927040100000000000000e4f9dc5be5b81e8960440426837ed657cf4c6197d25bc11f4cb12318400f89f20100000000000000ba237906be10ac8e9c209307ca33f73258b048e2684cfa95ddd149ac278d62c001000000000000007ba9cfd9f113e110ab434ef6678b464d114bfe6f970db26357c3dfca149bf9250100000000000000a577519c486f98ce81578c5e96c64cc0c29c5febd868b0e3804b55242febd09001000000000000003d8874311525da57093fb1ee2ef4529f16401edec15377f17215fbae51683ad801000000000000003943ac233778ed04db43307a720a56717f1f76332f0440df1befc92770db9df601000000000000009290deb6334f7a3e9a7ec43a8d85c42da689e63021d40cedacc539db0d660e600100000000000000abb004827c680cbd3dcfa92189c1dcc91500e57254a54285b3cea52540858102010000000000000064c99d806bec03038aa17580cf1437e43db68727821747b89ac0510a60699ba70100000000000000d61dbba3b78963d3aacab3051b8ed5345e97a12877833e53d340ae34b5934dac01000000000000008818f43c172fa7d37344b0df074fe7795da61113634ede5585525e491d528a06010000000000000078cfb0b5d7df6741e1a2399c33c42ca8c9ce29c93b3d308ba3ec5116dcfb393101000000000000002c709a8dfb482ef308243eddeb503a4395ed0410f04a201a3e1cf4cf74d83901010000000000000079e5bac7bf14d0ffc9a2d0eac55aa2827d7f28445974626d9efbeaef56a44f1501000000000000003c9aa43e2709356cf6003654cc40afd5b33b7840f5c1a4fc132f370dd325acad0100000000000000dbb6a0dafd2cab7af574101286ea000f110506fa5faa974f94921207db8912d30100000000000000898ee658b00aab03dfc508c2039e90f69cc4c33e93be14dad73f42c91d2dcfa40100000000000000edf71b81d6765e54f3570986c687ab52270ca0c88491fc6d4cecd32a045847af0100000000000000f9f3a99f52a9991475dbe0140739f816e8ee43a3ee0534d8e1c168d744293bda0100000000000000de590edce038302a478d35462299b4c0505daaf5f41ba29a17908116e038dd4c0100000000000000fd7c31202bb5673c345380538f1f431d2c8c9d2bbc892fdf8ab6a8ff79c050e90100000000000000340f9100df01977e8e7e1a950405082dc0fd8a2864743f73505ce779806938c10100000000000000938a0a6a1c32e7352518e591891b542e54221ece6cd43bea11a3362ae63f6db2010000000000000014480a4ce2ff1192a9adc0284e98efa480037efac4f315a19e39a82198188bd50100000000000000d1e9fb415e53bfeed0b1d3691cf780d547fcc291963d045bcc38cad420ff4f5801000000000000004f82dc9418d43695b2b1a0dde38098de6da47bc3b496d4a8cec02e5a1eb9d6e7010000000000000007c99b07a496bd4a6702ec9dae942860a2e3a11c1b7864f057d8b91e194d178a0100000000000000ecdcb4dc4e0a912e4191a5921ae28dbad4a50eca41084e26fe9920450e989684010000000000000035e400a7fa21cd5b3d33f0fbccd4fed4a1e15bbd7857f2bb87dfc28c9ad5fad60100000000000000546715dd806e6cc12930c73d5ba29d957b94e6ddd8be4332025a94aaa67e138f01000000000000000337babc46b44e58b15a2f9932a002e79176ea68ac4f0ddc83bff4ad24ac4473010000000000000053077ad3e81700fdc7a8843f07a7b9e1bdefc8969316eb12effc762aebb7c2b2010000000000000015478d96bccae266214004d67aa3b6a7242ea77ca9da0b95c9858ed70c4e33980100000000000000363a99c82a7e0b1166f5c657fbfb4acc0f85607463604406b07e0c847ba7fa01010000000000000051b9cdc1329db7a66e364983888744783c44430f6e66b79a9090a4d96bd9fd0501000000000000000d3f2775617b62bde57ba6ead8d4cc81c7ed2f2e986fa3cb6772b92098667a0701000000000000002025e3f1c19bcf5c38bced4d1436bd42c4f6d696935edd7668d9f013f4fe58140100000000000000b305c5ff4dae3dc6a37300f48da85fc7e812cf100d30e368d9b4e607ba9cd6970100000000000000663bc85589190c716bf3a13d579e703994a887136896a5c33d79469f9cc793e0010000000000000017b0496f81a343c5a70f8be4aea6b672b87b390096f3bec0436462d8b395f0330100000000000000303b9ec383edc3588e6908c606272594af38ca91ef8afec5a93cb1c981b6b87d0100000000000000734c354b0bd3405b0fc5a9279b3d76e27202f4acc6f3db4743045dcfdb4adfc1010000000000000081a2d42998fdc7ca4a0d7d222e6db074b360609e96c1fe97e9d72d4852990024010000000000000013fce414bea13a7004c9002f75852d5cda84df895e9b7e52172386622e02027801000000000000000a7e67d1787b44f57095c96c4d5ec8fcaa00f60a3a81d5c6e6afbd0e7b91764701000000000000001f81141a8c46fe442e4555cc9d2da4e61c4412c27e6938683eec4bbd43e8879c01000000000000001b4f3f8329e00910838ebb786487dec1303da4a4676fb39b49100ecebc67e93e010000000000000032649d7f5f5d7a5480c9988d0e10cf099674760c1799d1d9d286c65b6a089cb2010000000000000089b141bc8a8a191f46f334b8f3e9be2847823c90571f1fb991ee4ad26ec4ca670100000000000000e807218f271d4f6df7fc4fd462962038dd07576fbfedaf8c3a1fc4203b68d03d01000000000000008a3849c6cabe5082e90ec44d3b8d9816b01bd577d5fecfb109a547a5cb10d03301000000000000000698bd71dcdad99aec0135bfd67990e7c7098b64cf8a4e60a3e60baf963f155101000000000000009991974d92238276f33623d914d786c604e0022210b942aff8709c43c741abce0100000000000000661a7a0c09c2c95efcf1b702cde4856f81b7ed2f96355e97b62b8c25cea7e23b01000000000000009c5cab7f76e5aa7945c71bf3396f72c5691e24c97ebb2f54adfd2a4abd58edfe01000000000000005ce13b8a5efc21c9fd13a8bd474154d52bef1560fccab16adee9803ccfac107a01000000000000009ccae3655ff1e0e1e24c773158cb8e9df4e3bf8c82e4801b38f11f89a58a73ee0100000000000000c662283d9f7d948be4ea0264aff66034a4408abd2a268b7c65fbca3cc4e9e9fd01000000000000009069fe7c096d0ad1cde77c884707683c32ad769ef63b2ac4362df593767d3c510100000000000000106acb7e95a971f93eb4b04c9734fbf0b843fdf72305c6bf91b2a107bd88369b0100000000000000cf641b6776631560b647681ddec2fd628220f50772cb054fd46a487aeece2c9d010000000000000072584021095324b7e330d5432ed1c5d243792024871c8b7c0efa47da681110110100000000000000c08ac272c273336e48055a262d8994e48e4d2d29aee52e718e57dcc8cc410eb1010000000000000025c11d23f84fb922baca0183556b991c685a8027c6381b7aff51d775c08ff66f01000000000000007867c23ab1bc7a93082ddc309920226614ad82109d789b53e6fc06975a12171d01000000000000009b5fe41ad1c51f30c188d2425e210b5ede18af172bad6bb1241a0db87dc866ad010000000000000054b3af8299f6b939f6891887c8bb565a5aeb8cdb602612341f36ebecb565a2e1010000000000000096085f27f62625487d72d5a7136ffde1d7893dc527c3a9d356889cc3a36200a801000000000000003b049747c16458b8aa28bc371d6c7dd682fa3d550ebec106e9da18cd869d84f701000000000000008ae56a7f764b81a89dfcce9bb5388fc26dc3cb302f91c0b7d9923f81f404201d01000000000000000f3d2e4e137de175ec06abc592f6fc70375f49e67a23746887a74dc6e81e182601000000000000006b5d2ed89a94234d3a4bf918a5ddc201b74b130c7357894478f3f584cf395b1c0100000000000000606430051fdc771566c8810592ccfe826bebe78158034e9dd32325c40eb37bf70100000000000000ba104520cab9a9230eed557ffc3e8da57e700bb314880f2dedb10b4ec1bb2e6d010000000000000074806a0eb19c96f5940e20341aa53a15b8e0446f2e9fa0dce8ac8328f5c05e93010000000000000099802e5ce1e01b97b91bf8b8f5c6f18ec206e81b4a5b75642466db51e65912a00100000000000000be146351435791ac5e09f1009f24d3deeb701a46054a93b800e1b89b09a5a5c50100000000000000a7874632409602506850eb78c1e51d0d90b456b0f7a84ae2d3f53d6d9f3c910501000000000000008997db137aa2aec72c715759c19b1fbb85285e5f703b63629ff9d36220d4c2fa01000000000000004c76ae91cb8357b3eca70b8eb0cb0c80ce09c43a8a6a8b89e1faa8404344b5c501000000000000003a5ef3ca58df56def71e6f37cbe34ebafdcc0fab1cada6d708a3edccc10e6e0a01000000000000000db25baf0d49341b8b7de7b91d7a6d139aa62d693124724539d88677655edcd301000000000000000fd4f7941562ee92fb9fce140fb7b045bc4aa12aea490061d05d171eb4f80081010000000000000076f524b45977ad44790c75904183077340b843e80817902cfc8a8f6d5c00b6780100000000000000ce6e57c4979e6e0759b274d0d4495994d3b9ee902f80501103f0e834026e8be1010000000000000034f57117f72eb65ccc867f16044b6e9e5e0c30c432a3dd443513db39645020620100000000000000142427ce7c87d257ebe1761bbe7672fba09268d3213c8d26c36fb4df1cc21bd90100000000000000b594cadae4ac5f40a0fe6b834a70756324955af9d8d70c25c94dfcf3fec49743010000000000000085c832363e832abf7a711a60c8b114f03b923da64af0386d21f5495f706fdc040100000000000000fe787732d7a1ce9769e4063351bb058a123be12e5d4ef4f5d93dd0ca348f2f2c0100000000000000b5cfda4d2876bcf99538a4861278260738117ca5f251b2e7f3d15a8476272a9101000000000000000acc7024c10d76dcc968a5f2482d7c5be47abe879e95a2fae7887206fd1e681f01000000000000006eb677c427e7f426c7f0c1b18034d9b3ed2778fe0e1c7c35b2aca7776b41b8720100000000000000152373151474d68bfaf80d5afc281c11a2063e7549e5a203b1dd3a03d6ce55af01000000000000005ccf60dd4e7ab47906f45d71046ee5fcd7c9fd83dc4657852caff29d7381523d0100000000000000e9e6f84ec1ddf8381a2e07ab4607ed640532b2e9e3afa6129b345ffa679f6fda01000000000000002567eead1b79eade19ff3101e539ba2b607f4f8f0d50827767b368fc17172b6e01000000000000004e05e2f383987e2ea50e4b44fa9b03e982c86e63f4a745563a61f3fc4bff0b7e0100000000000000ca4e5b60dde2ec87a31fe0436798e86c3cf4d66e96aeec7bab5280b66347e4bc01000000000000000037acf41acd143038de38d22b0c9e1a71b530953e78969c4601430eb29bbe0f0100000000000000cac959d88c030df36da2b6106b4325b2bda9d30c9b76b3d3afbbc9b1b2d1954b0100000000000000c42ea844b49eb458b775f307dbebb9f29830a98ba85a7ca722a9297d8eb9511d01000000000000008508fec6ee8b9effbcc14ec08d39b7ece13137987f14529e2d6b3495dc01e3c001000000000000008fef8dad7915c9bb3693440286389516b297c9164fc8aeba2efbbf7b67d9570a0100000000000000dfb4e70faf37e0c3122e956280437080743a05c30ed0835f7a571581b04c77290100000000000000340928ec9ca251728a06f3b5d5e773d41754d4603eeff0fe052f464d4cd8660301000000000000002ac100c9edec32ec109e63a1c8ace437facde2e12d796a9dffb6d971414f378901000000000000000dec13d4b8393c0f712d31955c82c1397cc59c7b641ce309f7e00e5e800a55ca010000000000000051cbcf3c493583f34f4bbebcd8313e5b7a8bae7da8eec4e7effa536d86476a2d0100000000000000dff478e845e134f827b5e8845be956695f6769ff6dd68626267a032d61162408010000000000000076c3003d7ec387de2822f45df32fd18c1cc18224d381c0b720dac2108add4d3b0100000000000000bb8c12244c2ae20d31775b6b8002a558887be9c468d503b583a4730bbaefac9d0100000000000000a3ac7579c92e98b5e774ffebc0c43b115b5748c00c5fe93e9adaceda6d7ed6e10100000000000000ba5b4e2e810ee7984f9d9be220b046e36b12a32bf4637a3e0d3ff4ab9c2420bb0100000000000000570fe760a3340189708ad335107114e4a64e02180c19577efc359812f79a5fb901000000000000006dd5ee873e7630279f7bfbc3e212868973c107ad5ccea8a698aa4c2ab4735c830100000000000000740c72e955fec776fb23768873e1710a89d6aec2dcea5d624341651aca9fcd460100000000000000bb33c9f11c7986db92ea1ce85444aac8eb1c18faaa443d2df8d6aa7a1e608213010000000000000093e276d7f8b042c4a18130b085eb2bb6a4c06095934b9cdb8d5883642ee9b6bc010000000000000051a0d4023b87bf0998957fa638dd3960c3b96a753f52cc4e4c57ee2c5295a2a701000000000000001fbb46dc9dabfad0d6df5b081731b48cf9d2b3a525d81f729921a359100849e20100000000000000bb74154689a4725aa1a2ef790d2f1fe6cafe6decf7c04b344b03777836de3ce80100000000000000120fba7ddaa09646826b69e9fc039b75858e7ad47a4f7427108d5bd888c0a16001000000000000003248125508698a238f53403cfdc7627f672ce9f8cf93fa72ce8dc8776b3fcfa001000000000000000a09fadb607e53682fcc706f101a6ab9f295c1e27ab32bdc6875baad163cf0e8010000000000000009e4ae032e54a6b4d2d8e0e30e1ec1b42c2eb5255def58b64280c39a7ae2a2230100000000000000a81e678f0adeaa9c5ab7d96a80381e47f601d9bc6d410b970e58c175e254d74e01000000000000007cb589ac52163016e157a3c01102e21f7540072721ab1ae15f06da8e531532f701000000000000006534178809a1ffd07aadc00b73226c701748ddc694935aaf3360e8690bb5404b0100000000000000ceceb7fdb6a93ea913ace600128652043dcace10928b4a24fde635f1c72b812c010000000000000072737c3205224b49df92fba1fd9f9f8e8dc7797b6e2694f31cad8e8d3e6282370100000000000000a2847de5b17cc8c8865e560770814dd352d6bf25f315b3a39f26ebd7688109960100000000000000bf9c3af8647f294e82d4544d0fb1165eaf9c33b0bc4e34cb12996d557055a5c60100000000000000c84c3f3ef8e02d435684bbd362371b4dcbe8821d78ca64efe76e2d85302d79cd0100000000000000eff02b3b76c33319976a8438a2a5b4b68392ed910c01dfd5d2b81226d521acde0100000000000000";
-        let versioned_authority_list_binary =
-            hex::decode(encoded_versioned_authority_list).unwrap();
-        let versioned_authority_list =
-            VersionedAuthorityList::decode(&mut versioned_authority_list_binary.as_slice())
-                .unwrap();
-        let authority_list: AuthorityList = versioned_authority_list.into();
-        println!("{}", to_string(&authority_list).unwrap());
-        let encoded_authority_list = "0x".to_string() + encode(authority_list.encode()).as_ref();
-        let set_id: u64 = 1101;
-        let encoded_first_block = r##"{"block":{"extrinsics":["0x280402000b101cdedb7201","0x1004140000"],"header":{"digest":{"logs":["0x064241424534023801000087c4d20f00000000","0x05424142450101987e387ab4bae9ff75775ac9f443f3b0c67a67b882971f17f935342027b9574cb02d317ed673e140fb427484110f719f160bbbd895cc012e5fc4131a2d980484"]},"extrinsicsRoot":"0x54949d175590963a8da7086d93db24b4ef8fc293ec9f78fac437018f471cf3e7","number":"0x2b99fe","parentHash":"0x1bfe3c8897a158a06c852452d14e79e076a4546427f8820006dce967caa2ab8a","stateRoot":"0x7406b1be4a2f70a42fe9aee585634400d59fee9fb8825d26800143208bebeac6"}},"justification":null}"##;
-        let first_block: SignedBlock = from_str(encoded_first_block).unwrap();
-        let first_block_hex_encoded = "0x".to_string() + encode(first_block.encode()).as_ref();
-        let encoded_second_block = r##"{"block":{"extrinsics":["0x280402000b8033dedb7201","0x1c040900f267ae00","0x1004140000"],"header":{"digest":{"logs":["0x0642414245b501014101000088c4d20f0000000066c6306a31a925f5f947011a7a3e7ccc1ce5571d85b01d32c8b5ae1557a3ed67974a2e5084adf965f248f2e19a21296c3ae133607df506223c0204fea5dfa6014b0c037a04a90774cad4d093934d7c3876a4aa627eac4d762c7903abae1b240e","0x05424142450101e68d322738413ed45f63e3c7f66511f2940b3d600a6dd475c8ad9fad77f07b5fed210d2e0efe90cb8c85f8b67e739854366300b6717a58c899f781ce7f11318c"]},"extrinsicsRoot":"0x7b5c52f0d6f125e81560f41876712815562bc2cce43a42280fa1d973c9f8bf5b","number":"0x2b99ff","parentHash":"0x93cd6c9490d544eed4efa0e90a0d13f0bb981fec55878042b69ba9ae54fa9b0f","stateRoot":"0xf71e199c3a505d47416aff00d933a26bcf9b8ab47dd8a98e4c3316e06527ec38"}},"justification":null}"##;
-        let second_block: SignedBlock = from_str(encoded_second_block).unwrap();
-        let second_block_hex_encoded = "0x".to_string() + encode(second_block.encode()).as_ref();
-        let encoded_third_block = r##"{"block":{"extrinsics":["0x280402000bf04adedb7201","0x1004140000"],"header":{"digest":{"logs":["0x064241424534020b00000089c4d20f00000000","0x05424142450101e2ae609365dcfbd928d8337c8d15759a6afba0b26ccbfb14f24407920395cb7ac0ac26364b43edde72072a016bad47a254271968e7cde0d6788f2e0c81be7888"]},"extrinsicsRoot":"0x25c0be3d9c0f752ea58db649735fa0cd1d61f7f2b88ba395cce76e306c132708","number":"0x2b9a00","parentHash":"0xb1c66cb4c220d9495af76befe39c08ac3b2bb8d97e324288b7ce6d9ba26e32e6","stateRoot":"0xe63104de515d8cd95d00fe3380afefbb6fcfaef1b9e01fb12df6f5e0148d8a60"}},"justification":[72,1,0,0,0,0,0,0,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,169,3,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,52,74,218,176,171,227,104,148,119,151,24,166,141,89,192,176,194,218,144,15,2,157,110,192,180,220,147,232,153,232,5,233,158,165,164,153,23,150,236,189,97,217,230,45,40,0,186,170,80,2,139,48,205,33,138,254,124,106,31,130,18,232,149,12,0,55,172,244,26,205,20,48,56,222,56,210,43,12,158,26,113,181,48,149,62,120,150,156,70,1,67,14,178,155,190,15,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,151,33,222,88,210,194,239,74,169,129,30,202,84,143,253,193,86,126,67,118,28,159,190,73,143,145,133,179,141,245,212,193,233,197,153,194,69,57,161,109,159,37,28,78,104,201,130,162,57,3,107,177,88,45,51,239,43,192,179,87,230,65,245,11,6,128,69,215,143,27,30,115,68,113,13,178,202,60,73,232,49,53,180,126,22,73,12,149,199,206,39,244,164,220,76,56,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,213,87,65,87,75,215,171,192,142,138,75,20,37,40,186,47,17,181,72,192,252,194,239,68,65,113,147,22,73,54,4,25,255,34,202,40,221,36,233,196,88,116,177,207,224,72,199,195,199,251,155,157,10,135,141,2,59,200,242,223,121,141,112,8,6,152,189,113,220,218,217,154,236,1,53,191,214,121,144,231,199,9,139,100,207,138,78,96,163,230,11,175,150,63,21,81,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,56,222,155,70,78,219,172,150,245,132,195,71,149,157,192,243,173,225,215,116,49,17,55,15,11,59,227,191,249,240,181,90,241,129,62,126,6,195,47,117,107,201,65,179,172,198,109,169,104,231,46,191,53,97,148,236,126,202,81,60,89,226,88,12,7,201,155,7,164,150,189,74,103,2,236,157,174,148,40,96,162,227,161,28,27,120,100,240,87,216,185,30,25,77,23,138,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,15,134,137,92,207,195,217,221,116,197,51,237,126,170,159,246,88,54,220,208,15,55,5,239,83,241,234,77,187,84,253,207,93,205,34,13,18,128,115,136,29,145,226,32,67,35,12,54,127,152,24,101,154,36,44,164,94,21,140,127,244,191,56,11,8,159,131,101,242,101,193,44,88,214,235,149,203,88,212,230,118,38,220,124,161,10,194,35,66,75,118,36,1,44,27,181,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,58,226,41,96,213,188,212,238,39,20,239,115,195,60,103,99,77,227,143,72,16,14,38,102,38,209,61,150,134,175,132,166,241,180,172,71,176,3,206,87,91,63,35,185,14,63,142,78,88,132,178,68,223,243,106,12,99,199,115,61,218,91,21,2,9,112,114,232,161,4,238,125,137,47,7,65,215,33,34,244,148,238,79,198,146,64,188,194,10,48,212,66,144,134,174,124,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,43,197,73,215,118,181,170,72,58,168,108,230,248,64,121,44,233,180,139,120,138,95,220,55,227,55,159,226,160,104,11,21,26,101,125,49,19,153,130,22,132,145,51,77,127,127,193,95,94,133,93,16,179,156,13,161,175,19,24,195,36,73,57,12,9,228,174,3,46,84,166,180,210,216,224,227,14,30,193,180,44,46,181,37,93,239,88,182,66,128,195,154,122,226,162,35,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,219,107,143,163,173,148,214,156,233,208,106,141,72,124,80,103,135,165,126,110,102,153,187,154,142,182,244,155,216,108,229,43,234,85,184,97,195,114,129,167,109,105,234,241,172,172,64,111,107,158,18,150,180,66,9,246,35,48,115,74,69,157,156,10,10,9,250,219,96,126,83,104,47,204,112,111,16,26,106,185,242,149,193,226,122,179,43,220,104,117,186,173,22,60,240,232,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,98,193,27,191,133,54,72,239,104,122,109,244,201,160,190,196,196,111,137,33,47,186,137,99,186,125,236,127,35,86,31,66,135,187,211,56,105,157,245,59,254,248,244,139,198,166,102,45,50,88,233,234,155,156,211,57,160,188,125,55,29,173,103,6,10,126,103,209,120,123,68,245,112,149,201,108,77,94,200,252,170,0,246,10,58,129,213,198,230,175,189,14,123,145,118,71,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,250,56,211,19,147,71,236,52,60,104,210,35,109,6,149,213,137,245,70,146,253,92,18,147,120,51,132,197,223,19,247,101,82,73,24,14,211,173,161,154,29,117,181,20,31,21,228,84,87,254,132,45,184,104,99,94,108,219,21,136,208,112,30,9,11,66,15,137,27,148,225,60,1,80,225,62,74,32,175,139,107,120,32,196,215,12,239,8,223,101,53,227,123,32,18,180,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,204,203,10,230,31,84,38,252,127,204,113,17,215,225,208,0,132,23,4,35,233,53,245,173,176,120,198,217,127,51,67,125,168,175,185,117,177,80,57,67,58,192,142,7,176,85,212,217,3,68,55,189,59,252,208,31,230,75,30,189,200,91,20,12,11,78,138,81,113,8,181,169,108,213,96,252,145,22,203,149,91,126,100,232,46,117,122,0,217,172,84,76,236,161,227,114,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,125,9,44,26,37,64,154,182,234,179,232,237,105,90,183,45,49,99,47,149,51,194,182,53,37,37,240,14,207,172,30,26,17,232,236,194,138,77,37,147,33,116,114,87,230,15,129,247,97,28,159,181,251,126,161,182,55,238,7,106,86,85,102,12,12,53,163,121,74,223,93,73,226,238,58,165,74,53,98,38,12,195,72,100,193,243,49,9,225,6,65,46,41,147,147,118,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,157,58,194,120,222,121,234,61,164,37,236,96,127,152,46,135,191,120,115,226,183,115,191,26,79,146,93,136,13,75,72,190,184,244,8,35,213,123,122,121,182,220,34,174,232,224,254,97,24,243,225,128,3,121,146,244,164,58,76,119,63,186,221,1,13,63,39,117,97,123,98,189,229,123,166,234,216,212,204,129,199,237,47,46,152,111,163,203,103,114,185,32,152,102,122,7,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,252,93,104,45,230,100,202,118,254,184,234,61,144,94,164,232,214,11,58,45,172,27,8,178,109,225,173,31,223,87,237,239,10,185,203,91,33,155,168,149,144,102,76,100,11,198,190,113,5,156,191,153,199,124,211,248,249,126,90,85,5,202,174,8,13,178,91,175,13,73,52,27,139,125,231,185,29,122,109,19,154,166,45,105,49,36,114,69,57,216,134,119,101,94,220,211,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,63,34,39,104,136,134,7,187,32,198,160,142,184,73,100,15,152,117,101,251,189,85,0,88,78,205,16,234,49,102,100,134,177,36,203,237,178,25,51,41,51,67,193,107,45,55,215,218,161,183,110,230,133,193,118,137,24,140,243,158,152,56,179,0,13,236,19,212,184,57,60,15,113,45,49,149,92,130,193,57,124,197,156,123,100,28,227,9,247,224,14,94,128,10,85,202,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,142,195,129,2,70,146,17,171,210,13,50,233,172,172,105,250,11,163,120,184,225,200,116,222,99,78,36,94,128,144,14,95,234,254,25,3,110,193,0,37,204,188,48,45,205,194,171,190,157,68,45,127,181,206,234,100,138,73,37,61,59,53,178,9,15,61,46,78,19,125,225,117,236,6,171,197,146,246,252,112,55,95,73,230,122,35,116,104,135,167,77,198,232,30,24,38,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,159,185,16,83,227,176,170,38,131,29,139,224,55,97,46,119,228,101,202,68,230,208,129,95,132,69,153,212,111,166,29,199,137,4,133,215,88,62,54,228,29,117,238,18,125,108,12,200,17,96,103,248,233,85,217,237,140,54,167,194,245,131,245,6,15,212,247,148,21,98,238,146,251,159,206,20,15,183,176,69,188,74,161,42,234,73,0,97,208,93,23,30,180,248,0,129,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,202,57,217,72,110,193,84,172,135,106,65,145,21,162,164,134,60,92,46,19,244,69,141,161,126,58,15,103,250,72,177,7,56,71,131,104,2,97,123,146,168,159,204,199,37,106,103,139,10,249,25,201,166,183,38,59,243,10,134,103,137,76,110,0,15,254,220,1,21,116,37,251,7,44,188,42,191,131,120,235,61,125,70,228,1,96,16,255,141,13,17,229,223,131,156,26,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,52,150,145,197,58,111,203,208,146,115,32,47,65,230,101,87,211,130,3,160,241,69,178,251,36,99,28,29,98,55,152,172,96,83,192,86,252,197,158,36,103,243,103,129,194,170,67,245,120,11,143,255,190,62,35,116,195,134,187,103,95,1,43,5,16,84,82,255,2,97,162,30,220,130,58,45,73,75,162,209,235,27,50,99,117,184,1,156,147,177,174,121,164,250,101,36,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,79,164,15,85,11,33,246,117,149,243,154,127,188,172,5,50,163,86,115,249,109,99,133,24,128,85,225,186,143,202,198,22,72,70,233,200,165,168,252,233,126,161,44,61,160,189,113,51,247,204,240,197,213,14,12,10,126,105,149,71,123,160,5,14,16,87,192,252,65,109,63,96,160,11,199,248,123,213,95,12,235,39,253,211,243,175,27,213,134,75,21,156,26,219,163,154,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,135,215,123,149,131,228,161,66,192,223,162,216,254,6,81,130,117,144,166,208,146,73,203,191,196,66,175,114,81,205,192,31,167,203,136,205,153,158,79,1,245,50,132,254,85,84,126,154,105,202,74,49,252,162,64,86,78,254,8,238,246,53,253,6,16,106,203,126,149,169,113,249,62,180,176,76,151,52,251,240,184,67,253,247,35,5,198,191,145,178,161,7,189,136,54,155,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,246,191,115,65,148,60,35,217,19,161,103,167,154,134,228,98,248,135,0,7,84,151,235,250,195,181,82,185,152,190,244,157,30,129,93,251,106,145,35,237,245,37,1,54,185,29,161,112,67,7,214,20,232,7,49,89,211,56,225,83,30,191,237,1,17,77,214,34,191,120,248,169,11,226,76,134,167,170,236,44,132,215,198,93,25,61,46,38,244,148,107,119,126,128,235,170,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,146,63,104,193,220,10,112,55,175,35,178,170,144,7,97,19,241,173,25,223,44,203,58,51,91,167,48,235,226,141,21,252,138,199,101,14,76,19,113,116,228,51,66,237,7,123,114,86,241,11,198,131,80,253,130,84,189,44,37,69,217,248,201,7,18,15,186,125,218,160,150,70,130,107,105,233,252,3,155,117,133,142,122,212,122,79,116,39,16,141,91,216,136,192,161,96,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,199,64,137,175,100,103,188,128,206,34,224,198,176,182,220,13,224,134,235,19,186,246,89,147,182,229,79,94,111,242,25,57,161,153,255,97,92,153,27,114,129,41,86,112,186,214,94,198,140,78,85,69,3,35,16,98,14,36,184,39,76,166,77,3,19,252,228,20,190,161,58,112,4,201,0,47,117,133,45,92,218,132,223,137,94,155,126,82,23,35,134,98,46,2,2,120,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,159,51,70,30,166,188,199,220,105,95,134,36,103,7,89,140,124,129,22,90,235,20,63,72,25,58,112,208,180,2,108,162,185,246,138,139,230,207,44,53,197,116,171,98,241,52,117,131,212,249,79,241,122,229,242,10,108,190,40,228,228,10,59,15,20,36,39,206,124,135,210,87,235,225,118,27,190,118,114,251,160,146,104,211,33,60,141,38,195,111,180,223,28,194,27,217,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,34,2,164,221,234,12,60,194,104,78,168,156,200,223,26,72,83,151,52,98,196,112,83,238,9,182,51,2,66,190,56,131,178,67,11,128,192,232,24,250,95,151,22,105,117,56,141,145,146,77,216,95,42,244,204,33,29,201,222,111,234,199,213,5,20,72,10,76,226,255,17,146,169,173,192,40,78,152,239,164,128,3,126,250,196,243,21,161,158,57,168,33,152,24,139,213,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,233,113,231,224,166,219,148,223,70,103,168,15,126,96,177,222,108,139,219,203,20,37,75,47,227,61,2,222,196,196,133,144,221,0,248,239,145,254,123,232,143,173,236,126,146,20,116,148,130,7,238,172,173,9,212,158,170,176,53,188,127,206,6,8,20,144,58,25,50,247,41,238,197,28,100,200,101,47,90,123,223,23,223,163,89,121,158,85,15,16,131,225,100,87,255,39,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,171,177,111,91,176,129,236,176,48,119,52,96,72,139,11,235,55,175,144,241,59,90,236,57,113,202,151,99,137,233,226,200,70,22,122,254,198,252,114,157,213,69,167,93,56,162,75,22,186,28,32,131,145,244,245,7,239,72,5,113,31,231,88,10,21,71,141,150,188,202,226,102,33,64,4,214,122,163,182,167,36,46,167,124,169,218,11,149,201,133,142,215,12,78,51,152,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,87,136,182,37,146,5,113,202,143,163,110,59,18,95,145,228,20,163,59,238,49,130,213,72,133,194,225,233,88,167,164,104,254,141,229,35,255,176,104,108,33,221,113,60,134,27,31,209,115,221,67,34,132,65,59,38,208,160,253,83,228,62,37,2,23,176,73,111,129,163,67,197,167,15,139,228,174,166,182,114,184,123,57,0,150,243,190,192,67,100,98,216,179,149,240,51,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,100,183,35,51,209,218,253,124,110,98,244,181,250,67,46,21,96,14,203,13,40,245,249,88,249,203,70,26,143,22,151,202,175,221,175,106,150,98,52,36,198,86,188,163,94,2,81,105,110,47,26,59,168,84,163,237,119,0,10,222,205,24,176,0,23,206,75,180,27,243,58,7,61,129,162,83,48,125,54,61,144,116,163,135,171,195,205,118,172,82,231,250,225,26,233,39,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,148,250,13,134,223,125,167,125,152,104,188,146,201,16,66,87,194,116,187,183,5,20,21,85,203,78,121,184,213,212,143,191,76,129,122,78,11,20,165,41,109,253,168,157,205,5,252,74,185,152,163,218,86,233,27,143,12,232,109,95,177,86,219,12,24,45,163,19,112,169,10,118,144,47,69,34,43,41,67,42,114,71,79,227,170,250,124,98,211,104,17,31,98,20,32,92,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,17,179,117,236,99,209,31,229,255,249,20,64,216,46,186,27,208,162,111,73,129,140,171,201,187,214,18,232,116,206,236,254,202,76,116,248,69,235,93,180,142,189,116,232,154,10,0,117,28,152,124,137,65,156,137,30,21,108,72,213,187,224,159,7,26,85,120,86,40,48,11,254,190,233,187,242,62,241,242,235,109,59,92,195,42,214,143,249,46,123,52,165,172,159,107,132,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,110,249,201,192,28,14,195,143,202,2,101,222,200,22,9,122,9,197,223,158,73,195,88,179,76,30,134,249,237,233,161,205,58,26,27,218,36,144,109,38,32,229,253,35,101,133,241,45,169,211,19,71,82,154,247,244,66,13,181,94,232,118,196,15,27,79,63,131,41,224,9,16,131,142,187,120,100,135,222,193,48,61,164,164,103,111,179,155,73,16,14,206,188,103,233,62,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,7,72,125,164,108,141,109,203,219,64,146,107,211,43,120,66,83,206,131,185,160,228,207,67,194,84,238,58,218,64,17,155,115,201,61,84,80,41,155,194,136,62,123,193,254,79,15,185,149,57,169,63,226,75,33,251,207,186,194,152,98,62,131,3,29,229,94,128,50,17,134,168,38,55,18,181,1,1,91,122,218,158,139,27,64,97,182,79,141,122,167,141,30,115,110,58,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,87,22,239,125,46,110,225,99,93,24,96,201,7,85,194,104,43,2,10,35,47,32,4,230,194,127,104,83,178,185,201,135,231,176,74,33,130,105,106,71,95,228,210,114,64,46,217,49,12,182,151,40,160,228,151,166,0,119,12,77,71,0,56,3,29,247,148,139,18,203,123,228,213,217,174,233,59,116,101,141,12,132,68,160,195,95,63,250,74,237,197,195,83,156,49,191,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,70,72,59,138,47,57,196,157,19,221,9,62,3,46,77,176,56,255,209,238,168,144,62,72,30,140,15,29,39,140,246,222,200,188,163,86,169,115,16,182,229,25,175,132,143,144,240,135,57,186,116,240,3,13,190,27,213,64,38,244,87,138,240,5,31,129,20,26,140,70,254,68,46,69,85,204,157,45,164,230,28,68,18,194,126,105,56,104,62,236,75,189,67,232,135,156,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,209,141,0,213,187,68,178,210,118,40,154,114,45,79,99,97,245,147,158,101,113,96,89,31,181,58,238,133,96,139,139,237,67,210,242,168,140,201,25,9,122,184,98,148,216,69,237,214,161,232,167,79,152,8,109,247,43,73,18,243,253,100,57,1,32,37,227,241,193,155,207,92,56,188,237,77,20,54,189,66,196,246,214,150,147,94,221,118,104,217,240,19,244,254,88,20,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,179,70,125,252,103,21,119,231,94,50,24,4,161,161,16,175,69,207,199,1,8,177,214,211,248,114,175,110,41,229,92,244,175,123,142,184,123,110,182,245,57,166,240,193,22,243,93,210,181,187,215,232,51,105,3,107,221,178,145,109,245,118,17,0,34,170,21,132,8,64,175,25,43,134,135,186,18,52,127,253,109,236,242,254,71,81,65,85,223,143,116,55,21,78,173,92,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,90,99,80,74,0,223,193,142,121,243,253,41,88,179,157,34,73,22,145,42,110,88,25,124,37,136,119,104,28,85,63,151,123,78,34,165,160,46,127,229,197,41,100,76,31,108,113,172,155,236,27,219,108,11,198,215,249,46,26,83,90,112,44,9,35,219,158,147,245,92,20,198,122,159,215,166,15,162,246,245,30,78,140,36,186,160,151,119,108,226,124,79,194,32,117,131,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,156,74,165,241,31,148,135,97,88,195,57,48,95,255,142,5,244,137,21,250,26,213,126,10,189,91,196,106,252,86,221,235,46,185,134,183,45,221,202,169,21,70,88,83,177,247,13,176,224,161,46,250,149,95,65,84,28,245,92,240,64,125,40,12,36,161,190,57,201,126,245,153,47,148,239,203,15,69,17,128,26,90,189,235,62,129,63,180,100,212,62,179,27,201,37,201,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,105,128,171,132,18,225,194,53,23,137,108,227,47,55,101,56,241,65,247,34,107,94,185,135,11,90,66,125,43,38,175,184,133,115,61,127,11,253,246,228,16,57,163,88,37,146,223,203,20,180,46,131,236,194,112,61,214,145,209,125,59,132,127,5,37,103,238,173,27,121,234,222,25,255,49,1,229,57,186,43,96,127,79,143,13,80,130,119,103,179,104,252,23,23,43,110,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,144,165,140,225,119,26,120,90,226,237,57,184,12,137,212,192,161,186,141,85,108,113,33,113,232,94,218,178,73,121,179,207,170,203,78,221,87,72,78,75,3,56,47,254,12,104,114,150,125,113,66,136,67,126,40,128,241,123,241,221,6,201,115,10,37,193,29,35,248,79,185,34,186,202,1,131,85,107,153,28,104,90,128,39,198,56,27,122,255,81,215,117,192,143,246,111,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,248,238,74,148,23,176,95,169,49,253,122,248,90,91,7,52,34,203,55,236,183,249,159,93,87,210,110,173,203,252,74,205,203,23,232,210,84,79,60,41,149,216,80,43,195,25,221,48,20,145,98,126,53,165,178,180,163,89,150,111,93,141,246,3,41,143,193,71,22,128,113,16,210,118,52,67,119,230,36,114,114,73,41,97,243,175,213,44,222,229,223,133,151,142,244,172,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,151,222,171,50,116,41,242,6,90,157,201,57,34,27,123,58,234,55,128,8,24,192,234,33,193,214,169,72,83,210,146,199,160,223,12,31,109,159,131,205,190,193,85,167,164,126,102,164,65,224,199,19,130,31,127,190,154,36,136,79,87,30,16,11,41,252,120,71,89,151,188,160,88,135,43,131,245,150,25,114,233,119,31,120,240,250,224,220,233,24,228,48,223,131,170,42,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,7,150,193,223,10,67,49,233,252,11,66,34,76,170,126,159,28,79,200,180,32,248,195,13,174,55,164,111,97,78,198,130,253,35,173,70,207,7,231,89,178,142,63,109,62,139,154,64,196,17,238,193,198,163,82,47,120,180,91,213,52,8,91,1,42,96,144,232,165,97,56,254,100,31,63,87,54,96,74,247,171,115,142,229,0,111,183,57,18,54,36,33,194,56,19,228,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,143,178,175,59,90,223,1,132,210,159,164,26,64,183,103,41,249,131,114,5,209,81,68,97,231,21,108,190,51,229,173,228,41,144,181,78,109,47,109,98,0,98,16,239,224,72,111,87,205,255,239,77,138,148,2,23,112,164,23,167,43,252,89,9,42,193,0,201,237,236,50,236,16,158,99,161,200,172,228,55,250,205,226,225,45,121,106,157,255,182,217,113,65,79,55,137,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,218,230,112,220,183,41,124,124,218,75,15,138,17,240,139,12,12,116,71,180,136,21,224,246,183,130,248,220,91,175,74,125,50,208,232,182,9,177,140,167,97,60,246,188,88,243,75,37,0,118,92,104,108,185,180,105,72,158,170,159,113,86,223,0,44,112,154,141,251,72,46,243,8,36,62,221,235,80,58,67,149,237,4,16,240,74,32,26,62,28,244,207,116,216,57,1,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,98,191,157,223,253,93,9,31,51,216,32,113,230,27,232,211,48,52,175,125,253,21,226,169,101,110,190,162,206,59,48,159,84,54,182,110,245,157,193,17,79,9,197,201,237,102,171,9,75,238,249,36,170,170,94,213,111,166,219,91,203,222,235,4,45,170,117,129,60,115,202,85,67,222,244,208,137,29,188,118,228,80,185,253,72,2,160,191,128,129,29,184,69,249,39,100,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,115,78,211,166,89,155,16,109,139,53,67,77,86,21,148,122,61,63,162,131,76,152,117,148,2,19,158,238,99,115,54,134,111,235,207,33,199,9,23,83,74,44,206,52,41,138,252,197,145,187,81,102,52,129,82,172,165,69,156,221,50,252,26,3,46,119,156,133,53,167,104,157,223,67,137,36,0,96,206,221,158,154,60,85,158,173,197,114,227,183,151,216,196,154,199,153,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,242,17,46,155,239,203,26,144,136,175,183,180,27,250,41,208,186,118,23,85,80,41,119,154,235,195,27,64,119,191,132,6,89,62,225,45,156,220,217,238,168,36,104,242,254,8,42,86,99,83,138,248,52,98,6,2,38,47,67,49,126,204,230,4,48,59,158,195,131,237,195,88,142,105,8,198,6,39,37,148,175,56,202,145,239,138,254,197,169,60,177,201,129,182,184,125,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,78,18,149,61,56,215,127,199,76,81,186,38,103,140,3,225,56,174,163,20,82,154,151,167,99,156,235,182,249,104,33,212,125,172,61,13,184,9,62,22,1,11,138,234,235,167,209,52,158,191,120,105,124,55,14,3,110,209,79,92,42,81,246,7,48,177,185,219,178,118,87,20,138,120,15,129,57,204,19,7,108,241,195,59,113,255,56,37,85,150,108,173,13,104,61,127,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,58,174,55,82,3,54,248,32,213,244,56,181,240,152,32,214,69,4,231,251,4,202,244,145,153,248,203,249,73,121,86,171,87,171,171,66,33,50,179,95,126,56,157,140,72,176,139,166,1,115,98,41,168,123,200,179,20,113,22,30,134,79,58,15,50,72,18,85,8,105,138,35,143,83,64,60,253,199,98,127,103,44,233,248,207,147,250,114,206,141,200,119,107,63,207,160,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,139,79,223,41,74,188,83,35,93,144,33,29,62,174,252,74,101,100,184,16,173,137,79,143,245,209,134,58,162,137,185,190,87,204,136,162,195,202,180,57,37,15,46,159,98,120,221,160,250,106,172,227,153,239,232,79,173,244,231,251,169,35,45,3,50,248,191,15,55,179,93,157,178,160,15,186,76,162,0,81,238,164,154,217,210,237,217,157,87,68,189,150,59,44,111,102,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,176,134,235,12,37,45,144,54,59,160,247,26,202,120,187,200,234,60,98,202,120,229,181,200,54,78,98,81,111,55,211,44,91,198,3,147,11,127,131,54,132,18,7,252,222,66,35,78,8,128,81,17,23,31,219,213,82,222,167,84,124,52,243,7,52,9,40,236,156,162,81,114,138,6,243,181,213,231,115,212,23,84,212,96,62,239,240,254,5,47,70,77,76,216,102,3,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,178,159,138,244,250,124,183,205,236,67,0,126,160,85,78,203,189,255,62,121,63,181,216,50,139,47,73,95,102,31,176,36,152,89,55,240,199,211,28,71,184,208,248,80,123,91,51,147,85,65,131,80,107,72,251,151,68,16,101,113,95,89,51,10,52,15,145,0,223,1,151,126,142,126,26,149,4,5,8,45,192,253,138,40,100,116,63,115,80,92,231,121,128,105,56,193,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,231,250,233,58,89,226,66,132,59,11,83,191,145,59,6,28,14,110,9,123,106,186,207,253,168,194,194,62,95,26,73,30,19,109,136,40,67,164,107,107,36,174,92,153,157,116,100,140,44,8,213,222,213,253,238,175,56,111,144,180,146,218,126,3,52,245,113,23,247,46,182,92,204,134,127,22,4,75,110,158,94,12,48,196,50,163,221,68,53,19,219,57,100,80,32,98,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,139,165,89,73,139,31,86,96,209,229,98,90,204,38,86,138,66,102,225,153,146,243,221,249,115,68,27,84,108,246,22,107,213,210,131,50,14,98,127,163,60,161,140,42,180,14,15,76,129,215,179,185,141,71,201,166,159,4,48,226,72,144,172,4,53,111,177,34,95,191,88,237,9,7,207,151,93,64,140,243,69,57,226,205,244,187,156,114,230,2,163,231,205,153,104,20,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,38,224,193,15,44,235,232,4,100,228,96,119,128,169,170,39,122,110,253,179,200,180,43,86,174,157,39,163,151,88,222,57,116,74,39,253,128,96,239,25,72,92,193,78,87,89,249,28,12,152,125,220,127,15,55,247,51,207,112,204,218,213,187,8,53,228,0,167,250,33,205,91,61,51,240,251,204,212,254,212,161,225,91,189,120,87,242,187,135,223,194,140,154,213,250,214,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,126,145,255,245,98,46,34,228,10,223,137,111,39,118,191,44,198,239,153,49,219,221,143,190,74,59,158,232,35,59,196,210,215,172,82,238,41,77,54,75,126,246,117,228,84,5,90,184,237,167,218,108,176,135,35,104,74,202,61,37,32,184,207,0,54,68,214,199,192,232,154,78,223,99,49,225,122,20,124,23,77,6,250,207,137,26,3,98,66,216,232,160,99,59,254,106,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,36,137,167,34,142,6,1,185,209,56,49,49,113,132,72,94,97,242,198,85,73,203,61,25,48,185,48,231,185,93,130,91,198,175,117,169,64,11,5,98,251,161,48,192,245,126,244,218,134,142,29,60,27,210,147,89,187,97,200,120,224,116,61,2,54,195,186,155,5,246,129,78,13,233,163,171,30,16,129,65,65,108,149,215,132,18,43,118,238,82,227,176,232,200,106,186,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,232,16,33,6,134,194,248,211,142,66,51,164,223,234,7,77,174,119,121,254,56,175,144,216,195,89,150,20,162,41,66,186,58,6,49,170,188,151,188,136,234,193,130,44,89,206,255,40,122,20,26,147,244,145,174,166,143,217,21,84,60,51,86,13,54,201,231,10,75,243,138,172,204,180,148,119,64,50,147,86,69,152,137,21,166,178,188,120,148,183,62,48,44,54,253,104,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,225,226,74,120,206,59,148,11,163,15,143,12,103,37,19,61,72,84,156,5,190,157,1,155,238,179,107,109,45,117,178,5,202,62,94,235,127,143,106,178,100,79,192,136,207,70,38,177,228,247,127,79,239,93,149,137,15,34,140,52,194,195,80,12,54,205,30,235,238,174,169,38,147,104,55,182,93,233,132,9,196,105,210,67,139,156,209,63,154,236,217,130,143,35,32,248,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,154,39,227,247,232,224,93,158,158,46,93,131,170,148,240,151,60,124,173,175,250,76,178,55,33,157,29,42,180,180,11,146,152,132,48,223,33,237,186,33,162,70,191,227,175,148,94,22,223,92,83,8,113,43,185,75,223,222,235,174,217,181,213,10,55,142,190,233,147,101,188,203,39,82,19,125,245,176,4,224,53,87,176,45,115,6,94,98,206,142,244,242,255,32,103,248,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,175,203,34,6,133,243,85,69,234,163,80,211,211,75,239,58,23,129,43,6,233,252,240,15,232,43,223,68,117,241,249,246,134,157,34,191,24,220,164,185,53,66,83,50,99,61,34,187,27,176,48,203,0,36,61,10,124,52,62,254,251,236,1,7,59,4,151,71,193,100,88,184,170,40,188,55,29,108,125,214,130,250,61,85,14,190,193,6,233,218,24,205,134,157,132,247,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,114,98,50,243,22,116,166,7,72,206,85,193,195,8,115,220,135,42,135,39,51,168,192,124,26,167,69,21,243,227,167,248,214,56,151,26,114,70,224,248,242,95,237,98,251,11,77,189,22,186,106,94,29,240,107,191,222,204,120,24,16,138,195,8,59,67,36,21,111,239,240,179,81,63,207,141,191,214,57,169,151,172,80,42,49,75,134,45,251,145,101,246,13,102,27,7,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,28,114,231,93,88,137,149,108,112,202,37,183,31,151,30,180,199,213,175,52,177,102,165,30,54,62,21,69,239,139,44,249,103,240,11,49,251,135,232,243,72,39,113,93,231,186,200,60,1,16,33,5,165,237,187,250,137,110,139,142,108,152,208,6,60,252,125,4,146,5,96,91,203,123,197,137,20,84,254,145,218,154,89,104,236,216,160,56,14,134,136,19,219,6,145,182,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,132,50,251,45,101,211,48,128,180,114,188,95,243,5,129,36,232,95,244,170,35,162,51,7,230,101,227,185,205,108,73,218,24,244,88,247,106,52,104,195,130,196,59,11,89,65,52,74,69,194,88,85,223,170,182,32,84,35,247,80,98,19,16,5,61,136,116,49,21,37,218,87,9,63,177,238,46,244,82,159,22,64,30,222,193,83,119,241,114,21,251,174,81,104,58,216,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,106,199,27,46,240,212,197,23,8,168,48,239,140,0,164,152,92,134,105,115,250,25,238,64,55,156,154,121,254,168,12,246,170,185,37,125,197,207,113,253,53,231,10,80,108,103,52,7,214,8,48,187,139,241,64,40,185,135,154,128,246,203,116,8,62,5,244,225,49,115,117,42,185,150,19,246,121,76,60,45,122,210,213,127,138,182,106,133,22,117,235,203,0,44,216,51,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,149,121,199,203,0,228,64,62,140,51,174,216,0,148,105,248,17,111,217,203,230,9,196,105,144,218,203,192,32,237,182,38,43,156,150,155,148,225,68,178,178,216,27,172,77,90,152,116,199,45,197,133,248,176,22,31,243,110,136,254,219,48,26,10,66,94,55,138,65,254,37,239,147,225,131,125,255,227,85,142,52,138,67,98,81,141,121,79,40,37,96,64,105,88,165,163,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,62,43,67,54,113,178,221,72,109,22,220,241,42,125,18,246,89,122,165,7,27,141,180,132,55,12,2,211,130,144,249,6,252,115,76,17,58,43,96,146,253,199,158,21,82,237,210,16,188,163,155,250,244,3,200,15,173,195,165,178,0,178,66,7,69,159,153,200,190,36,18,172,173,15,124,242,85,45,133,1,230,14,111,22,97,77,103,208,70,64,119,110,87,176,79,137,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,86,179,160,67,168,11,195,129,206,248,88,105,49,206,122,109,134,111,180,16,66,35,133,52,47,95,204,242,93,21,179,39,150,170,92,229,103,187,218,171,60,152,102,163,139,169,227,21,6,60,251,121,241,103,118,164,60,91,249,208,130,169,196,7,72,46,235,100,44,85,81,192,138,175,116,162,59,72,7,188,96,30,1,1,105,162,55,137,121,231,206,117,228,212,48,29,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,200,215,177,113,109,222,241,19,51,129,241,149,95,207,137,0,89,75,84,51,224,153,31,102,154,237,117,166,238,165,131,60,86,38,182,153,29,117,106,205,31,85,143,101,29,224,139,5,15,65,101,83,240,51,229,27,118,32,234,183,40,227,242,7,76,118,174,145,203,131,87,179,236,167,11,142,176,203,12,128,206,9,196,58,138,106,139,137,225,250,168,64,67,68,181,197,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,39,222,224,242,13,114,250,183,3,8,56,188,252,175,220,148,181,5,95,8,158,52,95,81,152,13,79,197,228,129,207,241,24,217,174,128,184,193,78,10,223,248,111,44,18,251,21,77,231,209,242,205,31,112,28,220,21,88,105,119,78,94,20,14,78,5,226,243,131,152,126,46,165,14,75,68,250,155,3,233,130,200,110,99,244,167,69,86,58,97,243,252,75,255,11,126,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,76,140,128,89,195,64,89,11,118,125,100,233,45,13,86,254,235,143,86,175,163,39,101,251,129,95,211,163,149,230,248,212,46,121,81,198,6,9,123,206,79,37,119,62,143,219,27,7,65,26,18,134,14,65,130,162,52,30,182,192,245,196,234,8,78,68,209,38,122,62,22,83,197,100,69,154,152,11,33,219,75,223,237,182,94,109,121,101,65,69,54,30,0,195,136,4,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,92,147,34,55,140,47,154,219,62,58,49,180,3,230,191,156,22,230,100,162,14,8,134,57,18,145,50,177,181,102,148,224,55,132,182,130,207,245,135,27,121,16,93,184,205,142,167,174,228,113,236,203,255,221,49,172,224,22,196,235,11,146,115,1,79,130,220,148,24,212,54,149,178,177,160,221,227,128,152,222,109,164,123,195,180,150,212,168,206,192,46,90,30,185,214,231,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,102,98,87,141,211,248,142,139,215,108,198,188,149,179,92,224,205,206,7,146,137,99,34,1,42,109,170,69,115,12,95,38,40,81,32,2,157,64,10,37,87,144,232,152,15,191,70,51,15,55,185,250,223,191,130,54,47,170,49,91,174,185,162,8,80,110,190,114,81,40,212,168,171,137,113,79,129,65,179,150,47,255,6,216,152,47,80,236,92,194,166,229,98,197,135,246,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,126,234,221,91,255,164,123,204,1,55,234,37,115,162,156,139,201,184,194,96,174,72,126,23,148,117,98,59,176,222,226,5,152,158,46,179,179,184,186,120,74,191,215,183,212,64,253,33,210,61,121,93,124,143,61,79,135,30,168,56,203,43,182,9,81,160,212,2,59,135,191,9,152,149,127,166,56,221,57,96,195,185,106,117,63,82,204,78,76,87,238,44,82,149,162,167,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,212,205,183,81,173,148,234,212,167,240,54,33,74,67,6,68,115,43,84,200,102,201,220,95,29,149,202,104,173,48,70,245,80,144,10,249,120,191,27,45,46,186,123,103,93,10,246,114,196,41,64,211,127,67,54,24,236,220,128,58,3,32,192,11,81,185,205,193,50,157,183,166,110,54,73,131,136,135,68,120,60,68,67,15,110,102,183,154,144,144,164,217,107,217,253,5,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,9,195,54,56,149,75,129,103,253,250,95,87,168,135,216,189,101,246,220,140,163,12,255,118,75,75,112,161,225,251,244,179,192,226,147,67,87,245,254,123,29,150,83,27,7,135,158,205,73,127,131,111,125,202,141,250,238,3,50,139,63,222,228,10,83,156,106,156,243,151,136,254,239,151,63,72,245,76,45,196,134,61,59,40,181,175,78,8,87,148,113,5,106,38,44,96,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,9,191,158,40,202,242,195,251,238,51,49,49,120,47,109,144,166,109,250,246,162,131,104,113,232,13,32,44,48,157,44,97,7,25,7,173,191,143,20,65,81,25,238,222,54,83,59,6,71,225,128,171,155,154,149,76,84,138,86,72,28,10,200,11,84,103,21,221,128,110,108,193,41,48,199,61,91,162,157,149,123,148,230,221,216,190,67,50,2,90,148,170,166,126,19,143,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,32,104,109,98,251,189,100,28,114,7,204,26,182,182,220,242,27,77,177,167,198,1,200,136,242,18,185,6,191,90,197,174,245,145,95,226,7,31,138,205,132,180,39,121,202,100,9,189,170,48,151,62,201,56,27,160,168,192,28,131,20,57,228,15,85,67,139,228,200,119,38,78,86,131,168,114,49,126,117,110,187,29,63,186,164,77,7,53,51,15,106,237,217,132,243,99,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,144,27,55,193,141,38,207,246,164,218,156,157,155,112,50,65,226,4,61,51,89,40,157,212,169,143,31,91,207,100,92,73,35,28,210,9,160,173,56,186,250,211,242,148,223,68,136,14,95,30,128,86,169,185,224,175,56,250,227,65,36,255,206,7,87,15,231,96,163,52,1,137,112,138,211,53,16,113,20,228,166,78,2,24,12,25,87,126,252,53,152,18,247,154,95,185,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,221,19,153,251,125,238,150,211,102,48,237,139,174,130,5,52,102,236,209,180,66,156,70,37,185,236,211,98,194,33,227,235,128,187,160,205,136,104,21,221,88,57,165,98,97,133,121,80,243,113,178,189,42,175,36,136,44,202,203,185,112,42,200,9,87,208,173,207,150,154,57,167,208,162,90,137,141,177,146,93,65,59,83,137,84,146,220,72,35,28,207,197,11,103,46,220,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,56,160,98,5,128,201,182,16,11,59,216,203,251,97,33,181,27,196,221,172,144,161,57,52,143,30,110,131,147,156,201,171,134,171,202,138,255,112,242,174,59,202,111,67,235,122,226,246,138,22,44,42,65,214,47,39,165,72,7,22,78,245,197,13,88,90,135,128,153,154,175,50,192,92,15,64,139,77,202,214,217,16,227,107,106,207,51,248,155,84,207,5,126,107,240,219,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,230,117,10,96,202,98,26,47,172,19,149,34,70,112,59,241,115,90,59,142,140,224,58,168,130,102,205,34,22,224,183,67,249,123,193,86,217,92,146,214,2,254,235,134,118,179,45,78,122,118,138,124,140,232,237,110,47,103,229,1,52,131,223,2,89,109,129,224,3,155,158,246,38,99,69,135,131,73,253,233,33,201,76,32,209,107,118,146,255,193,176,137,128,124,189,146,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,26,169,179,91,107,44,22,252,91,136,16,180,162,31,95,182,63,52,14,80,104,7,217,77,44,90,233,79,251,49,182,17,125,26,200,15,220,43,109,219,41,216,130,21,42,155,202,200,237,163,146,115,49,118,53,219,156,122,69,190,138,255,138,0,89,228,108,245,208,48,195,115,43,136,190,18,4,229,213,145,166,44,228,102,6,172,80,201,74,145,62,32,199,7,221,196,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,4,77,16,222,55,139,59,35,146,204,122,43,119,21,190,8,181,133,101,136,17,232,97,217,150,91,178,232,238,216,119,1,34,232,207,5,86,220,18,153,34,79,121,153,117,193,10,125,224,44,126,153,117,133,97,105,240,160,128,68,73,218,125,3,90,30,136,12,204,42,6,21,214,101,92,52,44,169,200,63,6,232,145,191,73,39,102,210,163,62,90,252,143,166,103,0,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,169,247,105,175,195,220,212,152,51,146,12,116,218,215,245,95,55,213,94,251,150,218,123,176,38,206,172,217,195,235,165,233,217,58,6,237,113,38,94,12,217,35,13,251,106,132,187,117,227,45,246,27,185,57,178,237,21,4,19,183,193,9,57,1,92,145,198,23,37,142,116,92,38,65,246,75,98,59,13,222,223,180,68,247,234,205,130,137,247,87,54,51,107,62,97,25,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,159,159,231,44,23,52,165,72,57,252,224,113,185,29,39,211,243,201,149,103,130,135,208,98,104,14,7,143,158,226,101,209,154,26,162,41,197,225,233,49,65,118,31,134,210,213,101,237,32,51,178,167,182,196,241,225,8,133,71,218,182,199,139,10,92,207,96,221,78,122,180,121,6,244,93,113,4,110,229,252,215,201,253,131,220,70,87,133,44,175,242,157,115,129,82,61,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,55,102,105,197,176,64,226,103,105,80,72,70,106,149,245,97,215,93,147,126,187,128,212,104,250,133,60,50,114,5,46,51,14,69,115,122,113,6,106,165,8,31,218,107,189,136,79,64,182,238,23,81,121,39,16,167,30,104,163,254,35,86,127,8,92,225,59,138,94,252,33,201,253,19,168,189,71,65,84,213,43,239,21,96,252,202,177,106,222,233,128,60,207,172,16,122,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,22,33,163,69,115,37,46,82,14,146,34,143,219,255,24,207,85,5,226,241,254,164,20,239,82,12,107,70,97,253,40,67,169,50,15,73,186,111,44,151,69,202,61,187,231,187,49,26,20,163,21,101,25,28,168,237,244,235,59,61,107,106,80,2,94,123,82,238,192,193,86,33,75,43,191,186,124,5,156,235,214,219,60,69,131,90,102,94,15,106,163,86,33,215,120,140,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,42,190,91,88,129,165,37,69,149,212,72,246,166,125,177,59,132,79,149,255,173,100,218,223,159,158,214,121,37,115,110,103,138,26,155,220,6,146,208,66,29,215,170,217,250,190,33,59,184,201,38,118,145,16,200,46,83,55,193,52,129,173,194,4,100,174,88,35,106,156,100,99,130,184,251,125,39,226,9,205,217,253,254,74,60,66,207,3,80,39,133,187,252,37,215,112,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,52,144,223,76,33,168,190,206,161,68,171,86,130,95,93,69,143,206,34,185,99,21,161,158,248,69,68,209,239,35,232,62,10,2,237,25,150,103,235,9,220,88,3,100,214,156,210,157,18,9,22,74,198,195,91,156,193,182,163,227,141,15,97,8,100,201,157,128,107,236,3,3,138,161,117,128,207,20,55,228,61,182,135,39,130,23,71,184,154,192,81,10,96,105,155,167,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,140,88,217,50,140,135,9,250,241,235,180,208,228,67,80,74,152,234,163,11,96,184,172,14,118,129,105,169,153,215,101,53,185,54,207,92,12,18,97,215,114,189,26,15,208,185,26,155,18,76,183,48,68,245,249,60,126,78,172,47,55,3,235,8,101,52,23,136,9,161,255,208,122,173,192,11,115,34,108,112,23,72,221,198,148,147,90,175,51,96,232,105,11,181,64,75,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,246,185,119,52,109,240,200,198,14,193,73,58,225,229,168,160,186,228,150,161,101,5,209,253,64,154,6,160,42,215,188,231,182,252,125,26,145,160,47,88,8,28,18,53,233,8,25,222,192,155,243,236,111,210,53,164,132,22,142,191,66,219,18,12,102,30,2,172,121,10,58,122,249,191,247,154,44,201,74,117,5,155,181,237,46,172,139,90,39,191,1,133,215,44,153,87,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,252,69,105,39,10,47,168,33,26,174,193,4,18,6,209,211,91,9,147,241,23,17,56,14,55,200,166,157,197,176,231,157,205,112,70,106,95,15,220,189,9,55,197,5,49,221,230,14,29,76,206,220,60,235,244,187,222,152,11,159,157,33,131,3,102,59,200,85,137,25,12,113,107,243,161,61,87,158,112,57,148,168,135,19,104,150,165,195,61,121,70,159,156,199,147,224,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,47,200,206,12,56,225,2,18,176,75,248,151,148,212,56,62,246,12,183,213,136,239,23,63,65,124,31,86,196,182,210,81,81,76,150,82,207,132,203,46,159,117,33,113,202,78,39,103,146,151,208,69,150,99,187,49,238,197,149,31,224,88,200,7,105,112,55,177,188,254,108,249,228,161,95,224,78,192,79,63,131,248,26,65,245,201,40,120,142,172,172,185,50,159,140,250,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,204,236,96,61,104,60,84,59,69,240,86,243,147,43,160,36,132,202,15,252,192,177,28,146,11,208,36,98,83,47,186,81,164,231,2,149,247,164,200,63,112,120,217,180,4,196,228,232,249,161,0,98,148,130,246,133,255,50,228,100,168,138,118,13,106,85,151,35,230,1,67,138,166,58,93,230,73,180,96,185,247,194,130,223,218,78,203,157,190,196,171,64,187,194,90,203,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,191,154,187,79,249,43,87,0,32,68,41,3,232,170,195,60,152,10,50,221,234,91,71,103,230,170,140,106,249,213,144,197,77,73,50,138,163,226,194,105,211,182,172,66,74,26,36,196,246,146,55,70,39,2,108,19,122,239,82,229,73,56,71,1,107,93,46,216,154,148,35,77,58,75,249,24,165,221,194,1,183,75,19,12,115,87,137,68,120,243,245,132,207,57,91,28,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,95,123,117,255,144,183,5,10,118,68,179,191,33,211,36,37,141,94,15,103,165,252,124,244,99,133,231,3,11,102,219,171,199,238,104,82,231,178,252,32,51,143,138,41,128,240,140,50,11,46,144,93,19,238,61,9,150,127,134,167,175,68,234,1,110,182,119,196,39,231,244,38,199,240,193,177,128,52,217,179,237,39,120,254,14,28,124,53,178,172,167,119,107,65,184,114,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,96,139,47,42,91,37,76,44,136,155,0,89,208,76,53,228,183,223,204,230,20,14,97,11,83,172,24,29,111,217,124,177,215,124,246,0,58,189,208,105,215,138,84,81,220,54,106,90,111,85,180,56,82,170,26,231,161,239,164,13,230,101,76,2,113,58,63,114,36,134,253,8,151,89,151,58,14,170,13,205,79,181,88,111,92,16,53,51,66,235,60,130,8,177,32,232,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,90,81,89,118,208,148,154,133,247,123,149,255,180,139,18,200,28,139,252,115,3,147,179,190,245,132,95,219,87,89,167,15,148,199,11,187,205,198,151,133,48,0,161,80,32,131,174,0,160,93,44,124,41,204,119,70,85,247,13,196,169,32,133,2,114,88,64,33,9,83,36,183,227,48,213,67,46,209,197,210,67,121,32,36,135,28,139,124,14,250,71,218,104,17,16,17,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,53,19,230,250,19,230,182,41,124,119,163,9,50,149,106,161,148,17,97,237,17,208,186,124,170,191,143,86,21,135,115,12,35,131,40,184,66,163,203,142,248,177,124,188,101,205,120,143,10,248,204,133,32,149,94,133,152,81,9,179,67,27,240,10,114,115,124,50,5,34,75,73,223,146,251,161,253,159,159,142,141,199,121,123,110,38,148,243,28,173,142,141,62,98,130,55,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,9,219,182,253,3,240,203,251,69,62,247,157,134,8,5,127,130,33,6,201,239,154,206,250,153,119,247,161,153,252,29,12,206,132,54,105,69,88,154,137,154,43,121,74,153,164,73,196,97,41,33,84,136,163,44,66,18,107,203,182,247,188,2,6,115,36,49,128,171,38,174,113,186,10,171,195,150,128,195,209,116,45,241,42,81,37,244,83,39,52,15,255,213,165,52,120,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,149,164,110,160,232,138,122,27,184,38,104,72,176,74,30,67,198,89,14,155,134,249,211,13,81,27,18,163,84,126,170,140,108,24,66,239,250,25,139,148,250,196,139,186,83,35,108,195,164,172,120,144,58,140,230,188,23,19,192,222,38,83,252,12,115,76,53,75,11,211,64,91,15,197,169,39,155,61,118,226,114,2,244,172,198,243,219,71,67,4,93,207,219,74,223,193,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,184,54,9,144,201,189,234,175,2,157,164,114,188,251,37,214,47,163,246,254,127,91,95,135,1,103,106,208,66,86,38,91,212,69,106,240,76,170,146,236,116,71,117,142,116,65,22,38,145,177,11,53,46,136,120,235,132,187,29,213,243,186,219,13,116,12,114,233,85,254,199,118,251,35,118,136,115,225,113,10,137,214,174,194,220,234,93,98,67,65,101,26,202,159,205,70,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,145,132,128,213,74,25,160,51,115,86,155,1,230,35,143,166,50,197,77,59,26,239,13,237,198,160,150,113,161,244,45,177,247,250,47,72,95,76,146,48,19,85,126,1,72,112,138,49,199,248,155,173,131,223,153,184,148,76,13,78,186,95,48,8,116,128,106,14,177,156,150,245,148,14,32,52,26,165,58,21,184,224,68,111,46,159,160,220,232,172,131,40,245,192,94,147,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,25,21,3,42,79,40,183,47,29,105,59,236,112,109,134,202,215,13,245,233,105,45,152,84,163,114,154,14,254,87,233,34,36,225,37,59,129,149,43,160,14,15,167,56,175,35,120,154,36,161,88,105,227,225,107,75,191,137,16,236,85,145,105,5,117,252,104,139,80,16,69,91,148,195,78,210,115,185,90,127,63,58,209,227,174,189,255,229,192,197,134,98,179,166,254,114,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,69,97,178,205,213,210,169,189,14,86,54,188,184,52,247,89,245,29,183,197,151,127,14,58,176,228,0,7,179,156,144,28,178,17,221,170,86,221,144,111,38,146,243,178,79,142,7,53,24,33,170,55,53,152,17,218,215,220,227,88,117,167,11,0,118,195,0,61,126,195,135,222,40,34,244,93,243,47,209,140,28,193,130,36,211,129,192,183,32,218,194,16,138,221,77,59,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,246,210,84,251,199,247,94,198,152,139,248,218,207,56,233,2,205,111,76,21,78,11,244,137,228,6,13,63,214,170,223,232,233,226,27,213,187,171,19,214,72,112,206,242,83,101,253,77,144,155,239,34,123,241,8,29,137,88,249,24,116,58,106,0,118,245,36,180,89,119,173,68,121,12,117,144,65,131,7,115,64,184,67,232,8,23,144,44,252,138,143,109,92,0,182,120,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,142,30,173,163,38,85,44,10,205,4,194,71,170,55,103,251,134,68,170,232,236,170,183,130,57,143,187,161,88,38,221,96,69,202,177,80,30,20,77,236,174,53,238,141,27,125,19,138,36,124,73,199,29,186,40,213,45,254,65,91,209,222,171,15,121,229,186,199,191,20,208,255,201,162,208,234,197,90,162,130,125,127,40,68,89,116,98,109,158,251,234,239,86,164,79,21,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,3,122,164,151,20,179,239,199,182,90,125,221,65,12,105,138,35,111,68,119,224,119,238,75,32,24,158,239,103,182,187,47,249,156,76,241,148,228,142,118,223,182,70,239,125,11,2,34,102,232,253,26,109,14,233,29,137,100,170,243,185,163,45,13,122,2,29,106,112,11,214,136,113,60,54,131,217,217,204,8,243,4,113,35,29,114,146,235,108,166,50,4,6,157,144,67,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,181,206,240,145,161,16,90,30,54,110,168,222,3,48,171,44,207,79,160,156,40,196,212,61,71,178,136,44,29,226,48,23,218,124,35,117,88,83,172,198,48,55,50,228,4,249,24,110,0,20,122,53,100,23,67,126,221,192,66,183,93,206,116,3,123,169,207,217,241,19,225,16,171,67,78,246,103,139,70,77,17,75,254,111,151,13,178,99,87,195,223,202,20,155,249,37,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,24,227,97,255,85,109,157,60,213,39,106,226,120,107,239,3,118,174,214,34,224,47,172,111,91,85,192,251,164,249,37,245,57,67,58,61,49,160,166,105,71,238,237,14,240,152,56,37,231,221,205,154,91,178,105,148,221,121,174,246,129,44,50,9,123,233,161,82,149,161,32,189,51,15,7,233,41,88,0,224,108,188,33,101,222,204,115,111,157,95,142,197,128,239,173,21,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,129,61,81,212,129,123,19,125,255,167,19,87,152,222,14,9,196,113,39,38,47,214,62,48,138,53,234,47,118,128,211,79,39,2,248,255,59,4,0,95,189,175,255,213,21,52,239,117,230,184,208,106,15,68,243,7,220,141,242,235,61,220,7,10,124,83,222,184,139,26,159,119,47,208,214,21,86,210,103,120,61,183,227,118,94,34,254,101,64,4,140,88,14,187,146,161,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,195,230,239,177,23,92,182,241,155,74,155,114,79,118,116,240,104,155,141,126,191,196,106,219,2,232,77,97,120,183,14,146,108,76,215,84,253,1,78,223,185,58,95,72,193,14,20,233,96,227,234,108,88,181,210,255,18,124,46,161,22,212,244,11,124,181,137,172,82,22,48,22,225,87,163,192,17,2,226,31,117,64,7,39,33,171,26,225,95,6,218,142,83,21,50,247,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,0,229,219,104,255,14,137,101,53,95,55,121,245,103,7,251,215,225,190,141,17,30,100,148,144,117,210,84,68,18,250,250,197,202,241,228,214,136,138,190,172,193,87,229,73,56,131,39,22,221,170,91,99,157,123,120,207,116,135,69,134,142,4,14,126,150,158,85,159,134,14,110,234,49,191,136,211,165,131,126,32,83,248,188,142,57,49,89,112,19,105,164,2,237,34,114,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,85,242,195,108,184,201,23,91,60,118,200,27,58,41,28,131,105,251,198,70,46,125,232,23,209,47,246,72,15,14,203,177,138,145,5,214,141,199,184,242,191,100,2,86,253,58,110,210,203,177,93,10,19,49,152,38,154,63,174,156,121,13,110,4,129,162,212,41,152,253,199,202,74,13,125,34,46,109,176,116,179,96,96,158,150,193,254,151,233,215,45,72,82,153,0,36,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,76,37,154,189,174,114,41,218,210,114,24,51,140,233,40,238,110,203,59,197,114,87,28,57,59,193,50,64,83,228,25,147,140,171,118,175,218,44,132,11,97,38,49,133,39,152,136,165,68,25,65,134,116,14,242,18,249,213,226,4,28,197,173,10,133,8,254,198,238,139,158,255,188,193,78,192,141,57,183,236,225,49,55,152,127,20,82,158,45,107,52,149,220,1,227,192,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,210,224,82,102,94,201,64,79,223,45,59,255,160,164,144,16,253,190,4,129,182,107,206,250,78,150,4,198,57,51,136,220,185,106,212,135,151,71,60,69,250,130,136,182,204,29,235,88,252,150,121,129,49,208,218,126,67,166,173,76,101,153,36,5,133,200,50,54,62,131,42,191,122,113,26,96,200,177,20,240,59,146,61,166,74,240,56,109,33,245,73,95,112,111,220,4,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,185,78,1,94,49,221,213,210,113,152,124,81,164,230,196,227,217,177,125,75,67,169,72,91,214,160,82,199,219,179,196,142,13,127,225,2,221,21,212,108,165,31,194,211,101,154,227,111,180,213,175,1,247,247,239,248,246,178,118,104,148,205,75,4,134,70,247,29,241,124,120,210,117,145,233,221,124,25,101,228,42,34,4,116,112,165,94,216,96,206,130,220,180,24,69,121,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,58,131,227,183,138,119,233,55,218,170,197,173,42,50,199,67,40,195,10,68,149,200,41,225,88,4,8,49,210,62,214,21,126,71,156,149,161,201,73,194,147,75,70,236,65,189,50,76,75,125,64,49,165,160,237,157,2,63,251,180,45,202,227,14,135,9,90,72,187,98,27,35,114,167,82,21,75,85,37,103,144,138,116,157,248,48,99,88,133,65,15,30,112,92,162,167,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,150,157,118,49,65,177,2,130,181,85,69,54,68,217,2,167,252,179,12,173,115,51,122,1,158,222,87,233,168,22,85,3,228,90,57,32,145,126,44,166,144,240,83,158,44,92,6,208,104,65,205,123,120,90,101,72,36,59,246,4,153,97,196,9,136,24,244,60,23,47,167,211,115,68,176,223,7,79,231,121,93,166,17,19,99,78,222,85,133,82,94,73,29,82,138,6,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,23,196,123,78,93,95,106,95,206,38,218,34,198,72,127,6,193,81,135,204,23,185,153,53,56,224,16,74,150,240,70,22,42,77,185,156,17,208,191,6,74,242,190,52,2,222,103,231,136,105,100,166,94,194,220,61,20,151,78,66,144,88,212,8,137,22,203,76,121,87,157,66,189,222,80,58,142,240,115,192,130,237,210,32,165,133,26,117,237,35,4,157,3,61,28,109,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,155,150,158,80,59,117,203,237,203,200,126,33,46,61,76,195,238,78,95,152,78,70,55,253,140,134,10,129,144,161,62,229,82,80,64,53,30,155,185,9,18,195,213,111,93,121,155,227,46,228,89,110,118,9,163,127,117,33,104,219,82,31,68,11,137,142,230,88,176,10,171,3,223,197,8,194,3,158,144,246,156,196,195,62,147,190,20,218,215,63,66,201,29,45,207,164,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,68,22,227,217,235,241,218,121,184,252,246,245,150,118,211,252,45,138,156,207,98,150,205,44,17,248,165,6,152,69,40,30,176,241,51,240,201,195,235,71,94,17,182,2,126,173,57,49,76,11,20,94,167,178,199,81,179,150,114,134,72,63,150,1,137,146,170,7,15,0,149,189,176,201,92,37,122,6,138,65,103,155,62,38,12,66,142,106,210,196,116,203,250,141,57,187,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,62,65,59,75,86,28,144,202,3,58,226,57,158,107,28,175,126,217,81,181,117,178,77,196,135,39,100,155,174,132,95,82,146,228,27,230,207,121,52,202,162,209,164,230,212,236,39,53,122,235,214,80,122,116,247,100,140,118,164,195,16,77,124,10,137,151,219,19,122,162,174,199,44,113,87,89,193,155,31,187,133,40,94,95,112,59,99,98,159,249,211,98,32,212,194,250,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,68,47,38,49,0,218,22,246,180,29,103,171,126,178,88,56,193,217,2,195,244,140,187,177,254,117,173,239,19,41,155,175,23,100,140,94,232,142,86,138,64,127,144,253,111,238,247,11,122,183,156,83,76,221,52,7,11,228,42,111,249,75,68,0,137,177,65,188,138,138,25,31,70,243,52,184,243,233,190,40,71,130,60,144,87,31,31,185,145,238,74,210,110,196,202,103,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,19,110,208,141,126,240,215,234,128,151,178,68,147,91,6,90,162,2,192,167,194,116,139,50,159,77,9,189,34,10,31,93,187,179,123,2,19,58,8,112,75,171,158,219,86,197,100,190,115,2,4,7,181,35,215,67,123,51,82,179,157,3,14,8,138,56,73,198,202,190,80,130,233,14,196,77,59,141,152,22,176,27,213,119,213,254,207,177,9,165,71,165,203,16,208,51,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,167,112,139,106,168,180,249,107,155,58,217,70,239,13,90,114,88,98,242,47,58,87,152,138,225,26,217,177,89,107,44,13,218,109,208,74,214,143,130,122,248,97,3,138,49,225,191,138,132,157,115,252,29,232,127,51,84,156,232,151,81,109,56,5,138,229,106,127,118,75,129,168,157,252,206,155,181,56,143,194,109,195,203,48,47,145,192,183,217,146,63,129,244,4,32,29,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,174,182,31,55,23,93,68,74,179,207,25,85,97,219,176,144,7,176,186,76,253,146,102,153,92,208,226,133,177,97,244,215,204,126,16,97,21,161,34,227,178,223,1,10,204,78,227,76,201,121,102,154,6,97,214,121,110,182,9,221,162,148,135,8,140,244,24,81,43,125,77,36,83,176,218,83,183,233,222,62,50,134,33,100,182,37,17,163,3,216,79,4,252,51,76,228,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,22,209,99,252,69,214,181,191,139,25,176,146,218,216,194,50,241,239,147,74,124,114,105,167,242,56,63,106,167,149,19,33,199,150,218,241,236,131,158,33,169,54,29,249,108,9,229,144,157,133,76,217,89,179,244,192,106,166,17,173,198,151,52,15,141,122,205,54,174,220,229,42,232,145,183,40,72,170,212,28,253,50,167,85,213,108,184,191,76,194,175,125,71,46,245,15,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,49,206,132,54,127,242,211,121,66,49,107,207,215,91,41,166,209,190,16,6,22,245,77,201,108,211,253,14,177,90,185,194,68,24,123,207,58,238,248,227,84,40,53,252,204,16,62,2,209,32,100,127,30,68,80,223,13,146,85,29,24,149,182,4,142,124,125,188,102,169,53,15,160,202,79,199,67,242,67,19,154,145,59,236,62,55,54,136,86,232,110,94,57,91,74,94,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,47,103,233,27,134,55,122,83,81,196,186,153,15,103,205,23,59,232,205,202,66,69,8,244,113,5,161,172,35,173,231,212,184,22,197,18,74,107,111,114,142,49,129,161,127,132,10,179,55,165,196,82,138,122,157,50,76,189,33,71,177,156,61,0,143,18,196,85,171,243,177,40,50,213,31,129,47,110,37,50,9,223,139,199,60,55,230,88,136,102,162,183,79,167,207,60,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,88,188,217,198,36,108,212,34,41,74,13,94,33,120,144,1,152,240,210,103,126,50,153,146,252,19,85,125,240,181,139,163,109,134,212,72,20,15,130,84,114,239,144,70,142,32,235,140,162,48,177,162,34,139,213,234,36,113,202,204,74,203,120,9,143,38,6,136,127,160,161,161,77,207,187,178,170,240,150,185,21,142,183,171,69,226,175,12,174,142,162,22,7,153,186,38,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,79,184,56,223,186,129,97,37,217,34,26,3,61,220,45,48,133,249,46,60,3,76,80,155,193,246,224,43,31,40,7,80,181,58,241,201,114,188,239,125,190,25,175,109,43,174,165,203,121,71,74,166,157,61,88,120,151,161,204,124,88,112,232,15,144,105,254,124,9,109,10,209,205,231,124,136,71,7,104,60,50,173,118,158,246,59,42,196,54,45,245,147,118,125,60,81,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,71,116,14,147,83,83,31,197,128,59,23,158,223,249,209,241,63,163,242,190,148,98,240,109,95,111,92,39,87,54,146,179,213,122,157,63,249,189,235,82,37,237,159,136,235,82,24,244,235,150,107,21,107,131,210,129,128,251,193,225,187,103,130,4,145,15,104,172,4,87,214,4,96,205,169,187,216,85,156,192,196,166,41,15,235,3,202,121,152,138,155,15,62,10,3,216,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,215,4,237,42,221,32,44,216,143,157,123,33,143,250,202,25,162,177,109,161,149,116,111,230,35,179,114,157,203,204,10,71,209,163,108,59,43,138,114,74,90,39,85,10,236,79,171,38,138,221,90,191,118,219,153,92,58,232,193,244,93,1,88,15,146,144,222,182,51,79,122,62,154,126,196,58,141,133,196,45,166,137,230,48,33,212,12,237,172,197,57,219,13,102,14,96,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,109,15,112,188,144,104,13,84,232,113,121,30,80,32,151,87,142,212,48,175,117,89,80,182,188,209,59,176,8,234,85,189,105,225,23,147,197,182,230,199,79,194,58,143,68,208,219,30,216,102,223,95,55,101,252,39,109,220,191,197,124,56,244,9,147,138,10,106,28,50,231,53,37,24,229,145,137,27,84,46,84,34,30,206,108,212,59,234,17,163,54,42,230,63,109,178,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,65,255,83,208,131,158,187,72,239,80,83,80,37,68,192,110,135,47,232,86,79,97,91,170,66,247,181,24,212,195,168,12,69,177,157,249,92,224,74,246,97,133,123,227,124,207,96,53,187,3,90,199,208,38,228,182,140,6,93,236,217,107,181,15,147,226,118,215,248,176,66,196,161,129,48,176,133,235,43,182,164,192,96,149,147,75,156,219,141,88,131,100,46,233,182,188,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,58,51,248,221,6,39,157,79,176,28,214,28,17,23,252,78,89,41,134,202,5,27,56,242,254,55,75,148,39,252,89,79,202,178,203,219,18,5,127,163,105,98,186,16,198,215,213,87,94,204,254,63,124,34,229,217,2,97,117,169,121,140,232,0,150,8,95,39,246,38,37,72,125,114,213,167,19,111,253,225,215,137,61,197,39,195,169,211,86,136,156,195,163,98,0,168,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,211,48,177,57,237,180,222,151,206,50,77,70,132,34,254,22,221,96,84,183,187,114,54,101,80,187,100,69,174,207,234,246,175,11,238,180,166,22,174,47,204,127,120,84,184,214,5,21,10,71,198,165,67,159,131,136,52,188,154,230,29,246,130,0,150,211,108,146,136,208,131,117,179,213,71,195,220,143,149,43,177,19,127,213,195,8,123,184,187,203,213,184,251,227,172,204,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,120,247,45,92,91,80,129,228,157,253,90,95,223,139,180,57,44,223,107,14,166,86,152,216,96,18,81,45,171,59,114,90,133,216,131,225,18,76,183,177,169,49,235,121,91,176,241,176,125,62,194,101,45,129,94,30,43,115,126,80,69,117,142,2,151,150,127,125,223,153,197,154,3,16,116,77,208,34,203,200,60,113,236,59,184,173,71,64,68,215,28,79,93,79,71,120,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,145,244,65,200,25,61,104,21,163,89,61,212,229,150,242,208,117,177,142,56,210,18,44,209,222,202,138,128,184,76,39,29,122,46,38,250,95,45,205,10,110,122,29,64,51,198,196,214,114,184,153,15,203,143,77,157,171,141,38,92,104,185,143,2,153,128,46,92,225,224,27,151,185,27,248,184,245,198,241,142,194,6,232,27,74,91,117,100,36,102,219,81,230,89,18,160,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,52,242,168,190,71,53,240,102,73,133,128,192,115,78,219,162,16,182,29,134,62,20,167,184,134,78,241,17,43,3,54,220,51,177,128,200,195,59,255,32,241,17,247,102,189,90,149,95,197,133,100,172,162,96,89,195,198,166,43,93,133,82,169,8,153,145,151,77,146,35,130,118,243,54,35,217,20,215,134,198,4,224,2,34,16,185,66,175,248,112,156,67,199,65,171,206,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,164,253,141,247,213,204,109,179,88,50,64,213,192,29,189,145,195,43,194,120,107,211,120,238,164,165,253,184,200,96,121,159,169,94,235,66,194,134,12,149,174,21,19,123,255,26,157,166,118,133,110,249,136,8,202,235,127,234,21,47,210,202,175,5,154,143,18,185,216,83,24,207,127,147,192,89,158,219,62,57,115,6,103,15,90,89,177,16,113,201,82,159,135,216,115,38,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,63,255,128,212,173,1,227,68,43,207,228,170,32,255,54,91,89,240,43,247,202,18,203,18,24,209,72,57,126,60,13,49,172,83,230,123,86,40,179,221,8,105,181,221,130,170,178,125,52,173,118,158,104,27,15,222,40,64,62,59,73,204,254,0,155,95,228,26,209,197,31,48,193,136,210,66,94,33,11,94,222,24,175,23,43,173,107,177,36,26,13,184,125,200,102,173,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,254,147,110,254,70,74,64,124,5,144,190,58,68,30,90,244,153,170,211,248,219,100,98,67,131,17,224,233,46,99,185,13,121,16,191,172,193,38,66,132,73,224,169,212,182,18,166,46,197,235,194,36,22,37,13,13,211,110,177,69,68,179,25,11,156,92,171,127,118,229,170,121,69,199,27,243,57,111,114,197,105,30,36,201,126,187,47,84,173,253,42,74,189,88,237,254,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,248,83,119,220,199,81,229,253,88,55,125,46,253,94,0,166,51,42,7,202,86,240,100,110,228,97,184,11,162,51,178,45,10,48,242,192,242,241,215,119,179,229,9,119,62,140,135,37,43,187,42,213,213,65,236,36,142,121,216,122,248,143,195,14,156,95,132,15,240,5,180,47,223,84,198,101,163,49,246,201,163,186,103,211,140,169,81,43,35,226,100,108,66,12,225,190,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,122,115,188,64,228,210,221,243,79,199,186,26,110,173,122,12,81,135,169,235,33,225,252,236,63,23,159,2,9,48,56,6,195,145,70,207,241,244,38,151,235,209,184,53,20,56,60,48,237,69,110,181,5,29,251,217,212,204,198,239,170,161,31,5,156,202,227,101,95,241,224,225,226,76,119,49,88,203,142,157,244,227,191,140,130,228,128,27,56,241,31,137,165,138,115,238,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,60,154,131,48,59,0,129,159,149,230,194,3,245,90,186,168,80,136,212,80,57,196,60,240,133,112,174,177,160,13,32,100,50,116,121,191,249,45,228,136,214,36,52,47,218,235,94,88,69,151,79,233,41,189,96,204,204,70,37,254,202,91,204,10,158,129,121,206,74,216,48,38,84,93,194,201,156,39,196,92,34,80,158,71,187,243,50,90,105,119,103,76,127,155,254,19,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,247,161,73,64,160,60,252,165,174,183,199,245,224,127,55,175,101,249,20,93,148,178,222,172,138,73,226,100,37,5,63,85,219,213,207,75,162,73,147,220,135,246,246,87,187,197,118,181,22,249,154,6,243,139,82,204,218,136,240,177,15,71,113,0,159,4,6,157,203,159,90,162,85,171,35,52,62,180,159,152,147,94,224,74,202,51,249,90,238,13,113,131,225,174,61,44,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,168,72,109,35,230,223,68,6,5,88,97,17,146,149,250,250,178,23,176,103,19,44,12,116,57,98,190,81,37,107,59,108,78,168,178,132,116,4,57,135,44,241,207,242,199,54,78,123,88,34,73,194,227,132,132,84,250,5,8,222,166,36,210,3,159,5,31,162,131,142,53,102,27,126,62,198,254,91,139,211,238,178,61,25,28,110,44,144,139,21,1,112,66,108,59,85,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,201,89,46,95,72,86,89,202,207,17,144,232,116,170,232,197,214,188,69,136,3,26,231,33,144,173,165,154,124,182,175,115,2,33,2,135,41,96,5,5,154,45,188,185,178,89,128,211,24,212,112,252,29,107,40,16,223,130,60,129,26,62,45,9,160,205,253,87,78,13,115,214,14,119,176,60,138,188,169,118,25,208,185,218,74,36,44,70,195,107,93,168,91,144,188,132,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,22,198,52,219,166,251,191,245,111,109,19,99,116,142,24,141,46,54,124,237,209,248,36,78,61,148,188,64,183,179,32,44,140,238,68,201,52,179,81,145,67,96,216,173,61,191,158,205,55,175,137,12,183,65,148,89,187,86,181,157,13,117,94,9,162,132,125,229,177,124,200,200,134,94,86,7,112,129,77,211,82,214,191,37,243,21,179,163,159,38,235,215,104,129,9,150,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,143,163,208,238,202,24,175,171,243,67,82,123,3,190,121,19,96,167,71,254,227,227,66,33,177,150,99,111,104,209,6,254,89,195,78,0,112,57,59,202,44,140,214,53,9,215,117,200,91,6,41,98,106,98,33,84,232,116,111,229,120,163,139,0,163,172,117,121,201,46,152,181,231,116,255,235,192,196,59,17,91,87,72,192,12,95,233,62,154,218,206,218,109,126,214,225,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,40,2,9,186,0,165,39,37,52,161,231,248,232,48,148,156,90,177,165,30,1,222,223,24,147,88,209,214,3,29,132,60,145,173,141,204,30,183,146,111,29,195,193,170,58,78,54,7,59,88,245,179,69,126,92,241,39,106,147,95,28,124,166,9,164,83,133,61,178,167,18,248,108,174,103,82,48,132,126,155,183,74,83,126,16,44,62,158,188,61,207,148,244,241,246,255,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,194,30,61,237,0,18,250,15,159,16,173,39,112,200,139,133,176,171,239,78,120,56,97,226,209,153,238,89,107,114,151,252,144,153,194,36,103,209,152,219,175,88,213,239,56,182,216,164,76,130,33,140,32,163,250,31,162,69,234,173,13,5,1,8,165,11,2,190,5,170,66,106,167,164,28,115,176,171,201,107,87,189,221,15,74,151,5,194,222,198,174,201,243,234,167,172,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,19,208,198,98,99,229,4,244,130,225,5,96,233,202,50,28,247,174,250,219,150,161,6,227,35,220,161,219,70,80,94,233,212,160,103,106,56,51,97,145,178,42,112,63,47,146,31,224,129,234,34,224,28,8,212,100,210,86,40,223,229,129,31,7,165,119,81,156,72,111,152,206,129,87,140,94,150,198,76,192,194,156,95,235,216,104,176,227,128,75,85,36,47,235,208,144,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,122,40,185,191,234,186,223,157,79,169,241,72,57,78,77,127,144,81,167,96,226,251,104,22,251,138,113,14,161,99,254,159,50,53,232,38,20,68,233,72,222,241,91,9,8,0,233,74,67,196,26,232,181,88,215,157,216,90,51,165,226,141,120,1,166,15,168,169,248,13,47,78,35,33,8,162,139,172,116,149,89,131,100,88,55,30,49,72,113,221,184,74,151,105,206,120,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,252,126,142,20,56,122,221,185,157,104,53,70,248,171,161,219,89,139,50,208,66,146,212,250,0,136,65,100,57,155,197,15,91,107,79,150,66,12,7,237,110,36,116,246,58,199,218,104,33,93,179,22,34,47,142,106,246,56,47,25,89,47,198,5,166,229,184,21,159,169,223,57,141,76,41,242,127,133,94,200,63,86,237,22,21,252,105,201,187,91,136,84,30,93,147,29,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,227,41,216,231,15,99,29,157,76,154,24,23,192,202,74,96,177,141,67,148,135,197,227,204,198,102,194,44,7,116,74,240,47,185,98,205,7,34,254,177,238,44,110,102,227,234,113,45,219,63,125,39,167,202,220,142,29,83,24,226,125,133,130,13,167,37,52,236,17,139,63,35,185,36,24,250,6,47,174,210,151,200,17,115,69,121,130,172,95,165,17,5,148,109,212,201,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,151,122,205,63,74,88,71,39,175,232,108,83,114,217,155,85,75,225,5,64,134,187,225,237,251,169,108,17,244,224,174,162,110,104,251,199,18,50,23,136,228,2,101,8,241,192,8,146,132,66,177,250,118,51,18,9,144,69,62,112,56,107,222,15,167,135,70,50,64,150,2,80,104,80,235,120,193,229,29,13,144,180,86,176,247,168,74,226,211,245,61,109,159,60,145,5,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,154,13,217,57,74,87,238,158,5,100,128,76,223,25,170,19,21,15,186,206,249,136,41,97,237,222,190,168,212,219,96,245,206,90,84,251,141,127,253,153,6,81,15,71,99,77,66,240,94,192,15,99,229,105,13,136,199,44,212,235,150,241,5,5,168,30,103,143,10,222,170,156,90,183,217,106,128,56,30,71,246,1,217,188,109,65,11,151,14,88,193,117,226,84,215,78,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,249,221,176,19,155,104,50,244,239,111,23,21,183,70,99,21,30,215,216,185,100,77,206,194,244,81,126,127,223,86,44,53,60,248,217,182,73,210,204,245,7,245,14,111,34,251,200,247,32,37,216,232,171,70,231,196,13,46,106,88,63,192,194,4,171,176,4,130,124,104,12,189,61,207,169,33,137,193,220,201,21,0,229,114,84,165,66,133,179,206,165,37,64,133,129,2,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,2,193,228,128,139,159,231,33,124,97,147,142,226,118,221,165,110,41,179,103,169,154,104,109,206,98,150,117,48,107,114,130,212,255,19,49,229,67,122,235,111,88,107,58,89,10,241,95,131,0,43,56,82,231,170,89,253,239,211,190,245,247,6,13,171,196,94,24,194,108,210,210,16,193,138,146,222,157,216,202,178,139,141,181,55,47,56,61,124,101,149,140,179,248,43,220,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,98,85,171,121,67,207,56,243,242,165,140,221,196,54,149,3,86,137,87,221,155,88,192,98,30,111,243,213,37,180,107,211,57,180,175,174,57,119,174,241,38,3,95,187,164,158,211,34,95,163,151,84,210,97,108,18,194,81,228,194,122,186,7,4,172,31,70,104,121,131,145,167,211,169,152,193,203,8,1,216,221,233,93,49,181,242,182,216,195,207,161,254,45,19,82,72,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,71,174,86,234,215,208,254,89,55,174,216,200,248,40,236,137,96,106,120,66,235,189,160,226,181,165,239,187,32,109,236,182,192,110,2,17,184,236,141,236,221,237,85,143,86,59,243,232,86,61,71,122,132,144,180,63,15,169,43,33,178,26,53,3,173,165,29,33,57,139,76,49,90,239,148,149,14,165,87,58,249,205,3,96,182,153,208,105,242,49,56,68,73,196,106,238,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,5,197,230,243,225,128,103,102,60,82,214,236,142,67,208,173,23,7,145,70,236,32,117,114,36,194,135,45,74,33,74,32,108,124,208,194,223,20,233,237,55,121,186,198,170,230,16,167,96,32,247,42,147,166,200,178,232,53,105,66,226,234,28,1,173,172,167,133,157,232,225,61,237,164,40,121,118,205,120,162,63,226,192,198,162,204,54,87,145,190,179,172,233,75,184,250,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,96,154,223,100,145,168,162,172,176,133,13,135,7,38,185,75,217,33,27,13,160,5,192,112,160,73,41,135,35,193,20,154,206,124,102,194,71,69,78,158,249,170,101,12,178,13,254,110,218,133,236,215,115,17,104,17,148,205,164,42,106,188,109,0,176,175,99,166,237,90,54,76,244,147,95,198,160,113,66,50,172,204,98,77,238,94,107,203,145,112,255,237,191,153,150,59,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,167,90,14,108,197,102,159,54,9,63,38,254,230,105,181,210,7,251,130,26,201,92,206,52,49,66,80,229,249,55,120,79,81,14,165,11,151,218,209,15,225,102,19,166,1,62,117,87,50,153,206,71,70,10,117,121,172,136,98,47,76,244,108,0,179,5,197,255,77,174,61,198,163,115,0,244,141,168,95,199,232,18,207,16,13,48,227,104,217,180,230,7,186,156,214,151,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,150,216,4,79,32,213,149,14,28,112,118,38,115,194,106,111,191,128,45,215,145,190,193,128,97,116,193,118,248,96,172,215,168,164,43,212,135,234,92,229,218,178,153,145,63,113,204,168,23,21,140,186,204,200,248,213,8,187,201,105,235,194,105,14,180,41,90,187,55,223,74,59,211,91,116,228,253,67,97,84,129,44,18,241,54,148,92,106,110,116,93,67,140,34,89,141,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,236,81,234,109,82,17,177,152,245,6,22,36,19,101,52,139,54,114,143,114,167,131,20,184,51,210,185,9,90,167,190,162,235,220,225,238,61,120,184,47,33,43,251,180,193,227,227,12,165,150,105,13,147,18,89,162,242,31,247,88,94,78,239,7,181,207,218,77,40,118,188,249,149,56,164,134,18,120,38,7,56,17,124,165,242,81,178,231,243,209,90,132,118,39,42,145,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,64,46,158,220,173,159,10,144,147,98,176,67,191,48,137,246,28,222,240,18,145,50,9,33,34,24,135,182,150,79,99,219,179,240,217,220,38,184,188,18,134,43,100,178,150,81,143,116,221,67,151,47,6,118,70,148,124,105,138,229,0,16,132,4,182,222,204,176,124,151,92,133,238,140,36,55,220,58,210,24,75,198,148,148,250,243,71,65,177,195,199,156,239,186,94,60,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,110,75,75,170,114,98,75,92,201,176,80,101,205,86,47,151,33,5,46,5,69,246,215,140,246,52,243,11,220,136,208,48,240,254,218,94,228,245,213,223,157,72,242,143,186,142,149,160,196,168,22,173,13,195,173,43,77,33,121,39,145,238,135,13,184,158,43,137,83,13,152,189,118,43,10,9,61,66,131,28,80,2,230,4,16,225,1,164,30,5,238,59,129,97,190,143,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,40,161,113,226,207,44,178,7,67,128,235,166,188,174,164,216,82,198,218,182,209,41,173,161,222,240,8,128,98,254,216,147,63,65,232,203,224,171,198,150,179,72,95,1,204,134,86,105,181,65,158,244,23,4,20,73,211,14,130,196,112,2,32,14,186,35,121,6,190,16,172,142,156,32,147,7,202,51,247,50,88,176,72,226,104,76,250,149,221,209,73,172,39,141,98,192,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,45,99,180,65,36,55,2,125,64,78,13,112,13,45,188,59,195,45,236,17,108,6,158,5,118,149,145,111,102,128,51,188,162,75,255,38,127,62,69,226,173,129,56,10,86,98,226,215,46,199,54,132,40,79,169,141,145,70,75,81,180,109,29,12,186,91,78,46,129,14,231,152,79,157,155,226,32,176,70,227,107,18,163,43,244,99,122,62,13,63,244,171,156,36,32,187,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,190,159,169,17,43,244,218,194,130,212,106,199,85,66,22,147,36,162,189,200,164,253,99,81,104,108,184,104,117,73,122,72,143,31,188,151,80,9,169,232,241,108,90,27,156,24,126,130,146,96,132,206,91,245,185,18,151,46,20,4,51,43,221,15,186,193,92,179,0,90,136,24,209,251,208,127,39,146,242,122,132,222,76,167,91,214,2,23,43,138,16,207,140,205,233,94,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,34,25,59,189,93,66,186,144,99,242,187,62,161,239,168,30,197,13,196,253,226,83,54,18,14,103,135,149,157,198,67,150,16,218,38,67,140,21,244,227,125,159,148,156,220,130,240,36,226,214,86,95,204,4,32,245,56,114,25,106,12,197,246,0,187,51,201,241,28,121,134,219,146,234,28,232,84,68,170,200,235,28,24,250,170,68,61,45,248,214,170,122,30,96,130,19,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,99,139,131,108,17,65,11,231,216,96,83,14,224,188,2,196,2,194,91,24,199,138,139,134,235,32,57,96,3,18,34,183,47,211,5,195,87,10,57,34,253,72,132,70,83,50,21,166,216,74,11,51,93,38,140,214,19,27,14,143,243,244,131,12,187,116,21,70,137,164,114,90,161,162,239,121,13,47,31,230,202,254,109,236,247,192,75,52,75,3,119,120,54,222,60,232,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,199,176,44,114,176,171,47,159,181,176,236,93,91,52,194,222,31,179,65,130,153,243,50,65,55,57,192,229,31,20,128,175,253,36,93,197,147,247,162,254,219,117,62,14,169,82,167,161,146,97,216,229,187,10,168,106,71,75,181,33,85,74,100,13,187,140,18,36,76,42,226,13,49,119,91,107,128,2,165,88,136,123,233,196,104,213,3,181,131,164,115,11,186,239,172,157,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,207,206,185,193,224,146,226,40,234,169,111,147,141,121,187,37,35,126,87,197,215,226,167,146,132,206,218,54,222,237,212,35,136,78,209,58,0,84,220,189,10,3,20,131,6,207,170,177,235,86,247,113,133,53,36,192,8,164,191,42,24,60,27,2,187,210,38,163,173,66,208,189,255,89,79,137,136,38,64,45,8,123,172,162,93,18,112,63,17,92,69,89,243,89,105,142,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,58,9,250,180,127,118,19,16,84,240,162,248,222,96,227,246,200,206,167,68,102,160,229,93,77,116,124,46,68,137,202,196,179,167,52,140,65,58,101,7,67,228,40,74,80,14,121,99,140,115,29,93,245,175,212,137,105,128,151,129,228,32,40,3,190,20,99,81,67,87,145,172,94,9,241,0,159,36,211,222,235,112,26,70,5,74,147,184,0,225,184,155,9,165,165,197,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,7,116,154,26,95,251,183,241,255,97,26,213,85,109,63,81,4,92,84,43,190,13,8,193,77,144,142,52,25,34,249,25,74,35,163,229,70,127,54,148,64,239,232,152,31,155,19,11,237,176,82,204,71,46,38,123,167,72,225,71,143,233,118,9,190,144,34,86,111,47,192,237,229,116,19,241,21,25,170,165,34,24,99,141,231,13,43,232,186,45,199,250,74,107,79,95,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,238,186,146,163,21,18,215,164,29,22,76,158,169,121,218,43,223,223,162,217,113,31,54,153,221,226,47,230,147,248,155,44,202,223,255,30,83,67,137,41,49,8,132,50,167,5,179,195,201,94,97,13,116,43,59,127,137,67,114,138,240,170,135,14,190,198,48,62,169,24,23,81,174,114,72,85,42,85,67,155,105,139,229,62,129,67,202,44,14,211,41,16,92,36,253,56,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,23,17,157,97,100,12,211,166,109,66,87,253,144,237,245,86,157,66,78,154,95,135,79,185,157,92,36,212,118,41,159,24,180,127,124,46,84,219,166,172,27,121,51,104,144,202,218,219,229,12,146,73,216,129,206,157,67,57,80,41,104,248,41,13,191,156,58,248,100,127,41,78,130,212,84,77,15,177,22,94,175,156,51,176,188,78,52,203,18,153,109,85,112,85,165,198,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,159,190,54,245,142,201,171,63,184,68,101,216,82,137,160,111,238,181,173,157,103,132,27,13,145,122,39,170,197,37,31,40,32,59,120,43,14,2,140,247,96,87,48,245,214,12,221,142,79,241,55,60,180,55,145,45,170,223,4,0,62,59,33,13,192,138,194,114,194,115,51,110,72,5,90,38,45,137,148,228,142,77,45,41,174,229,46,113,142,87,220,200,204,65,14,177,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,46,24,141,59,182,197,65,106,253,214,107,207,4,221,160,106,34,194,245,86,72,227,138,10,133,22,246,192,160,67,166,160,210,158,39,95,11,217,108,47,223,115,44,65,203,177,126,239,67,189,90,122,176,171,242,121,75,107,49,212,86,190,7,0,193,87,218,17,72,227,254,192,9,142,165,45,16,168,151,11,111,76,85,6,250,161,65,51,207,238,204,8,32,229,214,122,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,248,37,98,245,211,238,226,116,79,247,218,48,251,107,120,213,148,36,123,38,209,241,55,43,103,189,204,142,175,102,141,237,105,16,166,24,137,60,176,225,70,97,103,57,103,228,75,55,236,253,14,94,88,193,197,94,242,41,65,116,40,0,33,9,196,3,215,128,237,47,162,185,12,139,182,82,182,106,175,82,111,96,182,176,9,99,176,61,33,35,164,55,148,195,149,98,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,83,38,152,240,1,109,57,182,14,233,174,96,194,224,7,159,166,54,240,252,88,28,197,5,242,118,179,59,85,120,79,12,58,118,185,116,163,91,204,210,69,174,201,223,250,134,93,154,120,114,187,21,242,80,220,163,6,57,57,116,99,181,220,13,196,46,168,68,180,158,180,88,183,117,243,7,219,235,185,242,152,48,169,139,168,90,124,167,34,169,41,125,142,185,81,29,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,56,223,113,228,254,79,113,238,174,206,20,27,193,42,140,55,86,189,115,158,186,218,124,159,208,73,124,122,73,32,230,2,85,68,67,142,178,232,22,148,254,238,141,1,46,70,148,179,57,99,151,7,180,53,100,209,51,207,205,100,63,24,233,12,197,170,233,56,88,13,168,129,249,163,135,203,117,144,242,59,231,112,156,129,35,153,2,63,147,16,33,61,80,47,178,145,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,78,151,115,163,22,217,127,92,106,12,105,214,10,101,235,252,178,244,104,169,87,208,33,50,9,204,46,226,27,150,174,70,253,9,164,59,225,10,150,189,219,21,146,208,248,202,224,154,61,95,115,40,147,139,194,173,8,245,169,252,242,108,27,11,197,203,161,78,177,117,51,125,215,172,94,208,37,25,252,195,116,249,229,198,11,111,218,226,140,181,234,162,148,36,3,178,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,178,11,36,84,11,72,69,9,152,152,191,249,120,97,101,5,29,222,7,71,106,148,113,173,199,188,1,124,90,57,212,254,246,138,246,101,163,25,59,238,103,162,222,236,35,105,109,24,119,179,48,213,25,95,125,42,246,50,145,51,175,7,68,2,200,76,63,62,248,224,45,67,86,132,187,211,98,55,27,77,203,232,130,29,120,202,100,239,231,110,45,133,48,45,121,205,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,221,179,9,130,148,36,3,94,70,82,214,4,249,23,25,39,103,52,60,243,238,213,223,42,14,198,116,113,84,82,252,105,2,171,64,216,83,118,125,112,68,191,223,46,150,100,68,87,166,55,89,236,117,237,54,54,2,167,139,134,84,238,167,15,201,50,188,21,234,165,253,25,233,245,57,28,31,18,105,35,8,64,185,240,89,120,137,61,218,12,125,183,134,172,228,22,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,40,103,190,117,26,211,100,155,254,250,94,217,186,63,70,163,20,111,197,232,150,153,118,11,59,249,231,175,133,183,246,6,238,194,99,86,22,160,208,161,138,30,103,176,211,147,44,115,105,44,129,113,44,81,133,18,165,195,41,28,104,134,82,12,201,93,201,210,160,112,229,53,12,221,154,135,208,120,150,32,118,24,184,181,250,46,84,115,225,14,148,160,199,11,158,36,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,11,51,9,69,93,12,191,90,185,193,80,138,199,88,177,134,148,63,24,223,175,206,228,238,209,216,9,75,126,141,140,185,249,187,100,81,98,166,222,148,104,156,166,118,103,21,174,137,110,84,252,148,197,119,58,7,100,242,230,218,203,192,14,9,202,78,91,96,221,226,236,135,163,31,224,67,103,152,232,108,60,244,214,110,150,174,236,123,171,82,128,182,99,71,228,188,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,213,174,163,129,143,115,71,54,218,134,43,123,155,161,131,128,187,109,120,120,111,212,91,190,241,48,23,143,165,24,184,64,122,224,74,107,99,226,227,237,15,99,149,51,44,49,231,74,75,8,222,192,211,125,216,45,72,5,27,73,207,35,122,10,206,110,87,196,151,158,110,7,89,178,116,208,212,73,89,148,211,185,238,144,47,128,80,17,3,240,232,52,2,110,139,225,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,37,249,119,189,18,100,221,63,96,107,106,165,179,31,197,192,51,26,106,111,61,179,109,163,27,141,228,156,124,76,209,107,255,130,48,53,70,125,197,18,28,197,138,72,213,196,197,221,10,85,86,19,225,139,119,138,20,89,98,45,81,13,163,6,206,206,183,253,182,169,62,169,19,172,230,0,18,134,82,4,61,202,206,16,146,139,74,36,253,230,53,241,199,43,129,44,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,171,32,51,101,123,79,5,152,107,29,116,7,184,121,251,62,25,199,243,43,196,46,211,28,83,53,108,203,214,212,210,13,81,20,253,240,90,56,161,3,110,176,178,128,184,112,165,26,28,204,176,247,53,220,204,29,174,58,228,147,247,98,207,13,207,100,27,103,118,99,21,96,182,71,104,29,222,194,253,98,130,32,245,7,114,203,5,79,212,106,72,122,238,206,44,157,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,187,211,140,11,97,132,17,150,56,80,148,86,94,154,60,145,160,82,40,152,233,156,247,68,162,212,186,143,201,179,132,119,141,10,248,165,231,44,111,210,220,252,35,156,200,178,132,227,165,27,117,68,90,78,129,163,108,181,100,90,141,248,192,4,208,214,107,158,19,142,114,54,47,200,172,153,148,122,47,129,183,211,161,110,162,229,231,183,232,115,167,73,119,10,202,94,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,64,217,233,160,110,234,153,47,106,123,137,77,213,96,133,109,46,175,230,182,46,240,219,77,139,214,47,120,172,169,249,199,186,134,11,213,245,7,161,146,219,45,115,48,100,13,154,71,3,212,178,12,5,99,38,253,30,56,209,93,222,2,184,5,211,11,59,23,39,117,132,65,170,77,59,200,145,130,238,198,205,41,248,222,151,178,173,120,176,89,214,78,83,105,236,154,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,18,183,234,249,213,91,85,219,106,146,24,148,242,142,162,149,62,10,138,230,29,228,220,11,58,192,15,79,88,42,206,93,35,255,61,227,110,184,190,153,163,26,245,129,214,161,33,87,69,1,133,49,231,129,153,96,134,116,239,15,96,227,166,13,211,161,61,207,23,177,18,89,248,144,248,25,86,178,165,247,170,10,176,125,166,83,235,64,31,19,102,173,200,249,252,208,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,112,27,173,134,122,138,197,209,91,240,101,97,195,124,176,39,233,66,247,76,230,186,115,221,187,192,171,131,229,144,210,75,188,97,146,54,118,255,69,173,68,2,83,113,131,8,162,129,135,223,187,196,149,93,189,119,181,122,228,141,25,7,47,1,214,29,187,163,183,137,99,211,170,202,179,5,27,142,213,52,94,151,161,40,119,131,62,83,211,64,174,52,181,147,77,172,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,241,24,146,144,84,24,133,64,59,6,158,158,10,146,232,131,71,15,118,238,58,37,147,116,252,239,162,37,36,210,89,15,214,180,130,253,12,221,250,70,102,115,230,81,207,80,115,75,45,108,221,230,174,193,197,10,128,56,250,62,35,189,231,0,214,61,104,11,180,163,194,2,39,49,90,251,149,134,43,162,100,170,33,18,21,21,178,147,125,208,88,54,126,65,90,12,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,251,227,78,123,101,60,213,66,26,169,184,92,203,21,201,92,67,145,12,180,145,152,23,35,85,36,39,26,23,4,247,208,61,90,149,212,197,216,41,157,247,175,154,99,82,199,209,90,220,149,236,135,133,18,150,25,242,148,28,191,202,113,96,4,214,66,107,207,167,122,182,5,13,122,78,73,48,201,52,218,24,145,50,71,250,92,52,103,166,194,106,181,134,124,234,249,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,111,248,123,91,250,7,135,63,174,13,130,94,165,85,138,123,173,62,99,131,34,218,169,127,108,214,192,182,10,118,104,11,183,110,66,250,51,148,208,55,219,56,61,96,234,98,245,141,203,249,23,242,64,206,59,170,170,124,81,110,255,119,37,9,215,14,188,175,214,219,154,132,58,163,90,55,78,17,185,100,62,114,168,86,246,81,101,51,166,230,249,51,51,142,183,134,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,205,213,12,147,106,3,10,188,108,77,20,118,66,105,244,68,191,206,57,66,227,159,136,68,143,45,202,170,254,239,45,26,65,99,227,195,191,7,0,61,42,129,135,95,207,133,93,205,34,110,237,15,219,48,21,20,58,101,107,104,61,137,201,8,216,142,212,134,67,43,17,236,73,194,71,248,45,190,110,199,76,228,34,135,161,249,111,78,204,77,223,251,161,189,176,14,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,23,154,253,193,79,75,86,210,162,117,220,152,54,165,52,81,200,48,172,141,188,87,245,52,111,238,208,246,115,213,92,124,15,128,42,162,159,119,124,54,158,93,250,129,65,212,66,9,45,242,29,8,87,162,215,166,243,103,8,131,58,154,246,8,220,84,194,87,134,143,96,234,178,7,128,149,79,183,42,134,107,33,246,67,48,143,124,10,179,182,226,169,244,240,1,6,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,50,146,235,92,106,239,185,200,23,222,109,107,150,183,225,144,210,206,209,125,169,194,70,46,188,170,189,103,184,221,5,132,250,127,201,27,81,198,236,127,90,251,5,35,7,9,181,7,235,77,224,247,202,93,196,114,145,35,63,31,20,236,100,10,221,44,56,57,18,219,228,210,226,88,170,219,42,1,213,170,242,24,230,236,235,91,102,98,52,143,234,15,68,6,156,223,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,252,70,149,35,189,75,159,51,107,96,198,164,231,197,62,155,97,72,131,139,229,148,215,237,84,187,168,63,102,47,210,116,207,234,11,207,235,62,153,218,226,156,161,196,0,83,142,27,191,163,125,21,188,90,168,164,65,10,255,125,216,133,10,3,222,59,3,17,124,5,146,193,187,97,86,207,90,137,182,45,181,190,229,193,169,97,114,168,60,5,122,150,43,5,93,88,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,177,67,193,225,109,233,118,215,29,215,205,207,68,26,65,62,141,246,233,27,188,255,251,93,46,196,178,72,49,141,244,107,120,13,22,130,103,248,252,44,34,113,223,132,149,66,2,192,248,19,53,79,83,194,194,32,183,223,56,55,39,150,11,13,222,89,14,220,224,56,48,42,71,141,53,70,34,153,180,192,80,93,170,245,244,27,162,154,23,144,129,22,224,56,221,76,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,58,17,142,45,130,54,242,118,215,27,206,154,156,124,164,111,140,56,222,98,69,33,228,29,62,18,32,9,36,216,57,31,183,50,159,29,21,128,141,29,231,146,84,81,106,215,100,70,27,189,34,29,162,34,121,69,130,240,49,79,45,189,140,5,223,180,231,15,175,55,224,195,18,46,149,98,128,67,112,128,116,58,5,195,14,208,131,95,122,87,21,129,176,76,119,41,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,6,223,166,47,42,47,245,111,156,135,47,153,89,58,51,163,161,112,21,82,160,211,182,37,143,207,232,110,117,156,31,232,70,85,204,135,64,56,239,0,179,134,36,156,64,64,51,24,201,173,233,203,244,87,173,46,213,172,128,10,133,49,225,7,224,95,102,212,10,230,245,2,41,40,131,255,97,190,230,10,190,159,99,25,118,163,167,136,153,44,2,174,8,238,33,237,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,97,199,226,117,139,248,3,219,123,181,129,199,50,235,114,56,80,232,52,237,220,90,246,252,16,246,159,90,83,23,34,103,166,142,240,135,160,1,222,165,43,195,35,120,76,224,129,140,253,40,216,89,180,155,59,189,67,215,218,49,188,51,157,5,225,50,176,61,232,84,183,179,2,193,10,187,106,152,115,163,68,135,251,130,4,160,170,38,145,85,83,228,143,16,16,20,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,244,192,20,125,152,58,84,79,26,152,79,226,16,163,84,67,110,157,82,105,61,165,66,204,118,168,139,80,216,45,208,117,206,19,220,183,56,61,16,193,47,85,9,32,66,61,57,181,217,20,56,6,127,205,24,123,25,237,37,194,156,185,115,10,227,199,169,248,14,219,7,123,33,151,52,133,238,22,102,58,191,114,44,255,85,175,110,68,161,103,184,97,220,143,167,180,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,184,7,240,196,141,205,161,186,111,182,69,96,9,254,238,132,94,247,121,2,31,9,202,168,253,156,197,34,246,187,214,62,248,3,120,136,220,229,33,82,91,155,148,105,190,95,145,126,47,194,46,139,153,98,119,124,42,206,127,233,37,130,208,1,228,249,220,91,229,184,30,137,96,68,4,38,131,126,214,87,207,76,97,151,210,91,193,31,76,177,35,24,64,15,137,242,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,227,195,184,140,95,78,73,146,29,64,72,106,146,251,127,112,243,64,130,137,116,106,116,115,169,233,164,204,183,121,22,101,28,23,29,152,108,28,190,122,91,179,169,124,155,134,170,79,20,222,23,20,37,109,244,252,42,192,122,156,30,6,17,14,229,62,239,161,174,82,117,78,161,6,228,163,46,209,76,231,25,74,14,205,189,181,91,70,117,48,58,35,12,2,123,117,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,123,236,123,106,152,173,229,39,16,88,11,86,29,54,128,148,70,28,76,77,31,183,35,180,244,99,213,222,12,140,73,75,108,206,162,148,139,11,64,108,78,12,2,247,137,232,22,10,181,199,178,248,32,31,165,218,96,128,160,247,167,175,211,13,230,239,54,14,177,9,176,231,48,199,52,212,135,72,11,181,21,155,30,126,223,50,220,178,214,114,79,195,52,116,99,6,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,58,212,99,51,217,233,125,40,206,5,196,187,114,0,237,222,161,171,250,15,123,132,225,67,109,92,122,89,96,221,212,156,121,108,237,187,120,216,165,110,221,148,142,43,16,118,59,129,133,50,147,22,149,156,205,222,146,76,17,26,95,95,58,5,232,158,169,168,229,239,160,149,4,196,95,27,213,119,189,224,110,193,252,42,230,229,143,134,161,97,59,240,254,205,243,168,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,250,137,37,181,168,53,33,19,34,36,9,241,101,18,24,231,58,111,22,203,75,42,202,56,206,88,189,59,103,102,99,197,3,208,90,164,193,117,7,221,126,150,173,211,70,239,176,171,214,89,0,16,137,134,131,26,58,63,157,187,150,27,221,15,236,24,156,210,61,251,205,81,102,28,174,133,62,109,194,115,51,145,196,55,91,117,254,196,11,55,171,86,187,26,120,28,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,100,180,230,69,164,251,164,5,187,244,157,248,252,218,48,126,105,7,200,109,19,126,169,73,11,77,118,10,131,230,115,20,87,62,242,70,157,72,191,115,131,218,128,68,93,39,80,90,126,180,161,230,76,104,198,184,219,91,23,197,129,51,202,3,236,220,180,220,78,10,145,46,65,145,165,146,26,226,141,186,212,165,14,202,65,8,78,38,254,153,32,69,14,152,150,132,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,54,39,116,18,45,226,14,227,53,248,222,179,110,37,199,112,228,0,238,184,101,99,80,241,74,247,169,193,139,43,11,147,15,123,118,62,15,7,238,164,60,89,194,42,210,108,212,59,134,71,243,77,145,80,110,109,68,81,220,33,75,50,6,6,236,221,206,243,239,73,41,162,10,180,194,171,77,37,47,198,56,205,1,102,88,206,21,145,105,140,213,241,30,106,213,221,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,171,118,150,241,72,101,122,172,111,9,143,142,19,68,222,190,54,82,241,247,162,221,177,57,167,27,163,37,146,143,144,67,227,117,3,72,154,195,153,185,119,98,247,222,68,40,13,7,99,125,214,197,205,226,20,132,171,28,223,197,153,97,44,6,239,240,43,59,118,195,51,25,151,106,132,56,162,165,180,182,131,146,237,145,12,1,223,213,210,184,18,38,213,33,172,222,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,70,74,82,86,19,142,112,20,252,155,230,65,42,68,101,51,121,8,91,46,191,34,138,116,213,33,24,140,34,98,168,110,215,212,60,109,70,165,155,228,150,211,241,66,229,157,33,192,31,189,192,26,80,113,64,41,81,2,85,106,217,113,136,10,242,108,139,64,254,35,166,77,59,240,251,173,39,234,8,237,254,7,13,181,246,95,178,142,194,111,167,67,173,109,246,246,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,153,181,82,97,232,71,173,155,68,19,229,253,136,89,239,249,254,12,177,92,218,34,38,207,127,19,221,154,254,92,117,50,8,8,6,11,41,93,218,161,145,107,148,55,121,223,196,233,183,108,175,223,149,138,251,31,234,198,213,178,84,192,164,13,242,213,182,102,223,246,96,237,88,96,0,203,91,34,54,199,139,62,28,228,101,186,140,46,118,71,186,164,154,29,242,223,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,204,151,110,154,8,6,177,209,126,181,233,163,86,82,91,246,230,87,92,90,123,99,40,195,118,116,203,139,143,151,123,234,35,29,137,138,35,192,77,188,76,210,144,31,227,73,130,238,206,113,51,175,121,154,166,150,145,220,243,17,233,114,127,2,243,213,141,15,38,115,22,213,92,8,202,244,99,139,253,244,231,129,164,75,160,22,90,101,137,166,16,35,112,73,13,137,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,33,205,226,221,103,4,158,42,36,250,81,171,241,209,179,151,215,228,176,31,45,169,159,129,205,126,5,15,98,50,189,156,144,240,226,155,160,218,252,38,154,194,7,91,247,232,98,0,88,168,57,134,126,129,238,21,44,181,235,52,95,86,21,14,244,80,102,195,91,171,198,186,39,183,202,5,109,247,15,4,220,135,125,188,192,189,154,200,201,216,45,86,133,107,21,0,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,243,35,196,84,224,189,82,248,6,168,104,69,167,224,243,116,104,39,155,57,113,228,195,108,9,176,92,89,47,9,175,215,124,177,184,123,13,91,3,156,150,230,38,29,33,253,43,68,31,42,55,58,163,162,192,194,145,148,111,161,230,138,225,9,247,69,18,151,20,22,125,158,100,125,166,119,184,212,83,19,93,158,117,179,239,180,232,106,117,124,98,116,253,243,101,228,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,223,229,200,107,182,171,104,83,225,99,168,22,177,174,45,33,239,2,135,242,88,61,223,206,141,135,12,19,229,117,82,178,7,131,235,42,119,236,80,39,13,147,154,17,92,247,227,101,1,93,35,9,160,98,112,17,158,143,175,73,107,99,117,3,248,137,11,164,85,33,63,58,21,207,20,192,93,91,114,213,249,232,253,228,35,235,82,69,91,195,205,197,95,41,241,252,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,183,213,53,187,1,24,143,4,84,141,204,49,144,153,193,113,100,246,117,81,5,73,219,134,218,130,191,65,128,40,3,129,107,204,236,228,200,221,98,226,27,6,96,246,3,103,169,205,9,13,24,127,213,167,113,66,213,164,26,45,38,170,82,5,249,243,169,159,82,169,153,20,117,219,224,20,7,57,248,22,232,238,67,163,238,5,52,216,225,193,104,215,68,41,59,218,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,232,32,108,96,81,179,23,33,26,144,217,117,62,247,50,42,28,17,6,6,134,63,189,206,156,16,169,73,104,75,199,72,235,28,236,250,185,117,108,158,241,212,56,6,126,127,70,51,162,137,51,201,145,32,253,232,80,119,163,131,160,138,28,9,250,131,30,186,28,103,188,156,185,126,117,254,175,3,167,57,163,170,168,193,23,228,250,89,195,69,190,8,96,10,225,211,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,108,5,203,9,104,232,241,243,226,218,224,174,99,191,102,12,153,209,176,246,88,81,46,192,172,20,119,170,219,54,237,227,195,3,212,15,216,48,223,249,208,100,85,208,183,166,24,92,146,243,145,166,211,76,37,35,100,36,107,176,147,115,252,4,253,124,49,32,43,181,103,60,52,83,128,83,143,31,67,29,44,140,157,43,188,137,47,223,138,182,168,255,121,192,80,233,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,213,227,147,175,97,112,106,81,3,225,88,247,128,216,74,180,140,125,254,75,97,57,226,139,182,195,241,120,55,94,248,228,3,124,143,22,95,233,193,3,91,162,138,26,67,223,149,240,23,41,199,11,53,51,146,90,152,47,46,217,69,13,140,0,254,120,119,50,215,161,206,151,105,228,6,51,81,187,5,138,18,59,225,46,93,78,244,245,217,61,208,202,52,143,47,44,96,41,238,86,196,125,132,206,90,86,22,213,129,129,220,126,45,227,125,242,15,45,35,50,59,207,215,32,214,93,178,154,0,154,43,0,188,174,91,27,222,220,125,129,147,234,11,42,48,67,246,178,194,27,132,254,88,254,94,74,6,208,52,153,254,130,51,229,55,141,196,31,45,166,144,5,206,185,205,218,102,104,70,8,57,151,196,208,14,1,186,7,227,202,24,54,95,84,46,15,255,24,91,67,2,20,136,99,67,21,151,115,88,216,22,197,218,133,32,112,35,89,182,241,210,182,253,184,192,167,84,202,0]}"##;
-        let third_block: SignedBlock = from_str(encoded_third_block).unwrap();
-        let third_block_hex_encoded = "0x".to_string() + encode(third_block.encode()).as_ref();
-        let encoded_fourth_block = r##"{"block":{"extrinsics":["0x280402000b6062dedb7201","0x1c040900f667ae00","0x1004140000"],"header":{"digest":{"logs":["0x06424142453402500000008ac4d20f00000000","0x05424142450101480d0f8bbf3adab95a70c18487da84d18931bfaf1f1bb447eaa442ad442de938ab51df401cace75a6b8863193cc6948203f19ba4533a7a9039802e08718d1e8c"]},"extrinsicsRoot":"0x040226b4b8fa49f1ca610eeed78e4f597026dcc94a12af0efbdf472f5083d932","number":"0x2b9a01","parentHash":"0x6029ee56c47d84ce5a5616d58181dc7e2de37df20f2d23323bcfd720d65db29a","stateRoot":"0x03ceb2aae98c16376d50403ab166577ac63ab4f76d44012e920e78cbc5b3b65b"}},"justification":null}"##;
-        let fourth_block: SignedBlock = from_str(encoded_fourth_block).unwrap();
-        let fourth_block_hex_encoded = "0x".to_string() + encode(fourth_block.encode()).as_ref();
-
+    fn test_binary_inputs() {
         let storage = MockStorage::new();
         let api = MockApi::new(5);
         let querier = MockQuerier::default();
@@ -423,135 +3829,191 @@ mod tests {
             api,
             querier,
         };
-
-        let init_msg = InitMsg {
+        let init_block = hex::decode(&"0x5e9fc49076803d0ba88c719252ede5ae713d09367162d344e9b79ef3aac2efa03e620300fe518cc595e8f5ede8010cf6d26352f6a089ee52f992153a540c7b5d9b659ea272c9c1e535cf5ca49ab2d72059671d80f69c6dba7e6c0dca1e27c3832e873f2b08066175726120448dd10f0000000005617572610101fe734978fa3cb9804346988424124add53316e68e9dcd96a5dfc5a576fe61262031463e0e3a1cdb15538a763dddfbbdf2d3c47e3ecc72deebb3ba5ec59b1168204280402000bc0e95ebf720100"[2..]).unwrap();
+        let update_block = hex::decode(&"0xf157283bcfe5ace5f3258bdb595ee8c6761394a56c8e73b6aaf734e6fb1e7c92426203000ad92ba15285e38e29472d35c29a8e0097e0748fa66fca1b4c834e13f0604de6f7e776ac0632a86d967e1fc4694d51b15c06dadf6c2d0f60a0c661993ffa6d5308066175726120458dd10f00000000056175726101019c9a0a6afd95ff9b8a479bab6676867d19f388b187534394661f0b9ca540b86cd5847174d8b1075f61c01f3b0f5dfa8c643b15c226ebace6aa5aca43cd12ce8504280402000b30015fbf720100"[2..]).unwrap();
+        let authority_set =
+            "0x0488dc3417d5058ec4b4503e0c12ea1a0a89be200fe98922423d4334014fa6b0ee0100000000000000";
+        let init_msg = |block: &str, authority_set: &str| InitMsg {
+            client_id: "testclient".to_string(),
             name: "testtesttest".into(),
-            block: first_block_hex_encoded,
-            authority_set: encoded_authority_list.to_string(),
+            block: block.to_string(),
+            authority_set: authority_set.to_string(),
             max_headers_allowed_to_store: 256,
-            set_id,
+            set_id: 1,
             max_headers_allowed_between_justifications: 2,
+            justification: None,
+            authority_set_proof: None,
+            finality_gadget: None,
+            beefy_validator_set: None,
+            max_consensus_states_to_store: None,
+            retention_policy: None,
+            justification_verification: None,
+            snapshot: None,
         };
 
-        let result = init(&mut extern_dep, Env::default(), init_msg);
-        assert!(result.is_ok());
+        // Malformed inputs are rejected without panicking
+        for (block, authority_set, target) in &[
+            ("0", authority_set, "msg.block"),
+            ("0x", authority_set, "msg.block"),
+            ("0xabc", authority_set, "msg.block"),
+            ("0xzz", authority_set, "msg.block"),
+            ("not base64!", authority_set, "msg.block"),
+            ("", authority_set, "msg.block"),
+        ] {
+            let result = init(
+                &mut extern_dep,
+                Env::default(),
+                init_msg(block, authority_set),
+            );
+            match result.err().unwrap() {
+                StdError::ParseErr { target: t, .. } => assert_eq!(t, *target),
+                e => panic!("Unexpected error: {}", e),
+            }
+        }
+        let block = "0x".to_string() + encode(&init_block).as_ref();
+        let result = init(&mut extern_dep, Env::default(), init_msg(&block, "x"));
+        match result.err().unwrap() {
+            StdError::ParseErr { target, .. } => assert_eq!(target, "msg.authority_set"),
+            e => panic!("Unexpected error: {}", e),
+        }
 
-        let result = query(&extern_dep, QueryMsg::LatestHeight {});
-        assert!(result.is_ok());
-        let query_response: LatestHeightResponse =
-            from_binary(&result.unwrap()).expect("Deserializing Query response failed");
-        assert_eq!(query_response.last_finalized_header_hash.len(), 0);
-        assert_eq!(
-            query_response.best_header_hash,
-            first_block.block.header.hash().0
-        );
-        assert_eq!(
-            query_response.best_header_height,
-            first_block.block.header.number
-        );
+        // Base64 which happens to start with 0x isn't mistaken for hex
+        let bytes = vec![0xd3, 0x1c, 0x04, 0x00];
+        let base64 = Binary(bytes.clone()).to_base64();
+        assert!(base64.starts_with("0x"));
+        assert_eq!(decode_bytes("msg.block", &base64).unwrap(), bytes);
 
-        let contract_state = read_only_contract_state(&extern_dep.storage)
-            .load()
-            .expect("Contract state should exists");
-        assert_eq!(contract_state.name, "testtesttest");
-        assert!(contract_state.light_client_data.len() > 0);
-        assert_eq!(contract_state.headers_ingested_after_last_justification, 1);
-        assert_eq!(contract_state.max_headers_allowed_between_justifications, 2);
+        // Base64 and hex can be mixed
+        let block = Binary(init_block).to_base64();
+        let result = init(
+            &mut extern_dep,
+            Env::default(),
+            init_msg(&block, authority_set),
+        );
+        assert!(result.is_ok());
 
-        // Second header
-        let update_msg = HandleMsg::UpdateClient {
-            block: second_block_hex_encoded.to_string(),
-            authority_set: encoded_authority_list.to_string(),
+        let update_msg = |block: String| HandleMsg::UpdateClient {
+            client_id: "testclient".to_string(),
+            block,
+            authority_set: authority_set.to_string(),
         };
-        let result = handle(&mut extern_dep, Env::default(), update_msg);
+        let result = handle(&mut extern_dep, Env::default(), update_msg("0".into()));
+        assert!(result.is_err());
+        let result = handle(
+            &mut extern_dep,
+            Env::default(),
+            update_msg(Binary(update_block).to_base64()),
+        );
         assert!(result.is_ok());
 
-        let result = query(&extern_dep, QueryMsg::LatestHeight {});
-        assert!(result.is_ok());
+        let result = query(
+            &extern_dep,
+            QueryMsg::LatestHeight {
+                client_id: "testclient".to_string(),
+            },
+        );
         let query_response: LatestHeightResponse =
             from_binary(&result.unwrap()).expect("Deserializing Query response failed");
-        assert_eq!(query_response.last_finalized_header_hash.len(), 0);
-        assert_eq!(
-            query_response.best_header_hash,
-            second_block.block.header.hash().0
-        );
-        assert_eq!(
-            query_response.best_header_height,
-            second_block.block.header.number
-        );
-
-        let contract_state = read_only_contract_state(&extern_dep.storage)
-            .load()
-            .expect("Contract state should exists");
-        assert_eq!(contract_state.name, "testtesttest");
-        assert!(contract_state.light_client_data.len() > 0);
-        assert_eq!(contract_state.headers_ingested_after_last_justification, 2);
-        assert_eq!(contract_state.max_headers_allowed_between_justifications, 2);
+        assert_eq!(query_response.best_header_height, 55440);
+    }
 
-        // Third block
-        let update_msg = HandleMsg::UpdateClient {
-            block: third_block_hex_encoded.to_string(),
-            authority_set: encoded_authority_list.to_string(),
+    #[test]
+    fn test_import_error_codes() {
+        let mut extern_dep = Extern {
+            storage: MockStorage::new(),
+            api: MockApi::new(5),
+            querier: MockQuerier::default(),
         };
-        let result = handle(&mut extern_dep, Env::default(), update_msg);
-        assert!(result.is_ok());
+        let keys = [Ed25519Keyring::Alice, Ed25519Keyring::Bob];
+        let mut simulator = ChainSimulator::new(&keys);
+        simulator.push_headers(3);
+        let initial_block = SignedBlock {
+            block: Block::new(simulator.initial_header().clone(), vec![]),
+            justification: None,
+        };
+        let init_msg = InitMsg {
+            client_id: "testclient".to_string(),
+            name: "testtesttest".into(),
+            block: "0x".to_string() + encode(initial_block.encode()).as_ref(),
+            set_id: 0,
+            authority_set: "0x".to_string() + encode(make_ids(&keys).encode()).as_ref(),
+            max_headers_allowed_to_store: 256,
+            max_headers_allowed_between_justifications: 3,
+            justification: None,
+            authority_set_proof: None,
+            finality_gadget: None,
+            beefy_validator_set: None,
+            max_consensus_states_to_store: None,
+            retention_policy: None,
+            justification_verification: None,
+            snapshot: None,
+        };
+        assert!(init(&mut extern_dep, Env::default(), init_msg).is_ok());
+        let header_msg =
+            |header: &Header, justification: Option<Vec<u8>>| HandleMsg::UpdateHeader {
+                client_id: "testclient".to_string(),
+                header: "0x".to_string() + encode(header.encode()).as_ref(),
+                justification: justification.map(|j| "0x".to_string() + encode(j).as_ref()),
+            };
+        let error = |result: cosmwasm_std::HandleResult| result.err().unwrap().to_string();
 
-        let result = query(&extern_dep, QueryMsg::LatestHeight {});
-        assert!(result.is_ok());
-        let query_response: LatestHeightResponse =
-            from_binary(&result.unwrap()).expect("Deserializing Query response failed");
-        assert_eq!(
-            query_response.last_finalized_header_hash,
-            third_block.block.header.hash().0
-        );
-        assert_eq!(
-            query_response.best_header_hash,
-            third_block.block.header.hash().0
+        // Storage errors keep their codes through the import pipeline
+        let third_header = simulator.header(3).unwrap().clone();
+        let result = handle(
+            &mut extern_dep,
+            Env::default(),
+            header_msg(&third_header, None),
         );
-        assert_eq!(
-            query_response.best_header_height,
-            third_block.block.header.number
+        assert!(error(result).contains("[104] Unable to ingest header"));
+        let mut gap_header = create_next_header(simulator.initial_header());
+        gap_header.number = 3;
+        let result = handle(
+            &mut extern_dep,
+            Env::default(),
+            header_msg(&gap_header, None),
         );
+        assert!(error(result).contains("[208] Unable to ingest header"));
 
-        let contract_state = read_only_contract_state(&extern_dep.storage)
-            .load()
-            .expect("Contract state should exists");
-        assert_eq!(contract_state.name, "testtesttest");
-        assert!(contract_state.light_client_data.len() > 0);
-        assert_eq!(contract_state.headers_ingested_after_last_justification, 0);
-        assert_eq!(contract_state.max_headers_allowed_between_justifications, 2);
-
-        // Fourth block
-        let update_msg = HandleMsg::UpdateClient {
-            block: fourth_block_hex_encoded.to_string(),
-            authority_set: encoded_authority_list.to_string(),
-        };
-        let result = handle(&mut extern_dep, Env::default(), update_msg);
-        assert!(result.is_ok());
-
-        let result = query(&extern_dep, QueryMsg::LatestHeight {});
-        assert!(result.is_ok());
-        let query_response: LatestHeightResponse =
-            from_binary(&result.unwrap()).expect("Deserializing Query response failed");
-        assert_eq!(
-            query_response.last_finalized_header_hash,
-            third_block.block.header.hash().0
+        let second_header = simulator.header(2).unwrap().clone();
+        let result = handle(
+            &mut extern_dep,
+            Env::default(),
+            header_msg(&second_header, None),
         );
-        assert_eq!(
-            query_response.best_header_hash,
-            fourth_block.block.header.hash().0
+        assert!(result.is_ok());
+        let justification = simulator.justify(3);
+        let result = handle(
+            &mut extern_dep,
+            Env::default(),
+            header_msg(&third_header, Some(justification)),
         );
-        assert_eq!(
-            query_response.best_header_height,
-            fourth_block.block.header.number
+        assert!(result.is_ok());
+
+        let fork_header = simulator.fork(2).push_header();
+        let result = handle(
+            &mut extern_dep,
+            Env::default(),
+            header_msg(&fork_header, None),
         );
+        assert!(error(result).contains("[209] Unable to ingest header"));
 
-        let contract_state = read_only_contract_state(&extern_dep.storage)
-            .load()
-            .expect("Contract state should exists");
-        assert_eq!(contract_state.name, "testtesttest");
-        assert!(contract_state.light_client_data.len() > 0);
-        assert_eq!(contract_state.headers_ingested_after_last_justification, 1);
-        assert_eq!(contract_state.max_headers_allowed_between_justifications, 2);
+        let result = query(
+            &extern_dep,
+            QueryMsg::VerifyEvent {
+                client_id: "testclient".to_string(),
+                height: 4,
+                header_proof: None,
+                proof: vec![],
+                event_index: 0,
+                record_lengths: vec![],
+                pallet_index: None,
+                variant_index: None,
+            },
+        );
+        assert!(result
+            .err()
+            .unwrap()
+            .to_string()
+            .contains("[202] Unable to get finalized header"));
     }
 }
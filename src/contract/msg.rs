@@ -1,32 +1,282 @@
+use cosmwasm_std::HumanAddr;
+use parity_scale_codec::{Decode, Encode};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use crate::contract::state::H256;
 use crate::types::BlockNumber;
 
+/// Binary fields of messages, like SCALE encoded blocks, authority sets,
+/// justifications and proofs, are accepted either as `0x` prefixed hex
+/// or as base64, which is how `cosmwasm_std::Binary` is serialized.
 #[derive(Serialize, Deserialize, JsonSchema)]
 pub struct InitMsg {
+    pub client_id: String,
     pub name: String,
     pub block: String,
     pub set_id: u64,
     pub authority_set: String,
     pub max_headers_allowed_to_store: u64,
     pub max_headers_allowed_between_justifications: u64,
+    /// Grandpa justification of `block`, verified against `authority_set`
+    pub justification: Option<String>,
+    /// Storage proof of `:grandpa_authorities` and `Grandpa::CurrentSetId`
+    /// under state root of `block`, requires `justification`
+    pub authority_set_proof: Option<Vec<String>>,
+    /// Finality gadget used to finalize headers, GRANDPA if not set
+    pub finality_gadget: Option<FinalityGadget>,
+    /// Current BEEFY validator set, required by BEEFY clients
+    pub beefy_validator_set: Option<BeefyValidatorSetInfo>,
+    /// Amount of the latest finalized heights whose consensus states are
+    /// retained, independently of retained headers, 1000 if not set
+    pub max_consensus_states_to_store: Option<u64>,
+    /// Policy keeping finalized headers outside of the window of the latest
+    /// `max_headers_allowed_to_store` headers, nothing is kept if not set
+    pub retention_policy: Option<RetentionPolicyInfo>,
+    /// Way GRANDPA justifications are verified, `full` if not set
+    pub justification_verification: Option<JustificationVerificationMode>,
+    /// Snapshot returned by `ExportSnapshot`, restores the client instead
+    /// of creating it, in which case other fields but `client_id` are ignored
+    pub snapshot: Option<String>,
+}
+
+/// Finality gadget used by a client to finalize headers.
+#[derive(
+    Serialize, Deserialize, Encode, Decode, Clone, Copy, Debug, Default, PartialEq, JsonSchema,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum FinalityGadget {
+    #[default]
+    Grandpa,
+    Beefy,
+}
+
+/// Retention policy of headers falling out of the window. Only finalized
+/// headers are kept, unfinalized ones are always dropped.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct RetentionPolicyInfo {
+    /// Keep headers signaling change of GRANDPA authority set
+    #[serde(default)]
+    pub keep_authority_set_changes: bool,
+    /// Keep finalized headers whose height is a multiple of the interval
+    pub finalized_header_interval: Option<BlockNumber>,
+    /// Keep finalized headers less than this many blocks older than
+    /// the last finalized header
+    pub finalized_headers_depth: Option<BlockNumber>,
+    /// Maximum amount of headers kept outside of the window, the oldest
+    /// ones are dropped first, 1000 if not set
+    pub max_kept_headers: Option<u64>,
+}
+
+/// Way GRANDPA justifications are verified when headers are ingested.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum JustificationVerificationMode {
+    /// Every precommit signature and votes ancestry header is checked
+    #[default]
+    Full,
+    /// Signatures are only checked until supermajority of authority set weight
+    /// is proven, remaining precommits and unused ancestry headers are ignored
+    Bounded,
+}
+
+/// BEEFY validator set, identified by keccak merkle root of Ethereum
+/// addresses of its validators, as found in MMR leaves.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BeefyValidatorSetInfo {
+    pub id: u64,
+    pub len: u32,
+    pub root: String,
 }
 
 #[derive(Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum HandleMsg {
+    CreateClient {
+        client_id: String,
+        name: String,
+        block: String,
+        set_id: u64,
+        authority_set: String,
+        max_headers_allowed_to_store: u64,
+        max_headers_allowed_between_justifications: u64,
+        justification: Option<String>,
+        authority_set_proof: Option<Vec<String>>,
+        finality_gadget: Option<FinalityGadget>,
+        beefy_validator_set: Option<BeefyValidatorSetInfo>,
+        max_consensus_states_to_store: Option<u64>,
+        retention_policy: Option<RetentionPolicyInfo>,
+        justification_verification: Option<JustificationVerificationMode>,
+    },
+    /// Imports SCALE encoded signed block. Its body is checked against extrinsics
+    /// root of the header, so a block with empty body is accepted only if the
+    /// header commits to no extrinsics. Use `UpdateHeader` to submit header alone.
     UpdateClient {
+        client_id: String,
         block: String,
         authority_set: String,
     },
+    /// Same as `UpdateClient`, but takes SCALE encoded header and its
+    /// optional justification instead of the whole signed block.
+    UpdateHeader {
+        client_id: String,
+        header: String,
+        justification: Option<String>,
+    },
+    /// Finalizes header of BEEFY client with SCALE encoded `BeefyFinalityProof`,
+    /// importing the header first if it isn't known yet.
+    UpdateBeefy {
+        client_id: String,
+        header: String,
+        finality_proof: String,
+    },
+    /// Creates parachain client, which follows heads of parachain `para_id`
+    /// proven against headers finalized by relay chain client `relay_client_id`.
+    CreateParachainClient {
+        client_id: String,
+        name: String,
+        relay_client_id: String,
+        para_id: u32,
+    },
+    /// Updates head of parachain client with SCALE encoded parachain header,
+    /// proven by storage proof of `Paras::Heads(para_id)` under state root
+    /// of finalized relay chain header at `relay_height`.
+    UpdateParachain {
+        client_id: String,
+        relay_height: BlockNumber,
+        relay_header_proof: Option<HeaderInclusionProof>,
+        header: String,
+        proof: Vec<String>,
+    },
+    /// Restores client under `client_id` from snapshot returned by `ExportSnapshot`.
+    RestoreClient { client_id: String, snapshot: String },
+    /// Updates config of client, only allowed to its admin.
+    /// Limits which aren't given are left unchanged.
+    UpdateConfig {
+        client_id: String,
+        max_headers_allowed_to_store: Option<u64>,
+        max_headers_allowed_between_justifications: Option<u64>,
+        max_consensus_states_to_store: Option<u64>,
+        /// Transfers admin rights to another address
+        new_admin: Option<HumanAddr>,
+        /// Gives up admin rights, after which config can't be updated anymore
+        #[serde(default)]
+        renounce_admin: bool,
+    },
+    /// Manages allowlist of relayers allowed to update client, only allowed
+    /// to its admin. Client without allowlist can be updated by anyone.
+    UpdateRelayers {
+        client_id: String,
+        /// Turns allowlist on, starting empty, or off, dropping it
+        allowlist_enabled: Option<bool>,
+        #[serde(default)]
+        add: Vec<HumanAddr>,
+        #[serde(default)]
+        remove: Vec<HumanAddr>,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum QueryMsg {
-    LatestHeight {},
+    LatestHeight {
+        client_id: String,
+    },
+    Headers {
+        client_id: String,
+        start_height: Option<BlockNumber>,
+        limit: Option<u32>,
+        reverse: Option<bool>,
+    },
+    StorageWindow {
+        client_id: String,
+    },
+    VerifyHeader {
+        client_id: String,
+        header_proof: HeaderInclusionProof,
+    },
+    Equivocations {
+        client_id: String,
+        justifications: Vec<String>,
+    },
+    /// Verifies grandpa justification against archived authority set
+    /// `set_id`, which doesn't need to be the current one.
+    VerifyJustification {
+        client_id: String,
+        set_id: u64,
+        justification: String,
+    },
+    /// Dry-runs verification of grandpa justification against archived
+    /// authority set `set_id`, or the current one if not given, and reports
+    /// every problem found instead of failing.
+    JustificationReport {
+        client_id: String,
+        set_id: Option<u64>,
+        justification: String,
+    },
+    VerifyExtrinsic {
+        client_id: String,
+        height: BlockNumber,
+        header_proof: Option<HeaderInclusionProof>,
+        extrinsic_index: u32,
+        extrinsic: String,
+        proof: Vec<String>,
+    },
+    ParachainHead {
+        client_id: String,
+    },
+    /// Reads value under `key` from storage proof verified against
+    /// state root of the latest head of parachain client.
+    VerifyParachainStorage {
+        client_id: String,
+        key: String,
+        proof: Vec<String>,
+    },
+    VerifyEvent {
+        client_id: String,
+        height: BlockNumber,
+        header_proof: Option<HeaderInclusionProof>,
+        proof: Vec<String>,
+        event_index: u32,
+        record_lengths: Vec<u32>,
+        pallet_index: Option<u8>,
+        variant_index: Option<u8>,
+    },
+    /// Consensus state recorded when header at `height` was finalized,
+    /// available as long as the height is within retention of consensus states.
+    ConsensusState {
+        client_id: String,
+        height: BlockNumber,
+    },
+    ConsensusStates {
+        client_id: String,
+        start_height: Option<BlockNumber>,
+        limit: Option<u32>,
+        reverse: Option<bool>,
+    },
+    /// Exports versioned, SCALE encoded snapshot of client, which can be
+    /// restored by `RestoreClient` or at instantiation of another contract.
+    ExportSnapshot {
+        client_id: String,
+    },
+    Config {
+        client_id: String,
+    },
+    /// Accepted and ineffective submissions of relayer to client. Invalid
+    /// submissions fail and aren't counted, see `RelayerStats`.
+    RelayerStats {
+        client_id: String,
+        relayer: HumanAddr,
+    },
+}
+
+/// Proof that header is included in MMR of finalized headers, which
+/// allows using headers that aren't retained in the storage anymore.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct HeaderInclusionProof {
+    pub header: String,
+    pub leaf_index: u64,
+    pub proof: Vec<String>,
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema)]
@@ -37,3 +287,198 @@ pub struct LatestHeightResponse {
     pub best_header_commitment_root: H256,
     pub current_authority_set: String,
 }
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct HeaderSummary {
+    pub number: BlockNumber,
+    pub hash: H256,
+    pub parent_hash: H256,
+    pub state_root: H256,
+    pub finalized: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct HeadersResponse {
+    pub headers: Vec<HeaderSummary>,
+    /// Height to start the next page at, if there are more headers to walk
+    pub next_height: Option<BlockNumber>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StorageWindowResponse {
+    pub oldest_height: BlockNumber,
+    pub newest_height: BlockNumber,
+    pub last_finalized_height: Option<BlockNumber>,
+    pub total_stored: u64,
+    /// Maximum amount of headers retained at once
+    pub capacity: u64,
+    /// Heights of older headers kept by retention policy
+    pub kept_heights: Vec<BlockNumber>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ConfigResponse {
+    pub admin: Option<HumanAddr>,
+    pub max_headers_allowed_to_store: u64,
+    pub max_headers_allowed_between_justifications: u64,
+    pub max_consensus_states_to_store: u64,
+    pub finality_gadget: FinalityGadget,
+    pub relayer_allowlist: Option<Vec<HumanAddr>>,
+}
+
+/// Submissions of relayer to client. Submissions carrying justification, or
+/// BEEFY finality proof, count towards both headers and justifications.
+/// Submissions rejected with an error aren't counted at all, as failed
+/// messages revert their storage writes. Ineffective ones are those which
+/// succeeded without changing the client: known headers not finalizing
+/// anything and justifications not finalizing new headers.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct RelayerStats {
+    pub accepted_headers: u64,
+    pub ineffective_headers: u64,
+    pub accepted_justifications: u64,
+    pub ineffective_justifications: u64,
+}
+
+/// Outcome of `UpdateClient`, returned as data of the handle response.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct UpdateClientResponse {
+    pub height: BlockNumber,
+    pub header_hash: H256,
+    pub is_new_best: bool,
+    /// Whether ingested header finalized new blocks
+    pub finalized: bool,
+    pub last_finalized_header_height: BlockNumber,
+    pub last_finalized_header_hash: H256,
+    /// Whether ingested header signals `ScheduledChange` of authority set
+    pub scheduled_change_detected: bool,
+    /// Height at which signaled authority set change is enacted
+    pub scheduled_change_at: Option<BlockNumber>,
+    pub authority_set_enacted: bool,
+    /// Id of GRANDPA authority set, or of BEEFY validator set for BEEFY clients
+    pub set_id: u64,
+    pub headers_ingested_after_last_justification: u64,
+    /// Weight proven by justification, only reported by clients
+    /// verifying justifications with `bounded` mode
+    pub verified_weight: Option<VerifiedWeightInfo>,
+}
+
+/// Weight of authorities whose precommits were verified by bounded
+/// verification of a justification.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct VerifiedWeightInfo {
+    pub verified_weight: u64,
+    /// Weight needed for supermajority of the authority set
+    pub threshold: u64,
+    pub total_weight: u64,
+    pub signatures_checked: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct VerifyExtrinsicResponse {
+    pub header_hash: H256,
+    pub extrinsics_root: H256,
+    pub extrinsic_hash: H256,
+}
+
+/// Parachain head tracked by parachain client, along with
+/// relay chain header it is proven against.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ParachainHead {
+    pub number: BlockNumber,
+    pub hash: H256,
+    pub state_root: H256,
+    pub relay_height: BlockNumber,
+    pub relay_hash: H256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ParachainHeadResponse {
+    pub para_id: u32,
+    pub relay_client_id: String,
+    /// Latest head, unless client hasn't been updated yet
+    pub head: Option<ParachainHead>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct VerifyParachainStorageResponse {
+    pub head: ParachainHead,
+    /// Stored value, or `None` if proof proves absence of the key
+    pub value: Option<String>,
+}
+
+/// State of finalized header needed to verify proofs against it. Headers
+/// don't carry timestamp, so time and height of the host chain at which
+/// the header was finalized are recorded instead.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ConsensusState {
+    pub height: BlockNumber,
+    pub hash: H256,
+    pub state_root: H256,
+    pub extrinsics_root: H256,
+    pub processed_time: u64,
+    pub processed_height: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ConsensusStatesResponse {
+    pub consensus_states: Vec<ConsensusState>,
+    /// Height to start the next page at, if there are more consensus states to walk
+    pub next_height: Option<BlockNumber>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ExportSnapshotResponse {
+    pub snapshot: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema)]
+pub struct VerifyEventResponse {
+    pub event_record: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema)]
+pub struct VerifyHeaderResponse {
+    pub height: BlockNumber,
+    pub header_hash: H256,
+    pub state_root: H256,
+    pub mmr_root: H256,
+    pub mmr_leaf_count: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct VerifyJustificationResponse {
+    pub set_id: u64,
+    /// Height of the header at which the authority set got activated
+    pub activation_height: BlockNumber,
+    pub activation_hash: H256,
+    pub target_height: BlockNumber,
+    pub target_hash: H256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct JustificationReportResponse {
+    pub set_id: u64,
+    /// Whether justification would be accepted by `UpdateClient`
+    pub valid: bool,
+    pub round: u64,
+    pub target_height: BlockNumber,
+    pub target_hash: H256,
+    /// Authority ids of distinct voters with valid precommits
+    pub signing_authorities: Vec<String>,
+    pub signed_weight: u64,
+    pub required_weight: u64,
+    pub total_weight: u64,
+    pub duplicate_voters: Vec<String>,
+    pub unknown_voters: Vec<String>,
+    /// Indices of precommits with invalid signatures
+    pub invalid_signatures: Vec<u64>,
+    /// Indices of precommits not proven to descend from the commit target
+    pub unrelated_precommits: Vec<u64>,
+    pub unused_ancestry_headers: Vec<H256>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema)]
+pub struct EquivocationsResponse {
+    pub equivocation_proofs: Vec<String>,
+}
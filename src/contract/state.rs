@@ -1,5 +1,11 @@
+use cosmwasm_std::CanonicalAddr;
+use parity_scale_codec::{Decode, Encode};
 use serde::{Deserialize, Serialize};
 
+use crate::common::types::client_snapshot::ClientSnapshot;
+use crate::contract::msg::{FinalityGadget, ParachainHead};
+use crate::types::{Block, BlockNumber};
+
 // This type is similar to primitive_types::H256 and
 // redeclared here to simplify state variables and
 // make them independent to parity types.
@@ -12,4 +18,52 @@ pub struct ContractState {
     pub headers_ingested_after_last_justification: u64,
     pub max_headers_allowed_between_justifications: u64,
     pub max_headers_allowed_to_store: u64,
+    #[serde(default)]
+    pub finality_gadget: FinalityGadget,
+    #[serde(default = "default_max_consensus_states_to_store")]
+    pub max_consensus_states_to_store: u64,
+    /// Heights of the oldest and the newest retained consensus states
+    #[serde(default)]
+    pub possible_consensus_states_window: Option<(BlockNumber, BlockNumber)>,
+    /// Address allowed to update config of the client, none once renounced
+    #[serde(default)]
+    pub possible_admin: Option<CanonicalAddr>,
+    /// Relayers allowed to update the client, anyone can if there's no allowlist
+    #[serde(default)]
+    pub possible_relayer_allowlist: Option<Vec<CanonicalAddr>>,
+}
+
+pub fn default_max_consensus_states_to_store() -> u64 {
+    1000
+}
+
+/// State of parachain client, whose heads are proven against
+/// finalized headers of another client of the same contract.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ParachainState {
+    pub name: String,
+    pub relay_client_id: String,
+    pub para_id: u32,
+    pub possible_latest_head: Option<ParachainHead>,
+}
+
+/// Config of client, carried by its snapshot along with the light client state.
+#[derive(Clone, Encode, Decode)]
+pub struct SnapshotConfig {
+    pub name: String,
+    pub max_headers_allowed_to_store: u64,
+    pub max_headers_allowed_between_justifications: u64,
+    pub headers_ingested_after_last_justification: u64,
+    pub finality_gadget: FinalityGadget,
+    pub max_consensus_states_to_store: u64,
+}
+
+/// Snapshot returned by `ExportSnapshot`. `integrity_hash` is blake2 256
+/// hash of SCALE encoded `version`, `config` and `client`.
+#[derive(Encode, Decode)]
+pub struct VersionedSnapshot {
+    pub version: u8,
+    pub config: SnapshotConfig,
+    pub client: ClientSnapshot<Block>,
+    pub integrity_hash: sp_core::H256,
 }
@@ -4,11 +4,10 @@ use crate::common::traits::header_backend::HeaderBackend;
 use crate::common::traits::storage::Storage;
 use crate::common::types::block_check_params::BlockCheckParams;
 use crate::common::types::block_import_params::BlockImportParams;
-use crate::common::types::blockchain_error::BlockchainError;
 use crate::common::types::consensus_error::ConsensusError;
 use crate::common::types::import_result::ImportResult;
-use crate::common::types::imported_aux::ImportedAux;
-use crate::common::utils::fetch_light_authority_set;
+use crate::common::types::justification_verification::JustificationVerification;
+use crate::common::utils::{fetch_justification_verification, fetch_light_authority_set};
 use crate::justification::{GrandpaJustification, ProvableJustification};
 use finality_grandpa::BlockNumberOps;
 use parity_scale_codec::{Decode, Encode};
@@ -86,7 +85,7 @@ fn do_import_block<S, C, Block: BlockT, J>(
     mut block: BlockImportParams<Block>,
 ) -> Result<ImportResult, ConsensusError>
 where
-    C: HeaderBackend<Block> + Finalizer<Block> + BlockImport<Block> + Clone,
+    C: HeaderBackend<Block> + Finalizer<Block> + BlockImport<Block, Error = ConsensusError> + Clone,
     S: Storage<Block>,
     NumberFor<Block>: finality_grandpa::BlockNumberOps,
     DigestFor<Block>: Encode,
@@ -102,7 +101,7 @@ where
     let imported_aux = match import_result {
         Ok(ImportResult::Imported(aux)) => aux,
         Ok(r) => return Ok(r),
-        Err(e) => return Err(ConsensusError::ClientImport(e.to_string()).into()),
+        Err(e) => return Err(e),
     };
 
     match justification {
@@ -127,37 +126,42 @@ where
     NumberFor<Block>: finality_grandpa::BlockNumberOps,
     J: ProvableJustification<Block>,
 {
-    let possible_light_authority_set =
-        fetch_light_authority_set(storage).map_err(|e| ConsensusError::Other(Box::new(e)))?;
+    let possible_light_authority_set = fetch_light_authority_set(storage.clone())?;
     if possible_light_authority_set.is_none() {
         return Err(ConsensusError::InvalidAuthoritiesSet);
     }
     let light_authority_set = possible_light_authority_set.unwrap();
 
     // Verify if justification is valid and it finalizes correct block
-    let justification = J::decode_and_verify_finalization(
-        &justification,
-        light_authority_set.set_id(),
-        (hash, number),
-        &light_authority_set.authorities(),
-    );
+    let verification = match fetch_justification_verification(storage)? {
+        JustificationVerification::Full => J::decode_and_verify_finalization(
+            &justification,
+            light_authority_set.set_id(),
+            (hash, number),
+            &light_authority_set.authorities(),
+        )
+        .map(|_| None),
+        JustificationVerification::Bounded => J::decode_and_verify_finalization_bounded(
+            &justification,
+            light_authority_set.set_id(),
+            (hash, number),
+            &light_authority_set.authorities(),
+        )
+        .map(Some),
+    };
 
     // BadJustification error means that justification has been successfully decoded, but
-    // it isn't valid within current authority set
-    let justification = match justification {
-        Err(BlockchainError::BadJustification(_)) => {
-            let mut imported_aux = ImportedAux::default();
-            imported_aux.needs_finality_proof = true;
-            return Ok(ImportResult::Imported(imported_aux));
-        }
-        Err(e) => {
-            return Err(ConsensusError::ClientImport(e.to_string()).into());
-        }
-        Ok(justification) => justification,
-    };
+    // it isn't valid within current authority set, its report is passed along with the error
+    let possible_verified_weight = verification?;
 
     // finalize the block
-    do_finalize_block(client, hash, number, justification.encode())
+    match do_finalize_block(client, hash, number, justification)? {
+        ImportResult::Imported(mut imported_aux) => {
+            imported_aux.possible_verified_weight = possible_verified_weight;
+            Ok(ImportResult::Imported(imported_aux))
+        }
+        import_result => Ok(import_result),
+    }
 }
 
 /// Finalize the block.
@@ -174,7 +178,7 @@ where
     // finalize the block
     client
         .finalize_block(BlockId::Hash(hash), Some(justification))
-        .map_err(|e| ConsensusError::ClientImport(e.to_string()))?;
+        .map_err(ConsensusError::from)?;
 
     // we just finalized this block, so if we were importing it, it is now the new best
     Ok(ImportResult::imported(true))
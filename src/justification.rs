@@ -1,11 +1,16 @@
-use std::collections::{HashMap, HashSet};
+use alloc::string::ToString;
+use sp_std::collections::{btree_map::BTreeMap, btree_set::BTreeSet};
+use sp_std::convert::TryFrom;
+use sp_std::fmt;
+use sp_std::prelude::*;
 
+use ed25519_dalek::Verifier;
 use finality_grandpa::voter_set::VoterSet;
 use finality_grandpa::{BlockNumberOps, Error as GrandpaError};
 use parity_scale_codec::{Decode, Encode};
-use sp_core::crypto::Pair;
 use sp_finality_grandpa::{
-    AuthorityId, AuthorityPair, AuthoritySignature, RoundNumber, SetId as SetIdNumber,
+    AuthorityId, AuthoritySignature, Equivocation, EquivocationProof, RoundNumber,
+    SetId as SetIdNumber,
 };
 use sp_runtime::traits::{Block as BlockT, Header as HeaderT, NumberFor};
 use sp_runtime::Justification;
@@ -37,6 +42,16 @@ impl From<BlockchainError> for Error {
 /// A GRANDPA message for a substrate chain.
 pub type Message<Block> = finality_grandpa::Message<<Block as BlockT>::Hash, NumberFor<Block>>;
 pub type Precommit<Block> = finality_grandpa::Precommit<<Block as BlockT>::Hash, NumberFor<Block>>;
+/// A signed GRANDPA precommit for a substrate chain.
+pub type SignedPrecommit<Block> = finality_grandpa::SignedPrecommit<
+    <Block as BlockT>::Hash,
+    NumberFor<Block>,
+    AuthoritySignature,
+    AuthorityId,
+>;
+/// Proof of a GRANDPA equivocation for a substrate chain.
+pub type GrandpaEquivocationProof<Block> =
+    EquivocationProof<<Block as BlockT>::Hash, NumberFor<Block>>;
 
 /// Justification used to prove block finality.
 pub trait ProvableJustification<Block: BlockT>: Encode + Decode {
@@ -51,6 +66,15 @@ pub trait ProvableJustification<Block: BlockT>: Encode + Decode {
         authorities: &[(AuthorityId, u64)],
     ) -> BlockchainResult<()>;
 
+    /// Same as `verify_finalization`, but with bounded cost, returning
+    /// the weight that has been proven.
+    fn verify_finalization_bounded(
+        &self,
+        set_id: u64,
+        finalized_target: (Block::Hash, NumberFor<Block>),
+        authorities: &[(AuthorityId, u64)],
+    ) -> BlockchainResult<VerifiedWeight>;
+
     fn decode_and_verify_finalization(
         justification: &Justification,
         set_id: u64,
@@ -61,6 +85,17 @@ pub trait ProvableJustification<Block: BlockT>: Encode + Decode {
             .map_err(|_| BlockchainError::JustificationDecode)?;
         justification.verify_finalization(set_id, finalized_target, authorities)
     }
+
+    fn decode_and_verify_finalization_bounded(
+        justification: &Justification,
+        set_id: u64,
+        finalized_target: (Block::Hash, NumberFor<Block>),
+        authorities: &[(AuthorityId, u64)],
+    ) -> BlockchainResult<VerifiedWeight> {
+        let justification = Self::decode(&mut &**justification)
+            .map_err(|_| BlockchainError::JustificationDecode)?;
+        justification.verify_finalization_bounded(set_id, finalized_target, authorities)
+    }
 }
 
 /// Check a message signature by encoding the message as a localized payload and
@@ -75,14 +110,13 @@ pub fn check_message_sig_with_buffer<Block: BlockT>(
     set_id: SetIdNumber,
     buf: &mut Vec<u8>,
 ) -> Result<(), ()> {
-    let as_public = id.clone();
     localized_payload_with_buffer(round, set_id, message, buf);
 
-    if AuthorityPair::verify(signature, buf, &as_public) {
-        Ok(())
-    } else {
-        Err(())
-    }
+    // Signatures are checked with ed25519-dalek rather than host functions of
+    // Substrate runtimes, so that verification works in any environment
+    let signature = ed25519_dalek::Signature::try_from(signature.as_ref()).map_err(|_| ())?;
+    let public_key = ed25519_dalek::PublicKey::from_bytes(id.as_ref()).map_err(|_| ())?;
+    public_key.verify(buf, &signature).map_err(|_| ())
 }
 
 /// Encode round message localized to a given round and set id using the given
@@ -114,13 +148,124 @@ pub type Commit<Block> = finality_grandpa::Commit<
 ///
 /// This is meant to be stored in the db and passed around the network to other
 /// nodes, and are used by syncing nodes to prove authority set handoffs.
-#[derive(Encode, Decode)]
+#[derive(Encode, Decode, Clone)]
 pub struct GrandpaJustification<Block: BlockT> {
     pub round: u64,
     pub commit: Commit<Block>,
     pub votes_ancestries: Vec<Block::Header>,
 }
 
+/// Weight of the voters proven by bounded verification of a justification.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VerifiedWeight {
+    /// Weight of distinct voters whose precommits were verified and counted.
+    pub verified_weight: u64,
+    /// Weight needed for supermajority of the voter set.
+    pub threshold: u64,
+    /// Weight of the whole voter set.
+    pub total_weight: u64,
+    /// Amount of precommit signatures checked.
+    pub signatures_checked: usize,
+}
+
+/// Outcome of checking every precommit and ancestry header of a justification,
+/// collected without stopping at the first problem, so that rejected
+/// justifications can be debugged.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JustificationReport<Block: BlockT> {
+    pub round: u64,
+    pub target_hash: Block::Hash,
+    pub target_number: NumberFor<Block>,
+    /// Distinct voters with valid signatures on precommits for the commit target
+    /// or its descendants, in order of appearance.
+    pub signing_authorities: Vec<AuthorityId>,
+    /// Weight of `signing_authorities`.
+    pub signed_weight: u64,
+    /// Weight needed for supermajority of the voter set.
+    pub required_weight: u64,
+    /// Weight of the whole voter set.
+    pub total_weight: u64,
+    /// Whether `finality_grandpa::validate_commit` finds supermajority for the commit target.
+    pub commit_valid: bool,
+    /// Authorities with more than one precommit.
+    pub duplicate_voters: Vec<AuthorityId>,
+    /// Authorities with precommits that aren't part of the voter set.
+    pub unknown_voters: Vec<AuthorityId>,
+    /// Indices of precommits with invalid signatures.
+    pub invalid_signatures: Vec<usize>,
+    /// Indices of precommits whose targets aren't proven to be the commit target
+    /// or its descendants by the votes ancestries.
+    pub unrelated_precommits: Vec<usize>,
+    /// Votes ancestries headers which aren't on a route of any precommit.
+    pub unused_ancestry_headers: Vec<Block::Hash>,
+}
+
+impl<Block: BlockT> JustificationReport<Block> {
+    /// Whether the justification would pass `GrandpaJustification::verify`.
+    pub fn is_valid(&self) -> bool {
+        self.commit_valid
+            && self.invalid_signatures.is_empty()
+            && self.unrelated_precommits.is_empty()
+            && self.unused_ancestry_headers.is_empty()
+    }
+}
+
+impl<Block: BlockT> fmt::Display for JustificationReport<Block> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let format_ids = |ids: &[AuthorityId]| {
+            ids.iter()
+                .map(|id| "0x".to_string() + hex::encode(id).as_ref())
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+
+        write!(
+            f,
+            "signed weight {} of required {} (total {}) by {} authorities",
+            self.signed_weight,
+            self.required_weight,
+            self.total_weight,
+            self.signing_authorities.len()
+        )?;
+        if !self.duplicate_voters.is_empty() {
+            write!(
+                f,
+                ", duplicate voters: [{}]",
+                format_ids(&self.duplicate_voters)
+            )?;
+        }
+        if !self.unknown_voters.is_empty() {
+            write!(
+                f,
+                ", unknown voters: [{}]",
+                format_ids(&self.unknown_voters)
+            )?;
+        }
+        if !self.invalid_signatures.is_empty() {
+            write!(
+                f,
+                ", invalid signatures of precommits: {:?}",
+                self.invalid_signatures
+            )?;
+        }
+        if !self.unrelated_precommits.is_empty() {
+            write!(
+                f,
+                ", precommits not descending from commit target: {:?}",
+                self.unrelated_precommits
+            )?;
+        }
+        if !self.unused_ancestry_headers.is_empty() {
+            write!(
+                f,
+                ", unused ancestry headers: {:?}",
+                self.unused_ancestry_headers
+            )?;
+        }
+        Ok(())
+    }
+}
+
 impl<Block: BlockT> GrandpaJustification<Block> {
     /// Validate the commit and the votes'
     /// ancestry proofs finalize the given block.
@@ -137,44 +282,270 @@ impl<Block: BlockT> GrandpaJustification<Block> {
             let msg = "invalid commit target in grandpa justification".to_string();
             Err(BlockchainError::BadJustification(msg))
         } else {
-            self.verify(set_id, voters)
+            self.verify_batched(set_id, voters)
+        }
+    }
+
+    /// Same as `verify_finalization`, but validates the commit with
+    /// `verify_bounded`, returning the weight that has been proven.
+    pub fn verify_finalization_bounded(
+        &self,
+        set_id: u64,
+        finalized_target: (Block::Hash, NumberFor<Block>),
+        voters: &VoterSet<AuthorityId>,
+    ) -> Result<VerifiedWeight, BlockchainError>
+    where
+        NumberFor<Block>: finality_grandpa::BlockNumberOps,
+    {
+        if (self.commit.target_hash, self.commit.target_number) != finalized_target {
+            let msg = "invalid commit target in grandpa justification".to_string();
+            Err(BlockchainError::BadJustification(msg))
+        } else {
+            self.verify_bounded(set_id, voters)
         }
     }
 
     /// Validate the commit and the votes' ancestry proofs.
     pub fn verify(&self, set_id: u64, voters: &VoterSet<AuthorityId>) -> Result<(), BlockchainError>
+    where
+        NumberFor<Block>: finality_grandpa::BlockNumberOps,
+    {
+        self.verify_with(set_id, voters, check_precommit_signatures::<Block>)
+    }
+
+    /// Validate the commit and the votes' ancestry proofs, same as `verify`,
+    /// but checks all precommit signatures in one batched ed25519 verification.
+    /// If batch verification fails, signatures are checked one by one to find
+    /// the invalid one.
+    pub fn verify_batched(
+        &self,
+        set_id: u64,
+        voters: &VoterSet<AuthorityId>,
+    ) -> Result<(), BlockchainError>
+    where
+        NumberFor<Block>: finality_grandpa::BlockNumberOps,
+    {
+        self.verify_with(set_id, voters, batch_check_precommit_signatures::<Block>)
+    }
+
+    /// Validate the commit with bounded cost. Precommits are ordered by voter
+    /// weight and every voter is counted once. Signatures are only checked until
+    /// precommits for the commit target, or its descendants, are proven to carry
+    /// supermajority of voter set weight. Ancestry is validated for counted votes only,
+    /// so remaining precommits and unused ancestry headers are ignored. Errors only
+    /// carry what has been counted so far, use `report` to find every problem.
+    pub fn verify_bounded(
+        &self,
+        set_id: u64,
+        voters: &VoterSet<AuthorityId>,
+    ) -> Result<VerifiedWeight, BlockchainError>
     where
         NumberFor<Block>: finality_grandpa::BlockNumberOps,
     {
         use finality_grandpa::Chain;
 
         let ancestry_chain = AncestryChain::<Block>::new(&self.votes_ancestries);
+        let threshold = voters.threshold().get();
 
-        match finality_grandpa::validate_commit(&self.commit, voters, &ancestry_chain) {
-            Ok(ref result) if result.ghost().is_some() => {}
-            _ => {
-                let msg = "invalid commit in grandpa justification".to_string();
-                return Err(BlockchainError::BadJustification(msg));
+        // Heaviest voters first, so that threshold is reached with fewest signature checks
+        let mut candidates: Vec<(usize, u64)> = self
+            .commit
+            .precommits
+            .iter()
+            .enumerate()
+            .filter_map(|(index, signed)| {
+                voters
+                    .get(&signed.id)
+                    .map(|info| (index, info.weight().get()))
+            })
+            .collect();
+        candidates.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+
+        let mut counted_voters = BTreeSet::new();
+        let mut verified_weight = 0u64;
+        let mut signatures_checked = 0usize;
+        let mut buf = Vec::new();
+        for (index, weight) in candidates {
+            if verified_weight >= threshold {
+                break;
+            }
+            let signed = &self.commit.precommits[index];
+            if counted_voters.contains(&signed.id) {
+                continue;
+            }
+
+            // Only votes for commit target or its descendants support finality of the target
+            if self.commit.target_hash != signed.precommit.target_hash
+                && ancestry_chain
+                    .ancestry(self.commit.target_hash, signed.precommit.target_hash)
+                    .is_err()
+            {
+                continue;
             }
+
+            signatures_checked += 1;
+            if check_message_sig_with_buffer::<Block>(
+                &finality_grandpa::Message::Precommit(signed.precommit.clone()),
+                &signed.id,
+                &signed.signature,
+                self.round,
+                set_id,
+                &mut buf,
+            )
+            .is_err()
+            {
+                return Err(BlockchainError::BadJustification(format!(
+                    "invalid signature for precommit {} in grandpa justification, verified weight {} of threshold {} with {} signatures checked",
+                    index, verified_weight, threshold, signatures_checked
+                )));
+            }
+
+            counted_voters.insert(signed.id.clone());
+            verified_weight += weight;
         }
 
+        if verified_weight < threshold {
+            return Err(BlockchainError::BadJustification(format!(
+                "verified weight {} of precommits in grandpa justification is below threshold {} with {} signatures checked",
+                verified_weight, threshold, signatures_checked
+            )));
+        }
+
+        Ok(VerifiedWeight {
+            verified_weight,
+            threshold,
+            total_weight: voters.total_weight().get(),
+            signatures_checked,
+        })
+    }
+
+    /// Checks all precommits and votes ancestries without failing early and
+    /// reports every problem found. Unlike `verify`, signatures are checked
+    /// one by one, so that all invalid ones are reported.
+    pub fn report(&self, set_id: u64, voters: &VoterSet<AuthorityId>) -> JustificationReport<Block>
+    where
+        NumberFor<Block>: finality_grandpa::BlockNumberOps,
+    {
+        use finality_grandpa::Chain;
+
+        let ancestry_chain = AncestryChain::<Block>::new(&self.votes_ancestries);
+        let commit_valid = matches!(
+            finality_grandpa::validate_commit(&self.commit, voters, &ancestry_chain),
+            Ok(ref result) if result.ghost().is_some()
+        );
+        let mut report = JustificationReport {
+            round: self.round,
+            target_hash: self.commit.target_hash,
+            target_number: self.commit.target_number,
+            signing_authorities: vec![],
+            signed_weight: 0,
+            required_weight: voters.threshold().get(),
+            total_weight: voters.total_weight().get(),
+            commit_valid,
+            duplicate_voters: vec![],
+            unknown_voters: vec![],
+            invalid_signatures: vec![],
+            unrelated_precommits: vec![],
+            unused_ancestry_headers: vec![],
+        };
+
+        let mut seen_voters = BTreeSet::new();
+        let mut visited_hashes = BTreeSet::new();
         let mut buf = Vec::new();
-        let mut visited_hashes = HashSet::new();
-        for signed in self.commit.precommits.iter() {
-            if let Err(_) = check_message_sig_with_buffer::<Block>(
+        for (index, signed) in self.commit.precommits.iter().enumerate() {
+            if !seen_voters.insert(signed.id.clone())
+                && !report.duplicate_voters.contains(&signed.id)
+            {
+                report.duplicate_voters.push(signed.id.clone());
+            }
+
+            let signature_valid = check_message_sig_with_buffer::<Block>(
                 &finality_grandpa::Message::Precommit(signed.precommit.clone()),
                 &signed.id,
                 &signed.signature,
                 self.round,
                 set_id,
                 &mut buf,
-            ) {
-                return Err(BlockchainError::BadJustification(
-                    "invalid signature for precommit in grandpa justification".to_string(),
-                )
-                .into());
+            )
+            .is_ok();
+            if !signature_valid {
+                report.invalid_signatures.push(index);
             }
 
+            let descends = signed.precommit.target_number >= self.commit.target_number
+                && (self.commit.target_hash == signed.precommit.target_hash
+                    || match ancestry_chain
+                        .ancestry(self.commit.target_hash, signed.precommit.target_hash)
+                    {
+                        Ok(route) => {
+                            visited_hashes.insert(signed.precommit.target_hash);
+                            visited_hashes.extend(route);
+                            true
+                        }
+                        Err(_) => false,
+                    });
+            if !descends {
+                report.unrelated_precommits.push(index);
+            }
+
+            match voters.get(&signed.id) {
+                None => {
+                    if !report.unknown_voters.contains(&signed.id) {
+                        report.unknown_voters.push(signed.id.clone());
+                    }
+                }
+                Some(info) => {
+                    if signature_valid
+                        && descends
+                        && !report.signing_authorities.contains(&signed.id)
+                    {
+                        report.signing_authorities.push(signed.id.clone());
+                        report.signed_weight += info.weight().get();
+                    }
+                }
+            }
+        }
+
+        report.unused_ancestry_headers = self
+            .votes_ancestries
+            .iter()
+            .map(|h: &Block::Header| h.hash())
+            .filter(|hash| !visited_hashes.contains(hash))
+            .collect();
+
+        report
+    }
+
+    fn verify_with<F>(
+        &self,
+        set_id: u64,
+        voters: &VoterSet<AuthorityId>,
+        check_signatures: F,
+    ) -> Result<(), BlockchainError>
+    where
+        NumberFor<Block>: finality_grandpa::BlockNumberOps,
+        F: FnOnce(
+            &[SignedPrecommit<Block>],
+            RoundNumber,
+            SetIdNumber,
+        ) -> Result<(), BlockchainError>,
+    {
+        use finality_grandpa::Chain;
+
+        let ancestry_chain = AncestryChain::<Block>::new(&self.votes_ancestries);
+
+        match finality_grandpa::validate_commit(&self.commit, voters, &ancestry_chain) {
+            Ok(ref result) if result.ghost().is_some() => {}
+            _ => {
+                let msg = "invalid commit in grandpa justification".to_string();
+                return Err(BlockchainError::BadJustification(msg));
+            }
+        }
+
+        check_signatures(&self.commit.precommits, self.round, set_id)?;
+
+        let mut visited_hashes = BTreeSet::new();
+        for signed in self.commit.precommits.iter() {
             if self.commit.target_hash == signed.precommit.target_hash {
                 continue;
             }
@@ -190,8 +561,7 @@ impl<Block: BlockT> GrandpaJustification<Block> {
                 _ => {
                     return Err(BlockchainError::BadJustification(
                         "invalid precommit ancestry proof in grandpa justification".to_string(),
-                    )
-                    .into());
+                    ));
                 }
             }
         }
@@ -206,21 +576,88 @@ impl<Block: BlockT> GrandpaJustification<Block> {
             return Err(BlockchainError::BadJustification(
                 "invalid precommit ancestries in grandpa justification with unused headers"
                     .to_string(),
-            )
-            .into());
+            ));
         }
 
         Ok(())
     }
 }
 
+/// Checks signatures of precommits one by one.
+fn check_precommit_signatures<Block: BlockT>(
+    precommits: &[SignedPrecommit<Block>],
+    round: RoundNumber,
+    set_id: SetIdNumber,
+) -> Result<(), BlockchainError> {
+    let mut buf = Vec::new();
+    for (index, signed) in precommits.iter().enumerate() {
+        if check_message_sig_with_buffer::<Block>(
+            &finality_grandpa::Message::Precommit(signed.precommit.clone()),
+            &signed.id,
+            &signed.signature,
+            round,
+            set_id,
+            &mut buf,
+        )
+        .is_err()
+        {
+            return Err(BlockchainError::BadJustification(format!(
+                "invalid signature for precommit {} in grandpa justification",
+                index
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks signatures of all precommits in a single batched ed25519 verification,
+/// falling back to checking them one by one to find the bad signature on failure.
+fn batch_check_precommit_signatures<Block: BlockT>(
+    precommits: &[SignedPrecommit<Block>],
+    round: RoundNumber,
+    set_id: SetIdNumber,
+) -> Result<(), BlockchainError> {
+    let mut messages = Vec::with_capacity(precommits.len());
+    let mut signatures = Vec::with_capacity(precommits.len());
+    let mut public_keys = Vec::with_capacity(precommits.len());
+    for signed in precommits.iter() {
+        let mut buf = Vec::new();
+        localized_payload_with_buffer(
+            round,
+            set_id,
+            &finality_grandpa::Message::<Block::Hash, NumberFor<Block>>::Precommit(
+                signed.precommit.clone(),
+            ),
+            &mut buf,
+        );
+        let signature = ed25519_dalek::Signature::try_from(signed.signature.as_ref());
+        let public_key = ed25519_dalek::PublicKey::from_bytes(signed.id.as_ref());
+        match (signature, public_key) {
+            (Ok(signature), Ok(public_key)) => {
+                messages.push(buf);
+                signatures.push(signature);
+                public_keys.push(public_key);
+            }
+            // Malformed key or signature can't be batched, individual checks will report it
+            _ => return check_precommit_signatures::<Block>(precommits, round, set_id),
+        }
+    }
+
+    let messages: Vec<&[u8]> = messages.iter().map(|m| m.as_slice()).collect();
+    match ed25519_dalek::verify_batch(&messages, &signatures, &public_keys) {
+        Ok(()) => Ok(()),
+        Err(_) => check_precommit_signatures::<Block>(precommits, round, set_id),
+    }
+}
+
 impl<Block: BlockT> ProvableJustification<Block> for GrandpaJustification<Block>
 where
     NumberFor<Block>: BlockNumberOps,
 {
     fn verify(&self, set_id: u64, authorities: &[(AuthorityId, u64)]) -> BlockchainResult<()> {
-        let voter_set = VoterSet::new(authorities.clone().to_owned().drain(..)).unwrap();
-        GrandpaJustification::verify(self, set_id, &voter_set)
+        let voter_set = VoterSet::new(authorities.iter().cloned()).unwrap();
+        GrandpaJustification::verify_batched(self, set_id, &voter_set)
     }
 
     fn verify_finalization(
@@ -229,22 +666,105 @@ where
         finalized_target: (Block::Hash, NumberFor<Block>),
         authorities: &[(AuthorityId, u64)],
     ) -> BlockchainResult<()> {
-        let voter_set = VoterSet::new(authorities.clone().to_owned().drain(..)).unwrap();
+        let voter_set = VoterSet::new(authorities.iter().cloned()).unwrap();
         GrandpaJustification::verify_finalization(self, set_id, finalized_target, &voter_set)?;
         Ok(())
     }
+
+    fn verify_finalization_bounded(
+        &self,
+        set_id: u64,
+        finalized_target: (Block::Hash, NumberFor<Block>),
+        authorities: &[(AuthorityId, u64)],
+    ) -> BlockchainResult<VerifiedWeight> {
+        let voter_set = VoterSet::new(authorities.iter().cloned()).unwrap();
+        GrandpaJustification::verify_finalization_bounded(
+            self,
+            set_id,
+            finalized_target,
+            &voter_set,
+        )
+    }
+}
+
+/// Inspects justifications of the given authority set and extracts proofs of
+/// precommit equivocations, i.e. different precommits signed by the same authority
+/// in the same round. Only precommits of known voters with valid signatures are
+/// considered, and at most one proof is extracted per offender and round, so that
+/// every proof is accepted by `pallet_grandpa::report_equivocation`.
+pub fn extract_equivocations<Block: BlockT>(
+    set_id: u64,
+    voters: &VoterSet<AuthorityId>,
+    justifications: &[GrandpaJustification<Block>],
+) -> Vec<GrandpaEquivocationProof<Block>> {
+    let mut first_votes: BTreeMap<
+        (RoundNumber, AuthorityId),
+        (Precommit<Block>, AuthoritySignature),
+    > = BTreeMap::new();
+    let mut reported_offenders = BTreeSet::new();
+    let mut equivocation_proofs = vec![];
+    let mut buf = Vec::new();
+
+    for justification in justifications {
+        for signed in justification.commit.precommits.iter() {
+            if !voters.contains(&signed.id) {
+                continue;
+            }
+            let key = (justification.round, signed.id.clone());
+            if reported_offenders.contains(&key) {
+                continue;
+            }
+            let first_precommit = first_votes.get(&key).map(|(precommit, _)| precommit);
+            if first_precommit == Some(&signed.precommit) {
+                continue;
+            }
+            if check_message_sig_with_buffer::<Block>(
+                &finality_grandpa::Message::Precommit(signed.precommit.clone()),
+                &signed.id,
+                &signed.signature,
+                justification.round,
+                set_id,
+                &mut buf,
+            )
+            .is_err()
+            {
+                continue;
+            }
+
+            match first_votes.get(&key) {
+                None => {
+                    first_votes.insert(key, (signed.precommit.clone(), signed.signature.clone()));
+                }
+                Some(first) => {
+                    let equivocation = finality_grandpa::Equivocation {
+                        round_number: justification.round,
+                        identity: signed.id.clone(),
+                        first: first.clone(),
+                        second: (signed.precommit.clone(), signed.signature.clone()),
+                    };
+                    equivocation_proofs.push(EquivocationProof::new(
+                        set_id,
+                        Equivocation::Precommit(equivocation),
+                    ));
+                    reported_offenders.insert(key);
+                }
+            }
+        }
+    }
+
+    equivocation_proofs
 }
 
 /// A utility trait implementing `finality_grandpa::Chain` using a given set of headers.
 /// This is useful when validating commits, using the given set of headers to
 /// verify a valid ancestry route to the target commit block.
 struct AncestryChain<Block: BlockT> {
-    ancestry: HashMap<Block::Hash, Block::Header>,
+    ancestry: BTreeMap<Block::Hash, Block::Header>,
 }
 
 impl<Block: BlockT> AncestryChain<Block> {
     fn new(ancestry: &[Block::Header]) -> AncestryChain<Block> {
-        let ancestry: HashMap<_, _> = ancestry
+        let ancestry: BTreeMap<_, _> = ancestry
             .iter()
             .cloned()
             .map(|h: Block::Header| (h.hash(), h))
@@ -289,3 +809,291 @@ where
         None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::justification::{
+        extract_equivocations, GrandpaJustification, JustificationReport, VerifiedWeight,
+    };
+    use crate::test_utils;
+    use crate::types::{Block, Header};
+    use finality_grandpa::voter_set::VoterSet;
+    use sp_core::H256;
+    use sp_finality_grandpa::{AuthorityId, Equivocation, EquivocationProof};
+    use sp_keyring::Ed25519Keyring;
+    use sp_runtime::traits::{Header as HeaderT, One};
+
+    fn create_justification(peers: &[Ed25519Keyring], set_id: u64) -> GrandpaJustification<Block> {
+        let header = Header::new(
+            One::one(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+        );
+        test_utils::create_justification(&header, 1, set_id, peers)
+    }
+
+    #[test]
+    fn test_batched_signature_verification() {
+        let peers = [
+            Ed25519Keyring::Alice,
+            Ed25519Keyring::Bob,
+            Ed25519Keyring::Charlie,
+        ];
+        let voters = VoterSet::new(peers.iter().map(|peer| (peer.public().into(), 1))).unwrap();
+
+        let justification = create_justification(&peers, 0);
+        assert!(justification.verify(0, &voters).is_ok());
+        assert!(justification.verify_batched(0, &voters).is_ok());
+
+        // Signed for another set id
+        let justification = create_justification(&peers, 1);
+        assert!(justification.verify_batched(0, &voters).is_err());
+
+        // Bob's signature is swapped with Charlie's one
+        let mut justification = create_justification(&peers, 0);
+        justification.commit.precommits[1].signature =
+            justification.commit.precommits[2].signature.clone();
+        let expected_error =
+            "bad justification for header: invalid signature for precommit 1 in grandpa justification";
+        assert_eq!(justification.report(0, &voters).invalid_signatures, vec![1]);
+        assert_eq!(
+            justification.verify(0, &voters).err().unwrap().to_string(),
+            expected_error
+        );
+        assert_eq!(
+            justification
+                .verify_batched(0, &voters)
+                .err()
+                .unwrap()
+                .to_string(),
+            expected_error
+        );
+    }
+
+    #[test]
+    fn test_bounded_verification() {
+        let peers = [
+            Ed25519Keyring::Alice,
+            Ed25519Keyring::Bob,
+            Ed25519Keyring::Charlie,
+            Ed25519Keyring::Dave,
+        ];
+        // Total weight of 6 needs 5 for supermajority
+        let voters = VoterSet::new(
+            peers
+                .iter()
+                .zip(&[1, 1, 1, 3])
+                .map(|(peer, weight)| (peer.public().into(), *weight)),
+        )
+        .unwrap();
+
+        // Dave is checked first, and Alice and Bob are enough to reach threshold,
+        // so Charlie's bad signature is never checked.
+        let mut justification = create_justification(&peers, 0);
+        justification.commit.precommits[2].signature =
+            justification.commit.precommits[0].signature.clone();
+        assert!(justification.verify(0, &voters).is_err());
+        let verified_weight = justification.verify_bounded(0, &voters).unwrap();
+        assert_eq!(
+            verified_weight,
+            VerifiedWeight {
+                verified_weight: 5,
+                threshold: 5,
+                total_weight: 6,
+                signatures_checked: 3,
+            }
+        );
+
+        // Duplicate precommits of a voter are counted once
+        let mut justification = create_justification(&peers[..3], 0);
+        let duplicate = justification.commit.precommits[0].clone();
+        justification.commit.precommits.push(duplicate.clone());
+        justification.commit.precommits.push(duplicate);
+        assert_eq!(
+            justification
+                .verify_bounded(0, &voters)
+                .err()
+                .unwrap()
+                .to_string(),
+            "bad justification for header: verified weight 3 of precommits in grandpa justification is below threshold 5 with 3 signatures checked"
+        );
+
+        // Bad signature needed to reach threshold
+        let mut justification = create_justification(&peers, 0);
+        justification.commit.precommits[3].signature =
+            justification.commit.precommits[0].signature.clone();
+        assert_eq!(
+            justification
+                .verify_bounded(0, &voters)
+                .err()
+                .unwrap()
+                .to_string(),
+            "bad justification for header: invalid signature for precommit 3 in grandpa justification, verified weight 0 of threshold 5 with 1 signatures checked"
+        );
+    }
+
+    #[test]
+    fn test_justification_report() {
+        let peers = [
+            Ed25519Keyring::Alice,
+            Ed25519Keyring::Bob,
+            Ed25519Keyring::Charlie,
+        ];
+        let voters = VoterSet::new(peers.iter().map(|peer| (peer.public().into(), 1))).unwrap();
+        let alice: AuthorityId = Ed25519Keyring::Alice.public().into();
+        let charlie: AuthorityId = Ed25519Keyring::Charlie.public().into();
+        let dave: AuthorityId = Ed25519Keyring::Dave.public().into();
+
+        let justification = create_justification(&peers, 0);
+        let report = justification.report(0, &voters);
+        assert!(report.is_valid());
+        assert_eq!(
+            report,
+            JustificationReport {
+                round: 1,
+                target_hash: justification.commit.target_hash,
+                target_number: 1,
+                signing_authorities: vec![
+                    alice.clone(),
+                    Ed25519Keyring::Bob.public().into(),
+                    charlie,
+                ],
+                signed_weight: 3,
+                required_weight: 3,
+                total_weight: 3,
+                commit_valid: true,
+                duplicate_voters: vec![],
+                unknown_voters: vec![],
+                invalid_signatures: vec![],
+                unrelated_precommits: vec![],
+                unused_ancestry_headers: vec![],
+            }
+        );
+
+        // Charlie's precommit is replaced with Alice's duplicate, Bob's signature
+        // is invalid, Dave isn't a voter and an unrelated header is in ancestry
+        let mut justification = create_justification(
+            &[
+                Ed25519Keyring::Alice,
+                Ed25519Keyring::Bob,
+                Ed25519Keyring::Alice,
+                Ed25519Keyring::Dave,
+            ],
+            0,
+        );
+        justification.commit.precommits[1].signature =
+            justification.commit.precommits[0].signature.clone();
+        let mut unrelated_header = Header::new(
+            2,
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+        );
+        unrelated_header.state_root = H256::repeat_byte(1);
+        justification
+            .votes_ancestries
+            .push(unrelated_header.clone());
+        let mut unrelated_precommit = justification.commit.precommits[0].clone();
+        unrelated_precommit.precommit.target_hash = unrelated_header.hash();
+        unrelated_precommit.precommit.target_number = 2;
+        justification.commit.precommits.push(unrelated_precommit);
+
+        let report = justification.report(0, &voters);
+        assert!(!report.is_valid());
+        assert!(!report.commit_valid);
+        assert_eq!(report.signing_authorities, vec![alice.clone()]);
+        assert_eq!((report.signed_weight, report.required_weight), (1, 3));
+        assert_eq!(report.duplicate_voters, vec![alice]);
+        assert_eq!(report.unknown_voters, vec![dave]);
+        assert_eq!(report.invalid_signatures, vec![1, 4]);
+        assert_eq!(report.unrelated_precommits, vec![4]);
+        assert_eq!(
+            report.unused_ancestry_headers,
+            vec![unrelated_header.hash()]
+        );
+
+        // Rejection stops at the first problem, report lists all of them
+        assert_eq!(
+            justification.verify(0, &voters).err().unwrap().to_string(),
+            "bad justification for header: invalid commit in grandpa justification"
+        );
+        let report = report.to_string();
+        assert!(report.starts_with(&format!(
+            "signed weight 1 of required 3 (total 3) by 1 authorities, duplicate voters: [0x{}]",
+            hex::encode(Ed25519Keyring::Alice.public())
+        )));
+        assert!(report.ends_with(&format!(
+            "invalid signatures of precommits: [1, 4], precommits not descending from commit target: [4], unused ancestry headers: [{:?}]",
+            unrelated_header.hash()
+        )));
+    }
+
+    #[test]
+    fn test_equivocation_extraction() {
+        let peers = [
+            Ed25519Keyring::Alice,
+            Ed25519Keyring::Bob,
+            Ed25519Keyring::Charlie,
+        ];
+        let voters = VoterSet::new(peers.iter().map(|peer| (peer.public().into(), 1))).unwrap();
+
+        let first_justification = create_justification(&peers, 0);
+        let mut conflicting_header = Header::new(
+            One::one(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+        );
+        conflicting_header.state_root = H256::repeat_byte(1);
+        // Alice and Bob voted for conflicting header in the same round, but
+        // Bob's conflicting vote has invalid signature.
+        let mut second_justification = test_utils::create_justification(
+            &conflicting_header,
+            1,
+            0,
+            &[Ed25519Keyring::Alice, Ed25519Keyring::Bob],
+        );
+        second_justification.commit.precommits[1].signature =
+            first_justification.commit.precommits[1].signature.clone();
+
+        let justifications = [first_justification, second_justification];
+        let equivocation_proofs = extract_equivocations(0, &voters, &justifications);
+        assert_eq!(equivocation_proofs.len(), 1);
+        let equivocation_proof = &equivocation_proofs[0];
+        assert_eq!(equivocation_proof.set_id(), 0);
+        assert_eq!(equivocation_proof.round(), 1);
+        let alice: AuthorityId = Ed25519Keyring::Alice.public().into();
+        assert_eq!(equivocation_proof.offender(), &alice);
+        assert_eq!(
+            equivocation_proof,
+            &EquivocationProof::new(
+                0,
+                Equivocation::Precommit(finality_grandpa::Equivocation {
+                    round_number: 1,
+                    identity: alice,
+                    first: (
+                        justifications[0].commit.precommits[0].precommit.clone(),
+                        justifications[0].commit.precommits[0].signature.clone(),
+                    ),
+                    second: (
+                        justifications[1].commit.precommits[0].precommit.clone(),
+                        justifications[1].commit.precommits[0].signature.clone(),
+                    ),
+                })
+            )
+        );
+
+        // Same equivocation is reported once, and votes signed for another set are ignored
+        let justifications = [
+            justifications[0].clone(),
+            justifications[1].clone(),
+            justifications[1].clone(),
+            test_utils::create_justification(&conflicting_header, 1, 1, &peers),
+        ];
+        assert_eq!(extract_equivocations(0, &voters, &justifications).len(), 1);
+    }
+}
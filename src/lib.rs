@@ -4,31 +4,71 @@
 // suppress instruction, we have put one at the crate level.
 // Once phase 2 is complete, this will be removed.
 #![allow(dead_code)]
+// Header and GRANDPA verification only needs `alloc`, so that it can be
+// reused inside runtimes and smart contracts. Client storage and the
+// CosmWasm contract are built on top of it with `std` feature.
+#![cfg_attr(not(feature = "std"), no_std)]
 
+#[cfg_attr(not(feature = "std"), macro_use)]
+extern crate alloc;
+
+#[cfg(feature = "std")]
+mod beefy;
+#[cfg(feature = "std")]
 mod block_import_wrapper;
+#[cfg(feature = "std")]
 mod block_processor;
+#[cfg(feature = "std")]
 mod client;
 mod common;
+#[cfg(feature = "std")]
 mod db;
+#[cfg(feature = "std")]
 mod genesis;
+#[cfg(feature = "std")]
 mod grandpa_block_import;
 mod justification;
+#[cfg(feature = "std")]
 mod light_state;
+#[cfg(feature = "std")]
+mod mmr;
+#[cfg(feature = "std")]
+mod state_proof;
+#[cfg(feature = "std")]
 mod storage;
 mod types;
 mod verifier;
 
+#[cfg(feature = "std")]
 pub mod contract;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod test_utils;
+pub use common::types::blockchain_error::BlockchainError;
+pub use common::types::light_authority_set::LightAuthoritySet;
+pub use common::types::next_change_in_authority::NextChangeInAuthority;
+pub use justification::{GrandpaJustification, JustificationReport, ProvableJustification};
+pub use verifier::{
+    check_extrinsics_root, find_forced_change, find_scheduled_change, verify_authority_change,
+    AuthorityChange,
+};
+
+#[cfg(feature = "std")]
+pub use common::types::block_import_error::BlockImportError;
+#[cfg(feature = "std")]
+pub use common::types::consensus_error::ConsensusError;
+#[cfg(feature = "std")]
+pub use common::types::light_client_error::LightClientError;
+#[cfg(feature = "std")]
 pub use contract::msg;
 
 /// WASM methods exposed to be used by CosmWasm handler
 /// All methods are thin wrapper around actual contract contained in
 /// contract module.
 
-#[cfg(target_arch = "wasm32")]
+#[cfg(all(target_arch = "wasm32", feature = "std"))]
 pub use wasm::{handle, init, query};
 
-#[cfg(target_arch = "wasm32")]
+#[cfg(all(target_arch = "wasm32", feature = "std"))]
 mod wasm {
     use super::contract;
     use cosmwasm_std::{
@@ -1,30 +1,58 @@
+use crate::beefy::{BeefyFinalityProof, BeefyValidatorSet};
 use crate::block_processor::setup_block_processor;
+use crate::client::Client;
+use crate::common::traits::finalizer::Finalizer;
 use crate::common::traits::header_backend::HeaderBackend;
 use crate::common::traits::storage::Storage as StorageT;
+use crate::common::types::archived_authority_set::ArchivedAuthoritySet;
 use crate::common::types::block_import_result::BlockImportResult;
 use crate::common::types::blockchain_error::BlockchainError;
 use crate::common::types::blockchain_info::BlockchainInfo;
+use crate::common::types::client_snapshot::ClientSnapshot;
 use crate::common::types::client_status::ClientStatus;
 use crate::common::types::incoming_block::IncomingBlock;
+use crate::common::types::ingested_header::IngestedHeader;
+use crate::common::types::justification_verification::JustificationVerification;
 use crate::common::types::light_authority_set::LightAuthoritySet;
+use crate::common::types::light_client_error::LightClientError;
 use crate::common::types::new_block_state::NewBlockState;
+use crate::common::types::retention_policy::RetentionPolicy;
+use crate::common::types::storage_window::StorageWindow;
 use crate::common::utils::{
-    fetch_light_authority_set, fetch_next_authority_change, initialize_storage,
-    insert_light_authority_set, NUM_COLUMNS,
+    archive_light_authority_set, fetch_archived_authority_set, fetch_beefy_validator_set,
+    fetch_justification_verification, fetch_light_authority_set, fetch_next_authority_change,
+    fetch_next_beefy_validator_set, initialize_storage, insert_beefy_validator_set,
+    insert_justification_verification, insert_light_authority_set, insert_next_beefy_validator_set,
+    store_next_authority_change, NUM_COLUMNS,
 };
 use crate::db::create;
 use crate::genesis::GenesisData;
+use crate::justification::{
+    extract_equivocations, GrandpaEquivocationProof, GrandpaJustification, JustificationReport,
+};
+use crate::mmr::HeaderMmr;
+use crate::state_proof::{
+    extract_event_record, grandpa_current_set_id_key, paras_heads_key, read_proof_value,
+    system_events_key, verify_extrinsic_proof, GRANDPA_AUTHORITIES_KEY,
+};
 use crate::types::{Block, Header};
-use parity_scale_codec::Encode;
+use finality_grandpa::voter_set::VoterSet;
+use parity_scale_codec::{Decode, Encode};
 use sp_api::BlockId;
-use sp_runtime::traits::{Block as BlockT, NumberFor};
+use sp_finality_grandpa::{AuthorityList, VersionedAuthorityList};
+use sp_runtime::traits::{Block as BlockT, Header as HeaderT, NumberFor};
 use sp_runtime::Justification;
 
-/// Initializes the database with initial header
-/// and authority set
+/// Initializes the database with initial header, authority set,
+/// retention policy of headers and the way justifications are verified.
+/// BEEFY validator set is only supplied for clients finalizing headers
+/// with BEEFY.
 pub(crate) fn initialize_state(
     initial_header: Header,
     initial_authority_set: LightAuthoritySet,
+    possible_beefy_validator_set: Option<BeefyValidatorSet>,
+    retention_policy: RetentionPolicy<NumberFor<Block>>,
+    justification_verification: JustificationVerification,
     max_headers_allowed_to_store: u64,
 ) -> Result<Vec<u8>, BlockchainError> {
     let db = create(NUM_COLUMNS);
@@ -34,12 +62,236 @@ pub(crate) fn initialize_state(
     };
     let empty_data = new_data.encode();
     let (data, storage) = initialize_storage(empty_data, max_headers_allowed_to_store)?;
+    archive_light_authority_set(
+        storage.clone(),
+        &ArchivedAuthoritySet::<Block> {
+            authority_set: initial_authority_set.clone(),
+            activation_number: *initial_header.number(),
+            activation_hash: initial_header.hash(),
+        },
+    )?;
     insert_light_authority_set(storage.clone(), initial_authority_set)?;
+    if let Some(beefy_validator_set) = possible_beefy_validator_set {
+        insert_beefy_validator_set(storage.clone(), beefy_validator_set)?;
+    }
+    storage.set_retention_policy(&retention_policy)?;
+    insert_justification_verification(storage.clone(), justification_verification)?;
     StorageT::<Block>::import_header(storage.as_ref(), initial_header, NewBlockState::Best)?;
 
     Ok(data.encode())
 }
 
+/// Exports snapshot of the database, which can be restored with `restore_state`.
+/// Last finalized header needs to be still retained.
+pub(crate) fn export_snapshot(
+    encoded_data: Vec<u8>,
+) -> Result<ClientSnapshot<Block>, BlockchainError> {
+    // It doesn't matter what is the value of max_headers_allowed_to_store as we are only reading the storage
+    let (_, storage) = initialize_storage(encoded_data.clone(), 2)?;
+    let status = current_status::<Block>(encoded_data.clone())?;
+    let info: BlockchainInfo<Block> = storage.info();
+    if info.finalized_hash != Default::default() && status.possible_last_finalized_header.is_none()
+    {
+        return Err(BlockchainError::UnknownBlock(format!(
+            "Last finalized header at height {} is no longer stored",
+            info.finalized_number
+        )));
+    }
+    let (retained_headers, _) = retained_headers(encoded_data, None, u32::MAX, false)?;
+    let light_authority_set = status
+        .possible_light_authority_set
+        .ok_or(BlockchainError::InvalidAuthoritiesSet)?;
+    // Sets are archived from the initial one up to the current one
+    let mut authority_set_archive = vec![];
+    for set_id in (0..=light_authority_set.set_id()).rev() {
+        match fetch_archived_authority_set(storage.clone(), set_id)? {
+            Some(archived_authority_set) => authority_set_archive.push(archived_authority_set),
+            None => break,
+        }
+    }
+    authority_set_archive.reverse();
+
+    Ok(ClientSnapshot {
+        possible_finalized_header: status.possible_last_finalized_header,
+        retained_headers,
+        light_authority_set,
+        authority_set_archive,
+        possible_next_change_in_authority: status.possible_next_change_in_authority,
+        possible_beefy_validator_set: fetch_beefy_validator_set(storage.clone())?,
+        possible_next_beefy_validator_set: fetch_next_beefy_validator_set(storage.clone())?,
+        header_mmr: storage.header_mmr()?,
+        retention_policy: storage.retention_policy()?,
+        justification_verification: fetch_justification_verification(storage.clone())?,
+    })
+}
+
+/// Restores the database from snapshot. Retained headers are imported one
+/// by one, so they need to form a chain, which includes finalized header.
+pub(crate) fn restore_state(
+    snapshot: ClientSnapshot<Block>,
+    max_headers_allowed_to_store: u64,
+) -> Result<Vec<u8>, BlockchainError> {
+    if snapshot.retained_headers.is_empty() {
+        return Err(BlockchainError::Msg(
+            "Snapshot doesn't contain any header".into(),
+        ));
+    }
+
+    let db = create(NUM_COLUMNS);
+    let new_data = crate::db::Data {
+        db,
+        genesis_data: GenesisData {},
+    };
+    let (data, storage) = initialize_storage(new_data.encode(), max_headers_allowed_to_store)?;
+    for archived_authority_set in snapshot.authority_set_archive.iter() {
+        archive_light_authority_set(storage.clone(), archived_authority_set)?;
+    }
+    insert_light_authority_set(storage.clone(), snapshot.light_authority_set)?;
+    if let Some(next_change_in_authority) = snapshot.possible_next_change_in_authority.as_ref() {
+        store_next_authority_change(storage.clone(), next_change_in_authority)?;
+    }
+    if let Some(beefy_validator_set) = snapshot.possible_beefy_validator_set {
+        insert_beefy_validator_set(storage.clone(), beefy_validator_set)?;
+    }
+    insert_next_beefy_validator_set(storage.clone(), snapshot.possible_next_beefy_validator_set)?;
+    storage.set_retention_policy(&snapshot.retention_policy)?;
+    insert_justification_verification(storage.clone(), snapshot.justification_verification)?;
+    for header in snapshot.retained_headers {
+        StorageT::<Block>::import_header(storage.as_ref(), header, NewBlockState::Best)?;
+    }
+    if let Some(finalized_header) = snapshot.possible_finalized_header {
+        storage.restore_finality::<Block>(finalized_header.hash(), &snapshot.header_mmr)?;
+    }
+
+    Ok(data.encode())
+}
+
+/// Verifies that initial header is finalized by the initial authority set,
+/// and optionally that the authority set and its id are the ones stored
+/// under the header's state root. Header shouldn't be the one enacting an
+/// authority set change, as its state would already hold the next set.
+pub(crate) fn verify_initial_state(
+    initial_header: &Header,
+    initial_authority_set: &LightAuthoritySet,
+    justification: &Justification,
+    possible_authority_set_proof: Option<Vec<Vec<u8>>>,
+) -> Result<(), BlockchainError> {
+    let voters = VoterSet::new(initial_authority_set.authorities())
+        .ok_or(BlockchainError::InvalidAuthoritiesSet)?;
+    let grandpa_justification =
+        GrandpaJustification::<Block>::decode(&mut justification.as_slice())
+            .map_err(|_| BlockchainError::JustificationDecode)?;
+    grandpa_justification.verify_finalization(
+        initial_authority_set.set_id(),
+        (initial_header.hash(), *initial_header.number()),
+        &voters,
+    )?;
+
+    if let Some(authority_set_proof) = possible_authority_set_proof {
+        let state_root = initial_header.state_root();
+        let read_value = |key: &[u8]| {
+            read_proof_value::<<Header as HeaderT>::Hashing>(
+                state_root,
+                authority_set_proof.clone(),
+                key,
+            )?
+            .ok_or_else(|| {
+                BlockchainError::InvalidProof(format!(
+                    "Key 0x{} is absent from the state",
+                    hex::encode(key)
+                ))
+            })
+        };
+
+        let encoded_authorities = read_value(GRANDPA_AUTHORITIES_KEY)?;
+        let authorities: AuthorityList =
+            VersionedAuthorityList::decode(&mut encoded_authorities.as_slice())
+                .map_err(|e| BlockchainError::DataDecode(e.to_string()))?
+                .into();
+        if authorities != initial_authority_set.authorities() {
+            return Err(BlockchainError::InvalidProof(
+                "Authority set doesn't match the one stored in the state".into(),
+            ));
+        }
+
+        let encoded_set_id = read_value(&grandpa_current_set_id_key())?;
+        let set_id = u64::decode(&mut encoded_set_id.as_slice())
+            .map_err(|e| BlockchainError::DataDecode(e.to_string()))?;
+        if set_id != initial_authority_set.set_id() {
+            return Err(BlockchainError::InvalidProof(format!(
+                "Authority set id {} doesn't match id {} stored in the state",
+                initial_authority_set.set_id(),
+                set_id
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+/// Verifies justification against archived authority set with `set_id`,
+/// which doesn't need to be the current one. Target of the justification
+/// doesn't need to be stored, verified justification is returned along
+/// with the authority set.
+pub(crate) fn verify_archived_justification(
+    encoded_data: Vec<u8>,
+    set_id: u64,
+    justification: &Justification,
+) -> Result<(ArchivedAuthoritySet<Block>, GrandpaJustification<Block>), BlockchainError> {
+    // It doesn't matter what is the value of max_headers_allowed_to_store as we are only reading the storage
+    let (_, storage) = initialize_storage(encoded_data, 2)?;
+    let archived_authority_set = fetch_archived_authority_set(storage, set_id)?
+        .ok_or_else(|| BlockchainError::Msg(format!("Authority set {} isn't archived", set_id)))?;
+    let voters = VoterSet::new(archived_authority_set.authority_set.authorities())
+        .ok_or(BlockchainError::InvalidAuthoritiesSet)?;
+    let grandpa_justification =
+        GrandpaJustification::<Block>::decode(&mut justification.as_slice())
+            .map_err(|_| BlockchainError::JustificationDecode)?;
+    grandpa_justification.verify_batched(set_id, &voters)?;
+
+    Ok((archived_authority_set, grandpa_justification))
+}
+
+/// Dry-runs verification of justification against archived authority set with
+/// `set_id`, or the current authority set if `set_id` isn't given. Instead of
+/// failing on the first problem, all of them are reported.
+pub(crate) fn report_justification(
+    encoded_data: Vec<u8>,
+    set_id: Option<u64>,
+    justification: &Justification,
+) -> Result<(ArchivedAuthoritySet<Block>, JustificationReport<Block>), BlockchainError> {
+    // It doesn't matter what is the value of max_headers_allowed_to_store as we are only reading the storage
+    let (_, storage) = initialize_storage(encoded_data, 2)?;
+    let set_id = match set_id {
+        Some(set_id) => set_id,
+        None => fetch_light_authority_set(storage.clone())?
+            .ok_or(BlockchainError::InvalidAuthoritiesSet)?
+            .set_id(),
+    };
+    let archived_authority_set = fetch_archived_authority_set(storage, set_id)?
+        .ok_or_else(|| BlockchainError::Msg(format!("Authority set {} isn't archived", set_id)))?;
+    let voters = VoterSet::new(archived_authority_set.authority_set.authorities())
+        .ok_or(BlockchainError::InvalidAuthoritiesSet)?;
+    let grandpa_justification =
+        GrandpaJustification::<Block>::decode(&mut justification.as_slice())
+            .map_err(|_| BlockchainError::JustificationDecode)?;
+    let report = grandpa_justification.report(set_id, &voters);
+
+    Ok((archived_authority_set, report))
+}
+
+/// Changes amount of headers retained in the window of light client storage,
+/// pruning the oldest headers right away if the window shrinks.
+pub(crate) fn resize_storage(
+    encoded_data: Vec<u8>,
+    max_headers_allowed_to_store: u64,
+) -> Result<Vec<u8>, BlockchainError> {
+    let (data, storage) = initialize_storage(encoded_data, max_headers_allowed_to_store)?;
+    storage.prune::<Block>()?;
+
+    Ok(data.encode())
+}
+
 /// Gives current status of database passed which includes
 /// current best header, finalized header, light authority set
 /// as well as next authority set change scheduled.
@@ -75,19 +327,24 @@ where
 /// Until justification is not provided block won't be marked as
 /// finalized. And if there are already `max_non_finalized_blocks`
 /// in db, it won't accept another header.
+/// If block body is supplied, it is checked against extrinsics root
+/// of the header, but isn't stored.
 pub(crate) fn ingest_finalized_header(
     encoded_data: Vec<u8>,
     finalized_header: Header,
+    body: Option<Vec<<Block as BlockT>::Extrinsic>>,
     justification: Option<Justification>,
     max_non_finalized_blocks_allowed: u64,
-) -> Result<(BlockImportResult<NumberFor<Block>>, Vec<u8>), String> {
-    let (mut block_processor_fn, data) =
-        setup_block_processor(encoded_data, max_non_finalized_blocks_allowed)
-            .map_err(|e| format!("{}", e))?;
+) -> Result<(IngestedHeader<Block>, Vec<u8>), LightClientError> {
+    let (mut block_processor_fn, data, storage) =
+        setup_block_processor(encoded_data, max_non_finalized_blocks_allowed)?;
+    let possible_previous_finalized = last_finalized(storage.info());
+    let previous_set_id = fetch_light_authority_set(storage.clone())?.map_or(0, |s| s.set_id());
+    let hash = finalized_header.hash();
     let incoming_block = IncomingBlock {
-        hash: finalized_header.hash(),
+        hash,
         header: Some(finalized_header),
-        body: None,
+        body,
         justification,
         allow_missing_state: false,
         import_existing: false,
@@ -100,34 +357,343 @@ pub(crate) fn ingest_finalized_header(
         BlockImportResult::ImportedKnown(_) => {}
         BlockImportResult::ImportedUnknown(_, aux) => {
             if aux.bad_justification || aux.needs_finality_proof {
-                return Err(format!(
-                    "Error: {}",
-                    "Justification is invalid or authority set is not updated."
-                ));
+                return Err(BlockchainError::BadJustification(
+                    "Justification is invalid or authority set is not updated.".into(),
+                )
+                .into());
             }
         }
     }
-    Ok((block_import_response, data.encode()))
+
+    // Scheduled change is stored along with the header signaling it
+    let possible_scheduled_change = fetch_next_authority_change::<_, Block>(storage.clone())?
+        .filter(|change| change.block_enacting_this_change.0 == hash);
+    let ingested_header = IngestedHeader {
+        block_import_result: block_import_response,
+        possible_previous_finalized,
+        possible_finalized: last_finalized(storage.info()),
+        possible_scheduled_change,
+        previous_set_id,
+        set_id: fetch_light_authority_set(storage)?.map_or(0, |s| s.set_id()),
+    };
+    Ok((ingested_header, data.encode()))
+}
+
+/// Ingests header finalized by BEEFY validators, as proven by MMR leaf
+/// appended by its child. Header is imported first, unless it is already
+/// known, and then finalized along with its ancestors. Commitments can be
+/// signed by the current validator set, or by the next one once the leaf
+/// of a finalized header has announced it. The next set becomes the current
+/// one only when it signs a commitment. Set ids of ingested header are the
+/// ones of current BEEFY validator sets.
+pub(crate) fn ingest_beefy_finalized_header(
+    encoded_data: Vec<u8>,
+    finalized_header: Header,
+    finality_proof: BeefyFinalityProof,
+    max_non_finalized_blocks_allowed: u64,
+) -> Result<(IngestedHeader<Block>, Vec<u8>), LightClientError> {
+    let (_, storage) = initialize_storage(encoded_data.clone(), max_non_finalized_blocks_allowed)?;
+    let validator_set = fetch_beefy_validator_set(storage.clone())?
+        .ok_or(BlockchainError::InvalidAuthoritiesSet)?;
+    let possible_next_validator_set = fetch_next_beefy_validator_set(storage)?;
+    let signed_by_next_set = possible_next_validator_set.as_ref().map(|v| v.id)
+        == Some(finality_proof.signed_commitment.commitment.validator_set_id);
+    let signing_validator_set = match &possible_next_validator_set {
+        Some(next_validator_set) if signed_by_next_set => next_validator_set,
+        _ => &validator_set,
+    };
+    let leaf = finality_proof.verify(signing_validator_set)?;
+    let hash = finalized_header.hash();
+    if leaf.parent_number_and_hash != (*finalized_header.number(), hash) {
+        return Err(BlockchainError::BadJustification(
+            "MMR leaf isn't appended by child of the header".into(),
+        )
+        .into());
+    }
+
+    let (mut ingested_header, encoded_data) = ingest_finalized_header(
+        encoded_data,
+        finalized_header,
+        None,
+        None,
+        max_non_finalized_blocks_allowed,
+    )?;
+    let (data, storage) = initialize_storage(encoded_data, max_non_finalized_blocks_allowed)?;
+    Client::new(storage.clone()).finalize_block(BlockId::<Block>::Hash(hash), None)?;
+    ingested_header.possible_finalized = last_finalized(storage.info());
+
+    let (current_validator_set, mut possible_next_validator_set) = if signed_by_next_set {
+        (signing_validator_set.clone(), None)
+    } else {
+        (validator_set.clone(), possible_next_validator_set)
+    };
+    if leaf.beefy_next_authority_set.id > current_validator_set.id {
+        possible_next_validator_set = Some(leaf.beefy_next_authority_set.clone());
+    }
+    ingested_header.previous_set_id = validator_set.id;
+    ingested_header.set_id = current_validator_set.id;
+    insert_beefy_validator_set(storage.clone(), current_validator_set)?;
+    insert_next_beefy_validator_set(storage, possible_next_validator_set)?;
+
+    Ok((ingested_header, data.encode()))
+}
+
+fn last_finalized(
+    info: BlockchainInfo<Block>,
+) -> Option<(NumberFor<Block>, <Block as BlockT>::Hash)> {
+    if info.finalized_hash == Default::default() {
+        None
+    } else {
+        Some((info.finalized_number, info.finalized_hash))
+    }
+}
+
+/// Fetches current BEEFY validator set, if client finalizes headers with BEEFY.
+pub(crate) fn beefy_validator_set(
+    encoded_data: Vec<u8>,
+) -> Result<Option<BeefyValidatorSet>, BlockchainError> {
+    // It doesn't matter what is the value of max_headers_allowed_to_store as we are only reading the storage
+    let (_, storage) = initialize_storage(encoded_data, 2)?;
+    fetch_beefy_validator_set(storage)
+}
+
+/// Fetches finalized header at given height, as long as it is
+/// still retained in the storage.
+pub(crate) fn finalized_header_at(
+    encoded_data: Vec<u8>,
+    height: NumberFor<Block>,
+) -> Result<Header, BlockchainError> {
+    // It doesn't matter what is the value of max_headers_allowed_to_store as we are only reading the storage
+    let (_, storage) = initialize_storage(encoded_data, 2)?;
+    let info: BlockchainInfo<Block> = storage.info();
+    if info.finalized_hash == Default::default() || height > info.finalized_number {
+        return Err(BlockchainError::UnknownBlock(format!(
+            "Block at height {} isn't finalized",
+            height
+        )));
+    }
+    let possible_header = storage.header(BlockId::<Block>::Number(height))?;
+    possible_header.ok_or_else(|| {
+        BlockchainError::UnknownBlock(format!("Block at height {} is no longer stored", height))
+    })
+}
+
+/// Verifies that header is part of finalized chain, using inclusion proof
+/// of its hash in MMR of finalized headers. Unlike `finalized_header_at`,
+/// it works for headers which are no longer retained in the storage.
+pub(crate) fn verify_header_inclusion(
+    encoded_data: Vec<u8>,
+    header: &Header,
+    leaf_index: u64,
+    proof: &[<Block as BlockT>::Hash],
+) -> Result<HeaderMmr<<Block as BlockT>::Hash>, BlockchainError> {
+    // It doesn't matter what is the value of max_headers_allowed_to_store as we are only reading the storage
+    let (_, storage) = initialize_storage(encoded_data, 2)?;
+    let header_mmr = storage.header_mmr()?;
+    header_mmr.verify_proof::<<Header as HeaderT>::Hashing>(leaf_index, header.hash(), proof)?;
+
+    Ok(header_mmr)
+}
+
+/// Fetches range of headers retained in the storage.
+pub(crate) fn storage_window(
+    encoded_data: Vec<u8>,
+) -> Result<StorageWindow<NumberFor<Block>>, BlockchainError> {
+    // It doesn't matter what is the value of max_headers_allowed_to_store as we are only reading the storage
+    let (_, storage) = initialize_storage(encoded_data, 2)?;
+    storage.storage_window::<Block>()
+}
+
+/// Fetches up to `limit` retained headers, starting at `start_height` and
+/// walking towards newer headers, or towards older ones if `reverse` is set.
+/// Without `start_height`, walk starts at the oldest header, or at the best
+/// one if `reverse` is set. Heights outside the window are skipped.
+pub(crate) fn retained_headers(
+    encoded_data: Vec<u8>,
+    start_height: Option<NumberFor<Block>>,
+    limit: u32,
+    reverse: bool,
+) -> Result<(Vec<Header>, StorageWindow<NumberFor<Block>>), BlockchainError> {
+    // It doesn't matter what is the value of max_headers_allowed_to_store as we are only reading the storage
+    let (_, storage) = initialize_storage(encoded_data, 2)?;
+    let window = storage.storage_window::<Block>()?;
+
+    let heights: Box<dyn Iterator<Item = NumberFor<Block>>> = if reverse {
+        let start_height = start_height.map_or(window.best_number, |h| h.min(window.best_number));
+        Box::new((window.oldest_number..=start_height).rev())
+    } else {
+        let start_height =
+            start_height.map_or(window.oldest_number, |h| h.max(window.oldest_number));
+        Box::new(start_height..=window.best_number)
+    };
+
+    let mut headers = vec![];
+    for height in heights.take(limit as usize) {
+        let possible_header = storage.header(BlockId::<Block>::Number(height))?;
+        headers.push(possible_header.ok_or_else(|| {
+            BlockchainError::Backend(format!(
+                "FATAL: Storage inconsistency. Unable to retrieve stored block at height {}",
+                height
+            ))
+        })?);
+    }
+
+    Ok((headers, window))
+}
+
+/// Verifies storage proof of `System::Events` against state root of
+/// given header, and returns SCALE encoded `EventRecord` at `event_index`,
+/// optionally checking that it is emitted by given pallet and is of given
+/// variant. Header must be verified to be finalized by the caller.
+pub(crate) fn verify_event_proof(
+    header: &Header,
+    proof: Vec<Vec<u8>>,
+    event_index: u32,
+    record_lengths: &[u32],
+    pallet_index: Option<u8>,
+    variant_index: Option<u8>,
+) -> Result<Vec<u8>, BlockchainError> {
+    let possible_encoded_events = read_proof_value::<<Header as HeaderT>::Hashing>(
+        header.state_root(),
+        proof,
+        &system_events_key(),
+    )?;
+    let encoded_events = possible_encoded_events.ok_or_else(|| {
+        BlockchainError::InvalidProof("System::Events key is absent from the state".into())
+    })?;
+
+    extract_event_record(
+        &encoded_events,
+        event_index,
+        record_lengths,
+        pallet_index,
+        variant_index,
+    )
+}
+
+/// Verifies ordered trie proof of SCALE encoded `extrinsic` at `extrinsic_index`
+/// against extrinsics root of given header. Header must be verified to be
+/// finalized by the caller.
+pub(crate) fn verify_extrinsic_inclusion(
+    header: &Header,
+    proof: Vec<Vec<u8>>,
+    extrinsic_index: u32,
+    extrinsic: &[u8],
+) -> Result<(), BlockchainError> {
+    verify_extrinsic_proof::<<Header as HeaderT>::Hashing>(
+        header.extrinsics_root(),
+        proof,
+        extrinsic_index,
+        extrinsic,
+    )
+}
+
+/// Verifies storage proof of `Paras::Heads(para_id)` against state root of
+/// given relay chain header, and checks that the stored head data is the
+/// encoded parachain header. Relay chain header must be verified to be
+/// finalized by the caller.
+pub(crate) fn verify_parachain_head(
+    relay_header: &Header,
+    para_id: u32,
+    proof: Vec<Vec<u8>>,
+    parachain_header: &Header,
+) -> Result<(), BlockchainError> {
+    let possible_encoded_head_data = read_proof_value::<<Header as HeaderT>::Hashing>(
+        relay_header.state_root(),
+        proof,
+        &paras_heads_key(para_id),
+    )?;
+    let encoded_head_data = possible_encoded_head_data.ok_or_else(|| {
+        BlockchainError::InvalidProof(format!(
+            "Paras::Heads({}) key is absent from the state",
+            para_id
+        ))
+    })?;
+    let head_data = Vec::<u8>::decode(&mut encoded_head_data.as_slice())
+        .map_err(|e| BlockchainError::DataDecode(e.to_string()))?;
+    if head_data != parachain_header.encode() {
+        return Err(BlockchainError::InvalidProof(format!(
+            "Head of parachain {} doesn't match the supplied header",
+            para_id
+        )));
+    }
+
+    Ok(())
+}
+
+/// Reads value stored under `key` from storage proof verified against given
+/// state root, e.g. the one of tracked parachain head.
+pub(crate) fn read_state_value(
+    state_root: &<Header as HeaderT>::Hash,
+    proof: Vec<Vec<u8>>,
+    key: &[u8],
+) -> Result<Option<Vec<u8>>, BlockchainError> {
+    read_proof_value::<<Header as HeaderT>::Hashing>(state_root, proof, key)
+}
+
+/// Extracts proofs of precommit equivocations from justifications created by
+/// the current authority set, in the format accepted by
+/// `pallet_grandpa::report_equivocation`.
+pub(crate) fn extract_equivocation_proofs(
+    encoded_data: Vec<u8>,
+    justifications: Vec<Justification>,
+) -> Result<Vec<GrandpaEquivocationProof<Block>>, BlockchainError> {
+    // It doesn't matter what is the value of max_headers_allowed_to_store as we are only reading the storage
+    let (_, storage) = initialize_storage(encoded_data, 2)?;
+    let light_authority_set =
+        fetch_light_authority_set(storage)?.ok_or(BlockchainError::InvalidAuthoritiesSet)?;
+    let voters = VoterSet::new(light_authority_set.authorities())
+        .ok_or(BlockchainError::InvalidAuthoritiesSet)?;
+
+    let mut grandpa_justifications = vec![];
+    for justification in justifications {
+        grandpa_justifications.push(
+            GrandpaJustification::<Block>::decode(&mut justification.as_slice())
+                .map_err(|_| BlockchainError::JustificationDecode)?,
+        );
+    }
+
+    Ok(extract_equivocations(
+        light_authority_set.set_id(),
+        &voters,
+        &grandpa_justifications,
+    ))
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::common::types::block_import_result::BlockImportResult;
+    use crate::common::types::blockchain_error::BlockchainError;
+    use crate::common::types::justification_verification::JustificationVerification;
     use crate::common::types::light_authority_set::LightAuthoritySet;
-    use crate::justification::{Commit, GrandpaJustification, Message, Precommit};
-    use crate::light_state::{current_status, ingest_finalized_header, initialize_state};
+    use crate::common::types::retention_policy::RetentionPolicy;
+    use crate::justification::{GrandpaJustification, VerifiedWeight};
+    use crate::light_state::{
+        beefy_validator_set, current_status, export_snapshot, finalized_header_at,
+        ingest_beefy_finalized_header, ingest_finalized_header, initialize_state,
+        report_justification, verify_archived_justification, verify_event_proof,
+        verify_header_inclusion, verify_initial_state,
+    };
+    use crate::state_proof::{
+        grandpa_current_set_id_key, system_events_key, GRANDPA_AUTHORITIES_KEY,
+    };
+    use crate::test_utils::{
+        create_finality_proof, create_justification, create_justification_commit,
+        create_next_header, make_ids, validator_keys, validator_set, ChainSimulator,
+    };
     use crate::types::{Block, Header};
     use clear_on_drop::clear::Clear;
-    use finality_grandpa::SignedPrecommit;
     use parity_scale_codec::Encode;
     use sp_core::crypto::Public;
     use sp_core::H256;
     use sp_finality_grandpa::{
-        AuthorityId, AuthorityList, AuthoritySignature, ScheduledChange, GRANDPA_ENGINE_ID,
+        AuthorityId, ScheduledChange, VersionedAuthorityList, GRANDPA_ENGINE_ID,
     };
     use sp_keyring::ed25519::Keyring;
     use sp_keyring::Ed25519Keyring;
-    use sp_runtime::traits::{Block as BlockT, Header as HeaderT, NumberFor, One};
+    use sp_runtime::traits::{BlakeTwo256, Block as BlockT, Header as HeaderT, NumberFor, One};
     use sp_runtime::{DigestItem, Justification};
+    use sp_trie::{trie_types::TrieDBMut, MemoryDB, TrieMut};
     use std::io::Write;
     use termcolor::{Color, ColorChoice, ColorSpec, StandardStream, WriteColor};
 
@@ -276,7 +842,14 @@ mod tests {
             ),
         );
 
-        let result = initialize_state(initial_header.clone(), authority_set, 2);
+        let result = initialize_state(
+            initial_header.clone(),
+            authority_set,
+            None,
+            RetentionPolicy::default(),
+            JustificationVerification::Full,
+            2,
+        );
         assert!(result.is_ok());
         let encoded_data = result.unwrap();
         assert!(encoded_data.len() > 0);
@@ -317,7 +890,8 @@ mod tests {
             ),
         );
 
-        let result = ingest_finalized_header(encoded_data, header.clone(), justification, 256);
+        let result =
+            ingest_finalized_header(encoded_data, header.clone(), None, justification, 256);
         assert!(result.is_ok());
         let encoded_data = result.unwrap().1;
         // Best header need to be updated
@@ -358,9 +932,10 @@ mod tests {
             ),
         );
 
-        let result = ingest_finalized_header(encoded_data, header.clone(), justification, 256);
+        let result =
+            ingest_finalized_header(encoded_data, header.clone(), None, justification, 256);
         assert!(result.is_err());
-        assert_eq!(result.err().unwrap(), expected_error);
+        assert_eq!(format!("{:?}", result.err().unwrap()), expected_error);
 
         write_failure_assert(
             print_level,
@@ -375,14 +950,6 @@ mod tests {
         );
     }
 
-    fn create_next_header(header: Header) -> Header {
-        let mut next_header = header.clone();
-        next_header.number += 1;
-        next_header.parent_hash = header.hash();
-        next_header.digest.clear();
-        next_header
-    }
-
     fn internal_assert_best_header(encoded_data: Vec<u8>, expected_to_be_best_header: &Header) {
         let result = current_status::<Block>(encoded_data.clone());
         assert!(result.is_ok());
@@ -531,7 +1098,7 @@ mod tests {
     #[test]
     fn test_initialize_db_success() {
         let (encoded_data, initial_header) = assert_successful_db_init(None, 1);
-        let next_header = create_next_header(initial_header);
+        let next_header = create_next_header(&initial_header);
         assert_successful_header_ingestion(encoded_data, next_header, None, 1);
     }
 
@@ -539,18 +1106,18 @@ mod tests {
     fn test_initialize_db_non_sequential_block() {
         let (encoded_data, initial_header) = assert_successful_db_init(None, 1);
 
-        let mut next_header = create_next_header(initial_header);
+        let mut next_header = create_next_header(&initial_header);
         // Let's change number of block to be non sequential
         next_header.number += 1;
 
-        assert_failed_header_ingestion(encoded_data, next_header, None, String::from("Other(ClientImport(\"Import failed: Trying to import blocks in non-sequential order. to be imported block need to be child of last best block or first block itself. Expected block number: 2. Got: 3\"))"), 1);
+        assert_failed_header_ingestion(encoded_data, next_header, None, String::from("Blockchain(NonSequentialImport(\"to be imported block need to be child of last best block or first block itself. Expected block number: 2. Got: 3\"))"), 1);
     }
 
     #[test]
     fn test_initialize_db_wrong_parent_hash() {
         let (encoded_data, initial_header) = assert_successful_db_init(None, 1);
 
-        let mut next_header = create_next_header(initial_header);
+        let mut next_header = create_next_header(&initial_header);
         // Setting wrong parent hash
         next_header.parent_hash = Default::default();
 
@@ -558,7 +1125,7 @@ mod tests {
             encoded_data,
             next_header,
             None,
-            String::from("UnknownParent"),
+            String::from("BlockImport(UnknownParent)"),
             1,
         );
     }
@@ -587,7 +1154,7 @@ mod tests {
         let (encoded_data, initial_header) =
             assert_successful_db_init(Some(genesis_authority_set.clone()), 1);
 
-        let mut first_header = create_next_header(initial_header);
+        let mut first_header = create_next_header(&initial_header);
         let change = ScheduledChange {
             next_authorities: first_voters.clone(),
             delay: 3,
@@ -596,9 +1163,9 @@ mod tests {
             GRANDPA_ENGINE_ID,
             sp_finality_grandpa::ConsensusLog::ScheduledChange(change.clone()).encode(),
         ));
-        let mut second_header = create_next_header(first_header.clone());
-        let third_header = create_next_header(second_header.clone());
-        let mut fourth_header = create_next_header(third_header.clone());
+        let mut second_header = create_next_header(&first_header);
+        let third_header = create_next_header(&second_header);
+        let mut fourth_header = create_next_header(&third_header);
         let new_change = ScheduledChange {
             next_authorities: second_voters.clone(),
             delay: 2,
@@ -642,7 +1209,7 @@ mod tests {
             encoded_data.clone(),
             second_header.clone(),
             None,
-            String::from("VerificationFailed(\"Scheduled change already exists.\")"),
+            String::from("BlockImport(VerificationFailed(\"Scheduled change already exists.\"))"),
             1,
         );
         // After clearing digest we should be able to ingest header
@@ -670,7 +1237,7 @@ mod tests {
             fourth_header.clone(),
             None,
             String::from(
-                "VerificationFailed(\"block trying to enact new authority set isn\\'t finalized\")",
+                "BlockImport(VerificationFailed(\"block trying to enact new authority set isn't finalized\"))",
             ),
             1,
         );
@@ -685,10 +1252,11 @@ mod tests {
             commit,
             votes_ancestries: vec![],
         };
+        let third_header_justification = grandpa_justification.encode();
         let encoded_data = assert_successful_header_ingestion(
             saved_encoded_data,
             third_header.clone(),
-            Some(grandpa_justification.encode()),
+            Some(third_header_justification.clone()),
             1,
         );
 
@@ -714,17 +1282,18 @@ mod tests {
         // In this case new authority set will be enacted and aux entry will be removed
 
         write_test_flow(format!("\n\nNow, a scenario where scheduled change isn't part of digest after two blocks delay. In this case new authority set will be enacted and aux entry will be removed"));
-        let fifth_header = create_next_header(fourth_header.clone());
+        let fifth_header = create_next_header(&fourth_header);
         let commit = create_justification_commit(1, 1, vec![fifth_header.clone()], &first_peers);
         let grandpa_justification: GrandpaJustification<Block> = GrandpaJustification {
             round: 1,
             commit,
             votes_ancestries: vec![],
         };
+        let fifth_header_justification = grandpa_justification.encode();
         let encoded_data = assert_successful_header_ingestion(
             encoded_data,
             fifth_header.clone(),
-            Some(grandpa_justification.encode()),
+            Some(fifth_header_justification.clone()),
             1,
         );
 
@@ -737,7 +1306,7 @@ mod tests {
         // was incremented by 1.
         assert_authority_set(encoded_data.clone(), &first_authority_set, 1);
 
-        let sixth_header = create_next_header(fifth_header.clone());
+        let sixth_header = create_next_header(&fifth_header);
         let encoded_data =
             assert_successful_header_ingestion(encoded_data, sixth_header.clone(), None, 1);
 
@@ -753,49 +1322,71 @@ mod tests {
         // so while ingesting new authority set it
         // was incremented by 1.
         assert_authority_set(encoded_data.clone(), &second_authority_set, 1);
-    }
 
-    fn make_ids(keys: &[Ed25519Keyring]) -> AuthorityList {
-        keys.iter()
-            .map(|key| key.clone().public().into())
-            .map(|id| (id, 1))
-            .collect()
-    }
-
-    fn create_justification_commit(
-        round: u64,
-        set_id: u64,
-        header_ancestry: Vec<Header>,
-        peers: &[Keyring],
-    ) -> Commit<Block> {
-        assert!(header_ancestry.len() > 0);
-        let first_header = header_ancestry.first().unwrap().clone();
-        let mut precommits: Vec<SignedPrecommit<H256, u32, AuthoritySignature, AuthorityId>> =
-            vec![];
-        for header in header_ancestry {
-            let precommit = Precommit::<Block> {
-                target_hash: header.hash().clone(),
-                target_number: *header.number(),
-            };
-            let msg = Message::<Block>::Precommit(precommit.clone());
-            let mut encoded_msg: Vec<u8> = Vec::new();
-            encoded_msg.clear();
-            (&msg, round, set_id).encode_to(&mut encoded_msg);
-            for peer in peers {
-                let signature = peer.sign(&encoded_msg[..]).into();
-                precommits.push(SignedPrecommit {
-                    precommit: precommit.clone(),
-                    signature,
-                    id: peer.public().into(),
-                });
-            }
-        }
+        // Justifications of previous sets can still be verified against archived sets
+        let (archived_authority_set, grandpa_justification) =
+            verify_archived_justification(encoded_data.clone(), 0, &third_header_justification)
+                .unwrap();
+        assert_eq!(archived_authority_set.activation_number, 1);
+        assert_eq!(
+            grandpa_justification.commit.target_hash,
+            third_header.hash()
+        );
+        let (archived_authority_set, grandpa_justification) =
+            verify_archived_justification(encoded_data.clone(), 1, &fifth_header_justification)
+                .unwrap();
+        assert_eq!(
+            archived_authority_set.authority_set.authorities(),
+            first_voters
+        );
+        assert_eq!(
+            (
+                archived_authority_set.activation_hash,
+                archived_authority_set.activation_number
+            ),
+            (fourth_header.hash(), fourth_header.number)
+        );
+        assert_eq!(
+            grandpa_justification.commit.target_hash,
+            fifth_header.hash()
+        );
+        let result =
+            verify_archived_justification(encoded_data.clone(), 1, &third_header_justification);
+        assert!(matches!(result, Err(BlockchainError::BadJustification(_))));
+        let result =
+            verify_archived_justification(encoded_data.clone(), 3, &fifth_header_justification);
+        assert_eq!(
+            result.err().unwrap().to_string(),
+            "Authority set 3 isn't archived"
+        );
 
-        Commit::<Block> {
-            target_hash: first_header.hash().clone(),
-            target_number: *first_header.number(),
-            precommits,
-        }
+        // Dry run reports all problems of a justification, by default against current set
+        let (archived_authority_set, report) =
+            report_justification(encoded_data.clone(), None, &third_header_justification).unwrap();
+        assert_eq!(archived_authority_set.authority_set.set_id(), 2);
+        assert!(!report.is_valid());
+        assert!(report.signing_authorities.is_empty());
+        assert_eq!(report.required_weight, 2);
+        assert_eq!(
+            report.unknown_voters,
+            genesis_voters
+                .iter()
+                .map(|(id, _)| id.clone())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(report.invalid_signatures, vec![0, 1]);
+        let (_, report) =
+            report_justification(encoded_data.clone(), Some(0), &third_header_justification)
+                .unwrap();
+        assert!(report.is_valid());
+        assert_eq!(report.signed_weight, 2);
+        let snapshot = export_snapshot(encoded_data).unwrap();
+        let archived_activations = snapshot
+            .authority_set_archive
+            .iter()
+            .map(|a| (a.authority_set.set_id(), a.activation_number))
+            .collect::<Vec<_>>();
+        assert_eq!(archived_activations, vec![(0, 1), (1, 5), (2, 7)]);
     }
 
     #[test]
@@ -812,20 +1403,20 @@ mod tests {
             assert_successful_db_init(Some(genesis_authority_set.clone()), 1);
         let initial_block = Block::new(initial_header.clone(), vec![]);
         write_test_flow(serde_json::to_string(&initial_block).unwrap());
-        let first_header = create_next_header(initial_header.clone());
+        let first_header = create_next_header(&initial_header);
         let encoded_data =
             assert_successful_header_ingestion(encoded_data, first_header.clone(), None, 1);
 
         // Now we will try to ingest a block with justification
-        let second_header = create_next_header(first_header.clone());
+        let second_header = create_next_header(&first_header);
 
-        let third_header = create_next_header(second_header.clone());
+        let third_header = create_next_header(&second_header);
 
-        let fourth_header = create_next_header(third_header.clone());
+        let fourth_header = create_next_header(&third_header);
 
-        let fifth_header = create_next_header(fourth_header.clone());
+        let fifth_header = create_next_header(&fourth_header);
 
-        let sixth_header = create_next_header(fifth_header.clone());
+        let sixth_header = create_next_header(&fifth_header);
 
         let header_ancestry = vec![
             second_header.clone(),
@@ -894,4 +1485,353 @@ mod tests {
         assert_finalized_header(encoded_data.clone(), &fifth_header, 1);
         write_test_flow(format!("third, fourth and fifth headers are now finalized"));
     }
+
+    #[test]
+    fn test_event_proof_verification() {
+        let peers = &[Ed25519Keyring::Alice];
+        let genesis_authority_set = LightAuthoritySet::new(0, make_ids(peers));
+
+        // Phase::ApplyExtrinsic(1), event 2 of pallet 5 with no topics
+        let event_record = (0u8, 1u32, 5u8, 2u8, 100u64, Vec::<H256>::new()).encode();
+        let mut encoded_events = vec![4u8]; // Compact encoded length of 1
+        encoded_events.extend_from_slice(&event_record);
+
+        let mut db = MemoryDB::<BlakeTwo256>::default();
+        let mut state_root = H256::default();
+        {
+            let mut trie = TrieDBMut::<BlakeTwo256>::new(&mut db, &mut state_root);
+            trie.insert(&system_events_key(), &encoded_events).unwrap();
+        }
+        let proof: Vec<Vec<u8>> = db.drain().into_iter().map(|(_, (node, _))| node).collect();
+
+        let initial_header = Header::new(
+            One::one(),
+            Default::default(),
+            state_root,
+            Default::default(),
+            Default::default(),
+        );
+        let encoded_data = initialize_state(
+            initial_header.clone(),
+            genesis_authority_set,
+            None,
+            RetentionPolicy::default(),
+            JustificationVerification::Full,
+            2,
+        )
+        .unwrap();
+        let record_lengths = [event_record.len() as u32];
+
+        // Initial header isn't finalized yet
+        assert!(finalized_header_at(encoded_data.clone(), 1).is_err());
+
+        let mut next_header = create_next_header(&initial_header);
+        next_header.state_root = Default::default();
+        let commit = create_justification_commit(1, 0, vec![next_header.clone()], peers);
+        let grandpa_justification: GrandpaJustification<Block> = GrandpaJustification {
+            round: 1,
+            commit,
+            votes_ancestries: vec![],
+        };
+        let encoded_data = assert_successful_header_ingestion(
+            encoded_data,
+            next_header.clone(),
+            Some(grandpa_justification.encode()),
+            1,
+        );
+
+        let header = finalized_header_at(encoded_data.clone(), 1).unwrap();
+        assert_eq!(header, initial_header);
+        let result =
+            verify_event_proof(&header, proof.clone(), 0, &record_lengths, Some(5), Some(2));
+        assert_eq!(result.unwrap(), event_record);
+
+        // Event is from another pallet
+        let result = verify_event_proof(&header, proof.clone(), 0, &record_lengths, Some(6), None);
+        assert!(result.is_err());
+
+        // Initial header is first leaf of MMR of finalized headers, and its sibling
+        // is the next header.
+        let result =
+            verify_header_inclusion(encoded_data.clone(), &header, 0, &[next_header.hash()]);
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap().leaf_count(), 2);
+        let result = verify_header_inclusion(encoded_data.clone(), &header, 1, &[header.hash()]);
+        assert!(result.is_err());
+
+        // Proof doesn't match state root of second header
+        let header = finalized_header_at(encoded_data, 2).unwrap();
+        let result = verify_event_proof(&header, proof, 0, &record_lengths, None, None);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_initial_state_verification() {
+        let peers = &[Ed25519Keyring::Alice, Ed25519Keyring::Bob];
+        let authority_set = LightAuthoritySet::new(3, make_ids(peers));
+
+        let mut db = MemoryDB::<BlakeTwo256>::default();
+        let mut state_root = H256::default();
+        {
+            let mut trie = TrieDBMut::<BlakeTwo256>::new(&mut db, &mut state_root);
+            trie.insert(
+                GRANDPA_AUTHORITIES_KEY,
+                &VersionedAuthorityList::from(authority_set.authorities()).encode(),
+            )
+            .unwrap();
+            trie.insert(&grandpa_current_set_id_key(), &3u64.encode())
+                .unwrap();
+        }
+        let proof: Vec<Vec<u8>> = db.drain().into_iter().map(|(_, (node, _))| node).collect();
+
+        let initial_header = Header::new(
+            One::one(),
+            Default::default(),
+            state_root,
+            Default::default(),
+            Default::default(),
+        );
+        let justification_of = |set_id, peers: &[Keyring]| {
+            create_justification(&initial_header, 1, set_id, peers).encode()
+        };
+        let justification = justification_of(3, peers);
+
+        let result = verify_initial_state(&initial_header, &authority_set, &justification, None);
+        assert!(result.is_ok());
+        let result = verify_initial_state(
+            &initial_header,
+            &authority_set,
+            &justification,
+            Some(proof.clone()),
+        );
+        assert!(result.is_ok());
+
+        // Justification is signed for another set id
+        let result = verify_initial_state(
+            &initial_header,
+            &authority_set,
+            &justification_of(2, peers),
+            None,
+        );
+        assert!(result.is_err());
+        // Justification lacks supermajority of the set
+        let result = verify_initial_state(
+            &initial_header,
+            &authority_set,
+            &justification_of(3, &peers[..1]),
+            None,
+        );
+        assert!(result.is_err());
+
+        // Set id is stored as 3 under the state root
+        let wrong_set_id = LightAuthoritySet::new(4, make_ids(peers));
+        let result = verify_initial_state(
+            &initial_header,
+            &wrong_set_id,
+            &justification_of(4, peers),
+            Some(proof.clone()),
+        );
+        assert_eq!(
+            result.err().unwrap().to_string(),
+            "Invalid proof: Authority set id 4 doesn't match id 3 stored in the state"
+        );
+
+        // Authorities stored under the state root are Alice and Bob
+        let peers = &[Ed25519Keyring::Alice, Ed25519Keyring::Charlie];
+        let wrong_authorities = LightAuthoritySet::new(3, make_ids(peers));
+        let result = verify_initial_state(
+            &initial_header,
+            &wrong_authorities,
+            &justification_of(3, peers),
+            Some(proof),
+        );
+        assert_eq!(
+            result.err().unwrap().to_string(),
+            "Invalid proof: Authority set doesn't match the one stored in the state"
+        );
+    }
+
+    #[test]
+    fn test_beefy_finalization() {
+        let keys = validator_keys(4);
+        let initial_header = Header::new(
+            1,
+            Default::default(),
+            Default::default(),
+            [1u8; 32].into(),
+            Default::default(),
+        );
+        let encoded_data = initialize_state(
+            initial_header.clone(),
+            LightAuthoritySet::new(0, vec![]),
+            Some(validator_set(0, &keys)),
+            RetentionPolicy::default(),
+            JustificationVerification::Full,
+            10,
+        )
+        .unwrap();
+
+        // Proof signed by less than 2/3 of validators is rejected
+        let second_header = create_next_header(&initial_header);
+        let finality_proof =
+            create_finality_proof(&second_header, 0, &keys, &[0, 1], Default::default());
+        assert!(ingest_beefy_finalized_header(
+            encoded_data.clone(),
+            second_header.clone(),
+            finality_proof,
+            10
+        )
+        .is_err());
+
+        // Proof of another header is rejected
+        let next_validator_set = validator_set(1, &keys[1..]);
+        let finality_proof = create_finality_proof(
+            &second_header,
+            0,
+            &keys,
+            &[0, 1, 2],
+            next_validator_set.clone(),
+        );
+        let result = ingest_beefy_finalized_header(
+            encoded_data.clone(),
+            create_next_header(&second_header),
+            finality_proof.clone(),
+            10,
+        );
+        assert_eq!(result.err().unwrap().code(), 205);
+
+        let (_, encoded_data) =
+            ingest_beefy_finalized_header(encoded_data, second_header.clone(), finality_proof, 10)
+                .unwrap();
+        let status = current_status::<Block>(encoded_data.clone()).unwrap();
+        assert_eq!(
+            status.possible_last_finalized_header,
+            Some(second_header.clone())
+        );
+        // Announced validator set doesn't replace the current one yet
+        assert_eq!(
+            beefy_validator_set(encoded_data.clone()).unwrap(),
+            Some(validator_set(0, &keys))
+        );
+
+        // Already imported header is still finalized by the current validator set
+        let third_header = create_next_header(&second_header);
+        let (_, encoded_data) =
+            ingest_finalized_header(encoded_data, third_header.clone(), None, None, 10).unwrap();
+        let finality_proof =
+            create_finality_proof(&third_header, 0, &keys, &[0, 1, 2], Default::default());
+        let (result, encoded_data) =
+            ingest_beefy_finalized_header(encoded_data, third_header.clone(), finality_proof, 10)
+                .unwrap();
+        assert!(matches!(
+            result.block_import_result,
+            BlockImportResult::ImportedKnown(3)
+        ));
+        assert!(!result.validator_set_enacted());
+        let status = current_status::<Block>(encoded_data.clone()).unwrap();
+        assert_eq!(
+            status.possible_last_finalized_header,
+            Some(third_header.clone())
+        );
+
+        // Commitment signed by the next validator set makes it the current one
+        let fourth_header = create_next_header(&third_header);
+        let finality_proof = create_finality_proof(
+            &fourth_header,
+            1,
+            &keys[1..],
+            &[0, 1, 2],
+            Default::default(),
+        );
+        let (result, encoded_data) =
+            ingest_beefy_finalized_header(encoded_data, fourth_header.clone(), finality_proof, 10)
+                .unwrap();
+        assert!(result.validator_set_enacted());
+        assert_eq!(result.set_id, 1);
+        assert_eq!(
+            beefy_validator_set(encoded_data.clone()).unwrap(),
+            Some(next_validator_set)
+        );
+
+        // Previous validator set can't finalize headers anymore
+        let fifth_header = create_next_header(&fourth_header);
+        let finality_proof =
+            create_finality_proof(&fifth_header, 0, &keys, &[0, 1, 2], Default::default());
+        assert!(
+            ingest_beefy_finalized_header(encoded_data, fifth_header, finality_proof, 10).is_err()
+        );
+    }
+
+    #[test]
+    fn test_bounded_justification_verification() {
+        let keys = [
+            Keyring::Alice,
+            Keyring::Bob,
+            Keyring::Charlie,
+            Keyring::Dave,
+        ];
+        let mut simulator = ChainSimulator::new(&keys);
+        simulator.push_headers(2);
+
+        let ingest_justified = |verification| {
+            let encoded_data = initialize_state(
+                simulator.initial_header().clone(),
+                LightAuthoritySet::new(0, make_ids(&keys)),
+                None,
+                RetentionPolicy::default(),
+                verification,
+                256,
+            )
+            .unwrap();
+            let mut simulator = simulator.clone();
+            let (_, encoded_data) = ingest_finalized_header(
+                encoded_data,
+                simulator.header(2).unwrap().clone(),
+                None,
+                None,
+                256,
+            )
+            .unwrap();
+            let (result, encoded_data) = ingest_finalized_header(
+                encoded_data,
+                simulator.header(3).unwrap().clone(),
+                None,
+                Some(simulator.justify(3)),
+                256,
+            )
+            .unwrap();
+            let status = current_status::<Block>(encoded_data.clone()).unwrap();
+            assert_eq!(status.possible_last_finalized_header.unwrap().number, 3);
+            match result.block_import_result {
+                BlockImportResult::ImportedUnknown(_, aux) => {
+                    (aux.possible_verified_weight, encoded_data)
+                }
+                BlockImportResult::ImportedKnown(_) => panic!("Header is already known"),
+            }
+        };
+
+        // Full verification doesn't stop early, so there is no bounded weight to report
+        let (possible_verified_weight, _) = ingest_justified(JustificationVerification::Full);
+        assert_eq!(possible_verified_weight, None);
+
+        // Signatures are checked only until 3 out of 4 equally weighted authorities are proven
+        let (possible_verified_weight, encoded_data) =
+            ingest_justified(JustificationVerification::Bounded);
+        assert_eq!(
+            possible_verified_weight,
+            Some(VerifiedWeight {
+                verified_weight: 3,
+                threshold: 3,
+                total_weight: 4,
+                signatures_checked: 3,
+            })
+        );
+
+        // Way of verification is carried by snapshots
+        let snapshot = export_snapshot(encoded_data).unwrap();
+        assert_eq!(
+            snapshot.justification_verification,
+            JustificationVerification::Bounded
+        );
+    }
 }
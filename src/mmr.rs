@@ -0,0 +1,235 @@
+use crate::common::types::blockchain_error::BlockchainError;
+use crate::common::types::blockchain_result::BlockchainResult;
+use parity_scale_codec::{Decode, Encode};
+use sp_runtime::traits::Hash as HashT;
+
+/// Merkle Mountain Range accumulating hashes of finalized headers.
+///
+/// Only peaks of the mountains are kept, which is enough to append
+/// new leaves and to verify inclusion proofs of any leaf ever appended,
+/// even after the corresponding header has been pruned from the storage.
+#[derive(Debug, Clone, Default, PartialEq, Encode, Decode)]
+pub struct HeaderMmr<Hash> {
+    leaf_count: u64,
+    peaks: Vec<Hash>,
+}
+
+impl<Hash> HeaderMmr<Hash>
+where
+    Hash: AsRef<[u8]> + Clone + PartialEq,
+{
+    /// Reconstructs MMR from its peaks, e.g. to verify proofs against MMR
+    /// maintained elsewhere. There must be exactly one peak per set bit of
+    /// `leaf_count`, ordered from the highest mountain.
+    pub fn from_peaks(leaf_count: u64, peaks: Vec<Hash>) -> BlockchainResult<Self> {
+        if peaks.len() != leaf_count.count_ones() as usize {
+            return Err(BlockchainError::InvalidProof(format!(
+                "Expected {} peaks for {} leaves, got {}",
+                leaf_count.count_ones(),
+                leaf_count,
+                peaks.len()
+            )));
+        }
+        Ok(Self { leaf_count, peaks })
+    }
+
+    /// Amount of leaves appended so far.
+    pub fn leaf_count(&self) -> u64 {
+        self.leaf_count
+    }
+
+    /// Root of the MMR, acquired by bagging peaks from right to left.
+    /// Returns `None` if no leaf has been appended yet.
+    pub fn root<H: HashT<Output = Hash>>(&self) -> Option<Hash> {
+        let mut peaks = self.peaks.iter().rev();
+        let last_peak = peaks.next()?.clone();
+        Some(peaks.fold(last_peak, |acc, peak| merge::<H>(peak, &acc)))
+    }
+
+    /// Appends new leaf, merging mountains of equal height.
+    pub fn append<H: HashT<Output = Hash>>(&mut self, leaf: Hash) {
+        let mut node = leaf;
+        // Every trailing one bit of leaf count is a mountain of same height as the new node
+        let mut count = self.leaf_count;
+        while count & 1 == 1 {
+            let left = self
+                .peaks
+                .pop()
+                .expect("every set bit of leaf count has a corresponding peak; qed");
+            node = merge::<H>(&left, &node);
+            count >>= 1;
+        }
+        self.peaks.push(node);
+        self.leaf_count += 1;
+    }
+
+    /// Verifies that `leaf` is at `leaf_index` in the MMR. Proof consists of
+    /// sibling hashes on the path from the leaf to the peak of its mountain,
+    /// starting from the leaf's sibling.
+    pub fn verify_proof<H: HashT<Output = Hash>>(
+        &self,
+        leaf_index: u64,
+        leaf: Hash,
+        proof: &[Hash],
+    ) -> BlockchainResult<()> {
+        if leaf_index >= self.leaf_count {
+            return Err(BlockchainError::InvalidProof(format!(
+                "Leaf index {} is out of range. Total leaves: {}",
+                leaf_index, self.leaf_count
+            )));
+        }
+
+        let mut mountain_start = 0u64;
+        for (peak, height) in self.peaks.iter().zip(self.peak_heights()) {
+            let mountain_size = 1u64 << height;
+            if leaf_index >= mountain_start + mountain_size {
+                mountain_start += mountain_size;
+                continue;
+            }
+
+            if proof.len() != height as usize {
+                return Err(BlockchainError::InvalidProof(format!(
+                    "Expected {} proof items, got {}",
+                    height,
+                    proof.len()
+                )));
+            }
+            let mut position = leaf_index - mountain_start;
+            let mut node = leaf;
+            for sibling in proof {
+                node = if position & 1 == 0 {
+                    merge::<H>(&node, sibling)
+                } else {
+                    merge::<H>(sibling, &node)
+                };
+                position >>= 1;
+            }
+
+            return if &node == peak {
+                Ok(())
+            } else {
+                Err(BlockchainError::InvalidProof(
+                    "MMR proof doesn't lead to the stored peak".into(),
+                ))
+            };
+        }
+
+        unreachable!("leaf index is checked to be covered by one of the mountains; qed")
+    }
+
+    /// Heights of the mountains from left to right, which are the
+    /// set bits of leaf count from the most significant one.
+    fn peak_heights(&self) -> impl Iterator<Item = u32> + '_ {
+        (0..64u32)
+            .rev()
+            .filter(move |height| self.leaf_count & (1u64 << height) != 0)
+    }
+}
+
+fn merge<H: HashT>(left: &H::Output, right: &H::Output) -> H::Output {
+    let mut concatenated = left.as_ref().to_vec();
+    concatenated.extend_from_slice(right.as_ref());
+    <H as HashT>::hash(&concatenated)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mmr::{merge, HeaderMmr};
+    use sp_core::H256;
+    use sp_runtime::traits::BlakeTwo256;
+
+    fn leaves(amount: u64) -> Vec<H256> {
+        (0..amount).map(|i| H256::from_low_u64_be(i + 1)).collect()
+    }
+
+    /// Builds proof of leaf at `position` in the perfect binary tree over `leaves`.
+    fn mountain_proof(leaves: &[H256], position: usize) -> (H256, Vec<H256>) {
+        if leaves.len() == 1 {
+            return (leaves[0], vec![]);
+        }
+        let half = leaves.len() / 2;
+        let (left, right) = (&leaves[..half], &leaves[half..]);
+        let (left_root, right_root, mut proof) = if position < half {
+            let (left_root, proof) = mountain_proof(left, position);
+            (left_root, mountain_proof(right, 0).0, proof)
+        } else {
+            let (right_root, proof) = mountain_proof(right, position - half);
+            (mountain_proof(left, 0).0, right_root, proof)
+        };
+        proof.push(if position < half {
+            right_root
+        } else {
+            left_root
+        });
+        (merge::<BlakeTwo256>(&left_root, &right_root), proof)
+    }
+
+    fn proof(leaves: &[H256], leaf_index: usize) -> Vec<H256> {
+        let mut mountain_start = 0;
+        for height in (0..64u32).rev() {
+            let size = 1usize << height;
+            if leaves.len() & size == 0 {
+                continue;
+            }
+            if leaf_index < mountain_start + size {
+                let mountain = &leaves[mountain_start..mountain_start + size];
+                return mountain_proof(mountain, leaf_index - mountain_start).1;
+            }
+            mountain_start += size;
+        }
+        unreachable!()
+    }
+
+    #[test]
+    fn test_mmr_append_and_root() {
+        let mut mmr = HeaderMmr::<H256>::default();
+        assert_eq!(mmr.root::<BlakeTwo256>(), None);
+
+        let leaves = leaves(3);
+        for leaf in leaves.iter() {
+            mmr.append::<BlakeTwo256>(*leaf);
+        }
+        assert_eq!(mmr.leaf_count(), 3);
+        assert_eq!(mmr.peaks.len(), 2);
+
+        let first_peak = merge::<BlakeTwo256>(&leaves[0], &leaves[1]);
+        assert_eq!(
+            mmr.root::<BlakeTwo256>(),
+            Some(merge::<BlakeTwo256>(&first_peak, &leaves[2]))
+        );
+    }
+
+    #[test]
+    fn test_mmr_proof_verification() {
+        let leaves = leaves(11);
+        let mut mmr = HeaderMmr::<H256>::default();
+        for leaf in leaves.iter() {
+            mmr.append::<BlakeTwo256>(*leaf);
+        }
+        // 11 = 8 + 2 + 1
+        assert_eq!(mmr.peaks.len(), 3);
+
+        for (leaf_index, leaf) in leaves.iter().enumerate() {
+            let proof = proof(&leaves, leaf_index);
+            assert!(mmr
+                .verify_proof::<BlakeTwo256>(leaf_index as u64, *leaf, &proof)
+                .is_ok());
+        }
+
+        // Wrong leaf
+        let proof = proof(&leaves, 3);
+        assert!(mmr
+            .verify_proof::<BlakeTwo256>(3, leaves[4], &proof)
+            .is_err());
+        // Wrong position
+        assert!(mmr
+            .verify_proof::<BlakeTwo256>(2, leaves[3], &proof)
+            .is_err());
+        // Truncated proof
+        assert!(mmr
+            .verify_proof::<BlakeTwo256>(3, leaves[3], &proof[1..])
+            .is_err());
+        // Leaf out of range
+        assert!(mmr.verify_proof::<BlakeTwo256>(11, leaves[0], &[]).is_err());
+    }
+}
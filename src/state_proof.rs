@@ -0,0 +1,319 @@
+use crate::common::types::blockchain_error::BlockchainError;
+use crate::common::types::blockchain_result::BlockchainResult;
+use parity_scale_codec::{Compact, Decode, Encode};
+use sp_core::hashing::{twox_128, twox_64};
+use sp_runtime::traits::Hash as HashT;
+use sp_trie::{read_trie_value, Layout, StorageProof};
+
+/// Well known storage key of GRANDPA authorities, which holds
+/// SCALE encoded `VersionedAuthorityList`.
+pub const GRANDPA_AUTHORITIES_KEY: &[u8] = b":grandpa_authorities";
+
+/// Returns storage key of plain storage value, which is
+/// `twox_128(pallet) ++ twox_128(item)`.
+pub fn storage_value_key(pallet: &[u8], item: &[u8]) -> Vec<u8> {
+    let mut key = twox_128(pallet).to_vec();
+    key.extend_from_slice(&twox_128(item));
+    key
+}
+
+/// Returns storage key of `System::Events`.
+pub fn system_events_key() -> Vec<u8> {
+    storage_value_key(b"System", b"Events")
+}
+
+/// Returns storage key of `Grandpa::CurrentSetId`.
+pub fn grandpa_current_set_id_key() -> Vec<u8> {
+    storage_value_key(b"Grandpa", b"CurrentSetId")
+}
+
+/// Returns storage key of `Paras::Heads(para_id)` of relay chain, which
+/// holds SCALE encoded head data, i.e. the encoded parachain header. The
+/// map is hashed with `Twox64Concat`.
+pub fn paras_heads_key(para_id: u32) -> Vec<u8> {
+    let encoded_para_id = para_id.encode();
+    let mut key = storage_value_key(b"Paras", b"Heads");
+    key.extend_from_slice(&twox_64(&encoded_para_id));
+    key.extend_from_slice(&encoded_para_id);
+    key
+}
+
+/// Reads value stored under `key` from storage proof after verifying
+/// the proof against given state root. Returns `None` if proof proves
+/// absence of the key.
+pub fn read_proof_value<H>(
+    state_root: &H::Output,
+    proof: Vec<Vec<u8>>,
+    key: &[u8],
+) -> BlockchainResult<Option<Vec<u8>>>
+where
+    H: HashT,
+{
+    let db = StorageProof::new(proof).into_memory_db::<H>();
+    read_trie_value::<Layout<H>, _>(&db, state_root, key)
+        .map_err(|e| BlockchainError::InvalidProof(format!("{}", e)))
+}
+
+/// Verifies that SCALE encoded `extrinsic` is at `extrinsic_index` in the
+/// ordered trie of block extrinsics with given root. Keys of the ordered
+/// trie are compact encoded indices of extrinsics.
+pub fn verify_extrinsic_proof<H>(
+    extrinsics_root: &H::Output,
+    proof: Vec<Vec<u8>>,
+    extrinsic_index: u32,
+    extrinsic: &[u8],
+) -> BlockchainResult<()>
+where
+    H: HashT,
+{
+    let key = Compact(extrinsic_index).encode();
+    match read_proof_value::<H>(extrinsics_root, proof, &key)? {
+        Some(ref value) if value.as_slice() == extrinsic => Ok(()),
+        Some(_) => Err(BlockchainError::InvalidProof(format!(
+            "Extrinsic at index {} doesn't match the supplied one",
+            extrinsic_index
+        ))),
+        None => Err(BlockchainError::InvalidProof(format!(
+            "Extrinsic index {} is absent from the block",
+            extrinsic_index
+        ))),
+    }
+}
+
+/// Extracts SCALE encoded `EventRecord` at `event_index` from the encoded
+/// value of `System::Events`.
+///
+/// Runtime event type isn't known to light client, so it can't find where
+/// one record ends and the next one begins. Caller needs to supply encoded
+/// length of every record, which is then checked to cover the value exactly
+/// and to start every record with a valid `Phase`.
+///
+/// Optionally, pallet index and variant index of the event are matched
+/// against first two bytes of the encoded runtime event.
+pub fn extract_event_record(
+    encoded_events: &[u8],
+    event_index: u32,
+    record_lengths: &[u32],
+    pallet_index: Option<u8>,
+    variant_index: Option<u8>,
+) -> BlockchainResult<Vec<u8>> {
+    let mut input = encoded_events;
+    let Compact(total_events) = Compact::<u32>::decode(&mut input)
+        .map_err(|e| BlockchainError::DataDecode(e.to_string()))?;
+
+    if record_lengths.len() != total_events as usize {
+        return Err(BlockchainError::DataDecode(format!(
+            "Expected lengths of {} event records, got {}",
+            total_events,
+            record_lengths.len()
+        )));
+    }
+    let total_length: u64 = record_lengths.iter().map(|l| *l as u64).sum();
+    if total_length != input.len() as u64 {
+        return Err(BlockchainError::DataDecode(format!(
+            "Event record lengths add up to {} bytes, but encoded records are {} bytes",
+            total_length,
+            input.len()
+        )));
+    }
+    if event_index >= total_events {
+        return Err(BlockchainError::DataDecode(format!(
+            "Event index {} is out of range. Total events: {}",
+            event_index, total_events
+        )));
+    }
+
+    let mut offset = 0usize;
+    let mut possible_event_record = None;
+    for (index, length) in record_lengths.iter().enumerate() {
+        let record = &input[offset..offset + *length as usize];
+        let event_offset = phase_length(record).ok_or_else(|| {
+            BlockchainError::DataDecode(format!("Event record {} has invalid phase", index))
+        })?;
+        // Every runtime event starts with pallet index followed by variant index
+        if record.len() < event_offset + 2 {
+            return Err(BlockchainError::DataDecode(format!(
+                "Event record {} is too short",
+                index
+            )));
+        }
+        if index == event_index as usize {
+            possible_event_record = Some((record, event_offset));
+        }
+        offset += *length as usize;
+    }
+
+    let (event_record, event_offset) =
+        possible_event_record.expect("event_index is checked to be less than total events; qed");
+    let (found_pallet_index, found_variant_index) =
+        (event_record[event_offset], event_record[event_offset + 1]);
+    if pallet_index.unwrap_or(found_pallet_index) != found_pallet_index
+        || variant_index.unwrap_or(found_variant_index) != found_variant_index
+    {
+        return Err(BlockchainError::Msg(format!(
+            "Event at index {} is from pallet {} with variant {}",
+            event_index, found_pallet_index, found_variant_index
+        )));
+    }
+
+    Ok(event_record.to_vec())
+}
+
+/// Length of encoded `frame_system::Phase` at the start of the record,
+/// or `None` if it isn't a valid phase.
+fn phase_length(record: &[u8]) -> Option<usize> {
+    match record.first() {
+        // ApplyExtrinsic(u32)
+        Some(0) if record.len() >= 5 => Some(5),
+        // Finalization or Initialization
+        Some(1) | Some(2) => Some(1),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::state_proof::{
+        extract_event_record, paras_heads_key, read_proof_value, system_events_key,
+        verify_extrinsic_proof,
+    };
+    use parity_scale_codec::{Compact, Encode};
+    use sp_core::H256;
+    use sp_runtime::traits::BlakeTwo256;
+    use sp_trie::{trie_types::TrieDBMut, Layout, MemoryDB, TrieConfiguration, TrieMut};
+
+    fn encoded_events() -> (Vec<u8>, Vec<u32>) {
+        // Phase::ApplyExtrinsic(0), Balances::Transfer-like event, no topics
+        let first_record = (0u8, 0u32, 4u8, 2u8, [7u8; 32], Vec::<H256>::new()).encode();
+        // Phase::Finalization, System::ExtrinsicSuccess-like event, one topic
+        let second_record = (1u8, 0u8, 0u8, vec![H256::repeat_byte(1)]).encode();
+        let mut encoded_events = Compact(2u32).encode();
+        encoded_events.extend_from_slice(&first_record);
+        encoded_events.extend_from_slice(&second_record);
+        (
+            encoded_events,
+            vec![first_record.len() as u32, second_record.len() as u32],
+        )
+    }
+
+    fn state_with_events(encoded_events: &[u8]) -> (H256, Vec<Vec<u8>>) {
+        let mut db = MemoryDB::<BlakeTwo256>::default();
+        let mut root = H256::default();
+        {
+            let mut trie = TrieDBMut::<BlakeTwo256>::new(&mut db, &mut root);
+            trie.insert(&system_events_key(), encoded_events).unwrap();
+            trie.insert(b":code", &[1, 2, 3]).unwrap();
+        }
+        let proof = db.drain().into_iter().map(|(_, (node, _))| node).collect();
+        (root, proof)
+    }
+
+    #[test]
+    fn test_read_proof_value() {
+        let (encoded_events, _) = encoded_events();
+        let (root, proof) = state_with_events(&encoded_events);
+
+        let result = read_proof_value::<BlakeTwo256>(&root, proof.clone(), &system_events_key());
+        assert_eq!(result.unwrap(), Some(encoded_events));
+
+        let result = read_proof_value::<BlakeTwo256>(&root, proof.clone(), b":heappages");
+        assert_eq!(result.unwrap(), None);
+
+        let result =
+            read_proof_value::<BlakeTwo256>(&H256::repeat_byte(3), proof, &system_events_key());
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_extract_event_record() {
+        let (encoded_events, record_lengths) = encoded_events();
+        let first_record_end = 1 + record_lengths[0] as usize;
+
+        let result = extract_event_record(&encoded_events, 0, &record_lengths, Some(4), Some(2));
+        assert_eq!(
+            result.unwrap(),
+            encoded_events[1..first_record_end].to_vec()
+        );
+
+        let result = extract_event_record(&encoded_events, 1, &record_lengths, None, None);
+        assert_eq!(result.unwrap(), encoded_events[first_record_end..].to_vec());
+
+        let result = extract_event_record(&encoded_events, 0, &record_lengths, Some(5), None);
+        assert_eq!(
+            result.err().unwrap().to_string(),
+            "Event at index 0 is from pallet 4 with variant 2"
+        );
+
+        let result = extract_event_record(&encoded_events, 2, &record_lengths, None, None);
+        assert!(result.is_err());
+
+        let result = extract_event_record(
+            &encoded_events,
+            0,
+            &[record_lengths[0], record_lengths[1] + 1],
+            None,
+            None,
+        );
+        assert!(result.is_err());
+
+        let result = extract_event_record(&encoded_events, 0, &record_lengths[..1], None, None);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_verify_extrinsic_proof() {
+        let extrinsics: Vec<Vec<u8>> = (0..3u8)
+            .map(|i| vec![i; 10 + i as usize].encode())
+            .collect();
+        let mut db = MemoryDB::<BlakeTwo256>::default();
+        let mut root = H256::default();
+        {
+            let mut trie = TrieDBMut::<BlakeTwo256>::new(&mut db, &mut root);
+            for (index, extrinsic) in extrinsics.iter().enumerate() {
+                trie.insert(&Compact(index as u32).encode(), extrinsic)
+                    .unwrap();
+            }
+        }
+        assert_eq!(
+            root,
+            Layout::<BlakeTwo256>::ordered_trie_root(extrinsics.iter())
+        );
+        let proof: Vec<Vec<u8>> = db.drain().into_iter().map(|(_, (node, _))| node).collect();
+
+        for (index, extrinsic) in extrinsics.iter().enumerate() {
+            let result = verify_extrinsic_proof::<BlakeTwo256>(
+                &root,
+                proof.clone(),
+                index as u32,
+                extrinsic,
+            );
+            assert!(result.is_ok());
+        }
+
+        let result = verify_extrinsic_proof::<BlakeTwo256>(&root, proof.clone(), 1, &extrinsics[0]);
+        assert_eq!(
+            result.err().unwrap().to_string(),
+            "Invalid proof: Extrinsic at index 1 doesn't match the supplied one"
+        );
+        let result = verify_extrinsic_proof::<BlakeTwo256>(&root, proof.clone(), 3, &extrinsics[0]);
+        assert_eq!(
+            result.err().unwrap().to_string(),
+            "Invalid proof: Extrinsic index 3 is absent from the block"
+        );
+        let result =
+            verify_extrinsic_proof::<BlakeTwo256>(&H256::repeat_byte(1), proof, 0, &extrinsics[0]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_paras_heads_key() {
+        let key = paras_heads_key(2000);
+        assert_eq!(
+            hex::encode(&key[..32]),
+            "cd710b30bd2eab0352ddcc26417aa1941b3c252fcb29d88eff4f3de5de4476c3"
+        );
+        assert_eq!(key.len(), 32 + 8 + 4);
+        assert_eq!(&key[40..], &2000u32.to_le_bytes());
+        assert_ne!(paras_heads_key(2001)[32..40], key[32..40]);
+    }
+}
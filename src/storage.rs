@@ -8,11 +8,16 @@ use crate::common::types::blockchain_info::BlockchainInfo;
 use crate::common::types::blockchain_result::BlockchainResult;
 use crate::common::types::cached_header_metadata::CachedHeaderMetadata;
 use crate::common::types::new_block_state::NewBlockState;
+use crate::common::types::retention_policy::RetentionPolicy;
+use crate::common::types::storage_window::StorageWindow;
 use crate::db::Data;
+use crate::mmr::HeaderMmr;
+use crate::verifier::{find_forced_change, find_scheduled_change};
 use kvdb::{DBTransaction, KeyValueDB};
 use parity_scale_codec::{Decode, Encode};
 use sp_runtime::generic::BlockId;
 use sp_runtime::traits::{Block as BlockT, Header as HeaderT, NumberFor, One, Zero};
+use std::collections::VecDeque;
 use std::io;
 
 const META_COLUMN: u32 = 0;
@@ -21,6 +26,10 @@ const AUX_COLUMN: u32 = 2;
 const LOOKUP_COLUMN: u32 = 3;
 
 const META_KEY: &[u8] = b"meta";
+const MMR_KEY: &[u8] = b"mmr";
+const RETENTION_POLICY_KEY: &[u8] = b"retention_policy";
+const KEPT_HEADERS_KEY: &[u8] = b"kept_headers";
+const KEPT_RECENT_HEADERS_KEY: &[u8] = b"kept_recent_headers";
 
 /// Database metadata.
 #[derive(Debug, Encode, Decode)]
@@ -88,6 +97,136 @@ impl Storage {
         }
     }
 
+    /// Fetches MMR accumulated over hashes of all finalized headers.
+    pub fn header_mmr<H>(&self) -> BlockchainResult<HeaderMmr<H>>
+    where
+        H: Encode + Decode + Default,
+    {
+        let possible_encoded_mmr = self.data.db.get(META_COLUMN, MMR_KEY).map_err(db_err)?;
+        match possible_encoded_mmr {
+            Some(encoded_mmr) => {
+                HeaderMmr::decode(&mut encoded_mmr.as_slice()).map_err(codec_error)
+            }
+            None => Ok(HeaderMmr::default()),
+        }
+    }
+
+    /// Fetches policy deciding which headers are kept outside of the window.
+    pub fn retention_policy<N>(&self) -> BlockchainResult<RetentionPolicy<N>>
+    where
+        N: Decode,
+    {
+        let possible_encoded_policy = self
+            .data
+            .db
+            .get(META_COLUMN, RETENTION_POLICY_KEY)
+            .map_err(db_err)?;
+        match possible_encoded_policy {
+            Some(encoded_policy) => {
+                RetentionPolicy::decode(&mut encoded_policy.as_slice()).map_err(codec_error)
+            }
+            None => Ok(RetentionPolicy::default()),
+        }
+    }
+
+    pub fn set_retention_policy<N>(&self, policy: &RetentionPolicy<N>) -> BlockchainResult<()>
+    where
+        N: Encode,
+    {
+        let mut tx = self.data.db.transaction();
+        tx.put(
+            META_COLUMN,
+            RETENTION_POLICY_KEY,
+            policy.encode().as_slice(),
+        );
+        self.data.db.write(tx).map_err(db_err)
+    }
+
+    /// Fetches numbers of headers kept outside of the window under the given key,
+    /// in ascending order. Headers kept for being finalized recently are stored
+    /// apart from the ones kept regardless of their age.
+    fn kept_numbers<N>(&self, key: &[u8]) -> BlockchainResult<VecDeque<N>>
+    where
+        N: Decode,
+    {
+        let possible_encoded_numbers = self.data.db.get(META_COLUMN, key).map_err(db_err)?;
+        match possible_encoded_numbers {
+            Some(encoded_numbers) => Vec::decode(&mut encoded_numbers.as_slice())
+                .map(VecDeque::from)
+                .map_err(codec_error),
+            None => Ok(VecDeque::new()),
+        }
+    }
+
+    /// Fetches range of headers retained in the storage. Headers older
+    /// than the window have been pruned, unless kept by retention policy.
+    pub fn storage_window<Block>(&self) -> BlockchainResult<StorageWindow<NumberFor<Block>>>
+    where
+        Block: BlockT,
+    {
+        let possible_meta: Option<StorageMeta<NumberFor<Block>, Block::Hash>> =
+            self.fetch_meta()?;
+        let meta = possible_meta.ok_or_else(|| {
+            BlockchainError::Backend("Unable to get metadata about blockchain".into())
+        })?;
+        let possible_oldest_header: Option<Block::Header> =
+            self.header(BlockId::<Block>::Hash(meta.oldest_stored_hash))?;
+        let oldest_header = possible_oldest_header.ok_or_else(|| {
+            BlockchainError::Backend(
+                "FATAL: Storage inconsistency. Unable to retrieve oldest stored block".into(),
+            )
+        })?;
+
+        Ok(StorageWindow {
+            oldest_number: *oldest_header.number(),
+            best_number: meta.best_number,
+            possible_finalized_number: if meta.finalized_hash == Default::default() {
+                None
+            } else {
+                Some(meta.finalized_number)
+            },
+            total_stored: meta.total_stored,
+            kept_numbers: {
+                let mut kept_numbers: Vec<NumberFor<Block>> =
+                    self.kept_numbers(KEPT_HEADERS_KEY)?.into();
+                kept_numbers
+                    .extend(self.kept_numbers::<NumberFor<Block>>(KEPT_RECENT_HEADERS_KEY)?);
+                kept_numbers.sort();
+                kept_numbers
+            },
+        })
+    }
+
+    /// Marks stored header as finalized and restores MMR of finalized headers
+    /// when storage is restored from a snapshot. Unlike `finalize_header`, it
+    /// doesn't require finalization to be sequential, and doesn't append
+    /// the header to MMR, as restored MMR already contains it.
+    pub fn restore_finality<Block>(
+        &self,
+        finalized_hash: Block::Hash,
+        header_mmr: &HeaderMmr<Block::Hash>,
+    ) -> BlockchainResult<()>
+    where
+        Block: BlockT,
+    {
+        let possible_finalized_header = self.header(BlockId::<Block>::Hash(finalized_hash))?;
+        let finalized_header = possible_finalized_header
+            .ok_or_else(|| BlockchainError::UnknownBlock("Finalized header isn't stored".into()))?;
+        let possible_meta: Option<StorageMeta<NumberFor<Block>, Block::Hash>> =
+            self.fetch_meta()?;
+        let mut meta = possible_meta.ok_or_else(|| {
+            BlockchainError::Backend("Unable to get metadata about blockchain".into())
+        })?;
+
+        meta.finalized_hash = finalized_hash;
+        meta.finalized_number = *finalized_header.number();
+
+        let mut tx = self.data.db.transaction();
+        Self::tx_store_meta(&mut tx, &meta);
+        tx.put(META_COLUMN, MMR_KEY, header_mmr.encode().as_slice());
+        self.data.db.write(tx).map_err(db_err)
+    }
+
     fn store_meta<N, H>(&self, meta: StorageMeta<N, H>) -> BlockchainResult<()>
     where
         N: Encode + Decode,
@@ -98,6 +237,136 @@ impl Storage {
         self.data.db.write(tx).map_err(db_err)
     }
 
+    /// Prunes the oldest headers of the window until at most `capacity` headers
+    /// remain in it, unless retention policy keeps them outside of the window,
+    /// and drops kept headers the policy doesn't keep anymore.
+    fn tx_prune<Block>(
+        &self,
+        tx: &mut DBTransaction,
+        meta: &mut StorageMeta<NumberFor<Block>, Block::Hash>,
+        capacity: u64,
+    ) -> BlockchainResult<()>
+    where
+        Block: BlockT,
+    {
+        let policy = self.retention_policy::<NumberFor<Block>>()?;
+        let mut kept_numbers: VecDeque<NumberFor<Block>> = self.kept_numbers(KEPT_HEADERS_KEY)?;
+        let mut recent_numbers: VecDeque<NumberFor<Block>> =
+            self.kept_numbers(KEPT_RECENT_HEADERS_KEY)?;
+        let possible_finalized_number = if meta.finalized_hash == Default::default() {
+            None
+        } else {
+            Some(meta.finalized_number)
+        };
+
+        // We need to go down this in-efficient route, because
+        // to have a double linked list of headers require more storage and
+        // memory which we don't have.
+        // So, we need to backtrack every time we are above the limit.
+        if meta.total_stored > capacity {
+            let mut current_hash = meta.best_hash;
+            let amount_of_headers_to_backtrack = capacity;
+            let amount_of_headers_to_delete = meta.total_stored - capacity;
+            // First backtrack to the newest header we need to delete
+            for _ in 0..amount_of_headers_to_backtrack {
+                let possible_header = self.header(BlockId::<Block>::Hash(current_hash))?;
+                if possible_header.is_none() {
+                    return Err(BlockchainError::Backend(
+                        "FATAL: Storage inconsistency. Unable to retrieve stored block".into(),
+                    ));
+                }
+                let header = possible_header.unwrap();
+                meta.oldest_stored_hash = current_hash;
+                current_hash = *header.parent_hash();
+            }
+            // Now, Let's delete newest header and all headers older than newest header,
+            // unless retention policy keeps them outside of the window
+            let mut newly_kept_numbers = vec![];
+            let mut newly_recent_numbers = vec![];
+            for _ in 0..amount_of_headers_to_delete {
+                let possible_header = self.header(BlockId::<Block>::Hash(current_hash))?;
+                if possible_header.is_none() {
+                    return Err(BlockchainError::Backend(
+                        "FATAL: Storage inconsistency. Unable to retrieve stored block".into(),
+                    ));
+                }
+                let header = possible_header.unwrap();
+                let signals_authority_set_change = find_scheduled_change::<Block>(&header)
+                    .is_some()
+                    || find_forced_change::<Block>(&header).is_some();
+                if policy.keeps_regardless_of_age(
+                    *header.number(),
+                    signals_authority_set_change,
+                    possible_finalized_number,
+                ) {
+                    newly_kept_numbers.push(*header.number());
+                } else if policy.keeps_recent(*header.number(), possible_finalized_number) {
+                    newly_recent_numbers.push(*header.number());
+                } else {
+                    Self::tx_delete_header::<Block>(tx, &header);
+                }
+                meta.total_stored -= 1;
+                current_hash = *header.parent_hash();
+            }
+            kept_numbers.extend(newly_kept_numbers.into_iter().rev());
+            recent_numbers.extend(newly_recent_numbers.into_iter().rev());
+        }
+
+        // Headers kept only for being recently finalized are dropped once they
+        // get too old. They are sorted, so only the oldest ones need to be checked.
+        while let Some(number) = recent_numbers.front() {
+            if policy.keeps_recent(*number, possible_finalized_number) {
+                break;
+            }
+            self.tx_delete_kept_header::<Block>(tx, *number)?;
+            recent_numbers.pop_front();
+        }
+
+        // Oldest kept headers are dropped once there are too many of them
+        while kept_numbers.len() + recent_numbers.len() > policy.max_kept_headers as usize {
+            let number = match (kept_numbers.front(), recent_numbers.front()) {
+                (Some(kept), Some(recent)) if recent < kept => recent_numbers.pop_front(),
+                (Some(_), _) => kept_numbers.pop_front(),
+                (None, _) => recent_numbers.pop_front(),
+            };
+            if let Some(number) = number {
+                self.tx_delete_kept_header::<Block>(tx, number)?;
+            }
+        }
+        tx.put(
+            META_COLUMN,
+            KEPT_HEADERS_KEY,
+            Vec::from(kept_numbers).encode().as_slice(),
+        );
+        tx.put(
+            META_COLUMN,
+            KEPT_RECENT_HEADERS_KEY,
+            Vec::from(recent_numbers).encode().as_slice(),
+        );
+
+        Ok(())
+    }
+
+    /// Prunes the window down to `max_headers_allowed_to_store` headers right
+    /// away, instead of waiting for the next import. Used when the limit is
+    /// lowered or retention policy is changed.
+    pub fn prune<Block>(&self) -> BlockchainResult<()>
+    where
+        Block: BlockT,
+    {
+        let possible_meta: Option<StorageMeta<NumberFor<Block>, Block::Hash>> =
+            self.fetch_meta()?;
+        let mut meta = match possible_meta {
+            Some(meta) => meta,
+            None => return Ok(()),
+        };
+
+        let mut tx = self.data.db.transaction();
+        self.tx_prune::<Block>(&mut tx, &mut meta, self.max_headers_allowed_to_store)?;
+        Self::tx_store_meta(&mut tx, &meta);
+        self.data.db.write(tx).map_err(db_err)
+    }
+
     fn tx_store_meta<N, H>(tx: &mut DBTransaction, meta: &StorageMeta<N, H>)
     where
         N: Encode + Decode,
@@ -112,14 +381,38 @@ impl Storage {
     {
         let id = Self::header_hash_to_id::<Block>(&header.hash());
         tx.put(HEADER_COLUMN, id.as_slice(), header.encode().as_slice());
+        tx.put(
+            LOOKUP_COLUMN,
+            header.number().encode().as_slice(),
+            id.as_slice(),
+        );
     }
 
-    fn tx_delete_header<Block>(tx: &mut DBTransaction, hash: &Block::Hash)
+    fn tx_delete_header<Block>(tx: &mut DBTransaction, header: &Block::Header)
     where
         Block: BlockT,
     {
-        let id = Self::header_hash_to_id::<Block>(hash);
+        let id = Self::header_hash_to_id::<Block>(&header.hash());
         tx.delete(HEADER_COLUMN, id.as_slice());
+        tx.delete(LOOKUP_COLUMN, header.number().encode().as_slice());
+    }
+
+    fn tx_delete_kept_header<Block>(
+        &self,
+        tx: &mut DBTransaction,
+        number: NumberFor<Block>,
+    ) -> BlockchainResult<()>
+    where
+        Block: BlockT,
+    {
+        let possible_header = self.header(BlockId::<Block>::Number(number))?;
+        let header = possible_header.ok_or_else(|| {
+            BlockchainError::Backend(
+                "FATAL: Storage inconsistency. Unable to retrieve kept block".into(),
+            )
+        })?;
+        Self::tx_delete_header::<Block>(tx, &header);
+        Ok(())
     }
 
     fn header_hash_to_id<Block>(hash: &Block::Hash) -> Vec<u8>
@@ -312,42 +605,8 @@ where
         };
 
         let mut tx = self.data.db.transaction();
-
-        // We need to go down this in-efficient route, because
-        // to have a double linked list of headers require more storage and
-        // memory which we don't have.
-        // So, we need to backtrack every time we are above the limit.
-        if meta.total_stored >= self.max_headers_allowed_to_store {
-            let mut current_hash = meta.best_hash;
-            let amount_of_headers_to_backtrack = self.max_headers_allowed_to_store - 1;
-            let amount_of_headers_to_delete =
-                (meta.total_stored - self.max_headers_allowed_to_store) + 1;
-            // First backtrack to the newest header we need to delete
-            for _ in 0..amount_of_headers_to_backtrack {
-                let possible_header = self.header(BlockId::<Block>::Hash(current_hash))?;
-                if possible_header.is_none() {
-                    return Err(BlockchainError::Backend(format!(
-                        "FATAL: Storage inconsistency. Unable to retrieve stored block"
-                    )));
-                }
-                let header = possible_header.unwrap();
-                meta.oldest_stored_hash = current_hash;
-                current_hash = *header.parent_hash();
-            }
-            // Now, Let's delete newest header and all headers older than newest header
-            for _ in 0..amount_of_headers_to_delete {
-                let possible_header = self.header(BlockId::<Block>::Hash(current_hash))?;
-                if possible_header.is_none() {
-                    return Err(BlockchainError::Backend(format!(
-                        "FATAL: Storage inconsistency. Unable to retrieve stored block"
-                    )));
-                }
-                Self::tx_delete_header::<Block>(&mut tx, &current_hash);
-                meta.total_stored -= 1;
-                let header = possible_header.unwrap();
-                current_hash = *header.parent_hash();
-            }
-        }
+        // Room is made for the importing header
+        self.tx_prune::<Block>(&mut tx, &mut meta, self.max_headers_allowed_to_store - 1)?;
 
         let possible_header = self.header(BlockId::<Block>::Hash(header.hash()))?;
         if possible_header.is_some() {
@@ -427,7 +686,15 @@ where
         meta.finalized_hash = to_be_finalized_header.hash();
         meta.finalized_number = *to_be_finalized_header.number();
 
-        self.store_meta(meta)
+        // Every finalized header is accumulated in MMR, so that it remains
+        // provable after being pruned.
+        let mut header_mmr = self.header_mmr::<Block::Hash>()?;
+        header_mmr.append::<<Block::Header as HeaderT>::Hashing>(meta.finalized_hash);
+
+        let mut tx = self.data.db.transaction();
+        Self::tx_store_meta(&mut tx, &meta);
+        tx.put(META_COLUMN, MMR_KEY, header_mmr.encode().as_slice());
+        self.data.db.write(tx).map_err(db_err)
     }
 
     /// Get last finalized header.
@@ -472,20 +739,17 @@ mod tests {
     use crate::common::traits::header_backend::HeaderBackend;
     use crate::common::traits::storage::Storage as StorageT;
     use crate::common::types::new_block_state::NewBlockState;
+    use crate::common::types::retention_policy::RetentionPolicy;
     use crate::db::{create, Data};
     use crate::genesis::GenesisData;
     use crate::storage::Storage;
+    use crate::test_utils::create_next_header;
     use crate::types::{Block, Header};
     use parity_scale_codec::Encode;
     use sp_api::BlockId;
+    use sp_finality_grandpa::{ConsensusLog, ScheduledChange, GRANDPA_ENGINE_ID};
     use sp_runtime::traits::{Block as BlockT, Header as HeaderT, NumberFor, One};
-
-    fn create_next_header(header: Header) -> Header {
-        let mut next_header = header.clone();
-        next_header.number += 1;
-        next_header.parent_hash = header.hash();
-        next_header
-    }
+    use sp_runtime::DigestItem;
 
     #[test]
     fn test_storage_init() {
@@ -541,7 +805,7 @@ mod tests {
 
         // Let's store first max_headers_allowed_to_store number of headers
         for i in 0..max_headers_allowed_to_store {
-            current_header = create_next_header(current_header.clone());
+            current_header = create_next_header(&current_header);
             produced_headers.push(current_header.clone());
             assert!(StorageT::<Block>::import_header(
                 &storage,
@@ -569,7 +833,7 @@ mod tests {
         for i in max_headers_allowed_to_store
             ..((max_headers_allowed_to_store * 200) + (max_headers_allowed_to_store - 2) + 1)
         {
-            current_header = create_next_header(current_header.clone());
+            current_header = create_next_header(&current_header);
             produced_headers.push(current_header.clone());
 
             assert!(StorageT::<Block>::import_header(
@@ -614,6 +878,15 @@ mod tests {
                 meta.oldest_stored_hash,
                 produced_headers[last_header_to_be_deleted as usize + 1].hash()
             );
+
+            let window = storage.storage_window::<Block>().unwrap();
+            assert_eq!(
+                window.oldest_number,
+                produced_headers[last_header_to_be_deleted as usize + 1].number
+            );
+            assert_eq!(window.best_number, current_header.number);
+            assert_eq!(window.possible_finalized_number, None);
+            assert_eq!(window.total_stored, max_headers_allowed_to_store);
         }
 
         // Now, let's check if reducing max_headers_allowed_to_store parameter reduces storage.
@@ -621,7 +894,7 @@ mod tests {
         let result = Storage::new(data.clone(), max_headers_allowed_to_store);
         assert!(result.is_ok());
         let storage = result.unwrap();
-        current_header = create_next_header(current_header.clone());
+        current_header = create_next_header(&current_header);
         produced_headers.push(current_header.clone());
         assert!(StorageT::<Block>::import_header(
             &storage,
@@ -666,7 +939,7 @@ mod tests {
         let result = Storage::new(data.clone(), max_headers_allowed_to_store);
         assert!(result.is_ok());
         let storage = result.unwrap();
-        current_header = create_next_header(current_header.clone());
+        current_header = create_next_header(&current_header);
         produced_headers.push(current_header.clone());
         assert!(StorageT::<Block>::import_header(
             &storage,
@@ -697,5 +970,172 @@ mod tests {
             assert!(result.is_ok());
             assert!(result.unwrap().is_some());
         }
+
+        // Pruning shrinks storage right away, without waiting for the next import
+        let max_headers_allowed_to_store = 2;
+        let storage = Storage::new(data.clone(), max_headers_allowed_to_store).unwrap();
+        assert!(storage.prune::<Block>().is_ok());
+        let meta = storage
+            .fetch_meta::<NumberFor<Block>, <Block as BlockT>::Hash>()
+            .unwrap()
+            .unwrap();
+        assert_eq!(meta.total_stored, max_headers_allowed_to_store);
+        assert_eq!(
+            meta.oldest_stored_hash,
+            produced_headers[produced_headers.len() - 2].hash()
+        );
+        let result = HeaderBackend::<Block>::header(
+            &storage,
+            BlockId::<Block>::Hash(produced_headers[produced_headers.len() - 3].hash()),
+        );
+        assert!(result.unwrap().is_none());
+    }
+
+    #[test]
+    fn test_storage_retention_policy() {
+        let policy = RetentionPolicy {
+            keep_authority_set_changes: true,
+            possible_finalized_header_interval: Some(4),
+            possible_finalized_headers_depth: Some(2),
+            max_kept_headers: 10,
+        };
+        let mut current_header = Header::new(
+            One::one(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+        );
+        let mut produced_headers = vec![];
+        for _ in 0..12 {
+            produced_headers.push(current_header.clone());
+            current_header = create_next_header(&current_header);
+            current_header.digest = Default::default();
+            if current_header.number == 3 {
+                current_header.digest.push(DigestItem::Consensus(
+                    GRANDPA_ENGINE_ID,
+                    ConsensusLog::ScheduledChange(ScheduledChange::<NumberFor<Block>> {
+                        next_authorities: vec![],
+                        delay: 0,
+                    })
+                    .encode(),
+                ));
+            }
+            if current_header.number == 5 {
+                current_header.digest.push(DigestItem::Consensus(
+                    GRANDPA_ENGINE_ID,
+                    ConsensusLog::ForcedChange(
+                        1,
+                        ScheduledChange::<NumberFor<Block>> {
+                            next_authorities: vec![],
+                            delay: 0,
+                        },
+                    )
+                    .encode(),
+                ));
+            }
+        }
+        let is_stored = |storage: &Storage, header: &Header| {
+            HeaderBackend::<Block>::header(storage, BlockId::<Block>::Hash(header.hash()))
+                .unwrap()
+                .is_some()
+        };
+
+        // Unfinalized headers falling out of the window are dropped
+        let storage = Storage::new(
+            Data {
+                db: create(11),
+                genesis_data: GenesisData {},
+            },
+            3,
+        )
+        .unwrap();
+        storage.set_retention_policy(&policy).unwrap();
+        for header in &produced_headers[..6] {
+            StorageT::<Block>::import_header(&storage, header.clone(), NewBlockState::Best)
+                .unwrap();
+        }
+        assert!(!is_stored(&storage, &produced_headers[2]));
+        assert!(is_stored(&storage, &produced_headers[3]));
+        assert!(storage
+            .storage_window::<Block>()
+            .unwrap()
+            .kept_numbers
+            .is_empty());
+
+        // Finalized headers are kept as long as the policy keeps them
+        let storage = Storage::new(
+            Data {
+                db: create(11),
+                genesis_data: GenesisData {},
+            },
+            3,
+        )
+        .unwrap();
+        storage.set_retention_policy(&policy).unwrap();
+        assert_eq!(
+            storage.retention_policy::<NumberFor<Block>>().unwrap(),
+            policy
+        );
+        for (index, header) in produced_headers.iter().enumerate() {
+            StorageT::<Block>::import_header(&storage, header.clone(), NewBlockState::Best)
+                .unwrap();
+            if index > 0 {
+                StorageT::<Block>::finalize_header(
+                    &storage,
+                    BlockId::<Block>::Hash(produced_headers[index - 1].hash()),
+                )
+                .unwrap();
+            }
+        }
+        let window = storage.storage_window::<Block>().unwrap();
+        assert_eq!(window.oldest_number, 10);
+        assert_eq!(window.total_stored, 3);
+        // Header 3 signals scheduled authority set change, header 5 forced one,
+        // header 4 and 8 are multiples of the interval and header 9 was finalized recently
+        assert_eq!(window.kept_numbers, vec![3, 4, 5, 8, 9]);
+        for header in &produced_headers {
+            assert_eq!(
+                is_stored(&storage, header),
+                window.kept_numbers.contains(&header.number) || header.number >= 10
+            );
+        }
+        let result = HeaderBackend::<Block>::header(&storage, BlockId::<Block>::Number(8));
+        assert_eq!(result.unwrap(), Some(produced_headers[7].clone()));
+
+        // Oldest kept headers are dropped once there are too many of them
+        let storage = Storage::new(
+            Data {
+                db: create(11),
+                genesis_data: GenesisData {},
+            },
+            3,
+        )
+        .unwrap();
+        storage
+            .set_retention_policy(&RetentionPolicy {
+                max_kept_headers: 3,
+                ..policy
+            })
+            .unwrap();
+        for (index, header) in produced_headers.iter().enumerate() {
+            StorageT::<Block>::import_header(&storage, header.clone(), NewBlockState::Best)
+                .unwrap();
+            if index > 0 {
+                StorageT::<Block>::finalize_header(
+                    &storage,
+                    BlockId::<Block>::Hash(produced_headers[index - 1].hash()),
+                )
+                .unwrap();
+            }
+        }
+        let window = storage.storage_window::<Block>().unwrap();
+        assert_eq!(window.kept_numbers, vec![5, 8, 9]);
+        for header in &produced_headers {
+            assert_eq!(
+                is_stored(&storage, header),
+                window.kept_numbers.contains(&header.number) || header.number >= 10
+            );
+        }
     }
 }
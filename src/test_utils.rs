@@ -0,0 +1,563 @@
+//! Deterministic chain simulator for tests of this crate and of downstream
+//! crates, available with `test-utils` feature. It produces header chains
+//! along with GRANDPA justifications signed by `sp_keyring` authorities,
+//! and BEEFY finality proofs signed by deterministic validator keys.
+
+use parity_scale_codec::Encode;
+use sp_core::hashing::keccak_256;
+use sp_core::{ecdsa, H256};
+use sp_finality_grandpa::{AuthorityList, ConsensusLog, ScheduledChange, GRANDPA_ENGINE_ID};
+use sp_keyring::Ed25519Keyring;
+use sp_runtime::traits::{BlakeTwo256, Hash as HashT, Header as HeaderT};
+use sp_runtime::{DigestItem, Justification};
+
+use crate::beefy::{
+    ethereum_address, merge, BeefyFinalityProof, BeefyValidatorSet, Commitment, MmrLeaf,
+    MmrLeafProof, SignedCommitment,
+};
+pub use crate::justification::GrandpaJustification;
+use crate::justification::{Commit, Message, Precommit, SignedPrecommit};
+pub use crate::types::{Block, BlockNumber, Header, SignedBlock};
+
+/// Creates child of `parent` with empty digest.
+pub fn create_next_header(parent: &Header) -> Header {
+    let mut header = parent.clone();
+    header.number += 1;
+    header.parent_hash = parent.hash();
+    header.digest = Default::default();
+    header
+}
+
+/// Creates authority list of `keys`, all of them with weight of 1.
+pub fn make_ids(keys: &[Ed25519Keyring]) -> AuthorityList {
+    keys.iter().map(|key| (key.public().into(), 1)).collect()
+}
+
+/// Creates commit for the first header of `header_ancestry`, with precommits
+/// of every peer for every header of `header_ancestry`.
+pub fn create_justification_commit(
+    round: u64,
+    set_id: u64,
+    header_ancestry: Vec<Header>,
+    peers: &[Ed25519Keyring],
+) -> Commit<Block> {
+    assert!(!header_ancestry.is_empty());
+    let first_header = header_ancestry.first().unwrap().clone();
+    let mut precommits: Vec<SignedPrecommit<Block>> = vec![];
+    let mut encoded_msg: Vec<u8> = Vec::new();
+    for header in header_ancestry {
+        let precommit = Precommit::<Block> {
+            target_hash: header.hash(),
+            target_number: *header.number(),
+        };
+        let msg = Message::<Block>::Precommit(precommit.clone());
+        encoded_msg.clear();
+        (&msg, round, set_id).encode_to(&mut encoded_msg);
+        for peer in peers {
+            precommits.push(SignedPrecommit::<Block> {
+                precommit: precommit.clone(),
+                signature: peer.sign(&encoded_msg[..]).into(),
+                id: peer.public().into(),
+            });
+        }
+    }
+
+    Commit::<Block> {
+        target_hash: first_header.hash(),
+        target_number: *first_header.number(),
+        precommits,
+    }
+}
+
+/// Justification of `header` in `round`, signed by every peer for authority set `set_id`.
+pub fn create_justification(
+    header: &Header,
+    round: u64,
+    set_id: u64,
+    peers: &[Ed25519Keyring],
+) -> GrandpaJustification<Block> {
+    GrandpaJustification {
+        round,
+        commit: create_justification_commit(round, set_id, vec![header.clone()], peers),
+        votes_ancestries: vec![],
+    }
+}
+
+/// Secret keys of `amount` BEEFY validators, derived from their index.
+pub fn validator_keys(amount: u8) -> Vec<secp256k1::SecretKey> {
+    (1..=amount)
+        .map(|i| secp256k1::SecretKey::parse(&[i; 32]).unwrap())
+        .collect()
+}
+
+/// Ethereum addresses of BEEFY validators of `keys`.
+pub fn validator_addresses(keys: &[secp256k1::SecretKey]) -> Vec<[u8; 20]> {
+    keys.iter()
+        .map(|key| ethereum_address(&secp256k1::PublicKey::from_secret_key(key)))
+        .collect()
+}
+
+/// Builds merkle root and proof of leaf at `position`, promoting odd nodes.
+pub fn merkle_root_and_proof(leaves: &[[u8; 20]], mut position: usize) -> (H256, Vec<H256>) {
+    let mut level: Vec<H256> = leaves.iter().map(|l| H256(keccak_256(l))).collect();
+    let mut proof = vec![];
+    while level.len() > 1 {
+        if let Some(sibling) = level.get(position ^ 1) {
+            proof.push(*sibling);
+        }
+        level = level
+            .chunks(2)
+            .map(|pair| match pair {
+                [left, right] => merge(left, right),
+                [single] => *single,
+                _ => unreachable!(),
+            })
+            .collect();
+        position >>= 1;
+    }
+    (level[0], proof)
+}
+
+/// BEEFY validator set `id` made of `keys`.
+pub fn validator_set(id: u64, keys: &[secp256k1::SecretKey]) -> BeefyValidatorSet {
+    BeefyValidatorSet {
+        id,
+        len: keys.len() as u32,
+        root: merkle_root_and_proof(&validator_addresses(keys), 0).0,
+    }
+}
+
+fn sign_commitment(commitment: &Commitment, key: &secp256k1::SecretKey) -> ecdsa::Signature {
+    let message = secp256k1::Message::parse(&keccak_256(&commitment.encode()));
+    secp256k1::sign(&message, key).into()
+}
+
+/// Creates proof finalizing `header` by signers among `keys`, with MMR of
+/// two leaves, the second of which is appended by child of `header`.
+pub fn create_finality_proof(
+    header: &Header,
+    validator_set_id: u64,
+    keys: &[secp256k1::SecretKey],
+    signers: &[usize],
+    next_validator_set: BeefyValidatorSet,
+) -> BeefyFinalityProof {
+    let leaf = MmrLeaf {
+        parent_number_and_hash: (*header.number(), header.hash()),
+        parachain_heads: H256::zero(),
+        beefy_next_authority_set: next_validator_set,
+    };
+    let sibling_leaf = H256::repeat_byte(7);
+    let peak = merge(&sibling_leaf, &H256(keccak_256(&leaf.encode())));
+    let commitment = Commitment {
+        payload: peak,
+        block_number: header.number() + 1,
+        validator_set_id,
+    };
+
+    let addresses = validator_addresses(keys);
+    let signatures = (0..keys.len())
+        .map(|i| {
+            if signers.contains(&i) {
+                Some(sign_commitment(&commitment, &keys[i]))
+            } else {
+                None
+            }
+        })
+        .collect();
+    let validator_proofs = (0..keys.len())
+        .filter(|i| signers.contains(i))
+        .map(|i| merkle_root_and_proof(&addresses, i).1)
+        .collect();
+
+    BeefyFinalityProof {
+        signed_commitment: SignedCommitment {
+            commitment,
+            signatures,
+        },
+        validator_proofs,
+        leaf,
+        leaf_proof: MmrLeafProof {
+            leaf_index: 1,
+            leaf_count: 2,
+            items: vec![sibling_leaf],
+            peaks: vec![peak],
+        },
+    }
+}
+
+/// GRANDPA authority set of simulated chain.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SimulatedAuthoritySet {
+    pub set_id: u64,
+    pub keys: Vec<Ed25519Keyring>,
+    /// Number of the header signaling the set, none for the initial set
+    pub possible_signal_number: Option<BlockNumber>,
+    /// Number of the first header finalized by the set
+    pub activation_number: BlockNumber,
+}
+
+impl SimulatedAuthoritySet {
+    pub fn authority_list(&self) -> AuthorityList {
+        make_ids(&self.keys)
+    }
+}
+
+/// Simulated chain following a single fork, finalized by GRANDPA. Headers are
+/// deterministic, so the same calls always produce the same chain, and headers
+/// of forks are told apart by their state roots.
+#[derive(Clone, Debug)]
+pub struct ChainSimulator {
+    headers: Vec<Header>,
+    authority_sets: Vec<SimulatedAuthoritySet>,
+    fork_path: Vec<u32>,
+    forks_created: u32,
+    round: u64,
+}
+
+impl ChainSimulator {
+    /// Creates chain with initial header at height 1, finalized
+    /// by authority set 0 made of `keys`.
+    pub fn new(keys: &[Ed25519Keyring]) -> Self {
+        let mut simulator = ChainSimulator {
+            headers: vec![],
+            authority_sets: vec![SimulatedAuthoritySet {
+                set_id: 0,
+                keys: keys.to_vec(),
+                possible_signal_number: None,
+                activation_number: 1,
+            }],
+            fork_path: vec![],
+            forks_created: 0,
+            round: 0,
+        };
+        let initial_header = Header::new(
+            1,
+            Default::default(),
+            simulator.state_root(1),
+            Default::default(),
+            Default::default(),
+        );
+        simulator.headers.push(initial_header);
+        simulator
+    }
+
+    pub fn initial_header(&self) -> &Header {
+        self.headers.first().unwrap()
+    }
+
+    pub fn best_header(&self) -> &Header {
+        self.headers.last().unwrap()
+    }
+
+    pub fn header(&self, number: BlockNumber) -> Option<&Header> {
+        let initial_number = self.initial_header().number;
+        if number < initial_number {
+            return None;
+        }
+        self.headers.get((number - initial_number) as usize)
+    }
+
+    pub fn headers(&self) -> &[Header] {
+        &self.headers
+    }
+
+    /// Appends header with empty digest.
+    pub fn push_header(&mut self) -> Header {
+        self.push_header_with_digest(vec![])
+    }
+
+    /// Appends `amount` headers with empty digests.
+    pub fn push_headers(&mut self, amount: usize) -> Vec<Header> {
+        (0..amount).map(|_| self.push_header()).collect()
+    }
+
+    /// Appends header carrying `digest_items`.
+    pub fn push_header_with_digest(&mut self, digest_items: Vec<DigestItem<H256>>) -> Header {
+        let mut header = create_next_header(self.best_header());
+        header.state_root = self.state_root(header.number);
+        for item in digest_items {
+            header.digest.push(item);
+        }
+        self.headers.push(header.clone());
+        header
+    }
+
+    /// Appends header signaling `ScheduledChange` to authority set of `keys`,
+    /// which finalizes headers from `delay` blocks after the signal on.
+    pub fn schedule_change(&mut self, keys: &[Ed25519Keyring], delay: BlockNumber) -> Header {
+        let change = ScheduledChange {
+            next_authorities: make_ids(keys),
+            delay,
+        };
+        let log = ConsensusLog::ScheduledChange(change);
+        self.push_change(log, keys, delay)
+    }
+
+    /// Appends header signaling `ForcedChange` to authority set of `keys`, which
+    /// takes over `delay` blocks after the signal, whether the signal got finalized
+    /// or not. `median_last_finalized` is the number of the header it builds on.
+    ///
+    /// Light client only enacts `ScheduledChange`, so it keeps verifying
+    /// justifications against the previous set after a forced change. Simulated
+    /// forced changes are only good for code inspecting header digests, like
+    /// retention policy, not for justifications of the headers following them.
+    pub fn force_change(
+        &mut self,
+        keys: &[Ed25519Keyring],
+        delay: BlockNumber,
+        median_last_finalized: BlockNumber,
+    ) -> Header {
+        let change = ScheduledChange {
+            next_authorities: make_ids(keys),
+            delay,
+        };
+        let log = ConsensusLog::ForcedChange(median_last_finalized, change);
+        self.push_change(log, keys, delay)
+    }
+
+    fn push_change(
+        &mut self,
+        log: ConsensusLog<BlockNumber>,
+        keys: &[Ed25519Keyring],
+        delay: BlockNumber,
+    ) -> Header {
+        let current_set = self.authority_sets.last().unwrap();
+        assert!(
+            current_set.activation_number <= self.best_header().number + 1,
+            "Authority set {} is pending already",
+            current_set.set_id
+        );
+        let set_id = current_set.set_id + 1;
+        let header = self
+            .push_header_with_digest(vec![DigestItem::Consensus(GRANDPA_ENGINE_ID, log.encode())]);
+        self.authority_sets.push(SimulatedAuthoritySet {
+            set_id,
+            keys: keys.to_vec(),
+            possible_signal_number: Some(header.number),
+            activation_number: header.number + delay,
+        });
+        header
+    }
+
+    /// Authority set finalizing header at `number`.
+    pub fn authority_set_at(&self, number: BlockNumber) -> &SimulatedAuthoritySet {
+        self.authority_sets
+            .iter()
+            .rev()
+            .find(|set| set.activation_number <= number)
+            .unwrap_or_else(|| self.authority_sets.first().unwrap())
+    }
+
+    /// Justification of header at `number` signed by all
+    /// authorities of the set finalizing it.
+    pub fn justify(&mut self, number: BlockNumber) -> Justification {
+        let authority_set = self.authority_set_at(number).clone();
+        self.justify_with(number, authority_set.set_id, &authority_set.keys)
+            .encode()
+    }
+
+    /// Justification of header at `number` signed by `keys` for authority set
+    /// `set_id`, which may be used to produce invalid justifications.
+    pub fn justify_with(
+        &mut self,
+        number: BlockNumber,
+        set_id: u64,
+        keys: &[Ed25519Keyring],
+    ) -> GrandpaJustification<Block> {
+        let header = self
+            .header(number)
+            .unwrap_or_else(|| panic!("Header {} isn't part of the chain", number))
+            .clone();
+        self.round += 1;
+        create_justification(&header, self.round, set_id, keys)
+    }
+
+    /// Signed block of header at `number` with empty body,
+    /// justified if `justified` is set.
+    pub fn signed_block(&mut self, number: BlockNumber, justified: bool) -> SignedBlock {
+        let header = self
+            .header(number)
+            .unwrap_or_else(|| panic!("Header {} isn't part of the chain", number))
+            .clone();
+        SignedBlock {
+            block: Block {
+                header,
+                extrinsics: vec![],
+            },
+            justification: if justified {
+                Some(self.justify(number))
+            } else {
+                None
+            },
+        }
+    }
+
+    /// Creates fork sharing headers up to `number` with this chain, along with
+    /// authority sets signaled by them. Headers appended to the fork differ from
+    /// headers of this chain and of its other forks at the same heights.
+    pub fn fork(&mut self, number: BlockNumber) -> ChainSimulator {
+        let initial_number = self.initial_header().number;
+        assert!(
+            number >= initial_number && number <= self.best_header().number,
+            "Header {} isn't part of the chain",
+            number
+        );
+        self.forks_created += 1;
+        let mut fork_path = self.fork_path.clone();
+        fork_path.push(self.forks_created);
+
+        ChainSimulator {
+            headers: self.headers[..=(number - initial_number) as usize].to_vec(),
+            authority_sets: self
+                .authority_sets
+                .iter()
+                .filter(|set| set.possible_signal_number.unwrap_or_default() <= number)
+                .cloned()
+                .collect(),
+            fork_path,
+            forks_created: 0,
+            round: self.round,
+        }
+    }
+
+    fn state_root(&self, number: BlockNumber) -> H256 {
+        BlakeTwo256::hash_of(&(&self.fork_path, number))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::common::types::block_import_result::BlockImportResult;
+    use crate::common::types::justification_verification::JustificationVerification;
+    use crate::common::types::light_authority_set::LightAuthoritySet;
+    use crate::common::types::retention_policy::RetentionPolicy;
+    use crate::light_state::{current_status, ingest_finalized_header, initialize_state};
+    use crate::test_utils::{make_ids, ChainSimulator};
+    use crate::types::Block;
+    use crate::verifier::find_scheduled_change;
+    use sp_finality_grandpa::{ConsensusLog, GRANDPA_ENGINE_ID};
+    use sp_keyring::Ed25519Keyring;
+    use sp_runtime::generic::OpaqueDigestItemId;
+    use sp_runtime::traits::Header as HeaderT;
+
+    #[test]
+    fn test_chain_simulator() {
+        let genesis_keys = [Ed25519Keyring::Alice, Ed25519Keyring::Bob];
+        let next_keys = [Ed25519Keyring::Charlie, Ed25519Keyring::Dave];
+        let mut simulator = ChainSimulator::new(&genesis_keys);
+        let mut encoded_data = initialize_state(
+            simulator.initial_header().clone(),
+            LightAuthoritySet::new(0, make_ids(&genesis_keys)),
+            None,
+            RetentionPolicy::default(),
+            JustificationVerification::Full,
+            256,
+        )
+        .unwrap();
+
+        let signal = simulator.schedule_change(&next_keys, 2);
+        assert_eq!(
+            find_scheduled_change::<Block>(&signal).map(|c| c.delay),
+            Some(2)
+        );
+        simulator.push_headers(3);
+        assert_eq!(simulator.best_header().number, 5);
+        assert_eq!(simulator.authority_set_at(3).set_id, 0);
+        assert_eq!(simulator.authority_set_at(4).set_id, 1);
+        assert_eq!(
+            simulator.authority_set_at(4).authority_list(),
+            make_ids(&next_keys)
+        );
+
+        // Simulated chain is accepted by the light client, with justifications
+        // signed by the authority set finalizing every header
+        for number in 2..=5 {
+            let header = simulator.header(number).unwrap().clone();
+            let justification = if number == 3 || number == 4 {
+                Some(simulator.justify(number))
+            } else {
+                None
+            };
+            let justified = justification.is_some();
+            let (result, data) =
+                ingest_finalized_header(encoded_data, header, None, justification, 256).unwrap();
+            assert!(matches!(
+                result.block_import_result,
+                BlockImportResult::ImportedUnknown(..)
+            ));
+            // Change signaled by header 2 is enacted once header 4 is finalized
+            assert_eq!(
+                result
+                    .possible_scheduled_change
+                    .as_ref()
+                    .map(|c| c.next_change_at),
+                if number == 2 { Some(4) } else { None }
+            );
+            assert_eq!(result.finalized(), justified);
+            assert_eq!(result.validator_set_enacted(), number == 4);
+            encoded_data = data;
+        }
+        let status = current_status::<Block>(encoded_data.clone()).unwrap();
+        assert_eq!(status.possible_last_finalized_header.unwrap().number, 4);
+        assert_eq!(status.possible_light_authority_set.unwrap().set_id(), 1);
+
+        // Justification signed by outdated set is rejected
+        let mut extended = simulator.clone();
+        let header = extended.push_header();
+        let justification = extended.justify_with(6, 0, &genesis_keys);
+        assert!(ingest_finalized_header(
+            encoded_data.clone(),
+            header,
+            None,
+            Some(parity_scale_codec::Encode::encode(&justification)),
+            256,
+        )
+        .is_err());
+
+        // Report of rejected justification is passed along with the error
+        let mut justification = extended.justify_with(6, 1, &next_keys);
+        justification.commit.precommits[0].signature =
+            justification.commit.precommits[1].signature.clone();
+        let error = ingest_finalized_header(
+            encoded_data.clone(),
+            extended.header(6).unwrap().clone(),
+            None,
+            Some(parity_scale_codec::Encode::encode(&justification)),
+            256,
+        )
+        .err()
+        .unwrap();
+        assert!(error
+            .to_string()
+            .contains("invalid signature for precommit 0 in grandpa justification"));
+
+        // Forks share headers up to the fork point and diverge afterwards
+        let mut fork = simulator.fork(4);
+        let mut other_fork = simulator.fork(4);
+        let fork_header = fork.push_header();
+        let other_fork_header = other_fork.push_header();
+        assert_eq!(fork.header(4), simulator.header(4));
+        assert_eq!(fork_header.parent_hash, simulator.header(4).unwrap().hash());
+        assert_ne!(fork_header.hash(), simulator.header(5).unwrap().hash());
+        assert_ne!(fork_header.hash(), other_fork_header.hash());
+        assert_eq!(fork.authority_set_at(5).set_id, 1);
+        // The client follows a single chain, so competing header is refused
+        assert!(ingest_finalized_header(encoded_data, fork_header, None, None, 256).is_err());
+
+        // Same calls produce the same chain
+        let mut replayed = ChainSimulator::new(&genesis_keys);
+        replayed.schedule_change(&next_keys, 2);
+        replayed.push_headers(3);
+        assert_eq!(replayed.headers(), simulator.headers());
+
+        // Forced changes are signaled with their own consensus log
+        let forced_signal = fork.force_change(&genesis_keys, 0, 4);
+        let id = OpaqueDigestItemId::Consensus(&GRANDPA_ENGINE_ID);
+        let log = forced_signal
+            .digest()
+            .convert_first(|l| l.try_to::<ConsensusLog<u32>>(id));
+        assert!(matches!(log, Some(ConsensusLog::ForcedChange(4, _))));
+        assert_eq!(fork.authority_set_at(6).set_id, 2);
+        assert_eq!(fork.authority_set_at(6).keys, genesis_keys.to_vec());
+    }
+}
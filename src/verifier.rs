@@ -1,22 +1,44 @@
+#[cfg(feature = "std")]
 use crate::common::traits::aux_store::AuxStore;
+#[cfg(feature = "std")]
 use crate::common::traits::header_backend::HeaderBackend;
+#[cfg(feature = "std")]
 use crate::common::traits::verifier::Verifier;
+#[cfg(feature = "std")]
+use crate::common::types::archived_authority_set::ArchivedAuthoritySet;
+#[cfg(feature = "std")]
+use crate::common::types::block_import_error::BlockImportError;
+#[cfg(feature = "std")]
 use crate::common::types::block_import_params::BlockImportParams;
+#[cfg(feature = "std")]
 use crate::common::types::block_origin::BlockOrigin;
+#[cfg(feature = "std")]
+use crate::common::types::blockchain_error::BlockchainError;
+#[cfg(feature = "std")]
 use crate::common::types::light_authority_set::LightAuthoritySet;
 use crate::common::types::next_change_in_authority::NextChangeInAuthority;
+#[cfg(feature = "std")]
 use crate::common::utils::{
-    delete_next_authority_change, fetch_light_authority_set, fetch_next_authority_change,
-    insert_light_authority_set, GRANDPA_AUTHORITY_CHANGE_INTERMEDIATE_KEY,
+    archive_light_authority_set, delete_next_authority_change, fetch_light_authority_set,
+    fetch_next_authority_change, insert_light_authority_set,
+    GRANDPA_AUTHORITY_CHANGE_INTERMEDIATE_KEY,
 };
+use alloc::string::String;
+#[cfg(feature = "std")]
 use parity_scale_codec::alloc::borrow::Cow;
+#[cfg(feature = "std")]
 use parity_scale_codec::alloc::sync::Arc;
+use parity_scale_codec::Encode;
 use sp_finality_grandpa::{ConsensusLog, ScheduledChange, GRANDPA_ENGINE_ID};
 use sp_runtime::generic::OpaqueDigestItemId;
 use sp_runtime::traits::Header;
 use sp_runtime::traits::{Block as BlockT, NumberFor};
+use sp_trie::{Layout, TrieConfiguration};
 
-fn find_scheduled_change<B: BlockT>(header: &B::Header) -> Option<ScheduledChange<NumberFor<B>>> {
+/// Finds `ScheduledChange` signaled by the header, if any.
+pub fn find_scheduled_change<B: BlockT>(
+    header: &B::Header,
+) -> Option<ScheduledChange<NumberFor<B>>> {
     let id = OpaqueDigestItemId::Consensus(&GRANDPA_ENGINE_ID);
 
     let filter_log = |log: ConsensusLog<NumberFor<B>>| match log {
@@ -31,10 +53,93 @@ fn find_scheduled_change<B: BlockT>(header: &B::Header) -> Option<ScheduledChang
         .convert_first(|l| l.try_to(id).and_then(filter_log))
 }
 
+/// Finds `ForcedChange` signaled by the header, if any, along with
+/// the median last finalized block number it was signaled with.
+pub fn find_forced_change<B: BlockT>(
+    header: &B::Header,
+) -> Option<(NumberFor<B>, ScheduledChange<NumberFor<B>>)> {
+    let id = OpaqueDigestItemId::Consensus(&GRANDPA_ENGINE_ID);
+
+    let filter_log = |log: ConsensusLog<NumberFor<B>>| match log {
+        ConsensusLog::ForcedChange(median, change) => Some((median, change)),
+        _ => None,
+    };
+
+    header
+        .digest()
+        .convert_first(|l| l.try_to(id).and_then(filter_log))
+}
+
+/// Checks that block body matches extrinsics root of the header, so
+/// that arbitrary extrinsics can't be attached to a valid header.
+pub fn check_extrinsics_root<B: BlockT>(
+    header: &B::Header,
+    extrinsics: &[B::Extrinsic],
+) -> Result<(), String> {
+    let extrinsics_root = Layout::<<B::Header as Header>::Hashing>::ordered_trie_root(
+        extrinsics.iter().map(Encode::encode),
+    );
+    if extrinsics_root != *header.extrinsics_root() {
+        return Err(format!(
+            "Block body doesn't match extrinsics root. Expected: {}, got: {}",
+            header.extrinsics_root(),
+            extrinsics_root
+        ));
+    }
+    Ok(())
+}
+
+/// Authority set change that a header brings, as decided by
+/// `verify_authority_change`.
+pub struct AuthorityChange<B: BlockT> {
+    /// Whether the header enacts the pending change
+    pub enacts_pending: bool,
+    /// Change scheduled by the header, if any
+    pub scheduled: Option<NextChangeInAuthority<B>>,
+}
+
+/// Checks the header against the pending authority change and the last
+/// finalized block number, and finds the change it schedules, if any.
+/// Fetching and storing the changes is left to the caller, like
+/// `GrandpaVerifier` does with its client storage.
+pub fn verify_authority_change<B: BlockT>(
+    header: &B::Header,
+    pending_change: Option<&NextChangeInAuthority<B>>,
+    finalized_number: NumberFor<B>,
+) -> Result<AuthorityChange<B>, String> {
+    let enacting_change = pending_change.filter(|change| change.next_change_at == *header.number());
+    let enacts_pending = enacting_change.is_some();
+    if let Some(change) = enacting_change {
+        let (_, enacting_header_number) = change.block_enacting_this_change;
+        if finalized_number < enacting_header_number {
+            return Err("block trying to enact new authority set isn't finalized".into());
+        }
+    }
+
+    let scheduled = match find_scheduled_change::<B>(header) {
+        Some(_) if pending_change.is_some() && !enacts_pending => {
+            return Err("Scheduled change already exists.".into());
+        }
+        Some(scheduled_change) => Some(NextChangeInAuthority::new(
+            *header.number() + scheduled_change.delay,
+            (header.hash(), *header.number()),
+            scheduled_change,
+        )),
+        None => None,
+    };
+
+    Ok(AuthorityChange {
+        enacts_pending,
+        scheduled,
+    })
+}
+
+#[cfg(feature = "std")]
 pub struct GrandpaVerifier<S> {
     storage: Arc<S>,
 }
 
+#[cfg(feature = "std")]
 impl<S> GrandpaVerifier<S> {
     pub fn new(storage: Arc<S>) -> Self {
         Self {
@@ -43,6 +148,7 @@ impl<S> GrandpaVerifier<S> {
     }
 }
 
+#[cfg(feature = "std")]
 impl<S, Block> Verifier<Block> for GrandpaVerifier<S>
 where
     S: AuxStore + HeaderBackend<Block>,
@@ -53,51 +159,30 @@ where
         _origin: BlockOrigin,
         header: <Block as BlockT>::Header,
         justification: Option<Vec<u8>>,
-        _body: Option<Vec<<Block as BlockT>::Extrinsic>>,
-    ) -> Result<BlockImportParams<Block>, String> {
-        let (possible_authority_change, scheduled_change_exists) = {
-            let possible_authority_change =
-                fetch_next_authority_change::<S, Block>(self.storage.clone())
-                    .map_err(|e| format!("{}", e))?;
-            match possible_authority_change {
-                Some(authority_change) => {
-                    if authority_change.next_change_at == *header.number() {
-                        delete_next_authority_change(self.storage.clone())
-                            .map_err(|e| format!("{}", e))?;
-                        (Some(authority_change), false)
-                    } else {
-                        (None, true)
-                    }
-                }
-                None => (None, false),
-            }
-        };
-
-        if let Some(authority_change) = possible_authority_change.as_ref() {
-            let (_, enacting_header_number) = authority_change.block_enacting_this_change;
-            let info = self.storage.info();
-            if info.finalized_number < enacting_header_number {
-                return Err("block trying to enact new authority set isn't finalized".into());
-            }
+        body: Option<Vec<<Block as BlockT>::Extrinsic>>,
+    ) -> Result<BlockImportParams<Block>, BlockImportError> {
+        if let Some(extrinsics) = body.as_ref() {
+            check_extrinsics_root::<Block>(&header, extrinsics)
+                .map_err(BlockImportError::VerificationFailed)?;
         }
 
-        let found_scheduled_authority_change = find_scheduled_change::<Block>(&header);
-        let possible_next_authority_change: Option<NextChangeInAuthority<Block>> =
-            match found_scheduled_authority_change {
-                Some(scheduled_change) => {
-                    if scheduled_change_exists {
-                        Err("Scheduled change already exists.")
-                    } else {
-                        Ok(Some(NextChangeInAuthority::new(
-                            *header.number() + scheduled_change.delay,
-                            (header.hash(), *header.number()),
-                            scheduled_change,
-                        )))
-                    }
-                }
-                None => Ok(None),
-            }?;
+        let possible_authority_change =
+            fetch_next_authority_change::<S, Block>(self.storage.clone())?;
+        let authority_change = verify_authority_change::<Block>(
+            &header,
+            possible_authority_change.as_ref(),
+            self.storage.info().finalized_number,
+        )
+        .map_err(BlockImportError::VerificationFailed)?;
+        let possible_next_authority_change = authority_change.scheduled;
+        let possible_authority_change = if authority_change.enacts_pending {
+            delete_next_authority_change(self.storage.clone())?;
+            possible_authority_change
+        } else {
+            None
+        };
 
+        let (header_hash, header_number) = (header.hash(), *header.number());
         let mut block_import_params: BlockImportParams<Block> =
             BlockImportParams::new(BlockOrigin::NetworkBroadcast, header);
         block_import_params.justification = justification;
@@ -109,10 +194,9 @@ where
         }
 
         if let Some(authority_change) = possible_authority_change {
-            let possible_current_authority_set =
-                fetch_light_authority_set(self.storage.clone()).map_err(|e| format!("{}", e))?;
+            let possible_current_authority_set = fetch_light_authority_set(self.storage.clone())?;
             let current_authority_set = if possible_current_authority_set.is_none() {
-                Err("No previous authority set found")
+                Err(BlockchainError::InvalidAuthoritiesSet)
             } else {
                 Ok(possible_current_authority_set.unwrap())
             }?;
@@ -120,8 +204,17 @@ where
                 &current_authority_set,
                 authority_change.change.next_authorities,
             );
-            insert_light_authority_set(self.storage.clone(), next_authority_set)
-                .map_err(|e| format!("{}", e))?;
+            // Set is archived along with the header enacting it, as the current
+            // one gets overwritten by the next change
+            archive_light_authority_set(
+                self.storage.clone(),
+                &ArchivedAuthoritySet::<Block> {
+                    authority_set: next_authority_set.clone(),
+                    activation_number: header_number,
+                    activation_hash: header_hash,
+                },
+            )?;
+            insert_light_authority_set(self.storage.clone(), next_authority_set)?;
         }
 
         Ok(block_import_params)
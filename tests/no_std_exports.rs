@@ -0,0 +1,100 @@
+//! Verifies with nothing but the exports available without `std` feature, the
+//! way runtimes and smart contracts depend on the verification core, while
+//! fixtures come from `test_utils`.
+//!
+//! This doesn't run without `std`: such a build only compiles for wasm32,
+//! where tests can't be run, so the test runs with default features and CI
+//! checks separately that the exports build for wasm32 without them.
+
+use parity_scale_codec::Encode;
+use sp_finality_grandpa::{ConsensusLog, ScheduledChange, GRANDPA_ENGINE_ID};
+use sp_keyring::Ed25519Keyring;
+use sp_runtime::generic::DigestItem;
+use sp_runtime::traits::{BlakeTwo256, Hash as HashT, Header as HeaderT};
+use substrate_client::test_utils::{create_justification, make_ids, Block, Header};
+use substrate_client::{
+    check_extrinsics_root, find_scheduled_change, verify_authority_change, GrandpaJustification,
+    LightAuthoritySet, ProvableJustification,
+};
+
+#[test]
+fn test_verification_core_exports() {
+    let peers = [
+        Ed25519Keyring::Alice,
+        Ed25519Keyring::Bob,
+        Ed25519Keyring::Charlie,
+    ];
+    let next_peers = [Ed25519Keyring::Dave, Ed25519Keyring::Eve];
+    let change = ScheduledChange {
+        next_authorities: make_ids(&next_peers),
+        delay: 0,
+    };
+    let mut header = Header::new(
+        1,
+        BlakeTwo256::ordered_trie_root(vec![]),
+        Default::default(),
+        Default::default(),
+        Default::default(),
+    );
+    header.digest.push(DigestItem::Consensus(
+        GRANDPA_ENGINE_ID,
+        ConsensusLog::ScheduledChange(change.clone()).encode(),
+    ));
+
+    // Body has to match extrinsics root of the header
+    assert!(check_extrinsics_root::<Block>(&header, &[]).is_ok());
+    let mut other_header = header.clone();
+    other_header.extrinsics_root = Default::default();
+    assert!(check_extrinsics_root::<Block>(&other_header, &[]).is_err());
+
+    // Justification is checked against the current set
+    let authority_set = LightAuthoritySet::new(0, make_ids(&peers));
+    let target = (header.hash(), *header.number());
+    let justification = create_justification(&header, 1, authority_set.set_id(), &peers).encode();
+    assert!(
+        GrandpaJustification::<Block>::decode_and_verify_finalization(
+            &justification,
+            authority_set.set_id(),
+            target,
+            &authority_set.authorities(),
+        )
+        .is_ok()
+    );
+    let justification = create_justification(&header, 1, 0, &peers[..1]).encode();
+    assert!(
+        GrandpaJustification::<Block>::decode_and_verify_finalization(
+            &justification,
+            authority_set.set_id(),
+            target,
+            &authority_set.authorities(),
+        )
+        .is_err()
+    );
+
+    // Scheduled change signaled by the header brings the next set
+    let found_change = find_scheduled_change::<Block>(&header).unwrap();
+    assert_eq!(found_change, change);
+    let authority_change = verify_authority_change::<Block>(&header, None, 0).unwrap();
+    assert!(!authority_change.enacts_pending);
+    let pending_change = authority_change.scheduled.unwrap();
+    assert_eq!(pending_change.next_change_at, 1);
+    assert!(verify_authority_change::<Block>(&header, Some(&pending_change), 0).is_err());
+    let authority_change =
+        verify_authority_change::<Block>(&header, Some(&pending_change), 1).unwrap();
+    assert!(authority_change.enacts_pending);
+    let next_authority_set = LightAuthoritySet::construct_next_authority_set(
+        &authority_set,
+        found_change.next_authorities,
+    );
+    assert_eq!(next_authority_set.set_id(), 1);
+    let justification = create_justification(&header, 1, 1, &next_peers).encode();
+    assert!(
+        GrandpaJustification::<Block>::decode_and_verify_finalization(
+            &justification,
+            next_authority_set.set_id(),
+            target,
+            &next_authority_set.authorities(),
+        )
+        .is_ok()
+    );
+}